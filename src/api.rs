@@ -1,18 +1,30 @@
-use crate::externs::{free, malloc, memcpy, memmove, memset, realloc, strdup, strlen};
+use crate::externs::{
+    free, malloc, malloc_usable_size, memcpy, memmove, memset, realloc, strdup, strlen,
+};
+#[cfg(feature = "serde")]
+use crate::limits::RECURSION_LIMIT;
 use crate::ops::{ForceAdd as _, ForceMul as _};
+use crate::scanner::MAX_SIMPLE_KEY_LENGTH;
+use crate::schema::{yaml_schema_implicit_resolver, yaml_schema_options_initialize};
 use crate::success::{Success, FAIL, OK};
-use crate::yaml::{size_t, yaml_char_t};
+use crate::yaml::{size_t, yaml_anchors_t, yaml_char_t, yaml_comment_t, yaml_scalar_source_t};
 use crate::{
-    libc, yaml_break_t, yaml_document_t, yaml_emitter_state_t, yaml_emitter_t, yaml_encoding_t,
-    yaml_event_t, yaml_mapping_style_t, yaml_mark_t, yaml_node_item_t, yaml_node_pair_t,
-    yaml_node_t, yaml_parser_state_t, yaml_parser_t, yaml_read_handler_t, yaml_scalar_style_t,
-    yaml_sequence_style_t, yaml_simple_key_t, yaml_tag_directive_t, yaml_token_t,
-    yaml_version_directive_t, yaml_write_handler_t, PointerExt, YAML_ALIAS_EVENT, YAML_ALIAS_TOKEN,
-    YAML_ANCHOR_TOKEN, YAML_ANY_ENCODING, YAML_DOCUMENT_END_EVENT, YAML_DOCUMENT_START_EVENT,
-    YAML_MAPPING_END_EVENT, YAML_MAPPING_NODE, YAML_MAPPING_START_EVENT, YAML_SCALAR_EVENT,
-    YAML_SCALAR_NODE, YAML_SCALAR_TOKEN, YAML_SEQUENCE_END_EVENT, YAML_SEQUENCE_NODE,
-    YAML_SEQUENCE_START_EVENT, YAML_STREAM_END_EVENT, YAML_STREAM_START_EVENT,
-    YAML_TAG_DIRECTIVE_TOKEN, YAML_TAG_TOKEN,
+    libc, yaml_banner_handler_t, yaml_break_t, yaml_capabilities_t, yaml_document_end_marker_t,
+    yaml_document_start_marker_t, yaml_document_t, yaml_duplicate_keys_t, yaml_emitter_options_t,
+    yaml_emitter_state_t, yaml_emitter_t, yaml_encoding_t, yaml_event_t, yaml_implicit_resolver_t,
+    yaml_key_comparator_t, yaml_mapping_style_t, yaml_mark_t, yaml_node_item_t, yaml_node_pair_t,
+    yaml_node_t, yaml_parser_state_t, yaml_parser_t, yaml_read_handler_t, yaml_scalar_handler_t,
+    yaml_scalar_style_t, yaml_schema_options_t, yaml_schema_t, yaml_sequence_style_t,
+    yaml_simple_key_t, yaml_stream_t, yaml_tag_directive_t, yaml_token_t, yaml_unicode_handler_t,
+    yaml_version_directive_t, yaml_wrap_policy_t, yaml_write_handler_t, PointerExt,
+    YAML_ALIAS_EVENT, YAML_ALIAS_NODE, YAML_ALIAS_TOKEN, YAML_ANCHOR_TOKEN, YAML_ANY_BREAK,
+    YAML_ANY_ENCODING, YAML_DOCUMENT_END_EVENT, YAML_DOCUMENT_START_EVENT,
+    YAML_DOUBLE_QUOTED_SCALAR_STYLE, YAML_EMIT_STREAM_START_STATE, YAML_MAPPING_END_EVENT,
+    YAML_MAPPING_NODE, YAML_MAPPING_START_EVENT, YAML_MEMORY_ERROR, YAML_NO_ERROR,
+    YAML_SCALAR_EVENT, YAML_SCALAR_NODE, YAML_SCALAR_TOKEN, YAML_SEQUENCE_END_EVENT,
+    YAML_SEQUENCE_NODE, YAML_SEQUENCE_START_EVENT, YAML_SINGLE_QUOTED_SCALAR_STYLE,
+    YAML_STREAM_END_EVENT, YAML_STREAM_START_EVENT, YAML_TAG_DIRECTIVE_TOKEN, YAML_TAG_TOKEN,
+    YAML_UTF8_ENCODING, YAML_WRITER_ERROR,
 };
 use core::mem::{size_of, MaybeUninit};
 use core::ptr::{self, addr_of_mut};
@@ -21,6 +33,7 @@ const INPUT_RAW_BUFFER_SIZE: usize = 16384;
 const INPUT_BUFFER_SIZE: usize = INPUT_RAW_BUFFER_SIZE * 3;
 const OUTPUT_BUFFER_SIZE: usize = 16384;
 const OUTPUT_RAW_BUFFER_SIZE: usize = OUTPUT_BUFFER_SIZE * 2 + 2;
+const OWNED_OUTPUT_SIZE: usize = 64;
 
 pub(crate) unsafe fn yaml_malloc(size: size_t) -> *mut libc::c_void {
     malloc(size)
@@ -40,6 +53,14 @@ pub(crate) unsafe fn yaml_free(ptr: *mut libc::c_void) {
     }
 }
 
+pub(crate) unsafe fn yaml_allocated(ptr: *mut libc::c_void) -> size_t {
+    if ptr.is_null() {
+        0
+    } else {
+        malloc_usable_size(ptr)
+    }
+}
+
 pub(crate) unsafe fn yaml_strdup(str: *const yaml_char_t) -> *mut yaml_char_t {
     if str.is_null() {
         return ptr::null_mut::<yaml_char_t>();
@@ -69,6 +90,57 @@ pub(crate) unsafe fn yaml_string_extend(
     *start = new_start;
 }
 
+/// Allocate the source text of a scalar in one block, to be freed with
+/// yaml_free().
+pub(crate) unsafe fn yaml_scalar_source_new(
+    text: *const yaml_char_t,
+    length: size_t,
+    value: *const yaml_char_t,
+    value_length: size_t,
+    style: yaml_scalar_style_t,
+) -> *mut yaml_scalar_source_t {
+    let size = (size_of::<yaml_scalar_source_t>() as size_t)
+        .force_add(length)
+        .force_add(value_length)
+        .force_add(2);
+    let source = yaml_malloc(size) as *mut yaml_scalar_source_t;
+    let text_copy = source.add(1) as *mut yaml_char_t;
+    let value_copy = text_copy.add(length as usize + 1);
+    memcpy(
+        text_copy as *mut libc::c_void,
+        text as *const libc::c_void,
+        length,
+    );
+    *text_copy.add(length as usize) = b'\0';
+    memcpy(
+        value_copy as *mut libc::c_void,
+        value as *const libc::c_void,
+        value_length,
+    );
+    *value_copy.add(value_length as usize) = b'\0';
+    (*source).text = text_copy;
+    (*source).length = length;
+    (*source).value = value_copy;
+    (*source).value_length = value_length;
+    (*source).style = style;
+    source
+}
+
+pub(crate) unsafe fn yaml_scalar_source_duplicate(
+    source: *const yaml_scalar_source_t,
+) -> *mut yaml_scalar_source_t {
+    if source.is_null() {
+        return ptr::null_mut::<yaml_scalar_source_t>();
+    }
+    yaml_scalar_source_new(
+        (*source).text,
+        (*source).length,
+        (*source).value,
+        (*source).value_length,
+        (*source).style,
+    )
+}
+
 pub(crate) unsafe fn yaml_string_join(
     a_start: *mut *mut yaml_char_t,
     a_pointer: *mut *mut yaml_char_t,
@@ -179,6 +251,7 @@ pub unsafe fn yaml_parser_initialize(parser: *mut yaml_parser_t) -> Success {
     STACK_INIT!((*parser).states, yaml_parser_state_t);
     STACK_INIT!((*parser).marks, yaml_mark_t);
     STACK_INIT!((*parser).tag_directives, yaml_tag_directive_t);
+    STACK_INIT!((*parser).comments, yaml_comment_t);
     OK
 }
 
@@ -201,6 +274,10 @@ pub unsafe fn yaml_parser_delete(parser: *mut yaml_parser_t) {
         yaml_free(tag_directive.prefix as *mut libc::c_void);
     }
     STACK_DEL!((*parser).tag_directives);
+    while !STACK_EMPTY!((*parser).comments) {
+        yaml_free(POP!((*parser).comments).value as *mut libc::c_void);
+    }
+    STACK_DEL!((*parser).comments);
     memset(
         parser as *mut libc::c_void,
         0,
@@ -271,6 +348,30 @@ pub unsafe fn yaml_parser_set_input_string(
     *fresh85 = input.wrapping_offset(size as isize);
 }
 
+/// Set a string input embedded in a larger text.
+///
+/// Works like yaml_parser_set_input_string(), but the marks produced by the
+/// parser are shifted by `index` bytes and `line` lines, and the marks on the
+/// first line by `column` columns, so that they point into the enclosing text.
+/// The embedded string is still scanned as if it started at the beginning of a
+/// line.
+pub unsafe fn yaml_parser_set_input_string_with_origin(
+    parser: *mut yaml_parser_t,
+    input: *const libc::c_uchar,
+    size: size_t,
+    index: size_t,
+    line: size_t,
+    column: size_t,
+) {
+    yaml_parser_set_input_string(parser, input, size);
+    (*parser).origin.index = index;
+    (*parser).origin.line = line;
+    (*parser).origin.column = column;
+    (*parser).mark.index = index;
+    (*parser).mark.line = line;
+    (*parser).consumed_end = (*parser).origin;
+}
+
 /// Set a generic input handler.
 pub unsafe fn yaml_parser_set_input(
     parser: *mut yaml_parser_t,
@@ -285,6 +386,94 @@ pub unsafe fn yaml_parser_set_input(
     *fresh90 = data;
 }
 
+/// Set an implicit tag resolver for plain scalars.
+pub unsafe fn yaml_parser_set_implicit_resolver(
+    parser: *mut yaml_parser_t,
+    resolver: yaml_implicit_resolver_t,
+    data: *mut libc::c_void,
+) {
+    __assert!(!parser.is_null());
+    let fresh92 = addr_of_mut!((*parser).implicit_resolver);
+    *fresh92 = Some(resolver);
+    let fresh93 = addr_of_mut!((*parser).implicit_resolver_data);
+    *fresh93 = data;
+}
+
+/// Resolve the tags of plain scalars with a schema.
+///
+/// This sets an implicit tag resolver that tags each plain scalar without an
+/// explicit tag as yaml_scalar_resolve() resolves it, so documents loaded by
+/// yaml_parser_load() carry `!!null`, `!!bool`, `!!int` and `!!float` tags
+/// instead of the default `!!str`. With the JSON schema, parsing fails on a
+/// plain scalar that is not a JSON literal. Note that yaml_emitter_dump()
+/// writes all tags but `!!str` explicitly.
+pub unsafe fn yaml_parser_set_schema(parser: *mut yaml_parser_t, schema: yaml_schema_t) {
+    let mut options = MaybeUninit::<yaml_schema_options_t>::uninit();
+    yaml_schema_options_initialize(options.as_mut_ptr(), schema);
+    yaml_parser_set_schema_options(parser, options.as_ptr());
+}
+
+/// Resolve the tags of plain scalars with a schema and the given settings.
+///
+/// This is yaml_parser_set_schema() with the settings of
+/// yaml_scalar_resolve_with_options(), which are copied into the parser. A
+/// plain scalar that does not resolve, as a number too large for its type does
+/// with YAML_OVERFLOW_ERROR, makes parsing fail at the mark of the scalar.
+pub unsafe fn yaml_parser_set_schema_options(
+    parser: *mut yaml_parser_t,
+    options: *const yaml_schema_options_t,
+) {
+    __assert!(!parser.is_null());
+    __assert!(!options.is_null());
+    (*parser).schema_options = *options;
+    yaml_parser_set_implicit_resolver(
+        parser,
+        yaml_schema_implicit_resolver,
+        addr_of_mut!((*parser).schema_options) as *mut libc::c_void,
+    );
+}
+
+/// Set if comments are kept in loaded documents.
+///
+/// With comment capture enabled, yaml_parser_load() attaches each comment to
+/// the nearest node of the document: a comment on the same line after a
+/// scalar becomes the trailing comment of the scalar, other comments become
+/// the leading comment of the node that follows them, and comments after the
+/// last node become the end comment of the document. yaml_emitter_dump()
+/// writes the comments back, except for the comments inside flow collections
+/// and before mapping values. Comments are not reported by
+/// yaml_parser_parse().
+pub unsafe fn yaml_parser_set_comments(parser: *mut yaml_parser_t, capture: bool) {
+    __assert!(!parser.is_null());
+    (*parser).capture_comments = capture;
+}
+
+/// Set if the source text of scalars is kept in loaded documents.
+///
+/// With the source kept, yaml_parser_load() stores the text each scalar was
+/// written as, with its quotes, escapes, block scalar header and line folding,
+/// in the `source` field of the node. yaml_emitter_dump() writes the text
+/// again instead of formatting the scalar anew as long as the value and the
+/// style of the node are unchanged, so the untouched parts of a document are
+/// written back as they were read. The text is only available for input set
+/// with yaml_parser_set_input_string() and UTF-8 encoded.
+pub unsafe fn yaml_parser_set_scalar_source(parser: *mut yaml_parser_t, keep: bool) {
+    __assert!(!parser.is_null());
+    (*parser).keep_scalar_source = keep;
+}
+
+/// Set if aliases are loaded as alias nodes.
+///
+/// By default yaml_parser_load() resolves an alias to the id of the anchored
+/// node, so the node is shared by every place it is referenced from. With alias
+/// nodes enabled, each alias is loaded as a YAML_ALIAS_NODE whose `target` is
+/// the anchored node, keeping apart the node and the places it is aliased
+/// from. yaml_emitter_dump() writes an alias node as an alias of its target.
+pub unsafe fn yaml_parser_set_alias_nodes(parser: *mut yaml_parser_t, enable: bool) {
+    __assert!(!parser.is_null());
+    (*parser).alias_nodes = enable;
+}
+
 /// Set the source encoding.
 pub unsafe fn yaml_parser_set_encoding(parser: *mut yaml_parser_t, encoding: yaml_encoding_t) {
     __assert!(!parser.is_null());
@@ -292,6 +481,44 @@ pub unsafe fn yaml_parser_set_encoding(parser: *mut yaml_parser_t, encoding: yam
     (*parser).encoding = encoding;
 }
 
+/// Get the source text between two marks.
+///
+/// Marks produced by the parser hold byte offsets into the UTF-8 text of the
+/// stream, not counting a byte order mark, so the slice can be taken directly
+/// from the input when it was set with yaml_parser_set_input_string() and is
+/// UTF-8 encoded. For any other input the function fails, as it does if the
+/// marks are out of order or past the end of the input.
+pub unsafe fn yaml_parser_source_slice(
+    parser: *mut yaml_parser_t,
+    start_mark: yaml_mark_t,
+    end_mark: yaml_mark_t,
+    slice: *mut *const yaml_char_t,
+    length: *mut size_t,
+) -> Success {
+    __assert!(!parser.is_null());
+    __assert!(!slice.is_null());
+    __assert!(!length.is_null());
+    if (*parser).input.string.start.is_null() || (*parser).encoding != YAML_UTF8_ENCODING {
+        return FAIL;
+    }
+    let start: *const yaml_char_t = if (*parser).bom {
+        (*parser).input.string.start.add(3)
+    } else {
+        (*parser).input.string.start
+    };
+    let size = (*parser).input.string.end.c_offset_from(start) as size_t;
+    let origin = (*parser).origin.index;
+    if start_mark.index < origin
+        || start_mark.index > end_mark.index
+        || end_mark.index - origin > size
+    {
+        return FAIL;
+    }
+    *slice = start.add((start_mark.index - origin) as usize);
+    *length = end_mark.index - start_mark.index;
+    OK
+}
+
 /// Initialize an emitter.
 ///
 /// This function creates a new emitter object. An application is responsible
@@ -309,6 +536,8 @@ pub unsafe fn yaml_emitter_initialize(emitter: *mut yaml_emitter_t) -> Success {
     QUEUE_INIT!((*emitter).events, yaml_event_t);
     STACK_INIT!((*emitter).indents, libc::c_int);
     STACK_INIT!((*emitter).tag_directives, yaml_tag_directive_t);
+    STACK_INIT!((*emitter).document_tag_directives, yaml_tag_directive_t);
+    STACK_INIT!((*emitter).emitted_anchors, *mut yaml_char_t);
     OK
 }
 
@@ -329,7 +558,22 @@ pub unsafe fn yaml_emitter_delete(emitter: *mut yaml_emitter_t) {
         yaml_free(tag_directive.prefix as *mut libc::c_void);
     }
     STACK_DEL!((*emitter).tag_directives);
+    while !STACK_EMPTY!((*emitter).document_tag_directives) {
+        let tag_directive = POP!((*emitter).document_tag_directives);
+        yaml_free(tag_directive.handle as *mut libc::c_void);
+        yaml_free(tag_directive.prefix as *mut libc::c_void);
+    }
+    STACK_DEL!((*emitter).document_tag_directives);
+    while !STACK_EMPTY!((*emitter).emitted_anchors) {
+        yaml_free(POP!((*emitter).emitted_anchors) as *mut libc::c_void);
+    }
+    STACK_DEL!((*emitter).emitted_anchors);
+    yaml_free((*emitter).scalar_key as *mut libc::c_void);
     yaml_free((*emitter).anchors as *mut libc::c_void);
+    yaml_free((*emitter).pending_comment as *mut libc::c_void);
+    if (*emitter).owned_output {
+        yaml_free((*emitter).output.string.buffer as *mut libc::c_void);
+    }
     memset(
         emitter as *mut libc::c_void,
         0,
@@ -337,6 +581,68 @@ pub unsafe fn yaml_emitter_delete(emitter: *mut yaml_emitter_t) {
     );
 }
 
+/// Reset an emitter to emit a new stream.
+///
+/// The emitter goes back to the state before the STREAM-START event or the
+/// yaml_emitter_open() call. Events not yet emitted and characters not yet
+/// flushed are discarded. The output and the settings of the emitter are
+/// kept, as are its allocated buffers.
+pub unsafe fn yaml_emitter_reset(emitter: *mut yaml_emitter_t) {
+    __assert!(!emitter.is_null());
+    (*emitter).error = YAML_NO_ERROR;
+    let fresh184 = addr_of_mut!((*emitter).problem);
+    *fresh184 = ptr::null::<libc::c_char>();
+    let fresh185 = addr_of_mut!((*emitter).buffer.pointer);
+    *fresh185 = (*emitter).buffer.start;
+    let fresh186 = addr_of_mut!((*emitter).buffer.last);
+    *fresh186 = (*emitter).buffer.start;
+    let fresh187 = addr_of_mut!((*emitter).raw_buffer.pointer);
+    *fresh187 = (*emitter).raw_buffer.start;
+    let fresh188 = addr_of_mut!((*emitter).raw_buffer.last);
+    *fresh188 = (*emitter).raw_buffer.start;
+    (*emitter).states.top = (*emitter).states.start;
+    while !QUEUE_EMPTY!((*emitter).events) {
+        yaml_event_delete(addr_of_mut!(DEQUEUE!((*emitter).events)));
+    }
+    (*emitter).events.head = (*emitter).events.start;
+    (*emitter).events.tail = (*emitter).events.start;
+    (*emitter).indents.top = (*emitter).indents.start;
+    while !STACK_EMPTY!((*emitter).tag_directives) {
+        let tag_directive = POP!((*emitter).tag_directives);
+        yaml_free(tag_directive.handle as *mut libc::c_void);
+        yaml_free(tag_directive.prefix as *mut libc::c_void);
+    }
+    while !STACK_EMPTY!((*emitter).emitted_anchors) {
+        yaml_free(POP!((*emitter).emitted_anchors) as *mut libc::c_void);
+    }
+    yaml_free((*emitter).scalar_key as *mut libc::c_void);
+    let fresh194 = addr_of_mut!((*emitter).scalar_key);
+    *fresh194 = ptr::null_mut::<yaml_char_t>();
+    (*emitter).scalar_key_length = 0;
+    yaml_free((*emitter).pending_comment as *mut libc::c_void);
+    let fresh195 = addr_of_mut!((*emitter).pending_comment);
+    *fresh195 = ptr::null_mut::<yaml_char_t>();
+    (*emitter).state = YAML_EMIT_STREAM_START_STATE;
+    (*emitter).indent = 0;
+    (*emitter).flow_level = 0;
+    (*emitter).line = 0;
+    (*emitter).column = 0;
+    (*emitter).bytes_written = 0;
+    (*emitter).lines_written = 0;
+    (*emitter).max_column = 0;
+    (*emitter).quoted_scalars = 0;
+    (*emitter).open_ended = 0;
+    (*emitter).document_index = 0;
+    (*emitter).opened = false;
+    (*emitter).closed = false;
+    yaml_free((*emitter).anchors as *mut libc::c_void);
+    let fresh189 = addr_of_mut!((*emitter).anchors);
+    *fresh189 = ptr::null_mut::<yaml_anchors_t>();
+    (*emitter).last_anchor_id = 0;
+    let fresh190 = addr_of_mut!((*emitter).document);
+    *fresh190 = ptr::null_mut::<yaml_document_t>();
+}
+
 unsafe fn yaml_string_write_handler(
     data: *mut libc::c_void,
     buffer: *mut libc::c_uchar,
@@ -365,6 +671,9 @@ unsafe fn yaml_string_write_handler(
                 .wrapping_sub(*(*emitter).output.string.size_written),
         );
         *(*emitter).output.string.size_written = (*emitter).output.string.size;
+        (*emitter).error = YAML_WRITER_ERROR;
+        let fresh191 = addr_of_mut!((*emitter).problem);
+        *fresh191 = b"output buffer is full\0" as *const u8 as *const libc::c_char;
         return 0;
     }
     memcpy(
@@ -386,8 +695,9 @@ unsafe fn yaml_string_write_handler(
 ///
 /// The emitter will write the output characters to the `output` buffer of the
 /// size `size`. The emitter will set `size_written` to the number of written
-/// bytes. If the buffer is smaller than required, the emitter produces the
-/// YAML_WRITE_ERROR error.
+/// bytes. If the buffer is smaller than required, the emitter fills it up and
+/// produces the YAML_WRITER_ERROR error with the problem "output buffer is
+/// full"; `size_written` is then the size of the buffer.
 pub unsafe fn yaml_emitter_set_output_string(
     emitter: *mut yaml_emitter_t,
     output: *mut libc::c_uchar,
@@ -412,6 +722,140 @@ pub unsafe fn yaml_emitter_set_output_string(
     *size_written = 0_u64;
 }
 
+unsafe fn yaml_owned_write_handler(
+    data: *mut libc::c_void,
+    buffer: *mut libc::c_uchar,
+    size: size_t,
+) -> libc::c_int {
+    let emitter: *mut yaml_emitter_t = data as *mut yaml_emitter_t;
+    let written: size_t = (*emitter).owned_output_size;
+    let needed: size_t = written.force_add(size).force_add(1);
+    if needed > (*emitter).output.string.size {
+        let mut capacity: size_t = (*emitter).output.string.size;
+        while capacity < needed {
+            capacity = capacity.force_mul(2);
+        }
+        let output = yaml_realloc(
+            (*emitter).output.string.buffer as *mut libc::c_void,
+            capacity,
+        ) as *mut libc::c_uchar;
+        if output.is_null() {
+            (*emitter).error = YAML_MEMORY_ERROR;
+            return 0;
+        }
+        let fresh0 = addr_of_mut!((*emitter).output.string.buffer);
+        *fresh0 = output;
+        (*emitter).output.string.size = capacity;
+    }
+    memcpy(
+        (*emitter).output.string.buffer.add(written as usize) as *mut libc::c_void,
+        buffer as *const libc::c_void,
+        size,
+    );
+    (*emitter).owned_output_size = written.force_add(size);
+    *(*emitter)
+        .output
+        .string
+        .buffer
+        .add((*emitter).owned_output_size as usize) = b'\0';
+    1
+}
+
+unsafe fn yaml_owned_output_initialize(emitter: *mut yaml_emitter_t) {
+    let output = yaml_malloc(OWNED_OUTPUT_SIZE as size_t) as *mut libc::c_uchar;
+    *output = b'\0';
+    let fresh0 = addr_of_mut!((*emitter).output.string.buffer);
+    *fresh0 = output;
+    (*emitter).output.string.size = OWNED_OUTPUT_SIZE as size_t;
+    (*emitter).owned_output_size = 0;
+}
+
+/// Set an output buffer owned by the emitter.
+///
+/// This is yaml_emitter_set_output_string() with a buffer that the emitter
+/// allocates and grows as needed. Retrieve the output characters with
+/// yaml_emitter_take_output() once the stream is finished or the emitter has
+/// been flushed.
+pub unsafe fn yaml_emitter_set_output_owned(emitter: *mut yaml_emitter_t) {
+    __assert!(!emitter.is_null());
+    __assert!(((*emitter).write_handler).is_none());
+    yaml_owned_output_initialize(emitter);
+    yaml_emitter_set_output_string(
+        emitter,
+        (*emitter).output.string.buffer,
+        OWNED_OUTPUT_SIZE as size_t,
+        addr_of_mut!((*emitter).owned_output_size),
+    );
+    let fresh158 = addr_of_mut!((*emitter).write_handler);
+    *fresh158 = Some(
+        yaml_owned_write_handler
+            as unsafe fn(*mut libc::c_void, *mut libc::c_uchar, size_t) -> libc::c_int,
+    );
+    (*emitter).owned_output = true;
+}
+
+#[cfg(feature = "async")]
+pub(crate) unsafe fn yaml_async_write_handler(
+    data: *mut libc::c_void,
+    _buffer: *mut libc::c_uchar,
+    _size: size_t,
+) -> libc::c_int {
+    if !data.is_null() {
+        *(data as *mut bool) = true;
+    }
+    -1
+}
+
+/// Set the output to be written to an asynchronous writer.
+///
+/// The emitter keeps the output characters in its own buffer instead of passing
+/// them to a write handler. Emit the events with yaml_emitter_emit_async(),
+/// which writes the characters to a tokio::io::AsyncWrite each time the
+/// emitter flushes its buffer. yaml_emitter_flush_async() writes whatever is
+/// buffered at any other time.
+#[cfg(feature = "async")]
+pub unsafe fn yaml_emitter_set_output_async(emitter: *mut yaml_emitter_t) {
+    __assert!(!emitter.is_null());
+    __assert!(((*emitter).write_handler).is_none());
+    let fresh161 = addr_of_mut!((*emitter).write_handler);
+    *fresh161 = Some(
+        yaml_async_write_handler
+            as unsafe fn(*mut libc::c_void, *mut libc::c_uchar, size_t) -> libc::c_int,
+    );
+    let fresh162 = addr_of_mut!((*emitter).write_handler_data);
+    *fresh162 = ptr::null_mut();
+}
+
+/// Take the output collected by the emitter.
+///
+/// `output` is set to the bytes written since the output was set, or since the
+/// previous call of this function, followed by a NUL byte, and `size` to their
+/// number, without the NUL byte. Only flushed output is included, which covers
+/// everything up to a STREAM-END event or a yaml_emitter_flush() call. The
+/// emitter continues with a new buffer.
+///
+/// The output must have been set with yaml_emitter_set_output_owned(). The
+/// application is responsible for freeing `output` with yaml_output_delete().
+pub unsafe fn yaml_emitter_take_output(
+    emitter: *mut yaml_emitter_t,
+    output: *mut *mut yaml_char_t,
+    size: *mut size_t,
+) {
+    __assert!(!emitter.is_null());
+    __assert!((*emitter).owned_output);
+    __assert!(!output.is_null());
+    __assert!(!size.is_null());
+    *output = (*emitter).output.string.buffer;
+    *size = (*emitter).owned_output_size;
+    yaml_owned_output_initialize(emitter);
+}
+
+/// Free the output taken with yaml_emitter_take_output() or made by
+/// yaml_document_dump_string().
+pub unsafe fn yaml_output_delete(output: *mut yaml_char_t) {
+    yaml_free(output as *mut libc::c_void);
+}
+
 /// Set a generic output handler.
 pub unsafe fn yaml_emitter_set_output(
     emitter: *mut yaml_emitter_t,
@@ -426,6 +870,19 @@ pub unsafe fn yaml_emitter_set_output(
     *fresh162 = data;
 }
 
+/// Set a document banner handler.
+pub unsafe fn yaml_emitter_set_document_banner(
+    emitter: *mut yaml_emitter_t,
+    handler: yaml_banner_handler_t,
+    data: *mut libc::c_void,
+) {
+    __assert!(!emitter.is_null());
+    let fresh163 = addr_of_mut!((*emitter).banner_handler);
+    *fresh163 = Some(handler);
+    let fresh164 = addr_of_mut!((*emitter).banner_handler_data);
+    *fresh164 = data;
+}
+
 /// Set the output encoding.
 pub unsafe fn yaml_emitter_set_encoding(emitter: *mut yaml_emitter_t, encoding: yaml_encoding_t) {
     __assert!(!emitter.is_null());
@@ -440,6 +897,115 @@ pub unsafe fn yaml_emitter_set_canonical(emitter: *mut yaml_emitter_t, canonical
     (*emitter).canonical = canonical;
 }
 
+/// Set the indentation increment of flow collections in the canonical format.
+///
+/// Values outside of 2..=9 fall back to the regular indentation increment.
+pub unsafe fn yaml_emitter_set_canonical_indent(emitter: *mut yaml_emitter_t, indent: libc::c_int) {
+    __assert!(!emitter.is_null());
+    (*emitter).canonical_indent = if 1 < indent && indent < 10 { indent } else { 0 };
+}
+
+/// Set if every document in the canonical format starts with a `%YAML`
+/// directive and a `%TAG` directive for the `!!` handle, even when the
+/// document start event has none.
+pub unsafe fn yaml_emitter_set_canonical_directives(
+    emitter: *mut yaml_emitter_t,
+    directives: bool,
+) {
+    __assert!(!emitter.is_null());
+    (*emitter).canonical_directives = directives;
+}
+
+/// Set if the tags of all nodes are written, even when they are implicit.
+///
+/// Tags are shortened with the tag directives of the document. Nodes that have
+/// no tag at all are still written without one.
+pub unsafe fn yaml_emitter_set_always_emit_tags(emitter: *mut yaml_emitter_t, always: bool) {
+    __assert!(!emitter.is_null());
+    (*emitter).always_emit_tags = always;
+}
+
+/// Set if tags are always written in the verbatim `!<...>` form.
+///
+/// By default a tag is written as `!handle!suffix` with the tag directive that
+/// has the longest matching prefix.
+pub unsafe fn yaml_emitter_set_verbatim_tags(emitter: *mut yaml_emitter_t, verbatim: bool) {
+    __assert!(!emitter.is_null());
+    (*emitter).verbatim_tags = verbatim;
+}
+
+/// Set the version directive written at the start of every document.
+///
+/// The version is 1.1 or 1.2, or 0.0 to write no directive. A version
+/// directive of the document start event takes precedence.
+pub unsafe fn yaml_emitter_set_version_directive(
+    emitter: *mut yaml_emitter_t,
+    major: libc::c_int,
+    minor: libc::c_int,
+) {
+    __assert!(!emitter.is_null());
+    __assert!(major == 0 && minor == 0 || major == 1 && (minor == 1 || minor == 2));
+    (*emitter).version_directive.major = major;
+    (*emitter).version_directive.minor = minor;
+}
+
+/// Add a tag directive written at the start of every document.
+///
+/// Tags that start with the `prefix` are written in the shorthand form with the
+/// `handle`. A tag directive of the document start event with the same handle
+/// takes precedence.
+pub unsafe fn yaml_emitter_add_tag_directive(
+    emitter: *mut yaml_emitter_t,
+    handle: *const yaml_char_t,
+    prefix: *const yaml_char_t,
+) {
+    __assert!(!emitter.is_null());
+    __assert!(!handle.is_null());
+    __assert!(!prefix.is_null());
+    let tag_directive = yaml_tag_directive_t {
+        handle: yaml_strdup(handle),
+        prefix: yaml_strdup(prefix),
+    };
+    PUSH!((*emitter).document_tag_directives, tag_directive);
+}
+
+/// Replace the tag directives written at the start of every document.
+///
+/// The directives from `tag_directives_start` to `tag_directives_end` are
+/// copied, as if each was passed to yaml_emitter_add_tag_directive(). An empty
+/// list removes the configured directives.
+pub unsafe fn yaml_emitter_set_tag_directives(
+    emitter: *mut yaml_emitter_t,
+    tag_directives_start: *const yaml_tag_directive_t,
+    tag_directives_end: *const yaml_tag_directive_t,
+) {
+    let mut tag_directive: *const yaml_tag_directive_t;
+    __assert!(!emitter.is_null());
+    __assert!(
+        !tag_directives_start.is_null() && !tag_directives_end.is_null()
+            || tag_directives_start == tag_directives_end
+    );
+    while !STACK_EMPTY!((*emitter).document_tag_directives) {
+        let tag_directive = POP!((*emitter).document_tag_directives);
+        yaml_free(tag_directive.handle as *mut libc::c_void);
+        yaml_free(tag_directive.prefix as *mut libc::c_void);
+    }
+    tag_directive = tag_directives_start;
+    while tag_directive != tag_directives_end {
+        yaml_emitter_add_tag_directive(emitter, (*tag_directive).handle, (*tag_directive).prefix);
+        tag_directive = tag_directive.add(1);
+    }
+}
+
+/// Set if aliases are checked to refer to an anchor emitted before them in
+/// the same document.
+///
+/// An alias to an unknown anchor then produces the YAML_EMITTER_ERROR error.
+pub unsafe fn yaml_emitter_set_check_aliases(emitter: *mut yaml_emitter_t, check: bool) {
+    __assert!(!emitter.is_null());
+    (*emitter).check_aliases = check;
+}
+
 /// Set the indentation increment.
 pub unsafe fn yaml_emitter_set_indent(emitter: *mut yaml_emitter_t, indent: libc::c_int) {
     __assert!(!emitter.is_null());
@@ -458,12 +1024,379 @@ pub unsafe fn yaml_emitter_set_unicode(emitter: *mut yaml_emitter_t, unicode: bo
     (*emitter).unicode = unicode;
 }
 
+/// Set a handler deciding for each scalar if its non-ASCII characters are
+/// escaped.
+///
+/// The handler overrides yaml_emitter_set_unicode() for scalars.
+pub unsafe fn yaml_emitter_set_unicode_handler(
+    emitter: *mut yaml_emitter_t,
+    handler: yaml_unicode_handler_t,
+    data: *mut libc::c_void,
+) {
+    __assert!(!emitter.is_null());
+    let fresh182 = addr_of_mut!((*emitter).unicode_handler);
+    *fresh182 = Some(handler);
+    let fresh183 = addr_of_mut!((*emitter).unicode_handler_data);
+    *fresh183 = data;
+}
+
+/// Set a handler that may replace the value of each scalar before it is
+/// written.
+pub unsafe fn yaml_emitter_set_scalar_handler(
+    emitter: *mut yaml_emitter_t,
+    handler: yaml_scalar_handler_t,
+    data: *mut libc::c_void,
+) {
+    __assert!(!emitter.is_null());
+    let fresh192 = addr_of_mut!((*emitter).scalar_handler);
+    *fresh192 = Some(handler);
+    let fresh193 = addr_of_mut!((*emitter).scalar_handler_data);
+    *fresh193 = data;
+}
+
+/// Set if the requested scalar styles are kept whenever they are legal.
+///
+/// By default a literal scalar with trailing spaces or spaces before a line
+/// break is written double-quoted. In strict mode it stays literal; the other
+/// styles are only downgraded when the value cannot be expressed in them.
+pub unsafe fn yaml_emitter_set_strict_styles(emitter: *mut yaml_emitter_t, strict: bool) {
+    __assert!(!emitter.is_null());
+    (*emitter).strict_styles = strict;
+}
+
+/// Set the style of scalars that are quoted because they cannot be written
+/// plain.
+///
+/// The style is either YAML_SINGLE_QUOTED_SCALAR_STYLE, the default, or
+/// YAML_DOUBLE_QUOTED_SCALAR_STYLE. Scalars that cannot be single-quoted are
+/// always double-quoted.
+pub unsafe fn yaml_emitter_set_quote_style(
+    emitter: *mut yaml_emitter_t,
+    style: yaml_scalar_style_t,
+) {
+    __assert!(!emitter.is_null());
+    __assert!(style == YAML_SINGLE_QUOTED_SCALAR_STYLE || style == YAML_DOUBLE_QUOTED_SCALAR_STYLE);
+    (*emitter).quote_style = style;
+}
+
+/// Set how plain and single-quoted scalars longer than the preferred width are
+/// broken into lines.
+pub unsafe fn yaml_emitter_set_wrap_policy(
+    emitter: *mut yaml_emitter_t,
+    policy: yaml_wrap_policy_t,
+) {
+    __assert!(!emitter.is_null());
+    (*emitter).wrap_policy = policy;
+}
+
+/// Set if double-quoted scalars are hard-wrapped with escaped line breaks so
+/// that no line exceeds the preferred width.
+pub unsafe fn yaml_emitter_set_strict_width(emitter: *mut yaml_emitter_t, strict: bool) {
+    __assert!(!emitter.is_null());
+    (*emitter).strict_width = strict;
+}
+
+/// Set if block collections nested in block collections start on the same line
+/// as the `-`, `?` or `:` indicator before them, as in `- key: value`.
+///
+/// This is the default. Otherwise they start on the next line.
+pub unsafe fn yaml_emitter_set_compact(emitter: *mut yaml_emitter_t, compact: bool) {
+    __assert!(!emitter.is_null());
+    (*emitter).expanded = !compact;
+}
+
+/// Set the number of spaces after the `-`, `?` and `:` block indicators.
+///
+/// Values outside of 1..=9 fall back to one space. A nested block collection
+/// on the same line as the indicator is placed by the indentation instead.
+pub unsafe fn yaml_emitter_set_indicator_spaces(emitter: *mut yaml_emitter_t, spaces: libc::c_int) {
+    __assert!(!emitter.is_null());
+    (*emitter).indicator_spaces = if 0 < spaces && spaces < 10 { spaces } else { 1 };
+}
+
+/// Set the length under which block collections are written in the flow style.
+///
+/// A sequence or mapping whose flow form (`[a, b]`, `{k: v}`) would be shorter
+/// than `threshold` characters is written in the flow style. Collections
+/// holding multiline or block scalars stay in the block style. 0 turns the
+/// heuristic off.
+pub unsafe fn yaml_emitter_set_compact_flow_threshold(
+    emitter: *mut yaml_emitter_t,
+    threshold: libc::c_int,
+) {
+    __assert!(!emitter.is_null());
+    (*emitter).compact_flow_threshold = if threshold > 0 { threshold } else { 0 };
+}
+
+/// Set if block sequences that are mapping values are indented.
+///
+/// By default such sequences are written indentless, with the `-` indicators
+/// in the same column as the mapping keys.
+pub unsafe fn yaml_emitter_set_indent_sequences(emitter: *mut yaml_emitter_t, indent: bool) {
+    __assert!(!emitter.is_null());
+    (*emitter).indent_sequences = indent;
+}
+
+/// Set if the pairs of each mapping are sorted by key in yaml_emitter_dump().
+///
+/// Scalar keys are ordered by their bytes and go before the other keys, which
+/// keep their original order. As the order of the nodes changes, an anchor
+/// name that more than one node of the document has is not kept, and the nodes
+/// that are referenced again get generated anchors.
+pub unsafe fn yaml_emitter_set_sort_keys(emitter: *mut yaml_emitter_t, sort: bool) {
+    __assert!(!emitter.is_null());
+    (*emitter).sort_keys = sort;
+}
+
+/// Set if yaml_emitter_dump() writes an anchor for every node, not only for
+/// the nodes that are referenced more than once.
+///
+/// Nodes without an anchor of their own get a generated `idNNN` anchor.
+pub unsafe fn yaml_emitter_set_anchor_all(emitter: *mut yaml_emitter_t, all: bool) {
+    __assert!(!emitter.is_null());
+    (*emitter).anchor_all = all;
+}
+
+/// Set if yaml_emitter_dump() emits a document without nodes as a document
+/// holding an empty scalar.
+///
+/// By default, as in libyaml, dumping a document without nodes closes the
+/// stream instead, so that no document can be dumped after it.
+pub unsafe fn yaml_emitter_set_empty_documents(emitter: *mut yaml_emitter_t, empty: bool) {
+    __assert!(!emitter.is_null());
+    (*emitter).empty_documents = empty;
+}
+
+/// Set how yaml_emitter_dump() handles duplicate mapping keys.
+///
+/// Two keys are the same if they are equal subtrees, leaving out styles, marks
+/// and anchors, so scalar keys need the same tag and value. The pairs of the
+/// document are not changed; the ones left out are only skipped.
+pub unsafe fn yaml_emitter_set_duplicate_keys(
+    emitter: *mut yaml_emitter_t,
+    duplicate_keys: yaml_duplicate_keys_t,
+) {
+    __assert!(!emitter.is_null());
+    (*emitter).duplicate_keys = duplicate_keys;
+}
+
+/// Set a mapping key comparator for yaml_emitter_dump().
+///
+/// The comparator replaces the default key order of
+/// yaml_emitter_set_sort_keys() and turns sorting on.
+pub unsafe fn yaml_emitter_set_key_comparator(
+    emitter: *mut yaml_emitter_t,
+    comparator: yaml_key_comparator_t,
+    data: *mut libc::c_void,
+) {
+    __assert!(!emitter.is_null());
+    (*emitter).sort_keys = true;
+    let fresh165 = addr_of_mut!((*emitter).key_comparator);
+    *fresh165 = Some(comparator);
+    let fresh166 = addr_of_mut!((*emitter).key_comparator_data);
+    *fresh166 = data;
+}
+
+/// Set when the document start marker `---` is written.
+pub unsafe fn yaml_emitter_set_document_start_marker(
+    emitter: *mut yaml_emitter_t,
+    marker: yaml_document_start_marker_t,
+) {
+    __assert!(!emitter.is_null());
+    (*emitter).document_start_marker = marker;
+}
+
+/// Set when the document end marker `...` is written.
+pub unsafe fn yaml_emitter_set_document_end_marker(
+    emitter: *mut yaml_emitter_t,
+    marker: yaml_document_end_marker_t,
+) {
+    __assert!(!emitter.is_null());
+    (*emitter).document_end_marker = marker;
+}
+
+/// Set if the line break after the last document is left out.
+///
+/// The break is still written when the last line of the document is a block
+/// scalar, as it belongs to the scalar value.
+pub unsafe fn yaml_emitter_set_omit_final_break(emitter: *mut yaml_emitter_t, omit: bool) {
+    __assert!(!emitter.is_null());
+    (*emitter).omit_final_break = omit;
+}
+
+/// Set if long comment lines are wrapped at the preferred width.
+pub unsafe fn yaml_emitter_set_wrap_comments(emitter: *mut yaml_emitter_t, wrap: bool) {
+    __assert!(!emitter.is_null());
+    (*emitter).wrap_comments = wrap;
+}
+
 /// Set the preferred line break.
 pub unsafe fn yaml_emitter_set_break(emitter: *mut yaml_emitter_t, line_break: yaml_break_t) {
     __assert!(!emitter.is_null());
     (*emitter).line_break = line_break;
 }
 
+/// Set the line break for line breaks inside scalars.
+///
+/// With YAML_ANY_BREAK, the default, line feeds inside scalars are written as
+/// the preferred line break and other line breaks are kept. Any other value is
+/// used for every line break inside a scalar, including CR LN pairs and the
+/// breaks added to fold long lines.
+pub unsafe fn yaml_emitter_set_scalar_break(
+    emitter: *mut yaml_emitter_t,
+    line_break: yaml_break_t,
+) {
+    __assert!(!emitter.is_null());
+    (*emitter).scalar_break = line_break;
+}
+
+/// Set if line breaks inside scalars are written exactly as they are.
+///
+/// This takes precedence over yaml_emitter_set_scalar_break().
+pub unsafe fn yaml_emitter_set_preserve_scalar_breaks(
+    emitter: *mut yaml_emitter_t,
+    preserve: bool,
+) {
+    __assert!(!emitter.is_null());
+    (*emitter).preserve_scalar_breaks = preserve;
+}
+
+/// Get the features compiled into the library.
+pub unsafe fn yaml_get_capabilities(capabilities: *mut yaml_capabilities_t) {
+    __assert!(!capabilities.is_null());
+    memset(
+        capabilities as *mut libc::c_void,
+        0,
+        size_of::<yaml_capabilities_t>() as libc::c_ulong,
+    );
+    (*capabilities).utf16 = true;
+    (*capabilities).schemas = true;
+    (*capabilities).comments = true;
+    (*capabilities).serde = cfg!(feature = "serde");
+    (*capabilities).serde_json = cfg!(feature = "serde_json");
+    (*capabilities).yaml_rust2 = cfg!(feature = "yaml-rust2");
+    (*capabilities).miette = cfg!(feature = "miette");
+    (*capabilities).async_output = cfg!(feature = "async");
+    #[cfg(feature = "serde")]
+    {
+        (*capabilities).recursion_limit = RECURSION_LIMIT as size_t;
+    }
+    (*capabilities).simple_key_length = MAX_SIMPLE_KEY_LENGTH;
+}
+
+/// Initialize an emitter configuration with the defaults of a new emitter.
+pub unsafe fn yaml_emitter_options_initialize(options: *mut yaml_emitter_options_t) {
+    __assert!(!options.is_null());
+    memset(
+        options as *mut libc::c_void,
+        0,
+        size_of::<yaml_emitter_options_t>() as libc::c_ulong,
+    );
+    (*options).indent = 2;
+    (*options).width = 80;
+    (*options).quote_style = YAML_SINGLE_QUOTED_SCALAR_STYLE;
+    (*options).compact = true;
+    (*options).indicator_spaces = 1;
+}
+
+/// Get the configuration of an emitter.
+///
+/// The handlers, the key comparator and the tag directives are not part of the
+/// configuration.
+pub unsafe fn yaml_emitter_get_options(
+    emitter: *const yaml_emitter_t,
+    options: *mut yaml_emitter_options_t,
+) {
+    __assert!(!emitter.is_null());
+    yaml_emitter_options_initialize(options);
+    (*options).encoding = (*emitter).encoding;
+    (*options).canonical = (*emitter).canonical;
+    (*options).canonical_indent = (*emitter).canonical_indent;
+    (*options).canonical_directives = (*emitter).canonical_directives;
+    (*options).always_emit_tags = (*emitter).always_emit_tags;
+    (*options).verbatim_tags = (*emitter).verbatim_tags;
+    (*options).version_directive = (*emitter).version_directive;
+    (*options).check_aliases = (*emitter).check_aliases;
+    if 1 < (*emitter).best_indent && (*emitter).best_indent < 10 {
+        (*options).indent = (*emitter).best_indent;
+    }
+    if (*emitter).best_width != 0 {
+        (*options).width = (*emitter).best_width;
+    }
+    (*options).unicode = (*emitter).unicode;
+    (*options).strict_styles = (*emitter).strict_styles;
+    if (*emitter).quote_style == YAML_DOUBLE_QUOTED_SCALAR_STYLE {
+        (*options).quote_style = YAML_DOUBLE_QUOTED_SCALAR_STYLE;
+    }
+    (*options).wrap_policy = (*emitter).wrap_policy;
+    (*options).strict_width = (*emitter).strict_width;
+    (*options).compact = !(*emitter).expanded;
+    if (*emitter).indicator_spaces > 0 {
+        (*options).indicator_spaces = (*emitter).indicator_spaces;
+    }
+    (*options).compact_flow_threshold = (*emitter).compact_flow_threshold;
+    (*options).indent_sequences = (*emitter).indent_sequences;
+    (*options).sort_keys = (*emitter).sort_keys;
+    (*options).anchor_all = (*emitter).anchor_all;
+    (*options).empty_documents = (*emitter).empty_documents;
+    (*options).duplicate_keys = (*emitter).duplicate_keys;
+    (*options).document_start_marker = (*emitter).document_start_marker;
+    (*options).document_end_marker = (*emitter).document_end_marker;
+    (*options).omit_final_break = (*emitter).omit_final_break;
+    (*options).wrap_comments = (*emitter).wrap_comments;
+    (*options).line_break = (*emitter).line_break;
+    (*options).scalar_break = (*emitter).scalar_break;
+    (*options).preserve_scalar_breaks = (*emitter).preserve_scalar_breaks;
+}
+
+/// Configure an emitter in one call.
+///
+/// This is the same as passing each option to its `yaml_emitter_set_`
+/// function. The encoding is only set if it is not YAML_ANY_ENCODING.
+pub unsafe fn yaml_emitter_set_options(
+    emitter: *mut yaml_emitter_t,
+    options: *const yaml_emitter_options_t,
+) {
+    __assert!(!emitter.is_null());
+    __assert!(!options.is_null());
+    if (*options).encoding != YAML_ANY_ENCODING {
+        yaml_emitter_set_encoding(emitter, (*options).encoding);
+    }
+    yaml_emitter_set_canonical(emitter, (*options).canonical);
+    yaml_emitter_set_canonical_indent(emitter, (*options).canonical_indent);
+    yaml_emitter_set_canonical_directives(emitter, (*options).canonical_directives);
+    yaml_emitter_set_always_emit_tags(emitter, (*options).always_emit_tags);
+    yaml_emitter_set_verbatim_tags(emitter, (*options).verbatim_tags);
+    yaml_emitter_set_version_directive(
+        emitter,
+        (*options).version_directive.major,
+        (*options).version_directive.minor,
+    );
+    yaml_emitter_set_check_aliases(emitter, (*options).check_aliases);
+    yaml_emitter_set_indent(emitter, (*options).indent);
+    yaml_emitter_set_width(emitter, (*options).width);
+    yaml_emitter_set_unicode(emitter, (*options).unicode);
+    yaml_emitter_set_strict_styles(emitter, (*options).strict_styles);
+    yaml_emitter_set_quote_style(emitter, (*options).quote_style);
+    yaml_emitter_set_wrap_policy(emitter, (*options).wrap_policy);
+    yaml_emitter_set_strict_width(emitter, (*options).strict_width);
+    yaml_emitter_set_compact(emitter, (*options).compact);
+    yaml_emitter_set_indicator_spaces(emitter, (*options).indicator_spaces);
+    yaml_emitter_set_compact_flow_threshold(emitter, (*options).compact_flow_threshold);
+    yaml_emitter_set_indent_sequences(emitter, (*options).indent_sequences);
+    yaml_emitter_set_sort_keys(emitter, (*options).sort_keys);
+    yaml_emitter_set_anchor_all(emitter, (*options).anchor_all);
+    yaml_emitter_set_empty_documents(emitter, (*options).empty_documents);
+    yaml_emitter_set_duplicate_keys(emitter, (*options).duplicate_keys);
+    yaml_emitter_set_document_start_marker(emitter, (*options).document_start_marker);
+    yaml_emitter_set_document_end_marker(emitter, (*options).document_end_marker);
+    yaml_emitter_set_omit_final_break(emitter, (*options).omit_final_break);
+    yaml_emitter_set_wrap_comments(emitter, (*options).wrap_comments);
+    yaml_emitter_set_break(emitter, (*options).line_break);
+    yaml_emitter_set_scalar_break(emitter, (*options).scalar_break);
+    yaml_emitter_set_preserve_scalar_breaks(emitter, (*options).preserve_scalar_breaks);
+}
+
 /// Free any memory allocated for a token object.
 pub unsafe fn yaml_token_delete(token: *mut yaml_token_t) {
     __assert!(!token.is_null());
@@ -494,7 +1427,7 @@ pub unsafe fn yaml_token_delete(token: *mut yaml_token_t) {
     );
 }
 
-unsafe fn yaml_check_utf8(start: *const yaml_char_t, length: size_t) -> Success {
+pub(crate) unsafe fn yaml_check_utf8(start: *const yaml_char_t, length: size_t) -> Success {
     let end: *const yaml_char_t = start.wrapping_offset(length as isize);
     let mut pointer: *const yaml_char_t = start;
     while pointer < end {
@@ -1074,6 +2007,7 @@ pub unsafe fn yaml_event_delete(event: *mut yaml_event_t) {
             yaml_free((*event).data.scalar.anchor as *mut libc::c_void);
             yaml_free((*event).data.scalar.tag as *mut libc::c_void);
             yaml_free((*event).data.scalar.value as *mut libc::c_void);
+            yaml_free((*event).data.scalar.source as *mut libc::c_void);
         }
         YAML_SEQUENCE_START_EVENT => {
             yaml_free((*event).data.sequence_start.anchor as *mut libc::c_void);
@@ -1085,6 +2019,8 @@ pub unsafe fn yaml_event_delete(event: *mut yaml_event_t) {
         }
         _ => {}
     }
+    yaml_free((*event).leading_comment as *mut libc::c_void);
+    yaml_free((*event).trailing_comment as *mut libc::c_void);
     memset(
         event as *mut libc::c_void,
         0,
@@ -1232,9 +2168,13 @@ pub unsafe fn yaml_document_delete(document: *mut yaml_document_t) {
     while !STACK_EMPTY!((*document).nodes) {
         let mut node = POP!((*document).nodes);
         yaml_free(node.tag as *mut libc::c_void);
+        yaml_free(node.anchor as *mut libc::c_void);
+        yaml_free(node.leading_comment as *mut libc::c_void);
+        yaml_free(node.trailing_comment as *mut libc::c_void);
         match node.type_ {
             YAML_SCALAR_NODE => {
                 yaml_free(node.data.scalar.value as *mut libc::c_void);
+                yaml_free(node.data.scalar.source as *mut libc::c_void);
             }
             YAML_SEQUENCE_NODE => {
                 STACK_DEL!(node.data.sequence.items);
@@ -1242,6 +2182,7 @@ pub unsafe fn yaml_document_delete(document: *mut yaml_document_t) {
             YAML_MAPPING_NODE => {
                 STACK_DEL!(node.data.mapping.pairs);
             }
+            YAML_ALIAS_NODE => {}
             _ => {
                 __assert!(false);
             }
@@ -1256,6 +2197,7 @@ pub unsafe fn yaml_document_delete(document: *mut yaml_document_t) {
         tag_directive = tag_directive.wrapping_offset(1);
     }
     yaml_free((*document).tag_directives.start as *mut libc::c_void);
+    yaml_free((*document).end_comment as *mut libc::c_void);
     memset(
         document as *mut libc::c_void,
         0,
@@ -1263,11 +2205,53 @@ pub unsafe fn yaml_document_delete(document: *mut yaml_document_t) {
     );
 }
 
+/// Create an empty stream of documents.
+///
+/// The documents are added with yaml_stream_append_document().
+pub unsafe fn yaml_stream_initialize(stream: *mut yaml_stream_t, encoding: yaml_encoding_t) {
+    __assert!(!stream.is_null());
+    memset(
+        stream as *mut libc::c_void,
+        0,
+        size_of::<yaml_stream_t>() as libc::c_ulong,
+    );
+    STACK_INIT!((*stream).documents, yaml_document_t);
+    (*stream).encoding = encoding;
+    (*stream).line_break = YAML_ANY_BREAK;
+}
+
+/// Delete a stream and all its documents.
+pub unsafe fn yaml_stream_delete(stream: *mut yaml_stream_t) {
+    __assert!(!stream.is_null());
+    while !STACK_EMPTY!((*stream).documents) {
+        let mut document = POP!((*stream).documents);
+        yaml_document_delete(&mut document);
+    }
+    STACK_DEL!((*stream).documents);
+}
+
+/// Add a document to the end of a stream.
+///
+/// The stream takes the responsibility for the document, which is deleted by
+/// yaml_stream_delete().
+pub unsafe fn yaml_stream_append_document(
+    stream: *mut yaml_stream_t,
+    document: *mut yaml_document_t,
+) -> Success {
+    __assert!(!stream.is_null());
+    __assert!(!document.is_null());
+    PUSH!((*stream).documents, *document);
+    OK
+}
+
 /// Get a node of a YAML document.
 ///
 /// The pointer returned by this function is valid until any of the functions
 /// modifying the documents are called.
 ///
+/// The ids of the nodes start at 1. yaml_document_nodes() gives the nodes as a
+/// slice indexed from 0.
+///
 /// Returns the node objct or NULL if `node_id` is out of range.
 pub unsafe fn yaml_document_get_node(
     document: *mut yaml_document_t,
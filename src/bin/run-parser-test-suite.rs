@@ -22,17 +22,13 @@ use std::fmt::Write as _;
 use std::fs::File;
 use std::io::{self, Read, Write};
 use std::mem::MaybeUninit;
-use std::process::{self, ExitCode};
+use std::process::ExitCode;
 use std::ptr::addr_of_mut;
 use std::slice;
 use unsafe_libyaml::{
-    yaml_event_delete, yaml_event_t, yaml_event_type_t, yaml_parser_delete, yaml_parser_initialize,
-    yaml_parser_parse, yaml_parser_set_input, yaml_parser_t, YAML_ALIAS_EVENT,
-    YAML_DOCUMENT_END_EVENT, YAML_DOCUMENT_START_EVENT, YAML_DOUBLE_QUOTED_SCALAR_STYLE,
-    YAML_FOLDED_SCALAR_STYLE, YAML_LITERAL_SCALAR_STYLE, YAML_MAPPING_END_EVENT,
-    YAML_MAPPING_START_EVENT, YAML_NO_EVENT, YAML_PLAIN_SCALAR_STYLE, YAML_SCALAR_EVENT,
-    YAML_SEQUENCE_END_EVENT, YAML_SEQUENCE_START_EVENT, YAML_SINGLE_QUOTED_SCALAR_STYLE,
-    YAML_STREAM_END_EVENT, YAML_STREAM_START_EVENT,
+    yaml_event_delete, yaml_event_format, yaml_event_t, yaml_event_type_t, yaml_parser_delete,
+    yaml_parser_initialize, yaml_parser_parse, yaml_parser_set_input, yaml_parser_t,
+    YAML_STREAM_END_EVENT,
 };
 
 pub(crate) unsafe fn unsafe_main(
@@ -64,6 +60,16 @@ pub(crate) unsafe fn unsafe_main(
 
     yaml_parser_set_input(parser, read_from_stdio, addr_of_mut!(stdin).cast());
 
+    unsafe fn write_to_stdio(data: *mut c_void, buffer: *mut u8, size: u64) -> i32 {
+        let stdout: *mut &mut dyn Write = data.cast();
+        let slice = slice::from_raw_parts(buffer, size as usize);
+        match (*stdout).write_all(slice) {
+            Ok(()) => 1,
+            Err(_) => 0,
+        }
+    }
+
+    let mut stdout = stdout;
     let mut event = MaybeUninit::<yaml_event_t>::uninit();
     let event = event.as_mut_ptr();
     loop {
@@ -82,101 +88,8 @@ pub(crate) unsafe fn unsafe_main(
         }
 
         let type_: yaml_event_type_t = (*event).type_;
-        if type_ == YAML_NO_EVENT {
-            let _ = writeln!(stdout, "???");
-        } else if type_ == YAML_STREAM_START_EVENT {
-            let _ = writeln!(stdout, "+STR");
-        } else if type_ == YAML_STREAM_END_EVENT {
-            let _ = writeln!(stdout, "-STR");
-        } else if type_ == YAML_DOCUMENT_START_EVENT {
-            let _ = write!(stdout, "+DOC");
-            if !(*event).data.document_start.implicit {
-                let _ = write!(stdout, " ---");
-            }
-            let _ = writeln!(stdout);
-        } else if type_ == YAML_DOCUMENT_END_EVENT {
-            let _ = write!(stdout, "-DOC");
-            if !(*event).data.document_end.implicit {
-                let _ = write!(stdout, " ...");
-            }
-            let _ = writeln!(stdout);
-        } else if type_ == YAML_MAPPING_START_EVENT {
-            let _ = write!(stdout, "+MAP");
-            if !(*event).data.mapping_start.anchor.is_null() {
-                let _ = write!(
-                    stdout,
-                    " &{}",
-                    CStr::from_ptr((*event).data.mapping_start.anchor as *const i8),
-                );
-            }
-            if !(*event).data.mapping_start.tag.is_null() {
-                let _ = write!(
-                    stdout,
-                    " <{}>",
-                    CStr::from_ptr((*event).data.mapping_start.tag as *const i8),
-                );
-            }
-            let _ = writeln!(stdout);
-        } else if type_ == YAML_MAPPING_END_EVENT {
-            let _ = writeln!(stdout, "-MAP");
-        } else if type_ == YAML_SEQUENCE_START_EVENT {
-            let _ = write!(stdout, "+SEQ");
-            if !(*event).data.sequence_start.anchor.is_null() {
-                let _ = write!(
-                    stdout,
-                    " &{}",
-                    CStr::from_ptr((*event).data.sequence_start.anchor as *const i8),
-                );
-            }
-            if !(*event).data.sequence_start.tag.is_null() {
-                let _ = write!(
-                    stdout,
-                    " <{}>",
-                    CStr::from_ptr((*event).data.sequence_start.tag as *const i8),
-                );
-            }
-            let _ = writeln!(stdout);
-        } else if type_ == YAML_SEQUENCE_END_EVENT {
-            let _ = writeln!(stdout, "-SEQ");
-        } else if type_ == YAML_SCALAR_EVENT {
-            let _ = write!(stdout, "=VAL");
-            if !(*event).data.scalar.anchor.is_null() {
-                let _ = write!(
-                    stdout,
-                    " &{}",
-                    CStr::from_ptr((*event).data.scalar.anchor as *const i8),
-                );
-            }
-            if !(*event).data.scalar.tag.is_null() {
-                let _ = write!(
-                    stdout,
-                    " <{}>",
-                    CStr::from_ptr((*event).data.scalar.tag as *const i8),
-                );
-            }
-            let _ = stdout.write_all(match (*event).data.scalar.style {
-                YAML_PLAIN_SCALAR_STYLE => b" :",
-                YAML_SINGLE_QUOTED_SCALAR_STYLE => b" '",
-                YAML_DOUBLE_QUOTED_SCALAR_STYLE => b" \"",
-                YAML_LITERAL_SCALAR_STYLE => b" |",
-                YAML_FOLDED_SCALAR_STYLE => b" >",
-                _ => process::abort(),
-            });
-            print_escaped(
-                stdout,
-                (*event).data.scalar.value,
-                (*event).data.scalar.length,
-            );
-            let _ = writeln!(stdout);
-        } else if type_ == YAML_ALIAS_EVENT {
-            let _ = writeln!(
-                stdout,
-                "=ALI *{}",
-                CStr::from_ptr((*event).data.alias.anchor as *const i8),
-            );
-        } else {
-            process::abort();
-        }
+        let _ = yaml_event_format(event, write_to_stdio, addr_of_mut!(stdout).cast());
+        let _ = writeln!(stdout);
 
         yaml_event_delete(event);
         if type_ == YAML_STREAM_END_EVENT {
@@ -187,23 +100,6 @@ pub(crate) unsafe fn unsafe_main(
     Ok(())
 }
 
-unsafe fn print_escaped(stdout: &mut dyn Write, mut str: *mut u8, length: u64) {
-    let end = str.offset(length as isize);
-    while str < end {
-        let repr = match &*str {
-            b'\\' => b"\\\\",
-            b'\0' => b"\\0",
-            b'\x08' => b"\\b",
-            b'\n' => b"\\n",
-            b'\r' => b"\\r",
-            b'\t' => b"\\t",
-            c => slice::from_ref(c),
-        };
-        let _ = stdout.write_all(repr);
-        str = str.offset(1);
-    }
-}
-
 fn main() -> ExitCode {
     let args = env::args_os().skip(1);
     if args.len() == 0 {
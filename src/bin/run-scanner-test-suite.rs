@@ -0,0 +1,130 @@
+#![warn(clippy::pedantic)]
+#![allow(
+    clippy::cast_lossless,
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap,
+    clippy::cast_sign_loss,
+    clippy::items_after_statements,
+    clippy::let_underscore_untyped,
+    clippy::missing_errors_doc,
+    clippy::missing_safety_doc,
+    clippy::too_many_lines,
+    clippy::uninlined_format_args
+)]
+
+mod cstr;
+
+use self::cstr::CStr;
+use std::env;
+use std::error::Error;
+use std::ffi::c_void;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::mem::MaybeUninit;
+use std::process::ExitCode;
+use std::ptr::addr_of_mut;
+use std::slice;
+use unsafe_libyaml::{
+    yaml_parser_delete, yaml_parser_initialize, yaml_parser_scan, yaml_parser_set_input,
+    yaml_parser_t, yaml_token_delete, yaml_token_format, yaml_token_t, yaml_token_type_t,
+    YAML_STREAM_END_TOKEN,
+};
+
+pub(crate) unsafe fn unsafe_main(
+    mut stdin: &mut dyn Read,
+    stdout: &mut dyn Write,
+) -> Result<(), Box<dyn Error>> {
+    let mut parser = MaybeUninit::<yaml_parser_t>::uninit();
+    let parser = parser.as_mut_ptr();
+    if yaml_parser_initialize(parser).fail {
+        return Err("Could not initialize the parser object".into());
+    }
+
+    unsafe fn read_from_stdio(
+        data: *mut c_void,
+        buffer: *mut u8,
+        size: u64,
+        size_read: *mut u64,
+    ) -> i32 {
+        let stdin: *mut &mut dyn Read = data.cast();
+        let slice = slice::from_raw_parts_mut(buffer.cast(), size as usize);
+        match (*stdin).read(slice) {
+            Ok(n) => {
+                *size_read = n as u64;
+                1
+            }
+            Err(_) => 0,
+        }
+    }
+
+    yaml_parser_set_input(parser, read_from_stdio, addr_of_mut!(stdin).cast());
+
+    unsafe fn write_to_vec(data: *mut c_void, buffer: *mut u8, size: u64) -> i32 {
+        let line: *mut Vec<u8> = data.cast();
+        (*line).extend_from_slice(slice::from_raw_parts(buffer, size as usize));
+        1
+    }
+
+    let mut token = MaybeUninit::<yaml_token_t>::uninit();
+    let token = token.as_mut_ptr();
+    let mut line = Vec::new();
+    let mut entry = Vec::new();
+    loop {
+        if yaml_parser_scan(parser, token).fail {
+            let error = scan_error(&*parser);
+            yaml_parser_delete(parser);
+            return Err(error.into());
+        }
+
+        let type_: yaml_token_type_t = (*token).type_;
+        entry.clear();
+        let _ = yaml_token_format(token, write_to_vec, addr_of_mut!(entry).cast());
+        if !entry.is_empty() {
+            if !line.is_empty() {
+                line.push(b' ');
+            }
+            line.extend_from_slice(&entry);
+        }
+
+        yaml_token_delete(token);
+        if type_ == YAML_STREAM_END_TOKEN {
+            break;
+        }
+    }
+    line.push(b'\n');
+    let _ = stdout.write_all(&line);
+    yaml_parser_delete(parser);
+    Ok(())
+}
+
+unsafe fn scan_error(parser: &yaml_parser_t) -> String {
+    let mut error = format!("Scan error: {}", CStr::from_ptr(parser.problem));
+    if parser.problem_mark.line != 0 || parser.problem_mark.column != 0 {
+        let _ = write!(
+            error,
+            "\nLine: {} Column: {}",
+            (parser.problem_mark.line).wrapping_add(1_u64),
+            (parser.problem_mark.column).wrapping_add(1_u64),
+        );
+    }
+    error
+}
+
+fn main() -> ExitCode {
+    let args = env::args_os().skip(1);
+    if args.len() == 0 {
+        let _ = writeln!(io::stderr(), "Usage: run-scanner-test-suite <in.yaml>...");
+        return ExitCode::FAILURE;
+    }
+    for arg in args {
+        let mut stdin = File::open(arg).unwrap();
+        let mut stdout = io::stdout();
+        let result = unsafe { unsafe_main(&mut stdin, &mut stdout) };
+        if let Err(err) = result {
+            let _ = writeln!(io::stderr(), "{}", err);
+            return ExitCode::FAILURE;
+        }
+    }
+    ExitCode::SUCCESS
+}
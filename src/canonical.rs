@@ -0,0 +1,267 @@
+use crate::api::{
+    yaml_document_add_mapping, yaml_document_add_scalar, yaml_document_add_sequence,
+    yaml_document_append_mapping_pair, yaml_document_append_sequence_item, yaml_document_delete,
+    yaml_document_initialize,
+};
+use crate::externs::{memset, strcmp};
+use crate::schema::{yaml_scalar_node_resolve, yaml_scalar_resolve};
+use crate::success::{Success, FAIL, OK};
+use crate::yaml::yaml_char_t;
+use crate::{
+    libc, yaml_canonicalize_options_t, yaml_document_t, yaml_node_pair_t, yaml_node_t,
+    yaml_resolved_scalar_t, PointerExt, YAML_ALIAS_NODE, YAML_ANY_MAPPING_STYLE,
+    YAML_ANY_SCALAR_STYLE, YAML_ANY_SEQUENCE_STYLE, YAML_BOOL_VALUE, YAML_CORE_SCHEMA,
+    YAML_DOUBLE_QUOTED_SCALAR_STYLE, YAML_FLOAT_VALUE, YAML_INT_VALUE, YAML_MAPPING_NODE,
+    YAML_NULL_VALUE, YAML_SCALAR_NODE, YAML_SEQUENCE_NODE, YAML_STR_VALUE,
+};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::mem::{size_of, MaybeUninit};
+use core::ptr;
+use core::slice;
+
+struct yaml_canonicalize_ctx_t {
+    document: *mut yaml_document_t,
+    canonical: *mut yaml_document_t,
+    options: yaml_canonicalize_options_t,
+    /// The copy of each node, while it is being copied or if aliases are kept.
+    copies: Vec<libc::c_int>,
+}
+
+/// Initialize the options of yaml_document_canonicalize() to expand aliases.
+pub unsafe fn yaml_canonicalize_options_initialize(options: *mut yaml_canonicalize_options_t) {
+    __assert!(!options.is_null());
+    memset(
+        options as *mut libc::c_void,
+        0,
+        size_of::<yaml_canonicalize_options_t>() as libc::c_ulong,
+    );
+}
+
+/// Turn a document into its normal form.
+///
+/// Two documents with the same content have the same normal form, which makes
+/// it suitable for hashing and comparing documents:
+///
+/// - The plain scalars tagged as strings by the loader are resolved with the
+///   core schema, and the scalars of the core schema types are given their
+///   canonical tag and value, such as `!!int 31` for `0x1F` and `!!bool true`
+///   for `True`.
+/// - The pairs of mappings are sorted by their scalar keys, which come before
+///   the other keys.
+/// - Unless `keep_aliases` is set, a node referenced more than once is copied
+///   for every reference, except for the references that make a cycle.
+/// - The styles, anchors, comments, marks and source text of the nodes are
+///   dropped, except that the strings that would be read as another type if
+///   they were plain are double-quoted. Each node records the transformation
+///   "canonicalize" and the id of the node it was made from.
+///
+/// Expanding the aliases of a document can make it exponentially larger. If
+/// `options` is NULL, the options set by yaml_canonicalize_options_initialize()
+/// are used. The document is left unchanged if the function fails.
+pub unsafe fn yaml_document_canonicalize(
+    document: *mut yaml_document_t,
+    options: *const yaml_canonicalize_options_t,
+) -> Success {
+    __assert!(!document.is_null());
+    let mut ctx = yaml_canonicalize_ctx_t {
+        document,
+        canonical: ptr::null_mut::<yaml_document_t>(),
+        options: MaybeUninit::zeroed().assume_init(),
+        copies: Vec::new(),
+    };
+    if options.is_null() {
+        yaml_canonicalize_options_initialize(&mut ctx.options);
+    } else {
+        ctx.options = *options;
+    }
+    let nodes = (*document).nodes.top.c_offset_from((*document).nodes.start) as usize;
+    if nodes == 0 {
+        return OK;
+    }
+    let mut canonical = MaybeUninit::<yaml_document_t>::uninit();
+    ctx.canonical = canonical.as_mut_ptr();
+    if yaml_document_initialize(
+        ctx.canonical,
+        (*document).version_directive,
+        (*document).tag_directives.start,
+        (*document).tag_directives.end,
+        (*document).start_implicit,
+        (*document).end_implicit,
+    )
+    .fail
+    {
+        return FAIL;
+    }
+    ctx.copies = vec![0; nodes];
+    if yaml_canonicalize_node(&mut ctx, 1) == 0 {
+        yaml_document_delete(ctx.canonical);
+        return FAIL;
+    }
+    yaml_document_delete(document);
+    *document = *ctx.canonical;
+    OK
+}
+
+unsafe fn yaml_canonicalize_node(
+    ctx: &mut yaml_canonicalize_ctx_t,
+    mut id: libc::c_int,
+) -> libc::c_int {
+    let mut node: *mut yaml_node_t = (*ctx.document).nodes.start.add(id as usize - 1);
+    while (*node).type_ == YAML_ALIAS_NODE {
+        id = (*node).data.alias.target;
+        node = (*ctx.document).nodes.start.add(id as usize - 1);
+    }
+    let copy = ctx.copies[id as usize - 1];
+    if copy != 0 {
+        return copy;
+    }
+    let copy = match (*node).type_ {
+        YAML_SCALAR_NODE => yaml_canonicalize_scalar(ctx.canonical, node),
+        YAML_SEQUENCE_NODE => {
+            let copy =
+                yaml_document_add_sequence(ctx.canonical, (*node).tag, YAML_ANY_SEQUENCE_STYLE);
+            if copy == 0 {
+                return 0;
+            }
+            ctx.copies[id as usize - 1] = copy;
+            let items = (*node).data.sequence.items;
+            let mut item = items.start;
+            while item < items.top {
+                let child = yaml_canonicalize_node(ctx, *item);
+                if child == 0 || yaml_document_append_sequence_item(ctx.canonical, copy, child).fail
+                {
+                    return 0;
+                }
+                item = item.add(1);
+            }
+            copy
+        }
+        YAML_MAPPING_NODE => {
+            let copy =
+                yaml_document_add_mapping(ctx.canonical, (*node).tag, YAML_ANY_MAPPING_STYLE);
+            if copy == 0 {
+                return 0;
+            }
+            ctx.copies[id as usize - 1] = copy;
+            let pairs = (*node).data.mapping.pairs;
+            let mut pair = pairs.start;
+            while pair < pairs.top {
+                let key = yaml_canonicalize_node(ctx, (*pair).key);
+                let value = if key == 0 {
+                    0
+                } else {
+                    yaml_canonicalize_node(ctx, (*pair).value)
+                };
+                if value == 0
+                    || yaml_document_append_mapping_pair(ctx.canonical, copy, key, value).fail
+                {
+                    return 0;
+                }
+                pair = pair.add(1);
+            }
+            yaml_canonicalize_sort_pairs(ctx.canonical, copy);
+            copy
+        }
+        _ => 0,
+    };
+    if copy != 0 {
+        let node = (*ctx.canonical).nodes.start.add(copy as usize - 1);
+        (*node).transform = b"canonicalize\0" as *const u8 as *const libc::c_char;
+        (*node).derived_from = id;
+    }
+    ctx.copies[id as usize - 1] = if ctx.options.keep_aliases { copy } else { 0 };
+    copy
+}
+
+unsafe fn yaml_canonicalize_scalar(
+    canonical: *mut yaml_document_t,
+    node: *mut yaml_node_t,
+) -> libc::c_int {
+    let value = (*node).data.scalar.value;
+    let length = (*node).data.scalar.length;
+    let mut resolved = MaybeUninit::<yaml_resolved_scalar_t>::uninit();
+    let resolved = resolved.as_mut_ptr();
+    if yaml_scalar_node_resolve(YAML_CORE_SCHEMA, node, resolved).fail {
+        return yaml_document_add_scalar(
+            canonical,
+            (*node).tag,
+            value,
+            length as libc::c_int,
+            YAML_ANY_SCALAR_STYLE,
+        );
+    }
+    let text = match (*resolved).type_ {
+        YAML_NULL_VALUE => String::from("null"),
+        YAML_BOOL_VALUE => format!("{}", (*resolved).boolean),
+        YAML_INT_VALUE if (*resolved).overflow => format!("{}", (*resolved).integer as u64),
+        YAML_INT_VALUE => format!("{}", (*resolved).integer),
+        YAML_FLOAT_VALUE => {
+            let float = (*resolved).float;
+            if float.is_nan() {
+                String::from(".nan")
+            } else if float.is_infinite() {
+                format!("{}.inf", if float < 0.0 { "-" } else { "" })
+            } else {
+                format!("{:?}", float)
+            }
+        }
+        _ => {
+            // A string that reads as another type when plain is quoted.
+            let tag = (*resolved).tag;
+            let style = if yaml_scalar_resolve(
+                YAML_CORE_SCHEMA,
+                ptr::null::<yaml_char_t>(),
+                value,
+                length,
+                resolved,
+            )
+            .ok && (*resolved).type_ != YAML_STR_VALUE
+            {
+                YAML_DOUBLE_QUOTED_SCALAR_STYLE
+            } else {
+                YAML_ANY_SCALAR_STYLE
+            };
+            return yaml_document_add_scalar(canonical, tag, value, length as libc::c_int, style);
+        }
+    };
+    yaml_document_add_scalar(
+        canonical,
+        (*resolved).tag,
+        text.as_ptr(),
+        text.len() as libc::c_int,
+        YAML_ANY_SCALAR_STYLE,
+    )
+}
+
+unsafe fn yaml_canonicalize_sort_pairs(document: *mut yaml_document_t, mapping: libc::c_int) {
+    let nodes = (*document).nodes.start;
+    let pairs = (*nodes.add(mapping as usize - 1)).data.mapping.pairs;
+    let pairs =
+        slice::from_raw_parts_mut(pairs.start, pairs.top.c_offset_from(pairs.start) as usize);
+    pairs.sort_by(|a: &yaml_node_pair_t, b: &yaml_node_pair_t| {
+        let a = nodes.add(a.key as usize - 1);
+        let b = nodes.add(b.key as usize - 1);
+        match ((*a).type_, (*b).type_) {
+            (YAML_SCALAR_NODE, YAML_SCALAR_NODE) => {
+                let value_a =
+                    slice::from_raw_parts((*a).data.scalar.value, (*a).data.scalar.length as usize);
+                let value_b =
+                    slice::from_raw_parts((*b).data.scalar.value, (*b).data.scalar.length as usize);
+                value_a.cmp(value_b).then_with(|| {
+                    strcmp(
+                        (*a).tag as *const libc::c_char,
+                        (*b).tag as *const libc::c_char,
+                    )
+                    .cmp(&0)
+                })
+            }
+            (YAML_SCALAR_NODE, _) => Ordering::Less,
+            (_, YAML_SCALAR_NODE) => Ordering::Greater,
+            _ => Ordering::Equal,
+        }
+    });
+}
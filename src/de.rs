@@ -0,0 +1,884 @@
+use crate::error::{Error, ErrorKind};
+use crate::externs::strlen;
+use crate::limits::{yaml_expansion_limit, RECURSION_LIMIT};
+use crate::schema::{yaml_core_float, yaml_core_resolve, Resolved};
+use crate::yaml::{size_t, yaml_char_t};
+use crate::{
+    libc, yaml_document_root_ref, yaml_document_t, yaml_event_delete, yaml_event_t, yaml_mark_t,
+    yaml_node_ref_t, yaml_parser_delete, yaml_parser_initialize, yaml_parser_parse,
+    yaml_parser_set_input_string, yaml_parser_source_slice, yaml_parser_t, PointerExt,
+    YAML_ALIAS_EVENT, YAML_ANY_SCALAR_STYLE, YAML_DOCUMENT_START_EVENT,
+    YAML_DOUBLE_QUOTED_SCALAR_STYLE, YAML_MAPPING_END_EVENT, YAML_MAPPING_NODE,
+    YAML_MAPPING_START_EVENT, YAML_PLAIN_SCALAR_STYLE, YAML_SCALAR_EVENT, YAML_SEQUENCE_END_EVENT,
+    YAML_SEQUENCE_NODE, YAML_SEQUENCE_START_EVENT, YAML_SINGLE_QUOTED_SCALAR_STYLE,
+    YAML_STREAM_END_EVENT,
+};
+use alloc::borrow::Cow;
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::mem::MaybeUninit;
+use core::ptr;
+use core::slice;
+use serde::de::{
+    self, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess, VariantAccess,
+    Visitor,
+};
+
+/// Deserialize an instance of `T` from a string of YAML text.
+///
+/// The input must contain at most one document. An empty input deserializes
+/// as null.
+///
+/// # Errors
+///
+/// Fails if the input is not valid YAML or does not match the structure of
+/// `T`.
+pub fn from_str<'de, T>(input: &'de str) -> Result<T, Error>
+where
+    T: de::Deserialize<'de>,
+{
+    from_slice(input.as_bytes())
+}
+
+/// Deserialize an instance of `T` from bytes of YAML text.
+///
+/// The encoding is detected from the byte order mark, defaulting to UTF-8.
+///
+/// # Errors
+///
+/// Fails if the input is not valid YAML or does not match the structure of
+/// `T`.
+pub fn from_slice<'de, T>(input: &'de [u8]) -> Result<T, Error>
+where
+    T: de::Deserialize<'de>,
+{
+    let mut deserializer = Deserializer::from_slice(input)?;
+    T::deserialize(&mut deserializer)
+}
+
+/// Deserialize an instance of `T` from a loaded document.
+///
+/// The nodes are read in place, without emitting and parsing the document
+/// again. An empty document deserializes as null.
+///
+/// # Safety
+///
+/// The document must have been loaded or built with the document functions,
+/// and must not be modified or deleted for `'de`.
+///
+/// # Errors
+///
+/// Fails if the document is nested too deeply or does not match the structure
+/// of `T`.
+pub unsafe fn from_document<'de, T>(document: &'de yaml_document_t) -> Result<T, Error>
+where
+    T: de::Deserialize<'de>,
+{
+    let mut deserializer = Deserializer::from_document(document)?;
+    T::deserialize(&mut deserializer)
+}
+
+/// Deserialize an instance of `T` from the subtree of a node of a loaded
+/// document.
+///
+/// # Errors
+///
+/// Fails if the subtree is nested too deeply or does not match the structure
+/// of `T`.
+pub fn from_node<'de, T>(node: yaml_node_ref_t<'de>) -> Result<T, Error>
+where
+    T: de::Deserialize<'de>,
+{
+    let mut deserializer = Deserializer::from_node(node)?;
+    T::deserialize(&mut deserializer)
+}
+
+/// A serde deserializer for a single YAML document.
+///
+/// The document is parsed into events up front. Aliases are followed by
+/// replaying the events of the anchored node, and scalars are borrowed from
+/// the input whenever their value appears verbatim in it.
+pub struct Deserializer<'de> {
+    input: &'de [u8],
+    events: Vec<Event<'de>>,
+    marks: Vec<yaml_mark_t>,
+    /// The index of the event following each node.
+    ends: Vec<usize>,
+    pos: usize,
+    /// The `(end, return)` positions of the aliases being replayed.
+    jumps: Vec<(usize, usize)>,
+    expanded: usize,
+    depth: usize,
+}
+
+enum Event<'de> {
+    Alias(usize),
+    Scalar(Scalar<'de>),
+    SequenceStart,
+    SequenceEnd,
+    MappingStart,
+    MappingEnd,
+}
+
+struct Scalar<'de> {
+    value: Cow<'de, str>,
+    tag: Tag,
+    plain: bool,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum Tag {
+    None,
+    Null,
+    Bool,
+    Int,
+    Float,
+    Str,
+}
+
+impl<'de> Deserializer<'de> {
+    /// Parse a YAML document from a string.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the input is not valid YAML or has more than one document.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(input: &'de str) -> Result<Self, Error> {
+        Deserializer::from_slice(input.as_bytes())
+    }
+
+    /// Parse a YAML document from bytes.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the input is not valid YAML or has more than one document.
+    pub fn from_slice(input: &'de [u8]) -> Result<Self, Error> {
+        let mut deserializer = Deserializer::new(input);
+        unsafe { deserializer.load()? };
+        if deserializer.events.is_empty() {
+            deserializer.push_null();
+        }
+        Ok(deserializer)
+    }
+
+    /// Read a loaded document.
+    ///
+    /// Scalars are borrowed from the document, and nodes referenced more than
+    /// once are replayed as aliases.
+    ///
+    /// # Safety
+    ///
+    /// The document must have been loaded or built with the document
+    /// functions, and must not be modified or deleted for `'de`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the document is nested deeper than the recursion limit.
+    pub unsafe fn from_document(document: &'de yaml_document_t) -> Result<Self, Error> {
+        let document = document as *const yaml_document_t as *mut yaml_document_t;
+        match yaml_document_root_ref(document) {
+            Some(root) => Deserializer::from_node(root),
+            None => {
+                let mut deserializer = Deserializer::new(&[]);
+                deserializer.push_null();
+                Ok(deserializer)
+            }
+        }
+    }
+
+    /// Read the subtree of a node of a loaded document.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the subtree is nested deeper than the recursion limit.
+    pub fn from_node(node: yaml_node_ref_t<'de>) -> Result<Self, Error> {
+        let mut deserializer = Deserializer::new(&[]);
+        deserializer.push_node(node, &mut BTreeMap::new(), 0)?;
+        Ok(deserializer)
+    }
+
+    fn new(input: &'de [u8]) -> Self {
+        Deserializer {
+            input,
+            events: Vec::new(),
+            marks: Vec::new(),
+            ends: Vec::new(),
+            pos: 0,
+            jumps: Vec::new(),
+            expanded: 0,
+            depth: 0,
+        }
+    }
+
+    /// Stand for an empty document with a null scalar.
+    fn push_null(&mut self) {
+        self.events.push(Event::Scalar(Scalar {
+            value: Cow::Borrowed(""),
+            tag: Tag::None,
+            plain: true,
+        }));
+        self.marks.push(yaml_mark_t {
+            index: 0,
+            line: 0,
+            column: 0,
+        });
+        self.ends.push(1);
+    }
+
+    fn push_node(
+        &mut self,
+        node: yaml_node_ref_t<'de>,
+        indices: &mut BTreeMap<libc::c_int, usize>,
+        depth: usize,
+    ) -> Result<(), Error> {
+        let index = self.events.len();
+        self.marks.push(node.node().start_mark);
+        self.ends.push(index + 1);
+        if let Some(&target) = indices.get(&node.id()) {
+            self.events.push(Event::Alias(target));
+            return Ok(());
+        }
+        indices.insert(node.id(), index);
+        let data = node.node().data;
+        let type_ = node.node().type_;
+        if (type_ == YAML_SEQUENCE_NODE || type_ == YAML_MAPPING_NODE) && depth >= RECURSION_LIMIT {
+            return Err(Error::new(
+                ErrorKind::Limit,
+                "recursion limit exceeded",
+                Some(node.node().start_mark),
+            ));
+        }
+        match type_ {
+            YAML_SEQUENCE_NODE => {
+                self.events.push(Event::SequenceStart);
+                let items = unsafe { data.sequence.items };
+                let mut item = items.start;
+                while item < items.top {
+                    // SAFETY: the items of a sequence are ids of nodes of the document.
+                    unsafe {
+                        self.push_node(node.child(*item), indices, depth + 1)?;
+                        item = item.add(1);
+                    }
+                }
+                self.push_end(index, node, Event::SequenceEnd);
+            }
+            YAML_MAPPING_NODE => {
+                self.events.push(Event::MappingStart);
+                let pairs = unsafe { data.mapping.pairs };
+                let mut pair = pairs.start;
+                while pair < pairs.top {
+                    // SAFETY: the pairs of a mapping hold ids of nodes of the document.
+                    unsafe {
+                        self.push_node(node.child((*pair).key), indices, depth + 1)?;
+                        self.push_node(node.child((*pair).value), indices, depth + 1)?;
+                        pair = pair.add(1);
+                    }
+                }
+                self.push_end(index, node, Event::MappingEnd);
+            }
+            _ => {
+                let style = unsafe { data.scalar.style };
+                let plain = style == YAML_PLAIN_SCALAR_STYLE || style == YAML_ANY_SCALAR_STYLE;
+                let value = match node.as_str() {
+                    Some(value) => Cow::Borrowed(value),
+                    None => Cow::Owned(
+                        String::from_utf8_lossy(node.as_bytes().unwrap_or_default()).into_owned(),
+                    ),
+                };
+                let tag = unsafe { yaml_de_tag(node.node().tag) };
+                // The loader tags plain scalars as strings by default.
+                let tag = if plain && tag == Tag::Str {
+                    Tag::None
+                } else {
+                    tag
+                };
+                self.events
+                    .push(Event::Scalar(Scalar { value, tag, plain }));
+            }
+        }
+        Ok(())
+    }
+
+    fn push_end(&mut self, start: usize, node: yaml_node_ref_t, event: Event<'de>) {
+        self.events.push(event);
+        self.marks.push(node.node().end_mark);
+        self.ends.push(self.events.len());
+        self.ends[start] = self.events.len();
+    }
+
+    unsafe fn load(&mut self) -> Result<(), Error> {
+        let mut parser = MaybeUninit::<yaml_parser_t>::uninit();
+        let parser = parser.as_mut_ptr();
+        if yaml_parser_initialize(parser).fail {
+            return Err(Error::new(ErrorKind::Memory, "out of memory", None));
+        }
+        yaml_parser_set_input_string(parser, self.input.as_ptr(), self.input.len() as size_t);
+        let mut event = MaybeUninit::<yaml_event_t>::uninit();
+        let event = event.as_mut_ptr();
+        let mut anchors = BTreeMap::new();
+        let mut starts = Vec::new();
+        let mut documents = 0;
+        let result = loop {
+            if yaml_parser_parse(parser, event).fail {
+                break Err(Error::from_parser(parser));
+            }
+            let result = if (*event).type_ == YAML_STREAM_END_EVENT {
+                Ok(true)
+            } else if (*event).type_ == YAML_DOCUMENT_START_EVENT {
+                documents += 1;
+                if documents > 1 {
+                    Err(Error::new(
+                        ErrorKind::Data,
+                        "deserializing from YAML containing more than one document is not supported",
+                        Some((*event).start_mark),
+                    ))
+                } else {
+                    Ok(false)
+                }
+            } else {
+                self.push_event(parser, event, &mut anchors, &mut starts)
+                    .map(|()| false)
+            };
+            yaml_event_delete(event);
+            match result {
+                Ok(false) => {}
+                Ok(true) => break Ok(()),
+                Err(error) => break Err(error),
+            }
+        };
+        yaml_parser_delete(parser);
+        result
+    }
+
+    unsafe fn push_event(
+        &mut self,
+        parser: *mut yaml_parser_t,
+        event: *mut yaml_event_t,
+        anchors: &mut BTreeMap<Vec<u8>, usize>,
+        starts: &mut Vec<usize>,
+    ) -> Result<(), Error> {
+        let index = self.events.len();
+        let mut anchor = ptr::null_mut::<yaml_char_t>();
+        let event_data = match (*event).type_ {
+            YAML_ALIAS_EVENT => {
+                let name = yaml_de_cstr((*event).data.alias.anchor);
+                match anchors.get(name) {
+                    Some(target) => Event::Alias(*target),
+                    None => {
+                        return Err(Error::new(
+                            ErrorKind::Composer,
+                            "found undefined alias",
+                            Some((*event).start_mark),
+                        ))
+                    }
+                }
+            }
+            YAML_SCALAR_EVENT => {
+                let scalar = (*event).data.scalar;
+                anchor = scalar.anchor;
+                let value = slice::from_raw_parts(scalar.value, scalar.length as usize);
+                let value = match self.borrow_scalar(parser, event, value) {
+                    Some(borrowed) => Cow::Borrowed(borrowed),
+                    None => Cow::Owned(String::from_utf8_lossy(value).into_owned()),
+                };
+                Event::Scalar(Scalar {
+                    value,
+                    tag: yaml_de_tag(scalar.tag),
+                    plain: scalar.style == YAML_PLAIN_SCALAR_STYLE,
+                })
+            }
+            YAML_SEQUENCE_START_EVENT => {
+                anchor = (*event).data.sequence_start.anchor;
+                starts.push(index);
+                Event::SequenceStart
+            }
+            YAML_MAPPING_START_EVENT => {
+                anchor = (*event).data.mapping_start.anchor;
+                starts.push(index);
+                Event::MappingStart
+            }
+            YAML_SEQUENCE_END_EVENT | YAML_MAPPING_END_EVENT => {
+                if let Some(start) = starts.pop() {
+                    self.ends[start] = index + 1;
+                }
+                if (*event).type_ == YAML_SEQUENCE_END_EVENT {
+                    Event::SequenceEnd
+                } else {
+                    Event::MappingEnd
+                }
+            }
+            _ => return Ok(()),
+        };
+        if !anchor.is_null() {
+            anchors.insert(yaml_de_cstr(anchor).to_vec(), index);
+        }
+        self.events.push(event_data);
+        self.marks.push((*event).start_mark);
+        self.ends.push(index + 1);
+        Ok(())
+    }
+
+    /// Find the value of a scalar verbatim in the input.
+    unsafe fn borrow_scalar(
+        &self,
+        parser: *mut yaml_parser_t,
+        event: *mut yaml_event_t,
+        value: &[u8],
+    ) -> Option<&'de str> {
+        let mut start = ptr::null::<yaml_char_t>();
+        let mut length: size_t = 0;
+        if yaml_parser_source_slice(
+            parser,
+            (*event).start_mark,
+            (*event).end_mark,
+            &mut start,
+            &mut length,
+        )
+        .fail
+        {
+            return None;
+        }
+        let offset = start.c_offset_from(self.input.as_ptr()) as usize;
+        let mut source = self.input.get(offset..offset + length as usize)?;
+        let style = (*event).data.scalar.style;
+        if style == YAML_SINGLE_QUOTED_SCALAR_STYLE || style == YAML_DOUBLE_QUOTED_SCALAR_STYLE {
+            source = &source[..source.len().checked_sub(1)?];
+        } else if style != YAML_PLAIN_SCALAR_STYLE {
+            return None;
+        }
+        if !source.ends_with(value) {
+            return None;
+        }
+        core::str::from_utf8(&source[source.len() - value.len()..]).ok()
+    }
+}
+
+unsafe fn yaml_de_cstr<'a>(string: *const yaml_char_t) -> &'a [u8] {
+    slice::from_raw_parts(string, strlen(string as *const libc::c_char) as usize)
+}
+
+unsafe fn yaml_de_tag(tag: *const yaml_char_t) -> Tag {
+    if tag.is_null() {
+        return Tag::None;
+    }
+    match yaml_de_cstr(tag) {
+        b"tag:yaml.org,2002:null" => Tag::Null,
+        b"tag:yaml.org,2002:bool" => Tag::Bool,
+        b"tag:yaml.org,2002:int" => Tag::Int,
+        b"tag:yaml.org,2002:float" => Tag::Float,
+        b"tag:yaml.org,2002:str" | b"!" => Tag::Str,
+        _ => Tag::None,
+    }
+}
+
+impl<'de> Deserializer<'de> {
+    /// Follow aliases to the next event to be consumed.
+    fn peek(&mut self) -> Result<usize, Error> {
+        while let Some(Event::Alias(target)) = self.events.get(self.pos) {
+            let target = *target;
+            let end = self.ends[target];
+            self.expanded += end - target;
+            if self.jumps.len() >= RECURSION_LIMIT {
+                return Err(self.error(ErrorKind::Limit, "recursion limit exceeded"));
+            }
+            if self.expanded > yaml_expansion_limit(self.events.len()) {
+                return Err(self.error(ErrorKind::Limit, "alias expansion limit exceeded"));
+            }
+            self.jumps.push((end, self.pos + 1));
+            self.pos = target;
+        }
+        if self.pos < self.events.len() {
+            Ok(self.pos)
+        } else {
+            Err(Error::new(
+                ErrorKind::Data,
+                "unexpected end of document",
+                None,
+            ))
+        }
+    }
+
+    fn next(&mut self) -> Result<usize, Error> {
+        let index = self.peek()?;
+        self.advance(index + 1);
+        Ok(index)
+    }
+
+    /// Move to `pos`, returning from aliases whose replay has finished.
+    fn advance(&mut self, mut pos: usize) {
+        while let Some(&(end, ret)) = self.jumps.last() {
+            if pos != end {
+                break;
+            }
+            pos = ret;
+            self.jumps.pop();
+        }
+        self.pos = pos;
+    }
+
+    /// Skip the next node without following aliases.
+    fn skip(&mut self) {
+        let end = self.ends[self.pos];
+        self.advance(end);
+    }
+
+    fn error(&self, kind: ErrorKind, message: &str) -> Error {
+        Error::new(kind, message, self.marks.get(self.pos).copied())
+    }
+
+    fn visit_sequence<V>(&mut self, visitor: V, mark: yaml_mark_t) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.enter(mark)?;
+        let mut access = SequenceAccess { de: self, len: 0 };
+        let value = visitor.visit_seq(&mut access)?;
+        let (mut len, expected) = (access.len, access.len);
+        while !self.peek_end()? {
+            self.skip();
+            len += 1;
+        }
+        if len != expected {
+            return Err(de::Error::invalid_length(
+                len,
+                &"fewer elements in sequence",
+            ));
+        }
+        self.next()?;
+        self.depth -= 1;
+        Ok(value)
+    }
+
+    fn visit_mapping<V>(&mut self, visitor: V, mark: yaml_mark_t) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.enter(mark)?;
+        let mut access = MappingAccess { de: self, len: 0 };
+        let value = visitor.visit_map(&mut access)?;
+        let (mut len, expected) = (access.len, access.len);
+        while !self.peek_end()? {
+            self.skip();
+            self.skip();
+            len += 1;
+        }
+        if len != expected {
+            return Err(de::Error::invalid_length(len, &"fewer entries in mapping"));
+        }
+        self.next()?;
+        self.depth -= 1;
+        Ok(value)
+    }
+
+    fn enter(&mut self, mark: yaml_mark_t) -> Result<(), Error> {
+        self.depth += 1;
+        if self.depth > RECURSION_LIMIT {
+            return Err(Error::new(
+                ErrorKind::Limit,
+                "recursion limit exceeded",
+                Some(mark),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Check whether the current sequence or mapping has no more entries.
+    fn peek_end(&mut self) -> Result<bool, Error> {
+        let index = self.peek()?;
+        Ok(matches!(
+            self.events[index],
+            Event::SequenceEnd | Event::MappingEnd
+        ))
+    }
+
+    /// Check whether the next node is a null scalar.
+    fn peek_null(&mut self) -> Result<bool, Error> {
+        let index = self.peek()?;
+        Ok(match &self.events[index] {
+            Event::Scalar(scalar) => match scalar.tag {
+                Tag::Null => true,
+                Tag::None => {
+                    scalar.plain && yaml_core_resolve(scalar.value.as_bytes()) == Resolved::Null
+                }
+                _ => false,
+            },
+            _ => false,
+        })
+    }
+}
+
+fn visit_scalar<'de, V>(scalar: &Scalar<'de>, visitor: V) -> Result<V::Value, Error>
+where
+    V: Visitor<'de>,
+{
+    let resolved = yaml_core_resolve(scalar.value.as_bytes());
+    let resolved = match scalar.tag {
+        Tag::None if scalar.plain => resolved,
+        Tag::None | Tag::Str => Resolved::Str,
+        Tag::Null => Resolved::Null,
+        Tag::Bool => match resolved {
+            Resolved::Bool(_) => resolved,
+            _ => return Err(de::Error::custom("invalid value for !!bool")),
+        },
+        Tag::Int => match resolved {
+            Resolved::Int(_) | Resolved::UInt(_) => resolved,
+            _ => return Err(de::Error::custom("invalid value for !!int")),
+        },
+        Tag::Float => match yaml_core_float(resolved) {
+            Some(float) => Resolved::Float(float),
+            None => return Err(de::Error::custom("invalid value for !!float")),
+        },
+    };
+    match resolved {
+        Resolved::Null => visitor.visit_unit(),
+        Resolved::Bool(value) => visitor.visit_bool(value),
+        Resolved::Int(int) => visitor.visit_i64(int),
+        Resolved::UInt(int) => visitor.visit_u64(int),
+        Resolved::Float(float) => visitor.visit_f64(float),
+        Resolved::Str => visit_str(scalar, visitor),
+    }
+}
+
+fn visit_str<'de, V>(scalar: &Scalar<'de>, visitor: V) -> Result<V::Value, Error>
+where
+    V: Visitor<'de>,
+{
+    match &scalar.value {
+        Cow::Borrowed(value) => visitor.visit_borrowed_str(value),
+        Cow::Owned(value) => visitor.visit_str(value),
+    }
+}
+
+impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        let index = self.next()?;
+        let mark = self.marks[index];
+        match &self.events[index] {
+            Event::Scalar(scalar) => visit_scalar(scalar, visitor),
+            Event::SequenceStart => self.visit_sequence(visitor, mark),
+            Event::MappingStart => self.visit_mapping(visitor, mark),
+            Event::SequenceEnd | Event::MappingEnd | Event::Alias(_) => Err(Error::new(
+                ErrorKind::Data,
+                "unexpected end of node",
+                Some(mark),
+            )),
+        }
+        .map_err(|error| error.with_mark(mark))
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        let index = self.peek()?;
+        match &self.events[index] {
+            Event::Scalar(scalar) if scalar.tag != Tag::Null => {}
+            _ => return self.deserialize_any(visitor),
+        }
+        self.advance(index + 1);
+        match &self.events[index] {
+            Event::Scalar(scalar) => {
+                visit_str(scalar, visitor).map_err(|error| error.with_mark(self.marks[index]))
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        if self.peek_null()? {
+            self.next()?;
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        let index = self.next()?;
+        let mark = self.marks[index];
+        match &self.events[index] {
+            Event::Scalar(scalar) => visitor
+                .visit_enum(scalar.value.to_string().into_deserializer())
+                .map_err(|error: Error| error.with_mark(mark)),
+            Event::MappingStart => {
+                self.enter(mark)?;
+                let value = visitor
+                    .visit_enum(Enum { de: self })
+                    .map_err(|error| error.with_mark(mark))?;
+                if !self.peek_end()? {
+                    return Err(Error::new(
+                        ErrorKind::Data,
+                        "expected a mapping with a single entry for an enum",
+                        Some(mark),
+                    ));
+                }
+                self.next()?;
+                self.depth -= 1;
+                Ok(value)
+            }
+            _ => Err(Error::new(
+                ErrorKind::Data,
+                "expected a scalar or a mapping with a single entry for an enum",
+                Some(mark),
+            )),
+        }
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.skip();
+        visitor.visit_unit()
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char bytes
+        byte_buf unit unit_struct seq tuple tuple_struct map struct
+    }
+}
+
+struct SequenceAccess<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+    len: usize,
+}
+
+impl<'de> SeqAccess<'de> for SequenceAccess<'_, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if self.de.peek_end()? {
+            return Ok(None);
+        }
+        self.len += 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+}
+
+struct MappingAccess<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+    len: usize,
+}
+
+impl<'de> MapAccess<'de> for MappingAccess<'_, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        if self.de.peek_end()? {
+            return Ok(None);
+        }
+        self.len += 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        seed.deserialize(&mut *self.de)
+    }
+}
+
+struct Enum<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+}
+
+impl<'de> EnumAccess<'de> for Enum<'_, 'de> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self), Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        if self.de.peek_end()? {
+            return Err(self.de.error(
+                ErrorKind::Data,
+                "expected a mapping with a single entry for an enum",
+            ));
+        }
+        let variant = seed.deserialize(&mut *self.de)?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for Enum<'_, 'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        de::Deserialize::deserialize(self.de)
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        seed.deserialize(self.de)
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        de::Deserializer::deserialize_seq(self.de, visitor)
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        de::Deserializer::deserialize_map(self.de, visitor)
+    }
+}
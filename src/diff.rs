@@ -0,0 +1,294 @@
+use crate::document::yaml_document_compare_subtrees;
+use crate::externs::strcmp;
+use crate::success::{Success, FAIL, OK};
+use crate::{
+    libc, yaml_compare_options_t, yaml_diff_handler_t, yaml_diff_op_t, yaml_diff_type_t,
+    yaml_document_t, yaml_node_t, PointerExt, YAML_ALIAS_NODE, YAML_DIFF_ADD, YAML_DIFF_REMOVE,
+    YAML_DIFF_REPLACE, YAML_MAPPING_NODE, YAML_SCALAR_NODE, YAML_SEQUENCE_NODE,
+};
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::slice;
+
+struct yaml_diff_ctx_t {
+    a: *mut yaml_document_t,
+    b: *mut yaml_document_t,
+    handler: yaml_diff_handler_t,
+    data: *mut libc::c_void,
+    /// The JSON Pointer to the nodes being compared, without the NUL.
+    path: Vec<u8>,
+    /// The pairs of nodes being compared, to stop at cycles.
+    pairs: Vec<(libc::c_int, libc::c_int)>,
+}
+
+/// Find the changes that turn a document into another one.
+///
+/// The `handler` is called with `data` for every change from `a` to `b`. A
+/// change is the addition, the removal or the replacement of a node at a path,
+/// a JSON Pointer (RFC 6901) made of the keys of mappings and the indices of
+/// sequences, where the empty path stands for the root node. Applied in the
+/// order they are reported, the changes turn `a` into `b`: the removed items of
+/// a sequence are reported from the last one and before the added ones.
+///
+/// Scalars are compared by their tags and values, and mappings by the values of
+/// their scalar keys, regardless of the order of the pairs. A collection whose
+/// tag differs, or a mapping with a key that is not a scalar and differs, is
+/// replaced as a whole. Styles, marks and anchors are not compared, and alias
+/// nodes are compared as the nodes they refer to.
+///
+/// Returns FAIL if the handler failed, which stops the diff.
+pub unsafe fn yaml_document_diff(
+    a: *mut yaml_document_t,
+    b: *mut yaml_document_t,
+    handler: yaml_diff_handler_t,
+    data: *mut libc::c_void,
+) -> Success {
+    __assert!(!a.is_null());
+    __assert!(!b.is_null());
+    let mut ctx = yaml_diff_ctx_t {
+        a,
+        b,
+        handler,
+        data,
+        path: Vec::new(),
+        pairs: Vec::new(),
+    };
+    match (
+        (*a).nodes.start == (*a).nodes.top,
+        (*b).nodes.start == (*b).nodes.top,
+    ) {
+        (true, true) => OK,
+        (true, false) => yaml_diff_report(&mut ctx, YAML_DIFF_ADD, 0, 1),
+        (false, true) => yaml_diff_report(&mut ctx, YAML_DIFF_REMOVE, 1, 0),
+        (false, false) => yaml_diff_node(&mut ctx, 1, 1),
+    }
+}
+
+unsafe fn yaml_diff_report(
+    ctx: &mut yaml_diff_ctx_t,
+    type_: yaml_diff_type_t,
+    old_node: libc::c_int,
+    new_node: libc::c_int,
+) -> Success {
+    ctx.path.push(b'\0');
+    let op = yaml_diff_op_t {
+        type_,
+        path: ctx.path.as_ptr(),
+        old_node,
+        new_node,
+    };
+    let result = (ctx.handler)(ctx.data, &op);
+    ctx.path.pop();
+    if result == 0 {
+        FAIL
+    } else {
+        OK
+    }
+}
+
+unsafe fn yaml_diff_resolve(
+    document: *mut yaml_document_t,
+    mut id: libc::c_int,
+) -> (libc::c_int, *mut yaml_node_t) {
+    let mut node = (*document).nodes.start.add(id as usize - 1);
+    while (*node).type_ == YAML_ALIAS_NODE {
+        id = (*node).data.alias.target;
+        node = (*document).nodes.start.add(id as usize - 1);
+    }
+    (id, node)
+}
+
+unsafe fn yaml_diff_node(ctx: &mut yaml_diff_ctx_t, x: libc::c_int, y: libc::c_int) -> Success {
+    let (x, a) = yaml_diff_resolve(ctx.a, x);
+    let (y, b) = yaml_diff_resolve(ctx.b, y);
+    if ctx.pairs.contains(&(x, y)) {
+        return OK;
+    }
+    if (*a).type_ != (*b).type_ || !yaml_diff_same_tag(a, b) {
+        return yaml_diff_report(ctx, YAML_DIFF_REPLACE, x, y);
+    }
+    ctx.pairs.push((x, y));
+    let success = match (*a).type_ {
+        YAML_SCALAR_NODE => {
+            if yaml_diff_scalar(a) == yaml_diff_scalar(b) {
+                OK
+            } else {
+                yaml_diff_report(ctx, YAML_DIFF_REPLACE, x, y)
+            }
+        }
+        YAML_SEQUENCE_NODE => yaml_diff_sequence(ctx, a, b),
+        YAML_MAPPING_NODE => {
+            if yaml_diff_scalar_keys(ctx.a, a) && yaml_diff_scalar_keys(ctx.b, b) {
+                yaml_diff_mapping(ctx, a, b)
+            } else {
+                yaml_diff_whole(ctx, x, y)
+            }
+        }
+        _ => yaml_diff_report(ctx, YAML_DIFF_REPLACE, x, y),
+    };
+    ctx.pairs.pop();
+    success
+}
+
+unsafe fn yaml_diff_sequence(
+    ctx: &mut yaml_diff_ctx_t,
+    a: *mut yaml_node_t,
+    b: *mut yaml_node_t,
+) -> Success {
+    let items_a = (*a).data.sequence.items;
+    let items_b = (*b).data.sequence.items;
+    let count_a = items_a.top.c_offset_from(items_a.start) as usize;
+    let count_b = items_b.top.c_offset_from(items_b.start) as usize;
+    let length = ctx.path.len();
+    let mut index = 0;
+    while index < count_a.min(count_b) {
+        yaml_diff_push_index(&mut ctx.path, index);
+        let success = yaml_diff_node(ctx, *items_a.start.add(index), *items_b.start.add(index));
+        ctx.path.truncate(length);
+        if success.fail {
+            return FAIL;
+        }
+        index += 1;
+    }
+    index = count_a;
+    while index > count_b {
+        index -= 1;
+        yaml_diff_push_index(&mut ctx.path, index);
+        let success = yaml_diff_report(ctx, YAML_DIFF_REMOVE, *items_a.start.add(index), 0);
+        ctx.path.truncate(length);
+        if success.fail {
+            return FAIL;
+        }
+    }
+    while index < count_b {
+        yaml_diff_push_index(&mut ctx.path, index);
+        let success = yaml_diff_report(ctx, YAML_DIFF_ADD, 0, *items_b.start.add(index));
+        ctx.path.truncate(length);
+        if success.fail {
+            return FAIL;
+        }
+        index += 1;
+    }
+    OK
+}
+
+unsafe fn yaml_diff_mapping(
+    ctx: &mut yaml_diff_ctx_t,
+    a: *mut yaml_node_t,
+    b: *mut yaml_node_t,
+) -> Success {
+    let pairs_a = (*a).data.mapping.pairs;
+    let pairs_b = (*b).data.mapping.pairs;
+    let length = ctx.path.len();
+    let mut pair = pairs_a.start;
+    while pair < pairs_a.top {
+        let key = (*ctx.a).nodes.start.add((*pair).key as usize - 1);
+        let key = yaml_diff_scalar(key);
+        yaml_diff_push_key(&mut ctx.path, key);
+        let value = yaml_diff_find_key(ctx.b, b, key);
+        let success = if value == 0 {
+            yaml_diff_report(ctx, YAML_DIFF_REMOVE, (*pair).value, 0)
+        } else {
+            yaml_diff_node(ctx, (*pair).value, value)
+        };
+        ctx.path.truncate(length);
+        if success.fail {
+            return FAIL;
+        }
+        pair = pair.add(1);
+    }
+    pair = pairs_b.start;
+    while pair < pairs_b.top {
+        let key = (*ctx.b).nodes.start.add((*pair).key as usize - 1);
+        let key = yaml_diff_scalar(key);
+        if yaml_diff_find_key(ctx.a, a, key) == 0 {
+            yaml_diff_push_key(&mut ctx.path, key);
+            let success = yaml_diff_report(ctx, YAML_DIFF_ADD, 0, (*pair).value);
+            ctx.path.truncate(length);
+            if success.fail {
+                return FAIL;
+            }
+        }
+        pair = pair.add(1);
+    }
+    OK
+}
+
+unsafe fn yaml_diff_whole(ctx: &mut yaml_diff_ctx_t, x: libc::c_int, y: libc::c_int) -> Success {
+    let options = yaml_compare_options_t {
+        ignore_styles: true,
+        ignore_marks: true,
+        ignore_anchors: true,
+        ignore_key_order: true,
+    };
+    let mut equal = false;
+    if yaml_document_compare_subtrees(ctx.a, x, ctx.b, y, &options, &mut equal).fail {
+        return FAIL;
+    }
+    if equal {
+        OK
+    } else {
+        yaml_diff_report(ctx, YAML_DIFF_REPLACE, x, y)
+    }
+}
+
+unsafe fn yaml_diff_same_tag(a: *mut yaml_node_t, b: *mut yaml_node_t) -> bool {
+    if (*a).tag.is_null() || (*b).tag.is_null() {
+        return (*a).tag.is_null() && (*b).tag.is_null();
+    }
+    strcmp(
+        (*a).tag as *const libc::c_char,
+        (*b).tag as *const libc::c_char,
+    ) == 0
+}
+
+unsafe fn yaml_diff_scalar<'a>(node: *mut yaml_node_t) -> &'a [u8] {
+    slice::from_raw_parts(
+        (*node).data.scalar.value,
+        (*node).data.scalar.length as usize,
+    )
+}
+
+unsafe fn yaml_diff_scalar_keys(document: *mut yaml_document_t, node: *mut yaml_node_t) -> bool {
+    let pairs = (*node).data.mapping.pairs;
+    let mut pair = pairs.start;
+    while pair < pairs.top {
+        if (*(*document).nodes.start.add((*pair).key as usize - 1)).type_ != YAML_SCALAR_NODE {
+            return false;
+        }
+        pair = pair.add(1);
+    }
+    true
+}
+
+unsafe fn yaml_diff_find_key(
+    document: *mut yaml_document_t,
+    node: *mut yaml_node_t,
+    key: &[u8],
+) -> libc::c_int {
+    let pairs = (*node).data.mapping.pairs;
+    let mut pair = pairs.start;
+    while pair < pairs.top {
+        if yaml_diff_scalar((*document).nodes.start.add((*pair).key as usize - 1)) == key {
+            return (*pair).value;
+        }
+        pair = pair.add(1);
+    }
+    0
+}
+
+fn yaml_diff_push_index(path: &mut Vec<u8>, index: usize) {
+    path.push(b'/');
+    path.extend_from_slice(index.to_string().as_bytes());
+}
+
+fn yaml_diff_push_key(path: &mut Vec<u8>, key: &[u8]) {
+    path.push(b'/');
+    for &byte in key {
+        match byte {
+            b'~' => path.extend_from_slice(b"~0"),
+            b'/' => path.extend_from_slice(b"~1"),
+            _ => path.push(byte),
+        }
+    }
+}
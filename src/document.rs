@@ -0,0 +1,2180 @@
+use crate::api::{
+    yaml_allocated, yaml_check_utf8, yaml_free, yaml_malloc, yaml_scalar_source_duplicate,
+    yaml_stack_extend, yaml_strdup,
+};
+use crate::externs::{memcpy, memset, strcmp, strlen};
+use crate::ops::{ForceAdd as _, ForceMul as _};
+use crate::success::{Success, FAIL, OK};
+use crate::yaml::{
+    size_t, yaml_alias_edge_t, yaml_char_t, yaml_document_t, yaml_node_item_t, yaml_node_pair_t,
+    yaml_node_t, YAML_MAPPING_NODE, YAML_SCALAR_NODE, YAML_SEQUENCE_NODE,
+};
+use crate::{
+    libc, yaml_compare_options_t, yaml_document_add_mapping, yaml_document_add_scalar,
+    yaml_document_add_sequence, yaml_document_append_mapping_pair,
+    yaml_document_append_sequence_item, yaml_document_delete, yaml_document_initialize,
+    yaml_mapping_style_t, yaml_mark_t, yaml_node_visitor_t, yaml_path_step_t, yaml_scalar_style_t,
+    yaml_sequence_merge_t, yaml_sequence_style_t, yaml_stack_t, yaml_visit_type_t, PointerExt,
+    YAML_ALIAS_NODE, YAML_ANY_MAPPING_STYLE, YAML_ANY_SCALAR_STYLE, YAML_ANY_SEQUENCE_STYLE,
+    YAML_APPEND_SEQUENCES, YAML_VISIT_ALIAS, YAML_VISIT_MAPPING_END, YAML_VISIT_MAPPING_START,
+    YAML_VISIT_SCALAR, YAML_VISIT_SEQUENCE_END, YAML_VISIT_SEQUENCE_START,
+};
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use core::marker::PhantomData;
+use core::mem::{size_of, MaybeUninit};
+use core::ptr::{self, addr_of_mut};
+use core::slice;
+use core::str;
+
+pub(crate) unsafe fn yaml_document_node_count(document: *const yaml_document_t) -> libc::c_int {
+    (*document).nodes.top.c_offset_from((*document).nodes.start) as libc::c_int
+}
+
+unsafe fn yaml_document_add_edge(
+    edges: *mut yaml_alias_edge_t,
+    size: size_t,
+    count: *mut size_t,
+    parent: libc::c_int,
+    target: libc::c_int,
+) {
+    if *count < size {
+        let edge = edges.add(*count as usize);
+        (*edge).parent = parent;
+        (*edge).target = target;
+    }
+    *count = (*count).force_add(1);
+}
+
+/// Describe where the shared nodes of a document are referenced from.
+///
+/// A node is shared if it is referenced more than once, which is what the
+/// emitter serializes as an anchor followed by aliases. One edge is produced
+/// for every reference to a shared node, ordered by the id of the referencing
+/// node. A `parent` of 0 stands for the document itself referencing its root
+/// node.
+///
+/// At most `size` edges are stored in `edges`, while `count` receives the total
+/// number of edges, so the function can be called with a `size` of 0 first to
+/// find out how large the array has to be.
+pub unsafe fn yaml_document_get_alias_graph(
+    document: *mut yaml_document_t,
+    edges: *mut yaml_alias_edge_t,
+    size: size_t,
+    count: *mut size_t,
+) -> Success {
+    __assert!(!document.is_null());
+    __assert!(!edges.is_null() || size == 0);
+    __assert!(!count.is_null());
+    *count = 0;
+    let nodes = yaml_document_node_count(document);
+    if nodes == 0 {
+        return OK;
+    }
+    let references =
+        yaml_malloc((size_of::<libc::c_int>() as libc::c_ulong).force_mul(nodes as libc::c_ulong))
+            as *mut libc::c_int;
+    if references.is_null() {
+        return FAIL;
+    }
+    memset(
+        references as *mut libc::c_void,
+        0,
+        (size_of::<libc::c_int>() as libc::c_ulong).force_mul(nodes as libc::c_ulong),
+    );
+    *references += 1;
+    let mut node: *mut yaml_node_t = (*document).nodes.start;
+    while node < (*document).nodes.top {
+        yaml_node_for_each_child(node, |child| {
+            *references.add(child as usize - 1) += 1;
+        });
+        node = node.add(1);
+    }
+    if *references > 1 {
+        yaml_document_add_edge(edges, size, count, 0, 1);
+    }
+    let mut parent: libc::c_int = 1;
+    node = (*document).nodes.start;
+    while node < (*document).nodes.top {
+        yaml_node_for_each_child(node, |child| {
+            if *references.add(child as usize - 1) > 1 {
+                yaml_document_add_edge(edges, size, count, parent, child);
+            }
+        });
+        parent += 1;
+        node = node.add(1);
+    }
+    yaml_free(references as *mut libc::c_void);
+    OK
+}
+
+unsafe fn yaml_node_for_each_child(node: *mut yaml_node_t, mut f: impl FnMut(libc::c_int)) {
+    match (*node).type_ {
+        YAML_SEQUENCE_NODE => {
+            let mut item: *mut yaml_node_item_t = (*node).data.sequence.items.start;
+            while item < (*node).data.sequence.items.top {
+                f(*item);
+                item = item.add(1);
+            }
+        }
+        YAML_MAPPING_NODE => {
+            let mut pair: *mut yaml_node_pair_t = (*node).data.mapping.pairs.start;
+            while pair < (*node).data.mapping.pairs.top {
+                f((*pair).key);
+                f((*pair).value);
+                pair = pair.add(1);
+            }
+        }
+        YAML_ALIAS_NODE => f((*node).data.alias.target),
+        _ => {}
+    }
+}
+
+/// Find the value of a key in a MAPPING node.
+///
+/// The key is compared with the values of the scalar keys of the mapping. If
+/// `length` is -1, the key is NUL-terminated.
+///
+/// Returns the id of the value of the first matching pair, or 0 if there is
+/// none or the node is not a mapping.
+pub unsafe fn yaml_document_get_mapping_value(
+    document: *mut yaml_document_t,
+    mapping: libc::c_int,
+    key: *const yaml_char_t,
+    mut length: libc::c_int,
+) -> libc::c_int {
+    __assert!(!document.is_null());
+    __assert!(mapping > 0 && mapping <= yaml_document_node_count(document));
+    __assert!(!key.is_null());
+    if length < 0 {
+        length = strlen(key as *const libc::c_char) as libc::c_int;
+    }
+    let key = slice::from_raw_parts(key, length as usize);
+    let node = (*document).nodes.start.add(mapping as usize - 1);
+    if (*node).type_ != YAML_MAPPING_NODE {
+        return 0;
+    }
+    let mut pair: *mut yaml_node_pair_t = (*node).data.mapping.pairs.start;
+    while pair < (*node).data.mapping.pairs.top {
+        let key_node = (*document).nodes.start.add((*pair).key as usize - 1);
+        if (*key_node).type_ == YAML_SCALAR_NODE
+            && slice::from_raw_parts(
+                (*key_node).data.scalar.value,
+                (*key_node).data.scalar.length as usize,
+            ) == key
+        {
+            return (*pair).value;
+        }
+        pair = pair.add(1);
+    }
+    0
+}
+
+/// Find the value of a key node in a MAPPING node.
+///
+/// The node `key` of `key_document`, which may be `document` itself, is
+/// compared with the keys of the mapping as yaml_document_compare() does,
+/// leaving out styles, marks, anchors and the order of pairs, so a sequence or
+/// mapping key is found by its content. Scalar keys match if they have the same
+/// tag and value.
+///
+/// Returns the id of the value of the first matching pair, or 0 if there is
+/// none or the node is not a mapping.
+pub unsafe fn yaml_document_get_mapping_value_by_node(
+    document: *mut yaml_document_t,
+    mapping: libc::c_int,
+    key_document: *mut yaml_document_t,
+    key: libc::c_int,
+) -> libc::c_int {
+    __assert!(!document.is_null());
+    __assert!(mapping > 0 && mapping <= yaml_document_node_count(document));
+    __assert!(!key_document.is_null());
+    __assert!(key > 0 && key <= yaml_document_node_count(key_document));
+    let node = (*document).nodes.start.add(mapping as usize - 1);
+    if (*node).type_ != YAML_MAPPING_NODE {
+        return 0;
+    }
+    let options = yaml_compare_options_t {
+        ignore_styles: true,
+        ignore_marks: true,
+        ignore_anchors: true,
+        ignore_key_order: true,
+    };
+    let mut pair: *mut yaml_node_pair_t = (*node).data.mapping.pairs.start;
+    while pair < (*node).data.mapping.pairs.top {
+        let mut equal = false;
+        if yaml_document_compare_subtrees(
+            document,
+            (*pair).key,
+            key_document,
+            key,
+            &options,
+            &mut equal,
+        )
+        .ok && equal
+        {
+            return (*pair).value;
+        }
+        pair = pair.add(1);
+    }
+    0
+}
+
+/// An iterator over the pairs of a mapping node, made by
+/// yaml_document_mapping_iter() or yaml_document_root_mapping_iter().
+///
+/// It yields the key and the value node of each pair. The iterator borrows the
+/// document for the lifetime `'a`.
+pub struct yaml_mapping_iter_t<'a> {
+    document: &'a yaml_document_t,
+    pair: *mut yaml_node_pair_t,
+    end: *mut yaml_node_pair_t,
+}
+
+impl Iterator for yaml_mapping_iter_t<'_> {
+    type Item = (*mut yaml_node_t, *mut yaml_node_t);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pair >= self.end {
+            return None;
+        }
+        unsafe {
+            let nodes = self.document.nodes.start;
+            let key = nodes.add((*self.pair).key as usize - 1);
+            let value = nodes.add((*self.pair).value as usize - 1);
+            self.pair = self.pair.add(1);
+            Some((key, value))
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end.c_offset_from(self.pair) as usize;
+        (len, Some(len))
+    }
+}
+
+/// An iterator over the items of a sequence node, made by
+/// yaml_document_sequence_iter().
+///
+/// The iterator borrows the document for the lifetime `'a`.
+pub struct yaml_sequence_iter_t<'a> {
+    document: &'a yaml_document_t,
+    item: *mut yaml_node_item_t,
+    end: *mut yaml_node_item_t,
+}
+
+impl Iterator for yaml_sequence_iter_t<'_> {
+    type Item = *mut yaml_node_t;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.item >= self.end {
+            return None;
+        }
+        unsafe {
+            let node = self.document.nodes.start.add(*self.item as usize - 1);
+            self.item = self.item.add(1);
+            Some(node)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end.c_offset_from(self.item) as usize;
+        (len, Some(len))
+    }
+}
+
+/// Iterate over the pairs of a MAPPING node.
+///
+/// The iterator is empty if the node is not a mapping.
+///
+/// # Safety
+///
+/// The document must have been loaded or built with the document functions.
+pub unsafe fn yaml_document_mapping_iter(
+    document: &yaml_document_t,
+    mapping: libc::c_int,
+) -> yaml_mapping_iter_t<'_> {
+    __assert!(mapping > 0 && mapping <= yaml_document_node_count(document));
+    let node = document.nodes.start.add(mapping as usize - 1);
+    let mut iter = yaml_mapping_iter_t {
+        document,
+        pair: ptr::null_mut::<yaml_node_pair_t>(),
+        end: ptr::null_mut::<yaml_node_pair_t>(),
+    };
+    if (*node).type_ == YAML_MAPPING_NODE {
+        iter.pair = (*node).data.mapping.pairs.start;
+        iter.end = (*node).data.mapping.pairs.top;
+    }
+    iter
+}
+
+/// Iterate over the pairs of the root node of a document.
+///
+/// The iterator is empty if the document is empty or the root node is not a
+/// mapping.
+///
+/// # Safety
+///
+/// The document must have been loaded or built with the document functions.
+pub unsafe fn yaml_document_root_mapping_iter(
+    document: &yaml_document_t,
+) -> yaml_mapping_iter_t<'_> {
+    if yaml_document_node_count(document) == 0 {
+        return yaml_mapping_iter_t {
+            document,
+            pair: ptr::null_mut::<yaml_node_pair_t>(),
+            end: ptr::null_mut::<yaml_node_pair_t>(),
+        };
+    }
+    yaml_document_mapping_iter(document, 1)
+}
+
+/// Iterate over the items of a SEQUENCE node.
+///
+/// The iterator is empty if the node is not a sequence.
+///
+/// # Safety
+///
+/// The document must have been loaded or built with the document functions.
+pub unsafe fn yaml_document_sequence_iter(
+    document: &yaml_document_t,
+    sequence: libc::c_int,
+) -> yaml_sequence_iter_t<'_> {
+    __assert!(sequence > 0 && sequence <= yaml_document_node_count(document));
+    let node = document.nodes.start.add(sequence as usize - 1);
+    let mut iter = yaml_sequence_iter_t {
+        document,
+        item: ptr::null_mut::<yaml_node_item_t>(),
+        end: ptr::null_mut::<yaml_node_item_t>(),
+    };
+    if (*node).type_ == YAML_SEQUENCE_NODE {
+        iter.item = (*node).data.sequence.items.start;
+        iter.end = (*node).data.sequence.items.top;
+    }
+    iter
+}
+
+/// Get the nodes of a document as a slice.
+///
+/// The node with the id `n` is at the index `n - 1` of the slice, so the root
+/// node comes first. The slice borrows the document, so no nodes can be added
+/// or removed while it is in use.
+///
+/// # Safety
+///
+/// The document must have been loaded or built with the document functions.
+pub unsafe fn yaml_document_nodes(document: &yaml_document_t) -> &[yaml_node_t] {
+    let count = yaml_document_node_count(document);
+    if count == 0 {
+        return &[];
+    }
+    slice::from_raw_parts(document.nodes.start, count as usize)
+}
+
+/// Get the nodes of a document as a mutable slice, as yaml_document_nodes()
+/// does.
+///
+/// # Safety
+///
+/// The document must have been loaded or built with the document functions.
+pub unsafe fn yaml_document_nodes_mut(document: &mut yaml_document_t) -> &mut [yaml_node_t] {
+    let count = yaml_document_node_count(document);
+    if count == 0 {
+        return &mut [];
+    }
+    slice::from_raw_parts_mut(document.nodes.start, count as usize)
+}
+
+/// An iterator over the nodes of a document, made by
+/// yaml_document_node_iter().
+///
+/// It yields the id and the node of each node, in the order of the ids. The
+/// iterator borrows the document for the lifetime `'a`.
+pub struct yaml_node_iter_t<'a> {
+    start: *mut yaml_node_t,
+    node: *mut yaml_node_t,
+    end: *mut yaml_node_t,
+    marker: PhantomData<&'a yaml_document_t>,
+}
+
+impl Iterator for yaml_node_iter_t<'_> {
+    type Item = (libc::c_int, *mut yaml_node_t);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.node >= self.end {
+            return None;
+        }
+        let node = self.node;
+        unsafe {
+            self.node = self.node.add(1);
+            Some((node.c_offset_from(self.start) as libc::c_int + 1, node))
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end.c_offset_from(self.node) as usize;
+        (len, Some(len))
+    }
+}
+
+/// Iterate over the nodes of a document with their ids.
+///
+/// # Safety
+///
+/// The document must have been loaded or built with the document functions.
+pub unsafe fn yaml_document_node_iter(document: &yaml_document_t) -> yaml_node_iter_t<'_> {
+    yaml_node_iter_t {
+        start: document.nodes.start,
+        node: document.nodes.start,
+        end: document.nodes.top,
+        marker: PhantomData,
+    }
+}
+
+/// Find the node an anchor was given to.
+///
+/// The anchors of the loaded nodes are kept on the nodes, so the anchor is the
+/// NUL-terminated name written in the source after the `&` indicator.
+///
+/// Returns the id of the first node with the anchor, or 0 if there is none.
+pub unsafe fn yaml_document_get_anchored_node(
+    document: *mut yaml_document_t,
+    anchor: *const yaml_char_t,
+) -> libc::c_int {
+    __assert!(!document.is_null());
+    __assert!(!anchor.is_null());
+    let mut node: *mut yaml_node_t = (*document).nodes.start;
+    while node < (*document).nodes.top {
+        if !(*node).anchor.is_null()
+            && strcmp(
+                (*node).anchor as *const libc::c_char,
+                anchor as *const libc::c_char,
+            ) == 0
+        {
+            return node.c_offset_from((*document).nodes.start) as libc::c_int + 1;
+        }
+        node = node.add(1);
+    }
+    0
+}
+
+/// An iterator over the anchored nodes of a document, made by
+/// yaml_document_anchor_iter().
+///
+/// It yields the anchor, without its NUL byte, and the id of each node with an
+/// anchor, in the order of the nodes. The iterator borrows the document for the
+/// lifetime `'a`.
+pub struct yaml_anchor_iter_t<'a> {
+    start: *mut yaml_node_t,
+    node: *mut yaml_node_t,
+    end: *mut yaml_node_t,
+    marker: PhantomData<&'a yaml_document_t>,
+}
+
+impl<'a> Iterator for yaml_anchor_iter_t<'a> {
+    type Item = (&'a [u8], libc::c_int);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        unsafe {
+            while self.node < self.end {
+                let node = self.node;
+                self.node = self.node.add(1);
+                if !(*node).anchor.is_null() {
+                    let id = node.c_offset_from(self.start) as libc::c_int + 1;
+                    let length = strlen((*node).anchor as *const libc::c_char) as usize;
+                    return Some((slice::from_raw_parts((*node).anchor, length), id));
+                }
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.end.c_offset_from(self.node) as usize))
+    }
+}
+
+/// Iterate over the anchors of the nodes of a document.
+///
+/// # Safety
+///
+/// The document must have been loaded or built with the document functions.
+pub unsafe fn yaml_document_anchor_iter(document: &yaml_document_t) -> yaml_anchor_iter_t<'_> {
+    yaml_anchor_iter_t {
+        start: document.nodes.start,
+        node: document.nodes.start,
+        end: document.nodes.top,
+        marker: PhantomData,
+    }
+}
+
+/// A node of a document, made by yaml_document_root_ref() or
+/// yaml_document_node_ref().
+///
+/// get() gives the value of a scalar key of a mapping and get_item() the item
+/// of a sequence, following alias nodes. The reference must not be used after
+/// any of the functions modifying the document are called.
+#[derive(Copy, Clone)]
+pub struct yaml_node_ref_t<'a> {
+    document: *mut yaml_document_t,
+    id: libc::c_int,
+    marker: PhantomData<&'a yaml_document_t>,
+}
+
+impl<'a> yaml_node_ref_t<'a> {
+    /// Alias nodes are followed at most as many times as there are nodes, so
+    /// that a cycle of alias nodes ends at one of them.
+    unsafe fn from_id(document: *mut yaml_document_t, mut id: libc::c_int) -> Self {
+        let count = yaml_document_node_count(document);
+        let mut steps = 0;
+        loop {
+            let node = (*document).nodes.start.add(id as usize - 1);
+            if (*node).type_ != YAML_ALIAS_NODE
+                || (*node).data.alias.target <= 0
+                || (*node).data.alias.target > count
+                || steps == count
+            {
+                break;
+            }
+            id = (*node).data.alias.target;
+            steps += 1;
+        }
+        yaml_node_ref_t {
+            document,
+            id,
+            marker: PhantomData,
+        }
+    }
+
+    /// The document of the node.
+    pub(crate) fn document(self) -> *mut yaml_document_t {
+        self.document
+    }
+
+    /// The node.
+    pub fn node(self) -> &'a yaml_node_t {
+        // SAFETY: from_id() is only given the ids of nodes of the document,
+        // which stays unmodified for 'a.
+        unsafe { &*(*self.document).nodes.start.add(self.id as usize - 1) }
+    }
+
+    /// The index of the node in the document, starting from 1.
+    pub fn id(self) -> libc::c_int {
+        self.id
+    }
+
+    /// The reference to another node of the document, following alias nodes.
+    pub(crate) unsafe fn child(self, id: libc::c_int) -> Self {
+        yaml_node_ref_t::from_id(self.document, id)
+    }
+
+    /// The value of the key in a mapping, or None if the node is not a
+    /// mapping or has no such scalar key.
+    pub fn get(self, key: &str) -> Option<Self> {
+        let node = self.node();
+        if node.type_ != YAML_MAPPING_NODE {
+            return None;
+        }
+        // SAFETY: the node is a mapping, whose pairs hold ids of nodes of the
+        // document.
+        unsafe {
+            let pairs = node.data.mapping.pairs;
+            let mut pair = pairs.start;
+            while pair < pairs.top {
+                if self.child((*pair).key).as_bytes() == Some(key.as_bytes()) {
+                    return Some(self.child((*pair).value));
+                }
+                pair = pair.add(1);
+            }
+        }
+        None
+    }
+
+    /// The item at the index in a sequence, or None if the node is not a
+    /// sequence or the index is out of bounds.
+    pub fn get_item(self, index: usize) -> Option<Self> {
+        let node = self.node();
+        if node.type_ != YAML_SEQUENCE_NODE {
+            return None;
+        }
+        // SAFETY: the node is a sequence, whose items are ids of nodes of the
+        // document, and the index is checked against their number.
+        unsafe {
+            let items = node.data.sequence.items;
+            if index >= items.top.c_offset_from(items.start) as usize {
+                return None;
+            }
+            Some(self.child(*items.start.add(index)))
+        }
+    }
+
+    /// The value of a scalar, or None if the node is not a scalar.
+    pub fn as_bytes(self) -> Option<&'a [u8]> {
+        let node = self.node();
+        if node.type_ != YAML_SCALAR_NODE {
+            return None;
+        }
+        // SAFETY: the node is a scalar, whose value has `length` bytes.
+        unsafe {
+            Some(slice::from_raw_parts(
+                node.data.scalar.value,
+                node.data.scalar.length as usize,
+            ))
+        }
+    }
+
+    /// The value of a scalar, or None if the node is not a scalar.
+    pub fn as_str(self) -> Option<&'a str> {
+        str::from_utf8(self.as_bytes()?).ok()
+    }
+}
+
+/// Get a reference to a node of a document, following alias nodes.
+///
+/// The document must remain valid and unmodified for `'a`.
+pub unsafe fn yaml_document_node_ref<'a>(
+    document: *mut yaml_document_t,
+    index: libc::c_int,
+) -> yaml_node_ref_t<'a> {
+    __assert!(!document.is_null());
+    __assert!(index > 0 && index <= yaml_document_node_count(document));
+    yaml_node_ref_t::from_id(document, index)
+}
+
+/// Get a reference to the root node of a document, or None if the document is
+/// empty.
+///
+/// The document must remain valid and unmodified for `'a`.
+pub unsafe fn yaml_document_root_ref<'a>(
+    document: *mut yaml_document_t,
+) -> Option<yaml_node_ref_t<'a>> {
+    __assert!(!document.is_null());
+    if yaml_document_node_count(document) == 0 {
+        return None;
+    }
+    Some(yaml_document_node_ref(document, 1))
+}
+
+/// Get the source text of a node.
+///
+/// `input` is the UTF-8 text of `size` bytes the document was loaded from
+/// with yaml_parser_set_input_string(), and the text between the start and the
+/// end mark of the node is stored in `slice` and `length`. A byte order mark
+/// at the beginning of the input is skipped, as the marks do not count it. If
+/// the document was loaded with yaml_parser_set_input_string_with_origin(),
+/// the marks point into the enclosing text, which must be passed instead. The
+/// function fails if the node lies outside of the input, as it does for nodes
+/// that were not loaded.
+pub unsafe fn yaml_document_get_node_source(
+    document: *mut yaml_document_t,
+    index: libc::c_int,
+    mut input: *const yaml_char_t,
+    mut size: size_t,
+    slice: *mut *const yaml_char_t,
+    length: *mut size_t,
+) -> Success {
+    __assert!(!document.is_null());
+    __assert!(index > 0 && index <= yaml_document_node_count(document));
+    __assert!(!input.is_null());
+    __assert!(!slice.is_null());
+    __assert!(!length.is_null());
+    if size >= 3 && slice::from_raw_parts(input, 3) == b"\xEF\xBB\xBF" {
+        input = input.add(3);
+        size -= 3;
+    }
+    let node = (*document).nodes.start.add(index as usize - 1);
+    let start = (*node).start_mark.index;
+    let end = (*node).end_mark.index;
+    if start > end || end > size {
+        return FAIL;
+    }
+    *slice = input.add(start as usize);
+    *length = end - start;
+    OK
+}
+
+/// Set the comments of a node.
+///
+/// The `leading` comment is written on the lines before the node and the
+/// `trailing` comment after the node on the same line, without the `#`
+/// indicators; a comment of several lines is separated by line breaks. NULL
+/// removes a comment. The function fails if a comment is not valid UTF-8.
+///
+/// The emitter writes trailing comments of plain and quoted scalars only, and
+/// no comments inside flow collections. The comment after the document content
+/// is kept in the `end_comment` field of the document.
+pub unsafe fn yaml_document_set_node_comments(
+    document: *mut yaml_document_t,
+    index: libc::c_int,
+    leading: *const yaml_char_t,
+    trailing: *const yaml_char_t,
+) -> Success {
+    __assert!(!document.is_null());
+    __assert!(index > 0 && index <= yaml_document_node_count(document));
+    if !leading.is_null() && yaml_check_utf8(leading, strlen(leading as *const libc::c_char)).fail
+        || !trailing.is_null()
+            && yaml_check_utf8(trailing, strlen(trailing as *const libc::c_char)).fail
+    {
+        return FAIL;
+    }
+    let node = (*document).nodes.start.add(index as usize - 1);
+    yaml_free((*node).leading_comment as *mut libc::c_void);
+    yaml_free((*node).trailing_comment as *mut libc::c_void);
+    (*node).leading_comment = yaml_strdup(leading);
+    (*node).trailing_comment = yaml_strdup(trailing);
+    OK
+}
+
+/// Attach application data to a node.
+///
+/// The `data` pointer is kept in the `user_data` field of the node, so that an
+/// application can annotate the nodes it visits without a table of node ids on
+/// the side. The pointer is not used by the library and the pointed data is
+/// not freed with the document. NULL removes the data.
+pub unsafe fn yaml_document_set_node_data(
+    document: *mut yaml_document_t,
+    index: libc::c_int,
+    data: *mut libc::c_void,
+) {
+    __assert!(!document.is_null());
+    __assert!(index > 0 && index <= yaml_document_node_count(document));
+    (*(*document).nodes.start.add(index as usize - 1)).user_data = data;
+}
+
+/// Get the application data attached to a node, or NULL.
+pub unsafe fn yaml_document_get_node_data(
+    document: *mut yaml_document_t,
+    index: libc::c_int,
+) -> *mut libc::c_void {
+    __assert!(!document.is_null());
+    __assert!(index > 0 && index <= yaml_document_node_count(document));
+    (*(*document).nodes.start.add(index as usize - 1)).user_data
+}
+
+/// Estimate the memory used by a document.
+///
+/// This is the size of the document object and of every block allocated for
+/// it: the stacks of nodes, items and pairs with their spare capacity, the
+/// tags, anchors, comments, values and source text of the nodes, and the
+/// directives. The overhead of the allocator is not counted. Applications that
+/// keep many documents around may use it to bound the size of their caches.
+pub unsafe fn yaml_document_memory_usage(document: *mut yaml_document_t) -> size_t {
+    __assert!(!document.is_null());
+    let mut size = size_of::<yaml_document_t>() as size_t;
+    size += yaml_allocated((*document).nodes.start as *mut libc::c_void);
+    let mut node = (*document).nodes.start;
+    while node < (*document).nodes.top {
+        size += yaml_allocated((*node).tag as *mut libc::c_void);
+        size += yaml_allocated((*node).anchor as *mut libc::c_void);
+        size += yaml_allocated((*node).leading_comment as *mut libc::c_void);
+        size += yaml_allocated((*node).trailing_comment as *mut libc::c_void);
+        match (*node).type_ {
+            YAML_SCALAR_NODE => {
+                size += yaml_allocated((*node).data.scalar.value as *mut libc::c_void);
+                size += yaml_allocated((*node).data.scalar.source as *mut libc::c_void);
+            }
+            YAML_SEQUENCE_NODE => {
+                size += yaml_allocated((*node).data.sequence.items.start as *mut libc::c_void);
+            }
+            YAML_MAPPING_NODE => {
+                size += yaml_allocated((*node).data.mapping.pairs.start as *mut libc::c_void);
+            }
+            _ => {}
+        }
+        node = node.add(1);
+    }
+    size += yaml_allocated((*document).version_directive as *mut libc::c_void);
+    let mut tag_directive = (*document).tag_directives.start;
+    while tag_directive < (*document).tag_directives.end {
+        size += yaml_allocated((*tag_directive).handle as *mut libc::c_void);
+        size += yaml_allocated((*tag_directive).prefix as *mut libc::c_void);
+        tag_directive = tag_directive.add(1);
+    }
+    size += yaml_allocated((*document).tag_directives.start as *mut libc::c_void);
+    size + yaml_allocated((*document).end_comment as *mut libc::c_void)
+}
+
+/// Initialize `copy` with a deep copy of `document`, keeping the node ids.
+pub(crate) unsafe fn yaml_document_duplicate(
+    copy: *mut yaml_document_t,
+    document: *mut yaml_document_t,
+) -> Success {
+    if yaml_document_initialize(
+        copy,
+        (*document).version_directive,
+        (*document).tag_directives.start,
+        (*document).tag_directives.end,
+        (*document).start_implicit,
+        (*document).end_implicit,
+    )
+    .fail
+    {
+        return FAIL;
+    }
+    let mut node: *mut yaml_node_t = (*document).nodes.start;
+    while node < (*document).nodes.top {
+        let mut duplicate = *node;
+        duplicate.tag = yaml_strdup((*node).tag);
+        duplicate.anchor = yaml_strdup((*node).anchor);
+        duplicate.leading_comment = yaml_strdup((*node).leading_comment);
+        duplicate.trailing_comment = yaml_strdup((*node).trailing_comment);
+        match (*node).type_ {
+            YAML_SCALAR_NODE => {
+                let length = (*node).data.scalar.length;
+                duplicate.data.scalar.value = yaml_malloc(length.force_add(1)) as *mut yaml_char_t;
+                memcpy(
+                    duplicate.data.scalar.value as *mut libc::c_void,
+                    (*node).data.scalar.value as *const libc::c_void,
+                    length,
+                );
+                *duplicate.data.scalar.value.add(length as usize) = b'\0';
+                duplicate.data.scalar.source =
+                    yaml_scalar_source_duplicate((*node).data.scalar.source);
+            }
+            YAML_SEQUENCE_NODE => {
+                STACK_INIT!(duplicate.data.sequence.items, yaml_node_item_t);
+                let mut item: *mut yaml_node_item_t = (*node).data.sequence.items.start;
+                while item < (*node).data.sequence.items.top {
+                    PUSH!(duplicate.data.sequence.items, *item);
+                    item = item.add(1);
+                }
+            }
+            YAML_MAPPING_NODE => {
+                STACK_INIT!(duplicate.data.mapping.pairs, yaml_node_pair_t);
+                let mut pair: *mut yaml_node_pair_t = (*node).data.mapping.pairs.start;
+                while pair < (*node).data.mapping.pairs.top {
+                    PUSH!(duplicate.data.mapping.pairs, *pair);
+                    pair = pair.add(1);
+                }
+            }
+            _ => {}
+        }
+        PUSH!((*copy).nodes, duplicate);
+        node = node.add(1);
+    }
+    (*copy).end_comment = yaml_strdup((*document).end_comment);
+    OK
+}
+
+/// Add a scalar to a document at the end of a path of keys and indices.
+///
+/// The path is made of `path_length` NUL-terminated segments, starting from
+/// the root node. Each segment is a key of a mapping or, if the node is a
+/// sequence, a decimal index into it. The mappings and sequences missing on
+/// the way are created, a sequence for a segment of decimal digits and a
+/// mapping otherwise, so a document can be built from a list of flattened
+/// (path, value) pairs. A new sequence item may only be appended right after
+/// the last one.
+///
+/// The function fails if the path goes through a scalar, uses a key as a
+/// sequence index, skips sequence items, or leads to a node that already
+/// exists.
+pub unsafe fn yaml_document_add_path(
+    document: *mut yaml_document_t,
+    path: *const *const yaml_char_t,
+    path_length: size_t,
+    value: *const yaml_char_t,
+    length: libc::c_int,
+) -> Success {
+    __assert!(!document.is_null());
+    __assert!(!path.is_null() || path_length == 0);
+    __assert!(!value.is_null());
+    let segments = if path_length == 0 {
+        &[][..]
+    } else {
+        slice::from_raw_parts(path, path_length as usize)
+    };
+    if yaml_document_node_count(document) == 0 {
+        let root = yaml_document_add_path_node(document, segments.first().copied(), value, length);
+        if root == 0 {
+            return FAIL;
+        }
+    } else if segments.is_empty() {
+        return FAIL;
+    }
+    let mut parent: libc::c_int = 1;
+    for (i, &segment) in segments.iter().enumerate() {
+        let node = (*document).nodes.start.add(parent as usize - 1);
+        let mut child: libc::c_int = 0;
+        match (*node).type_ {
+            YAML_MAPPING_NODE => {
+                child = yaml_document_get_mapping_value(document, parent, segment, -1);
+            }
+            YAML_SEQUENCE_NODE => {
+                let index = match yaml_path_segment_index(segment) {
+                    Some(index) => index,
+                    None => return FAIL,
+                };
+                let items = (*node).data.sequence.items;
+                let count = items.top.c_offset_from(items.start) as usize;
+                if index < count {
+                    child = *items.start.add(index);
+                } else if index > count {
+                    return FAIL;
+                }
+            }
+            _ => return FAIL,
+        }
+        let last = i + 1 == segments.len();
+        if child != 0 {
+            if last {
+                return FAIL;
+            }
+            parent = child;
+            continue;
+        }
+        let next = if last { None } else { Some(segments[i + 1]) };
+        child = yaml_document_add_path_node(document, next, value, length);
+        if child == 0 {
+            return FAIL;
+        }
+        let node = (*document).nodes.start.add(parent as usize - 1);
+        if (*node).type_ == YAML_MAPPING_NODE {
+            let key =
+                yaml_document_add_scalar(document, ptr::null(), segment, -1, YAML_ANY_SCALAR_STYLE);
+            if key == 0 || yaml_document_append_mapping_pair(document, parent, key, child).fail {
+                return FAIL;
+            }
+        } else if yaml_document_append_sequence_item(document, parent, child).fail {
+            return FAIL;
+        }
+        parent = child;
+    }
+    OK
+}
+
+unsafe fn yaml_document_add_path_node(
+    document: *mut yaml_document_t,
+    next: Option<*const yaml_char_t>,
+    value: *const yaml_char_t,
+    length: libc::c_int,
+) -> libc::c_int {
+    match next {
+        None => {
+            yaml_document_add_scalar(document, ptr::null(), value, length, YAML_ANY_SCALAR_STYLE)
+        }
+        Some(segment) if yaml_path_segment_index(segment).is_some() => {
+            yaml_document_add_sequence(document, ptr::null(), YAML_ANY_SEQUENCE_STYLE)
+        }
+        Some(_) => yaml_document_add_mapping(document, ptr::null(), YAML_ANY_MAPPING_STYLE),
+    }
+}
+
+unsafe fn yaml_path_segment_index(segment: *const yaml_char_t) -> Option<usize> {
+    let digits = slice::from_raw_parts(segment, strlen(segment as *const libc::c_char) as usize);
+    if digits.is_empty() || !digits.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    let mut index: usize = 0;
+    for digit in digits {
+        index = index
+            .checked_mul(10)?
+            .checked_add((digit - b'0') as usize)?;
+    }
+    Some(index)
+}
+
+/// Create a SCALAR node as a child of a SEQUENCE or MAPPING node.
+///
+/// This is yaml_document_add_scalar() followed by linking the new node to
+/// `parent`: it is appended to a sequence, for which `key` must be NULL, or
+/// added to a mapping as the value of the NUL-terminated `key`, which becomes a
+/// new scalar node. The key is not checked against the existing keys.
+///
+/// Returns the id of the new node or 0 on error, in which case the document is
+/// left unchanged.
+pub unsafe fn yaml_document_add_child_scalar(
+    document: *mut yaml_document_t,
+    parent: libc::c_int,
+    key: *const yaml_char_t,
+    tag: *const yaml_char_t,
+    value: *const yaml_char_t,
+    length: libc::c_int,
+    style: yaml_scalar_style_t,
+) -> libc::c_int {
+    if yaml_document_check_child_key(document, parent, key).fail {
+        return 0;
+    }
+    let child = yaml_document_add_scalar(document, tag, value, length, style);
+    yaml_document_link_child(document, parent, key, child)
+}
+
+/// Create a SEQUENCE node as a child of a SEQUENCE or MAPPING node.
+///
+/// The node is linked to `parent` as by yaml_document_add_child_scalar().
+pub unsafe fn yaml_document_add_child_sequence(
+    document: *mut yaml_document_t,
+    parent: libc::c_int,
+    key: *const yaml_char_t,
+    tag: *const yaml_char_t,
+    style: yaml_sequence_style_t,
+) -> libc::c_int {
+    if yaml_document_check_child_key(document, parent, key).fail {
+        return 0;
+    }
+    let child = yaml_document_add_sequence(document, tag, style);
+    yaml_document_link_child(document, parent, key, child)
+}
+
+/// Create a MAPPING node as a child of a SEQUENCE or MAPPING node.
+///
+/// The node is linked to `parent` as by yaml_document_add_child_scalar().
+pub unsafe fn yaml_document_add_child_mapping(
+    document: *mut yaml_document_t,
+    parent: libc::c_int,
+    key: *const yaml_char_t,
+    tag: *const yaml_char_t,
+    style: yaml_mapping_style_t,
+) -> libc::c_int {
+    if yaml_document_check_child_key(document, parent, key).fail {
+        return 0;
+    }
+    let child = yaml_document_add_mapping(document, tag, style);
+    yaml_document_link_child(document, parent, key, child)
+}
+
+unsafe fn yaml_document_check_child_key(
+    document: *mut yaml_document_t,
+    parent: libc::c_int,
+    key: *const yaml_char_t,
+) -> Success {
+    __assert!(!document.is_null());
+    __assert!(parent > 0 && parent <= yaml_document_node_count(document));
+    match (*(*document).nodes.start.add(parent as usize - 1)).type_ {
+        YAML_SEQUENCE_NODE => __assert!(key.is_null()),
+        YAML_MAPPING_NODE => __assert!(!key.is_null()),
+        _ => __assert!(false),
+    }
+    // The key is checked before any node is added, so that a failure leaves
+    // the document unchanged.
+    if key.is_null() {
+        OK
+    } else {
+        yaml_check_utf8(key, strlen(key as *const libc::c_char))
+    }
+}
+
+unsafe fn yaml_document_link_child(
+    document: *mut yaml_document_t,
+    parent: libc::c_int,
+    key: *const yaml_char_t,
+    child: libc::c_int,
+) -> libc::c_int {
+    if child == 0 {
+        return 0;
+    }
+    if key.is_null() {
+        if yaml_document_append_sequence_item(document, parent, child).fail {
+            return 0;
+        }
+    } else {
+        let key = yaml_document_add_scalar(document, ptr::null(), key, -1, YAML_ANY_SCALAR_STYLE);
+        if key == 0 || yaml_document_append_mapping_pair(document, parent, key, child).fail {
+            return 0;
+        }
+    }
+    child
+}
+
+/// Remove a node from a document.
+///
+/// The sequence items referring to the node are removed, and so are the
+/// mapping pairs that have it as the key or the value. The nodes after it move
+/// down by one, so their ids are decremented and all references to them are
+/// updated. If the root node is removed, the node with id 2 becomes the root.
+///
+/// The children of a removed collection stay in the document. Use
+/// yaml_document_detach_subtree() to remove them as well.
+pub unsafe fn yaml_document_remove_node(
+    document: *mut yaml_document_t,
+    index: libc::c_int,
+) -> Success {
+    __assert!(!document.is_null());
+    let nodes = yaml_document_node_count(document);
+    __assert!(index > 0 && index <= nodes);
+    let targets = yaml_document_alloc_targets(nodes);
+    if targets.is_null() {
+        return FAIL;
+    }
+    *targets.add(index as usize - 1) = 0;
+    yaml_document_remove_nodes(document, targets)
+}
+
+/// Replace a node of a document with another node of the same document.
+///
+/// The node `replacement` takes the place and the id of the node `index`,
+/// which is deleted, so every reference to either of them refers to the
+/// replacement afterwards. The nodes after `replacement` move down by one and
+/// the references to them are updated. The children of the deleted node stay
+/// in the document.
+///
+/// Returns FAIL and leaves the document unchanged if `replacement` contains
+/// `index`, as that would make it contain itself.
+pub unsafe fn yaml_document_replace_node(
+    document: *mut yaml_document_t,
+    index: libc::c_int,
+    replacement: libc::c_int,
+) -> Success {
+    __assert!(!document.is_null());
+    let nodes = yaml_document_node_count(document);
+    __assert!(index > 0 && index <= nodes);
+    __assert!(replacement > 0 && replacement <= nodes);
+    if index == replacement {
+        return OK;
+    }
+    if yaml_document_node_contains(document, replacement, index) {
+        return FAIL;
+    }
+    let targets = yaml_document_alloc_targets(nodes);
+    if targets.is_null() {
+        return FAIL;
+    }
+    let node = (*document).nodes.start.add(index as usize - 1);
+    yaml_node_free(node);
+    *node = *(*document).nodes.start.add(replacement as usize - 1);
+    *targets.add(replacement as usize - 1) = index;
+    yaml_document_remove_nodes(document, targets)
+}
+
+unsafe fn yaml_document_node_contains(
+    document: *mut yaml_document_t,
+    ancestor: libc::c_int,
+    descendant: libc::c_int,
+) -> bool {
+    let mut pending = vec![ancestor];
+    let mut seen = vec![false; yaml_document_node_count(document) as usize];
+    while let Some(id) = pending.pop() {
+        if id == descendant {
+            return true;
+        }
+        if seen[id as usize - 1] {
+            continue;
+        }
+        seen[id as usize - 1] = true;
+        yaml_node_for_each_child((*document).nodes.start.add(id as usize - 1), |child| {
+            pending.push(child);
+        });
+    }
+    false
+}
+
+/// Remove a node from a document together with its descendants.
+///
+/// This is yaml_document_remove_node() for the node and every node contained
+/// in it, except for the descendants that are also referenced from outside of
+/// the removed subtree, which stay in the document.
+pub unsafe fn yaml_document_detach_subtree(
+    document: *mut yaml_document_t,
+    index: libc::c_int,
+) -> Success {
+    __assert!(!document.is_null());
+    let nodes = yaml_document_node_count(document);
+    __assert!(index > 0 && index <= nodes);
+    let targets = yaml_document_alloc_targets(nodes);
+    if targets.is_null() {
+        return FAIL;
+    }
+    // Mark the subtree with -1, then mark the parts of it that are reachable
+    // from the rest of the document with -2.
+    memset(
+        targets as *mut libc::c_void,
+        0,
+        (size_of::<libc::c_int>() as libc::c_ulong).force_mul(nodes as libc::c_ulong),
+    );
+    *targets.add(index as usize - 1) = -1;
+    for mark in [-1, -2] {
+        let mut changed = true;
+        while changed {
+            changed = false;
+            let mut id: libc::c_int = 1;
+            let mut node: *mut yaml_node_t = (*document).nodes.start;
+            while node < (*document).nodes.top {
+                let parent = *targets.add(id as usize - 1);
+                if mark == -1 && parent == -1 || mark == -2 && parent != -1 && id != index {
+                    yaml_node_for_each_child(node, |child| {
+                        let target = targets.add(child as usize - 1);
+                        if child != index && *target == mark + 1 {
+                            *target = mark;
+                            changed = true;
+                        }
+                    });
+                }
+                id += 1;
+                node = node.add(1);
+            }
+        }
+    }
+    let mut id: libc::c_int = 1;
+    while id <= nodes {
+        let target = targets.add(id as usize - 1);
+        *target = if *target == -1 { 0 } else { id };
+        id += 1;
+    }
+    yaml_document_remove_nodes(document, targets)
+}
+
+/// Copy a node and its descendants into a new document.
+///
+/// The node becomes the root node of `extracted`, which is given the
+/// directives and the document markers of `document`. The nodes shared within
+/// the subtree are shared in the copy, so the emitter writes them with anchors
+/// and aliases, while the anchors of the source are not kept. The copies keep
+/// the marks of the source and record the transformation "extract" and the id
+/// of the node they were made from. The source document is left unchanged;
+/// yaml_document_detach_subtree() removes the subtree from it.
+///
+/// An application is responsible for freeing the extracted document using the
+/// yaml_document_delete() function.
+pub unsafe fn yaml_document_extract_subtree(
+    extracted: *mut yaml_document_t,
+    document: *mut yaml_document_t,
+    index: libc::c_int,
+) -> Success {
+    __assert!(!extracted.is_null());
+    __assert!(!document.is_null());
+    __assert!(index > 0 && index <= yaml_document_node_count(document));
+    if yaml_document_initialize(
+        extracted,
+        (*document).version_directive,
+        (*document).tag_directives.start,
+        (*document).tag_directives.end,
+        (*document).start_implicit,
+        (*document).end_implicit,
+    )
+    .fail
+    {
+        return FAIL;
+    }
+    if yaml_document_copy_subtree(
+        extracted,
+        document,
+        index,
+        b"extract\0" as *const u8 as *const libc::c_char,
+    ) == 0
+    {
+        yaml_document_delete(extracted);
+        return FAIL;
+    }
+    OK
+}
+
+unsafe fn yaml_document_alloc_targets(nodes: libc::c_int) -> *mut libc::c_int {
+    let targets =
+        yaml_malloc((size_of::<libc::c_int>() as libc::c_ulong).force_mul(nodes as libc::c_ulong))
+            as *mut libc::c_int;
+    if !targets.is_null() {
+        let mut id: libc::c_int = 1;
+        while id <= nodes {
+            *targets.add(id as usize - 1) = id;
+            id += 1;
+        }
+    }
+    targets
+}
+
+/// Compact the nodes of a document and rewrite the references between them.
+///
+/// `targets` holds, for every node id, the id that references to it are
+/// redirected to. A node whose target is its own id is kept. A node whose
+/// target is 0 is freed and the references to it are removed. A node with
+/// any other target has been moved there and only its slot is dropped. The
+/// `targets` array is freed.
+unsafe fn yaml_document_remove_nodes(
+    document: *mut yaml_document_t,
+    targets: *mut libc::c_int,
+) -> Success {
+    let nodes = yaml_document_node_count(document);
+    let ids =
+        yaml_malloc((size_of::<libc::c_int>() as libc::c_ulong).force_mul(nodes as libc::c_ulong))
+            as *mut libc::c_int;
+    if ids.is_null() {
+        yaml_free(targets as *mut libc::c_void);
+        return FAIL;
+    }
+    // An alias node goes away with the node it refers to, which may itself be
+    // an alias, so repeat until no more aliases are removed.
+    let mut changed = true;
+    while changed {
+        changed = false;
+        let mut id: libc::c_int = 1;
+        while id <= nodes {
+            let node = (*document).nodes.start.add(id as usize - 1);
+            let target = targets.add(id as usize - 1);
+            if (*node).type_ == YAML_ALIAS_NODE
+                && *target != 0
+                && *targets.add((*node).data.alias.target as usize - 1) == 0
+            {
+                *target = 0;
+                changed = true;
+            }
+            id += 1;
+        }
+    }
+    let mut kept: libc::c_int = 0;
+    let mut id: libc::c_int = 1;
+    while id <= nodes {
+        if *targets.add(id as usize - 1) == id {
+            kept += 1;
+            *ids.add(id as usize - 1) = kept;
+        }
+        id += 1;
+    }
+    let new_id = |child: libc::c_int| -> libc::c_int {
+        let target = *targets.add(child as usize - 1);
+        if target == 0 {
+            0
+        } else {
+            *ids.add(target as usize - 1)
+        }
+    };
+    id = 1;
+    while id <= nodes {
+        let node = (*document).nodes.start.add(id as usize - 1);
+        let target = *targets.add(id as usize - 1);
+        if target == 0 {
+            yaml_node_free(node);
+        } else if target == id {
+            match (*node).type_ {
+                YAML_SEQUENCE_NODE => {
+                    let items = addr_of_mut!((*node).data.sequence.items);
+                    let mut item: *mut yaml_node_item_t = (*items).start;
+                    let mut top: *mut yaml_node_item_t = (*items).start;
+                    while item < (*items).top {
+                        let child = new_id(*item);
+                        if child != 0 {
+                            *top = child;
+                            top = top.add(1);
+                        }
+                        item = item.add(1);
+                    }
+                    (*items).top = top;
+                }
+                YAML_MAPPING_NODE => {
+                    let pairs = addr_of_mut!((*node).data.mapping.pairs);
+                    let mut pair: *mut yaml_node_pair_t = (*pairs).start;
+                    let mut top: *mut yaml_node_pair_t = (*pairs).start;
+                    while pair < (*pairs).top {
+                        let key = new_id((*pair).key);
+                        let value = new_id((*pair).value);
+                        if key != 0 && value != 0 {
+                            *top = yaml_node_pair_t { key, value };
+                            top = top.add(1);
+                        }
+                        pair = pair.add(1);
+                    }
+                    (*pairs).top = top;
+                }
+                YAML_ALIAS_NODE => {
+                    (*node).data.alias.target = new_id((*node).data.alias.target);
+                }
+                _ => {}
+            }
+        }
+        id += 1;
+    }
+    id = 1;
+    while id <= nodes {
+        if *targets.add(id as usize - 1) == id {
+            *(*document)
+                .nodes
+                .start
+                .add(*ids.add(id as usize - 1) as usize - 1) =
+                *(*document).nodes.start.add(id as usize - 1);
+        }
+        id += 1;
+    }
+    (*document).nodes.top = (*document).nodes.start.add(kept as usize);
+    yaml_free(ids as *mut libc::c_void);
+    yaml_free(targets as *mut libc::c_void);
+    OK
+}
+
+unsafe fn yaml_node_free(node: *mut yaml_node_t) {
+    yaml_free((*node).tag as *mut libc::c_void);
+    yaml_free((*node).anchor as *mut libc::c_void);
+    yaml_free((*node).leading_comment as *mut libc::c_void);
+    yaml_free((*node).trailing_comment as *mut libc::c_void);
+    match (*node).type_ {
+        YAML_SCALAR_NODE => {
+            yaml_free((*node).data.scalar.value as *mut libc::c_void);
+            yaml_free((*node).data.scalar.source as *mut libc::c_void);
+        }
+        YAML_SEQUENCE_NODE => {
+            STACK_DEL!((*node).data.sequence.items);
+        }
+        YAML_MAPPING_NODE => {
+            STACK_DEL!((*node).data.mapping.pairs);
+        }
+        _ => {}
+    }
+}
+
+/// Walk the nodes of a document depth-first, starting from the root node.
+///
+/// The `visitor` is called with `data` for every node, and twice for every
+/// collection, before and after its contents. The keys and values of a mapping
+/// are visited in the order of the pairs, each key before its value.
+///
+/// Returns FAIL if the visitor failed, which stops the walk.
+pub unsafe fn yaml_document_walk(
+    document: *mut yaml_document_t,
+    visitor: yaml_node_visitor_t,
+    data: *mut libc::c_void,
+) -> Success {
+    __assert!(!document.is_null());
+    let nodes = yaml_document_node_count(document);
+    if nodes == 0 {
+        return OK;
+    }
+    let visited = yaml_malloc(nodes as libc::c_ulong) as *mut bool;
+    if visited.is_null() {
+        return FAIL;
+    }
+    memset(visited as *mut libc::c_void, 0, nodes as libc::c_ulong);
+    let mut path = MaybeUninit::<yaml_stack_t<yaml_path_step_t>>::uninit();
+    let path = path.as_mut_ptr();
+    STACK_INIT!(*path, yaml_path_step_t);
+    let success = yaml_document_walk_node(document, 1, visitor, data, visited, path);
+    STACK_DEL!(*path);
+    yaml_free(visited as *mut libc::c_void);
+    success
+}
+
+unsafe fn yaml_document_walk_node(
+    document: *mut yaml_document_t,
+    id: libc::c_int,
+    visitor: yaml_node_visitor_t,
+    data: *mut libc::c_void,
+    visited: *mut bool,
+    path: *mut yaml_stack_t<yaml_path_step_t>,
+) -> Success {
+    let node = (*document).nodes.start.add(id as usize - 1);
+    let depth = (*path).top.c_offset_from((*path).start) as size_t;
+    let visit = |type_: yaml_visit_type_t| visitor(data, type_, id, node, (*path).start, depth);
+    if *visited.add(id as usize - 1) {
+        return if visit(YAML_VISIT_ALIAS) == 0 {
+            FAIL
+        } else {
+            OK
+        };
+    }
+    *visited.add(id as usize - 1) = true;
+    let (start, end) = match (*node).type_ {
+        YAML_SEQUENCE_NODE => (YAML_VISIT_SEQUENCE_START, YAML_VISIT_SEQUENCE_END),
+        YAML_MAPPING_NODE => (YAML_VISIT_MAPPING_START, YAML_VISIT_MAPPING_END),
+        YAML_ALIAS_NODE => {
+            return if visit(YAML_VISIT_ALIAS) == 0 {
+                FAIL
+            } else {
+                OK
+            };
+        }
+        _ => {
+            return if visit(YAML_VISIT_SCALAR) == 0 {
+                FAIL
+            } else {
+                OK
+            };
+        }
+    };
+    let result = visit(start);
+    if result == 0 {
+        return FAIL;
+    }
+    if result != -1 {
+        let mut index: size_t = 0;
+        let mut failed = false;
+        yaml_node_for_each_child(node, |child| {
+            if failed {
+                return;
+            }
+            let step = if (*node).type_ == YAML_SEQUENCE_NODE {
+                yaml_path_step_t {
+                    parent: id,
+                    index,
+                    key: 0,
+                    is_key: false,
+                }
+            } else {
+                let pair = (*node).data.mapping.pairs.start.add(index as usize / 2);
+                yaml_path_step_t {
+                    parent: id,
+                    index: index / 2,
+                    key: (*pair).key,
+                    is_key: index % 2 == 0,
+                }
+            };
+            PUSH!(*path, step);
+            failed = yaml_document_walk_node(document, child, visitor, data, visited, path).fail;
+            let _ = POP!(*path);
+            index = index.force_add(1);
+        });
+        if failed {
+            return FAIL;
+        }
+    }
+    if visit(end) == 0 {
+        return FAIL;
+    }
+    OK
+}
+
+#[derive(Copy, Clone)]
+struct yaml_compare_pair_t {
+    a: libc::c_int,
+    b: libc::c_int,
+}
+
+struct yaml_compare_ctx_t {
+    a: *mut yaml_document_t,
+    b: *mut yaml_document_t,
+    options: yaml_compare_options_t,
+    /// The node of `b` matched with each node of `a`, or 0.
+    map_a: *mut libc::c_int,
+    /// The node of `a` matched with each node of `b`, or 0.
+    map_b: *mut libc::c_int,
+    /// The matched pairs, or the pairs being compared if anchors are ignored.
+    pairs: yaml_stack_t<yaml_compare_pair_t>,
+    /// Whether the pairs compared so far are equal, if anchors are ignored.
+    results: BTreeMap<(libc::c_int, libc::c_int), bool>,
+    /// The pairs found equal, in the order they were found, so that they can
+    /// be forgotten if a pair they assumed equal turns out not to be.
+    proven: yaml_stack_t<yaml_compare_pair_t>,
+}
+
+/// Initialize the options of yaml_document_compare() to compare everything.
+pub unsafe fn yaml_compare_options_initialize(options: *mut yaml_compare_options_t) {
+    __assert!(!options.is_null());
+    memset(
+        options as *mut libc::c_void,
+        0,
+        size_of::<yaml_compare_options_t>() as libc::c_ulong,
+    );
+}
+
+/// Compare the node graphs of two documents.
+///
+/// The documents are equal if their nodes have the same types, tags, values,
+/// styles, marks and anchors, and the same nodes are shared, as far as the
+/// `options` do not leave these out. If `options` is NULL, everything is
+/// compared. The directives of the documents are not compared.
+///
+/// The result is stored in `equal`.
+pub unsafe fn yaml_document_compare(
+    a: *mut yaml_document_t,
+    b: *mut yaml_document_t,
+    options: *const yaml_compare_options_t,
+    equal: *mut bool,
+) -> Success {
+    __assert!(!a.is_null());
+    __assert!(!b.is_null());
+    __assert!(!equal.is_null());
+    let nodes_a = yaml_document_node_count(a);
+    let nodes_b = yaml_document_node_count(b);
+    if nodes_a == 0 || nodes_b == 0 {
+        *equal = nodes_a == nodes_b;
+        return OK;
+    }
+    yaml_document_compare_subtrees(a, 1, b, 1, options, equal)
+}
+
+/// Add a pair to a MAPPING node unless the mapping already has the key.
+///
+/// This is yaml_document_append_mapping_pair() after looking the `key` node up
+/// with yaml_document_get_mapping_value_by_node(). Scalar keys are equal if
+/// they have the same tag and value, so `1` and `"1"` are the same key unless
+/// the loader gave them different tags.
+///
+/// The function fails without changing the mapping if the key is found.
+pub unsafe fn yaml_document_append_unique_mapping_pair(
+    document: *mut yaml_document_t,
+    mapping: libc::c_int,
+    key: libc::c_int,
+    value: libc::c_int,
+) -> Success {
+    if yaml_document_get_mapping_value_by_node(document, mapping, document, key) != 0 {
+        return FAIL;
+    }
+    yaml_document_append_mapping_pair(document, mapping, key, value)
+}
+
+/// Compare the subtree of the node `x` of `a` with the one of the node `y` of
+/// `b`, as yaml_document_compare() does for the root nodes.
+pub(crate) unsafe fn yaml_document_compare_subtrees(
+    a: *mut yaml_document_t,
+    x: libc::c_int,
+    b: *mut yaml_document_t,
+    y: libc::c_int,
+    options: *const yaml_compare_options_t,
+    equal: *mut bool,
+) -> Success {
+    let nodes_a = yaml_document_node_count(a);
+    let nodes_b = yaml_document_node_count(b);
+    let mut ctx = MaybeUninit::<yaml_compare_ctx_t>::uninit();
+    let ctx = ctx.as_mut_ptr();
+    (*ctx).a = a;
+    (*ctx).b = b;
+    if options.is_null() {
+        yaml_compare_options_initialize(addr_of_mut!((*ctx).options));
+    } else {
+        (*ctx).options = *options;
+    }
+    let size = (size_of::<libc::c_int>() as libc::c_ulong)
+        .force_mul((nodes_a as libc::c_ulong).force_add(nodes_b as libc::c_ulong));
+    (*ctx).map_a = yaml_malloc(size) as *mut libc::c_int;
+    if (*ctx).map_a.is_null() {
+        return FAIL;
+    }
+    memset((*ctx).map_a as *mut libc::c_void, 0, size);
+    (*ctx).map_b = (*ctx).map_a.add(nodes_a as usize);
+    STACK_INIT!((*ctx).pairs, yaml_compare_pair_t);
+    STACK_INIT!((*ctx).proven, yaml_compare_pair_t);
+    addr_of_mut!((*ctx).results).write(BTreeMap::new());
+    *equal = yaml_document_compare_nodes(ctx, x, y);
+    ptr::drop_in_place(addr_of_mut!((*ctx).results));
+    STACK_DEL!((*ctx).proven);
+    STACK_DEL!((*ctx).pairs);
+    yaml_free((*ctx).map_a as *mut libc::c_void);
+    OK
+}
+
+unsafe fn yaml_document_compare_nodes(
+    ctx: *mut yaml_compare_ctx_t,
+    x: libc::c_int,
+    y: libc::c_int,
+) -> bool {
+    if (*ctx).options.ignore_anchors {
+        // Nodes may be shared differently, so only a cycle stops the
+        // comparison, assuming the nodes are equal.
+        let mut pair: *mut yaml_compare_pair_t = (*ctx).pairs.start;
+        while pair < (*ctx).pairs.top {
+            if (*pair).a == x && (*pair).b == y {
+                return true;
+            }
+            pair = pair.add(1);
+        }
+        // A pair found unequal stays unequal, but a pair found equal may have
+        // assumed an enclosing pair equal, so it is only remembered until a
+        // pair being compared around it turns out not to be.
+        if let Some(&equal) = (*ctx).results.get(&(x, y)) {
+            return equal;
+        }
+        let proven = (*ctx).proven.top.c_offset_from((*ctx).proven.start) as usize;
+        PUSH!((*ctx).pairs, yaml_compare_pair_t { a: x, b: y });
+        let equal = yaml_document_compare_node_contents(ctx, x, y);
+        let _ = POP!((*ctx).pairs);
+        if equal {
+            PUSH!((*ctx).proven, yaml_compare_pair_t { a: x, b: y });
+        } else {
+            while (*ctx).proven.top.c_offset_from((*ctx).proven.start) as usize > proven {
+                let pair = POP!((*ctx).proven);
+                (*ctx).results.remove(&(pair.a, pair.b));
+            }
+        }
+        (*ctx).results.insert((x, y), equal);
+        return equal;
+    }
+    let mapped_a = *(*ctx).map_a.add(x as usize - 1);
+    let mapped_b = *(*ctx).map_b.add(y as usize - 1);
+    if mapped_a != 0 || mapped_b != 0 {
+        return mapped_a == y && mapped_b == x;
+    }
+    *(*ctx).map_a.add(x as usize - 1) = y;
+    *(*ctx).map_b.add(y as usize - 1) = x;
+    PUSH!((*ctx).pairs, yaml_compare_pair_t { a: x, b: y });
+    yaml_document_compare_node_contents(ctx, x, y)
+}
+
+unsafe fn yaml_document_compare_undo(ctx: *mut yaml_compare_ctx_t, count: usize) {
+    while (*ctx).pairs.top.c_offset_from((*ctx).pairs.start) as usize > count {
+        let pair = POP!((*ctx).pairs);
+        *(*ctx).map_a.add(pair.a as usize - 1) = 0;
+        *(*ctx).map_b.add(pair.b as usize - 1) = 0;
+    }
+}
+
+unsafe fn yaml_document_compare_node_contents(
+    ctx: *mut yaml_compare_ctx_t,
+    x: libc::c_int,
+    y: libc::c_int,
+) -> bool {
+    let options = (*ctx).options;
+    let a = (*(*ctx).a).nodes.start.add(x as usize - 1);
+    let b = (*(*ctx).b).nodes.start.add(y as usize - 1);
+    if (*a).type_ != (*b).type_ || !yaml_compare_strings((*a).tag, (*b).tag) {
+        return false;
+    }
+    if !options.ignore_anchors && !yaml_compare_strings((*a).anchor, (*b).anchor) {
+        return false;
+    }
+    if !options.ignore_marks
+        && !(yaml_compare_marks((*a).start_mark, (*b).start_mark)
+            && yaml_compare_marks((*a).end_mark, (*b).end_mark))
+    {
+        return false;
+    }
+    match (*a).type_ {
+        YAML_SCALAR_NODE => {
+            let (a, b) = ((*a).data.scalar, (*b).data.scalar);
+            if !options.ignore_styles
+                && (a.style != b.style
+                    || a.chomping != b.chomping
+                    || a.indentation_indicator != b.indentation_indicator)
+            {
+                return false;
+            }
+            a.length == b.length
+                && slice::from_raw_parts(a.value, a.length as usize)
+                    == slice::from_raw_parts(b.value, b.length as usize)
+        }
+        YAML_SEQUENCE_NODE => {
+            let (a, b) = ((*a).data.sequence, (*b).data.sequence);
+            if !options.ignore_styles && a.style != b.style {
+                return false;
+            }
+            let count = a.items.top.c_offset_from(a.items.start);
+            if count != b.items.top.c_offset_from(b.items.start) {
+                return false;
+            }
+            let mut index = 0;
+            while index < count {
+                if !yaml_document_compare_nodes(
+                    ctx,
+                    *a.items.start.offset(index),
+                    *b.items.start.offset(index),
+                ) {
+                    return false;
+                }
+                index += 1;
+            }
+            true
+        }
+        YAML_MAPPING_NODE => {
+            let (a, b) = ((*a).data.mapping, (*b).data.mapping);
+            if !options.ignore_styles && a.style != b.style {
+                return false;
+            }
+            let count = a.pairs.top.c_offset_from(a.pairs.start);
+            if count != b.pairs.top.c_offset_from(b.pairs.start) {
+                return false;
+            }
+            if !options.ignore_key_order {
+                let mut index = 0;
+                while index < count {
+                    let (x, y) = (*a.pairs.start.offset(index), *b.pairs.start.offset(index));
+                    if !yaml_document_compare_nodes(ctx, x.key, y.key)
+                        || !yaml_document_compare_nodes(ctx, x.value, y.value)
+                    {
+                        return false;
+                    }
+                    index += 1;
+                }
+                return true;
+            }
+            if count == 0 {
+                return true;
+            }
+            let used = yaml_malloc(count as libc::c_ulong) as *mut bool;
+            if used.is_null() {
+                return false;
+            }
+            memset(used as *mut libc::c_void, 0, count as libc::c_ulong);
+            let mut equal = true;
+            let mut x: *mut yaml_node_pair_t = a.pairs.start;
+            while equal && x < a.pairs.top {
+                equal = false;
+                let mut index = 0;
+                while index < count {
+                    let y = b.pairs.start.offset(index);
+                    if !*used.offset(index) {
+                        let pairs = (*ctx).pairs.top.c_offset_from((*ctx).pairs.start) as usize;
+                        if yaml_document_compare_nodes(ctx, (*x).key, (*y).key)
+                            && yaml_document_compare_nodes(ctx, (*x).value, (*y).value)
+                        {
+                            *used.offset(index) = true;
+                            equal = true;
+                            break;
+                        }
+                        yaml_document_compare_undo(ctx, pairs);
+                    }
+                    index += 1;
+                }
+                x = x.add(1);
+            }
+            yaml_free(used as *mut libc::c_void);
+            equal
+        }
+        YAML_ALIAS_NODE => {
+            yaml_document_compare_nodes(ctx, (*a).data.alias.target, (*b).data.alias.target)
+        }
+        _ => false,
+    }
+}
+
+unsafe fn yaml_compare_strings(a: *const yaml_char_t, b: *const yaml_char_t) -> bool {
+    if a.is_null() || b.is_null() {
+        return a.is_null() && b.is_null();
+    }
+    strcmp(a as *const libc::c_char, b as *const libc::c_char) == 0
+}
+
+fn yaml_compare_marks(a: yaml_mark_t, b: yaml_mark_t) -> bool {
+    a.index == b.index && a.line == b.line && a.column == b.column
+}
+
+struct yaml_merge_ctx_t {
+    document: *mut yaml_document_t,
+    overlay: *mut yaml_document_t,
+    sequences: yaml_sequence_merge_t,
+    /// The copy of each node of the overlay, or 0.
+    copies: *mut libc::c_int,
+    /// Is each node of the overlay being merged?
+    merging: *mut bool,
+    /// The transformation recorded in the copies, or NULL.
+    transform: *const libc::c_char,
+}
+
+/// Merge another document into a document.
+///
+/// The root nodes are merged if both are mappings, or if both are sequences
+/// and `sequences` is YAML_APPEND_SEQUENCES. Otherwise the root node of the
+/// `overlay` replaces the one of the `document`.
+///
+/// Mappings are merged pair by pair. A pair whose scalar key is already in the
+/// mapping has its value merged in the same way, and the other pairs are
+/// appended. Merged sequences get the items of the overlay appended. The nodes
+/// taken from the overlay are copied without their anchors, and the nodes that
+/// are no longer reachable from the root node are removed as by
+/// yaml_document_remove_node(). The copies keep the marks of the overlay nodes
+/// and record the transformation "merge" and the id of the overlay node.
+///
+/// A node of the document that is shared is merged for all of its references.
+pub unsafe fn yaml_document_merge(
+    document: *mut yaml_document_t,
+    overlay: *mut yaml_document_t,
+    sequences: yaml_sequence_merge_t,
+) -> Success {
+    __assert!(!document.is_null());
+    __assert!(!overlay.is_null());
+    __assert!(document != overlay);
+    let nodes = yaml_document_node_count(document);
+    let overlay_nodes = yaml_document_node_count(overlay);
+    if overlay_nodes == 0 {
+        return OK;
+    }
+    let size = (size_of::<libc::c_int>() as libc::c_ulong + 1)
+        .force_mul(overlay_nodes as libc::c_ulong)
+        .force_add(nodes as libc::c_ulong);
+    let copies = yaml_malloc(size) as *mut libc::c_int;
+    if copies.is_null() {
+        return FAIL;
+    }
+    memset(copies as *mut libc::c_void, 0, size);
+    let mut ctx = yaml_merge_ctx_t {
+        document,
+        overlay,
+        sequences,
+        copies,
+        merging: copies.add(overlay_nodes as usize) as *mut bool,
+        transform: b"merge\0" as *const u8 as *const libc::c_char,
+    };
+    let reachable = ctx.merging.add(overlay_nodes as usize);
+    yaml_document_mark_reachable(document, reachable);
+    let mut success = OK;
+    if nodes == 0 {
+        yaml_document_copy_node(&mut ctx, 1);
+    } else if yaml_document_can_merge(&mut ctx, 1, 1) {
+        yaml_document_merge_node(&mut ctx, 1, 1);
+    } else {
+        let root = yaml_document_copy_node(&mut ctx, 1);
+        success = yaml_document_move_to_root(document, root);
+        *reachable = false;
+    }
+    if success.ok {
+        success = yaml_document_remove_unreachable(document, reachable, nodes);
+    }
+    yaml_free(copies as *mut libc::c_void);
+    success
+}
+
+/// Move a node into the slot of the root node, which is deleted.
+///
+/// The references to the node refer to the new root node afterwards, and the
+/// nodes after it move down by one.
+pub(crate) unsafe fn yaml_document_move_to_root(
+    document: *mut yaml_document_t,
+    index: libc::c_int,
+) -> Success {
+    if index == 1 {
+        return OK;
+    }
+    let node = (*document).nodes.start;
+    yaml_node_free(node);
+    *node = *(*document).nodes.start.add(index as usize - 1);
+    let targets = yaml_document_alloc_targets(yaml_document_node_count(document));
+    if targets.is_null() {
+        return FAIL;
+    }
+    *targets.add(index as usize - 1) = 1;
+    yaml_document_remove_nodes(document, targets)
+}
+
+/// Copy the subtree of the node `index` of `source` into `document`, which
+/// may be the same document, and return the id of the copy or 0.
+///
+/// The copies are made as by yaml_document_merge(), without their anchors,
+/// and record `transform` unless it is NULL.
+pub(crate) unsafe fn yaml_document_copy_subtree(
+    document: *mut yaml_document_t,
+    source: *mut yaml_document_t,
+    index: libc::c_int,
+    transform: *const libc::c_char,
+) -> libc::c_int {
+    let nodes = yaml_document_node_count(source);
+    let size = (size_of::<libc::c_int>() as libc::c_ulong).force_mul(nodes as libc::c_ulong);
+    let copies = yaml_malloc(size) as *mut libc::c_int;
+    if copies.is_null() {
+        return 0;
+    }
+    memset(copies as *mut libc::c_void, 0, size);
+    let mut ctx = yaml_merge_ctx_t {
+        document,
+        overlay: source,
+        sequences: YAML_APPEND_SEQUENCES,
+        copies,
+        merging: ptr::null_mut::<bool>(),
+        transform,
+    };
+    let copy = yaml_document_copy_node(&mut ctx, index);
+    yaml_free(copies as *mut libc::c_void);
+    copy
+}
+
+/// Copy the subtree of a node reference to a document, returning the copy or 0.
+pub(crate) unsafe fn yaml_document_copy_ref(
+    document: *mut yaml_document_t,
+    node: yaml_node_ref_t,
+) -> libc::c_int {
+    yaml_document_copy_subtree(
+        document,
+        node.document(),
+        node.id(),
+        ptr::null::<libc::c_char>(),
+    )
+}
+
+unsafe fn yaml_document_can_merge(
+    ctx: *mut yaml_merge_ctx_t,
+    target: libc::c_int,
+    source: libc::c_int,
+) -> bool {
+    let target = (*(*ctx).document).nodes.start.add(target as usize - 1);
+    let source = (*(*ctx).overlay).nodes.start.add(source as usize - 1);
+    (*target).type_ == (*source).type_
+        && ((*target).type_ == YAML_MAPPING_NODE
+            || (*target).type_ == YAML_SEQUENCE_NODE && (*ctx).sequences == YAML_APPEND_SEQUENCES)
+}
+
+unsafe fn yaml_document_merge_node(
+    ctx: *mut yaml_merge_ctx_t,
+    target: libc::c_int,
+    source: libc::c_int,
+) {
+    let document = (*ctx).document;
+    let overlay = (*ctx).overlay;
+    *(*ctx).merging.add(source as usize - 1) = true;
+    let source_node = (*overlay).nodes.start.add(source as usize - 1);
+    if (*source_node).type_ == YAML_SEQUENCE_NODE {
+        let items = (*source_node).data.sequence.items;
+        let mut item: *mut yaml_node_item_t = items.start;
+        while item < items.top {
+            let copy = yaml_document_copy_node(ctx, *item);
+            let node = (*document).nodes.start.add(target as usize - 1);
+            PUSH!((*node).data.sequence.items, copy);
+            item = item.add(1);
+        }
+    } else {
+        let pairs = (*source_node).data.mapping.pairs;
+        let mut pair: *mut yaml_node_pair_t = pairs.start;
+        while pair < pairs.top {
+            let (value, index) = yaml_document_find_merge_key(ctx, target, (*pair).key);
+            if value == 0 {
+                let key = yaml_document_copy_node(ctx, (*pair).key);
+                let value = yaml_document_copy_node(ctx, (*pair).value);
+                let node = (*document).nodes.start.add(target as usize - 1);
+                PUSH!((*node).data.mapping.pairs, yaml_node_pair_t { key, value });
+            } else if yaml_document_can_merge(ctx, value, (*pair).value)
+                && !*(*ctx).merging.add((*pair).value as usize - 1)
+            {
+                yaml_document_merge_node(ctx, value, (*pair).value);
+            } else {
+                let copy = yaml_document_copy_node(ctx, (*pair).value);
+                let node = (*document).nodes.start.add(target as usize - 1);
+                (*(*node).data.mapping.pairs.start.add(index)).value = copy;
+            }
+            pair = pair.add(1);
+        }
+    }
+    *(*ctx).merging.add(source as usize - 1) = false;
+}
+
+/// Find the pair of the `target` mapping with the scalar key of the overlay
+/// node `key`, and return its value and position.
+unsafe fn yaml_document_find_merge_key(
+    ctx: *mut yaml_merge_ctx_t,
+    target: libc::c_int,
+    key: libc::c_int,
+) -> (libc::c_int, usize) {
+    let document = (*ctx).document;
+    let key = (*(*ctx).overlay).nodes.start.add(key as usize - 1);
+    if (*key).type_ != YAML_SCALAR_NODE {
+        return (0, 0);
+    }
+    let key = slice::from_raw_parts((*key).data.scalar.value, (*key).data.scalar.length as usize);
+    let pairs = (*(*document).nodes.start.add(target as usize - 1))
+        .data
+        .mapping
+        .pairs;
+    let mut index: usize = 0;
+    while pairs.start.add(index) < pairs.top {
+        let pair = pairs.start.add(index);
+        let node = (*document).nodes.start.add((*pair).key as usize - 1);
+        if (*node).type_ == YAML_SCALAR_NODE
+            && slice::from_raw_parts(
+                (*node).data.scalar.value,
+                (*node).data.scalar.length as usize,
+            ) == key
+        {
+            return ((*pair).value, index);
+        }
+        index += 1;
+    }
+    (0, 0)
+}
+
+unsafe fn yaml_document_copy_node(ctx: *mut yaml_merge_ctx_t, id: libc::c_int) -> libc::c_int {
+    let copy = *(*ctx).copies.add(id as usize - 1);
+    if copy != 0 {
+        return copy;
+    }
+    let document = (*ctx).document;
+    // The source node is read from a copy, as it may be in the document the
+    // copies are pushed to.
+    let mut source = *(*(*ctx).overlay).nodes.start.add(id as usize - 1);
+    let source: *mut yaml_node_t = &mut source;
+    let mut node = *source;
+    node.tag = yaml_strdup((*source).tag);
+    node.anchor = ptr::null_mut::<yaml_char_t>();
+    node.leading_comment = yaml_strdup((*source).leading_comment);
+    node.trailing_comment = yaml_strdup((*source).trailing_comment);
+    if !(*ctx).transform.is_null() {
+        node.transform = (*ctx).transform;
+        node.derived_from = id;
+    }
+    match node.type_ {
+        YAML_SCALAR_NODE => {
+            let length = (*source).data.scalar.length;
+            node.data.scalar.value = yaml_malloc(length.force_add(1)) as *mut yaml_char_t;
+            memcpy(
+                node.data.scalar.value as *mut libc::c_void,
+                (*source).data.scalar.value as *const libc::c_void,
+                length,
+            );
+            *node.data.scalar.value.add(length as usize) = b'\0';
+            node.data.scalar.source = yaml_scalar_source_duplicate((*source).data.scalar.source);
+        }
+        YAML_SEQUENCE_NODE => {
+            STACK_INIT!(node.data.sequence.items, yaml_node_item_t);
+        }
+        YAML_MAPPING_NODE => {
+            STACK_INIT!(node.data.mapping.pairs, yaml_node_pair_t);
+        }
+        _ => {}
+    }
+    PUSH!((*document).nodes, node);
+    let copy = yaml_document_node_count(document);
+    *(*ctx).copies.add(id as usize - 1) = copy;
+    // Mapping children come as a key followed by its value, so the key is kept
+    // until the value is copied.
+    let mut key = 0;
+    yaml_node_for_each_child(source, |child| {
+        let child = yaml_document_copy_node(ctx, child);
+        let node = (*document).nodes.start.add(copy as usize - 1);
+        if (*node).type_ == YAML_ALIAS_NODE {
+            (*node).data.alias.target = child;
+        } else if (*node).type_ == YAML_SEQUENCE_NODE {
+            PUSH!((*node).data.sequence.items, child);
+        } else if key == 0 {
+            key = child;
+        } else {
+            PUSH!(
+                (*node).data.mapping.pairs,
+                yaml_node_pair_t { key, value: child }
+            );
+            key = 0;
+        }
+    });
+    copy
+}
+
+pub(crate) unsafe fn yaml_document_mark_reachable(
+    document: *mut yaml_document_t,
+    reachable: *mut bool,
+) {
+    if yaml_document_node_count(document) == 0 {
+        return;
+    }
+    *reachable = true;
+    let mut changed = true;
+    while changed {
+        changed = false;
+        let mut id: libc::c_int = 1;
+        let mut node: *mut yaml_node_t = (*document).nodes.start;
+        while node < (*document).nodes.top {
+            if *reachable.add(id as usize - 1) {
+                yaml_node_for_each_child(node, |child| {
+                    if !*reachable.add(child as usize - 1) {
+                        *reachable.add(child as usize - 1) = true;
+                        changed = true;
+                    }
+                });
+            }
+            id += 1;
+            node = node.add(1);
+        }
+    }
+}
+
+/// Remove the nodes among the first `count` that were `reachable` from the
+/// root node but no longer are.
+pub(crate) unsafe fn yaml_document_remove_unreachable(
+    document: *mut yaml_document_t,
+    reachable: *mut bool,
+    count: libc::c_int,
+) -> Success {
+    let nodes = yaml_document_node_count(document);
+    let targets = yaml_document_alloc_targets(nodes);
+    if targets.is_null() {
+        return FAIL;
+    }
+    let still_reachable = yaml_malloc(nodes as libc::c_ulong) as *mut bool;
+    if still_reachable.is_null() {
+        yaml_free(targets as *mut libc::c_void);
+        return FAIL;
+    }
+    memset(
+        still_reachable as *mut libc::c_void,
+        0,
+        nodes as libc::c_ulong,
+    );
+    yaml_document_mark_reachable(document, still_reachable);
+    let mut id: libc::c_int = 1;
+    while id <= count {
+        if *reachable.add(id as usize - 1) && !*still_reachable.add(id as usize - 1) {
+            *targets.add(id as usize - 1) = 0;
+        }
+        id += 1;
+    }
+    yaml_free(still_reachable as *mut libc::c_void);
+    yaml_document_remove_nodes(document, targets)
+}
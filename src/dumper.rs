@@ -1,22 +1,47 @@
-use crate::api::{yaml_free, yaml_malloc};
-use crate::externs::{memset, strcmp};
+use crate::api::{yaml_free, yaml_malloc, yaml_strdup};
+use crate::document::{
+    yaml_compare_options_initialize, yaml_document_compare_subtrees, yaml_document_copy_ref,
+    yaml_document_duplicate,
+};
+use crate::emitter::yaml_emitter_set_emitter_error;
+use crate::externs::{memcpy, memset, strcmp, strlen};
 use crate::fmt::WriteToPtr;
 use crate::ops::ForceMul as _;
 use crate::success::{Success, FAIL, OK};
 use crate::yaml::{
-    yaml_anchors_t, yaml_char_t, yaml_document_t, yaml_emitter_t, yaml_event_t, yaml_mark_t,
-    yaml_node_item_t, yaml_node_pair_t, yaml_node_t, YAML_ALIAS_EVENT, YAML_ANY_ENCODING,
-    YAML_DOCUMENT_END_EVENT, YAML_DOCUMENT_START_EVENT, YAML_MAPPING_END_EVENT, YAML_MAPPING_NODE,
-    YAML_MAPPING_START_EVENT, YAML_SCALAR_EVENT, YAML_SCALAR_NODE, YAML_SEQUENCE_END_EVENT,
-    YAML_SEQUENCE_NODE, YAML_SEQUENCE_START_EVENT, YAML_STREAM_END_EVENT, YAML_STREAM_START_EVENT,
+    size_t, yaml_anchors_t, yaml_char_t, yaml_compare_options_t, yaml_document_t, yaml_emitter_t,
+    yaml_event_t, yaml_mark_t, yaml_node_item_t, yaml_node_pair_t, yaml_node_t, YAML_ALIAS_EVENT,
+    YAML_ANY_ENCODING, YAML_DOCUMENT_END_EVENT, YAML_DOCUMENT_START_EVENT, YAML_MAPPING_END_EVENT,
+    YAML_MAPPING_NODE, YAML_MAPPING_START_EVENT, YAML_SCALAR_EVENT, YAML_SCALAR_NODE,
+    YAML_SEQUENCE_END_EVENT, YAML_SEQUENCE_NODE, YAML_SEQUENCE_START_EVENT, YAML_STREAM_END_EVENT,
+    YAML_STREAM_START_EVENT,
+};
+use crate::{
+    libc, yaml_document_delete, yaml_document_initialize, yaml_emitter_delete, yaml_emitter_emit,
+    yaml_emitter_initialize, yaml_emitter_options_t, yaml_emitter_set_break,
+    yaml_emitter_set_encoding, yaml_emitter_set_options, yaml_emitter_set_output_owned,
+    yaml_emitter_set_unicode, yaml_emitter_take_output, yaml_error_t, yaml_event_handler_t,
+    yaml_node_ref_t, yaml_output_delete, yaml_stream_t, PointerExt, YAML_ALIAS_NODE,
+    YAML_ALLOW_DUPLICATE_KEYS, YAML_ANY_BREAK, YAML_ANY_SCALAR_STYLE, YAML_MEMORY_ERROR,
+    YAML_REJECT_DUPLICATE_KEYS, YAML_WRITER_ERROR,
 };
-use crate::{libc, yaml_document_delete, yaml_emitter_emit, PointerExt};
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::{self, Ordering};
+use core::fmt::{self, Display};
+use core::marker::PhantomData;
 use core::mem::{size_of, MaybeUninit};
 use core::ptr::{self, addr_of_mut};
+use core::slice;
+use core::str;
 
 /// Start a YAML stream.
 ///
-/// This function should be used before yaml_emitter_dump() is called.
+/// This function should be used before yaml_emitter_dump() is called. Opening a
+/// stream twice fails with the YAML_EMITTER_ERROR error.
 pub unsafe fn yaml_emitter_open(emitter: *mut yaml_emitter_t) -> Success {
     let mut event = MaybeUninit::<yaml_event_t>::uninit();
     let event = event.as_mut_ptr();
@@ -26,7 +51,12 @@ pub unsafe fn yaml_emitter_open(emitter: *mut yaml_emitter_t) -> Success {
         column: 0_u64,
     };
     __assert!(!emitter.is_null());
-    __assert!(!(*emitter).opened);
+    if (*emitter).opened {
+        return yaml_emitter_set_emitter_error(
+            emitter,
+            b"the stream is already opened\0" as *const u8 as *const libc::c_char,
+        );
+    }
     memset(
         event as *mut libc::c_void,
         0,
@@ -36,7 +66,7 @@ pub unsafe fn yaml_emitter_open(emitter: *mut yaml_emitter_t) -> Success {
     (*event).start_mark = mark;
     (*event).end_mark = mark;
     (*event).data.stream_start.encoding = YAML_ANY_ENCODING;
-    if yaml_emitter_emit(emitter, event).fail {
+    if yaml_emitter_dump_event(emitter, event).fail {
         return FAIL;
     }
     (*emitter).opened = true;
@@ -45,7 +75,8 @@ pub unsafe fn yaml_emitter_open(emitter: *mut yaml_emitter_t) -> Success {
 
 /// Finish a YAML stream.
 ///
-/// This function should be used after yaml_emitter_dump() is called.
+/// This function should be used after yaml_emitter_dump() is called. Closing a
+/// stream that was never opened fails with the YAML_EMITTER_ERROR error.
 pub unsafe fn yaml_emitter_close(emitter: *mut yaml_emitter_t) -> Success {
     let mut event = MaybeUninit::<yaml_event_t>::uninit();
     let event = event.as_mut_ptr();
@@ -55,7 +86,12 @@ pub unsafe fn yaml_emitter_close(emitter: *mut yaml_emitter_t) -> Success {
         column: 0_u64,
     };
     __assert!(!emitter.is_null());
-    __assert!((*emitter).opened);
+    if !(*emitter).opened {
+        return yaml_emitter_set_emitter_error(
+            emitter,
+            b"the stream is not opened\0" as *const u8 as *const libc::c_char,
+        );
+    }
     if (*emitter).closed {
         return OK;
     }
@@ -67,7 +103,7 @@ pub unsafe fn yaml_emitter_close(emitter: *mut yaml_emitter_t) -> Success {
     (*event).type_ = YAML_STREAM_END_EVENT;
     (*event).start_mark = mark;
     (*event).end_mark = mark;
-    if yaml_emitter_emit(emitter, event).fail {
+    if yaml_emitter_dump_event(emitter, event).fail {
         return FAIL;
     }
     (*emitter).closed = true;
@@ -80,9 +116,26 @@ pub unsafe fn yaml_emitter_close(emitter: *mut yaml_emitter_t) -> Success {
 /// the yaml_document_initialize() function. The emitter takes the
 /// responsibility for the document object and destroys its content after it is
 /// emitted. The document object is destroyed even if the function fails.
+///
+/// A document without nodes closes the stream, unless the emitter is set to
+/// emit it as a document holding an empty scalar with
+/// yaml_emitter_set_empty_documents(). Otherwise call yaml_emitter_close() to
+/// end the stream. Dumping a document after the stream is closed fails with the
+/// YAML_EMITTER_ERROR error.
 pub unsafe fn yaml_emitter_dump(
     emitter: *mut yaml_emitter_t,
     document: *mut yaml_document_t,
+) -> Success {
+    __assert!(!emitter.is_null());
+    yaml_emitter_dump_document(emitter, document, (*emitter).empty_documents)
+}
+
+/// Emit a YAML document, closing the stream for a document without nodes
+/// unless `empty` is set.
+unsafe fn yaml_emitter_dump_document(
+    emitter: *mut yaml_emitter_t,
+    document: *mut yaml_document_t,
+    empty: bool,
 ) -> Success {
     let current_block: u64;
     let mut event = MaybeUninit::<yaml_event_t>::uninit();
@@ -96,7 +149,15 @@ pub unsafe fn yaml_emitter_dump(
     __assert!(!document.is_null());
     let fresh0 = addr_of_mut!((*emitter).document);
     *fresh0 = document;
-    if !(*emitter).opened {
+    if (*emitter).closed {
+        let _ = yaml_emitter_set_emitter_error(
+            emitter,
+            b"the stream is already closed\0" as *const u8 as *const libc::c_char,
+        );
+        current_block = 5018439318894558507;
+    } else if yaml_emitter_prepare_document(emitter).fail {
+        current_block = 5018439318894558507;
+    } else if !(*emitter).opened {
         if yaml_emitter_open(emitter).fail {
             current_block = 5018439318894558507;
         } else {
@@ -107,55 +168,52 @@ pub unsafe fn yaml_emitter_dump(
     }
     match current_block {
         15619007995458559411 => {
-            if STACK_EMPTY!((*document).nodes) {
+            __assert!((*emitter).opened);
+            if STACK_EMPTY!((*document).nodes) && !empty {
                 if yaml_emitter_close(emitter).ok {
                     yaml_emitter_delete_document_and_anchors(emitter);
                     return OK;
                 }
-            } else {
-                __assert!((*emitter).opened);
-                let fresh1 = addr_of_mut!((*emitter).anchors);
-                *fresh1 = yaml_malloc(
-                    (size_of::<yaml_anchors_t>() as libc::c_ulong)
-                        .force_mul((*document).nodes.top.c_offset_from((*document).nodes.start)
-                            as libc::c_ulong),
-                ) as *mut yaml_anchors_t;
-                memset(
-                    (*emitter).anchors as *mut libc::c_void,
-                    0,
-                    (size_of::<yaml_anchors_t>() as libc::c_ulong)
-                        .force_mul((*document).nodes.top.c_offset_from((*document).nodes.start)
-                            as libc::c_ulong),
-                );
-                memset(
-                    event as *mut libc::c_void,
-                    0,
-                    size_of::<yaml_event_t>() as libc::c_ulong,
-                );
-                (*event).type_ = YAML_DOCUMENT_START_EVENT;
-                (*event).start_mark = mark;
-                (*event).end_mark = mark;
-                (*event).data.document_start.version_directive = (*document).version_directive;
-                (*event).data.document_start.tag_directives.start =
-                    (*document).tag_directives.start;
-                (*event).data.document_start.tag_directives.end = (*document).tag_directives.end;
-                (*event).data.document_start.implicit = (*document).start_implicit;
-                if yaml_emitter_emit(emitter, event).ok {
+                yaml_emitter_delete_document_and_anchors(emitter);
+                return FAIL;
+            }
+            memset(
+                event as *mut libc::c_void,
+                0,
+                size_of::<yaml_event_t>() as libc::c_ulong,
+            );
+            (*event).type_ = YAML_DOCUMENT_START_EVENT;
+            (*event).start_mark = mark;
+            (*event).end_mark = mark;
+            (*event).data.document_start.version_directive = (*document).version_directive;
+            (*event).data.document_start.tag_directives.start = (*document).tag_directives.start;
+            (*event).data.document_start.tag_directives.end = (*document).tag_directives.end;
+            (*event).data.document_start.implicit = (*document).start_implicit;
+            if yaml_emitter_dump_event(emitter, event).ok {
+                let dumped = if STACK_EMPTY!((*document).nodes) {
+                    yaml_emitter_dump_empty_node(emitter)
+                } else {
+                    if (*emitter).anchor_all {
+                        yaml_emitter_anchor_all_nodes(emitter);
+                    }
                     yaml_emitter_anchor_node(emitter, 1);
-                    if yaml_emitter_dump_node(emitter, 1).ok {
-                        memset(
-                            event as *mut libc::c_void,
-                            0,
-                            size_of::<yaml_event_t>() as libc::c_ulong,
-                        );
-                        (*event).type_ = YAML_DOCUMENT_END_EVENT;
-                        (*event).start_mark = mark;
-                        (*event).end_mark = mark;
-                        (*event).data.document_end.implicit = (*document).end_implicit;
-                        if yaml_emitter_emit(emitter, event).ok {
-                            yaml_emitter_delete_document_and_anchors(emitter);
-                            return OK;
-                        }
+                    yaml_emitter_dump_node(emitter, 1)
+                };
+                if dumped.ok {
+                    memset(
+                        event as *mut libc::c_void,
+                        0,
+                        size_of::<yaml_event_t>() as libc::c_ulong,
+                    );
+                    (*event).type_ = YAML_DOCUMENT_END_EVENT;
+                    (*event).start_mark = mark;
+                    (*event).end_mark = mark;
+                    (*event).data.document_end.implicit = (*document).end_implicit;
+                    (*event).leading_comment = (*document).end_comment;
+                    (*document).end_comment = ptr::null_mut::<yaml_char_t>();
+                    if yaml_emitter_dump_event(emitter, event).ok {
+                        yaml_emitter_delete_document_and_anchors(emitter);
+                        return OK;
                     }
                 }
             }
@@ -166,6 +224,430 @@ pub unsafe fn yaml_emitter_dump(
     FAIL
 }
 
+/// Emit a sequence of YAML documents as one stream.
+///
+/// The stream is opened if needed, the `count` documents starting at
+/// `documents` are emitted in order, and the stream is closed. A document
+/// without nodes is emitted as a document holding an empty scalar, whatever
+/// yaml_emitter_set_empty_documents() is set to. As with yaml_emitter_dump(),
+/// the emitter destroys the content of every document, even if the function
+/// fails.
+pub unsafe fn yaml_emitter_dump_all(
+    emitter: *mut yaml_emitter_t,
+    documents: *mut yaml_document_t,
+    count: size_t,
+) -> Success {
+    __assert!(!emitter.is_null());
+    __assert!(!documents.is_null() || count == 0);
+    let mut index: size_t = 0;
+    while index < count {
+        let document = documents.add(index as usize);
+        index += 1;
+        if yaml_emitter_dump_document(emitter, document, true).fail {
+            while index < count {
+                yaml_document_delete(documents.add(index as usize));
+                index += 1;
+            }
+            return FAIL;
+        }
+    }
+    if !(*emitter).opened && yaml_emitter_open(emitter).fail {
+        return FAIL;
+    }
+    yaml_emitter_close(emitter)
+}
+
+/// Emit a stream of YAML documents.
+///
+/// Unless the stream is already open, the emitter is set to the encoding and
+/// the line break of the stream, if they are set and the emitter has none.
+/// The documents are then emitted as with yaml_emitter_dump_all(), keeping
+/// their directives and their implicit or explicit markers, and the stream is
+/// closed. The emitter destroys every document, even if the function fails,
+/// and the stream is left empty.
+pub unsafe fn yaml_stream_dump(
+    emitter: *mut yaml_emitter_t,
+    stream: *mut yaml_stream_t,
+) -> Success {
+    __assert!(!emitter.is_null());
+    __assert!(!stream.is_null());
+    if !(*emitter).opened {
+        if (*emitter).encoding == YAML_ANY_ENCODING {
+            yaml_emitter_set_encoding(emitter, (*stream).encoding);
+        }
+        if (*emitter).line_break == YAML_ANY_BREAK {
+            yaml_emitter_set_break(emitter, (*stream).line_break);
+        }
+    }
+    let documents = (*stream).documents;
+    let success = yaml_emitter_dump_all(
+        emitter,
+        documents.start,
+        documents.top.c_offset_from(documents.start) as size_t,
+    );
+    (*stream).documents.top = documents.start;
+    success
+}
+
+/// Write a YAML document to a string.
+///
+/// This is yaml_emitter_dump() with an emitter that allows unescaped non-ASCII
+/// characters and writes a stream of the one document to a buffer of its own.
+/// On success, `output` is set to the buffer, which ends with a NUL byte, and
+/// `size` to the number of bytes before the NUL byte. The application is
+/// responsible for freeing `output` with yaml_output_delete().
+///
+/// Unlike yaml_emitter_dump(), the function leaves the document intact. If the
+/// function fails, `output` is set to NULL and, if `error` is not NULL, the
+/// error is described in it.
+pub unsafe fn yaml_document_dump_string(
+    document: *mut yaml_document_t,
+    output: *mut *mut yaml_char_t,
+    size: *mut size_t,
+    error: *mut yaml_error_t,
+) -> Success {
+    let mut copy = MaybeUninit::<yaml_document_t>::uninit();
+    let copy = copy.as_mut_ptr();
+    let mut emitter = MaybeUninit::<yaml_emitter_t>::uninit();
+    let emitter = emitter.as_mut_ptr();
+    __assert!(!document.is_null());
+    __assert!(!output.is_null());
+    __assert!(!size.is_null());
+    *output = ptr::null_mut::<yaml_char_t>();
+    *size = 0;
+    let _ = yaml_emitter_initialize(emitter);
+    let success = if yaml_document_duplicate(copy, document).fail {
+        (*emitter).error = YAML_MEMORY_ERROR;
+        FAIL
+    } else {
+        yaml_emitter_set_unicode(emitter, true);
+        yaml_emitter_set_output_owned(emitter);
+        if yaml_emitter_dump_document(emitter, copy, true).ok && yaml_emitter_close(emitter).ok {
+            yaml_emitter_take_output(emitter, output, size);
+            OK
+        } else {
+            FAIL
+        }
+    };
+    if success.fail && !error.is_null() {
+        memset(
+            error as *mut libc::c_void,
+            0,
+            size_of::<yaml_error_t>() as libc::c_ulong,
+        );
+        (*error).error = (*emitter).error;
+        (*error).problem = (*emitter).problem;
+    }
+    yaml_emitter_delete(emitter);
+    success
+}
+
+/// Turn a YAML document into events.
+///
+/// The events are the ones yaml_emitter_dump() would emit for the document,
+/// from STREAM-START to STREAM-END, with the same anchors given to shared
+/// nodes. Instead of being written, each event is passed to the `handler`
+/// with `data`, which takes the responsibility for it. The document is left
+/// intact. If the function fails, the events already passed to the handler
+/// stay with it and, if `error` is not NULL, the error is described in it.
+pub unsafe fn yaml_document_dump_events(
+    document: *mut yaml_document_t,
+    handler: yaml_event_handler_t,
+    data: *mut libc::c_void,
+    error: *mut yaml_error_t,
+) -> Success {
+    let mut copy = MaybeUninit::<yaml_document_t>::uninit();
+    let copy = copy.as_mut_ptr();
+    let mut emitter = MaybeUninit::<yaml_emitter_t>::uninit();
+    let emitter = emitter.as_mut_ptr();
+    __assert!(!document.is_null());
+    let _ = yaml_emitter_initialize(emitter);
+    let success = if yaml_document_duplicate(copy, document).fail {
+        (*emitter).error = YAML_MEMORY_ERROR;
+        FAIL
+    } else {
+        let fresh0 = addr_of_mut!((*emitter).event_handler);
+        *fresh0 = Some(handler);
+        let fresh1 = addr_of_mut!((*emitter).event_handler_data);
+        *fresh1 = data;
+        if yaml_emitter_dump_document(emitter, copy, true).ok && yaml_emitter_close(emitter).ok {
+            OK
+        } else {
+            FAIL
+        }
+    };
+    if success.fail && !error.is_null() {
+        memset(
+            error as *mut libc::c_void,
+            0,
+            size_of::<yaml_error_t>() as libc::c_ulong,
+        );
+        (*error).error = (*emitter).error;
+        (*error).problem = (*emitter).problem;
+    }
+    yaml_emitter_delete(emitter);
+    success
+}
+
+/// An emitter writing to a buffer of its own, before its stream is opened.
+///
+/// With yaml_opened_emitter_t, it checks the order of yaml_emitter_open(),
+/// yaml_emitter_dump() and yaml_emitter_close() with types rather than at run
+/// time: documents can only be dumped once open() has made an opened emitter,
+/// and close() consumes that emitter to return the output.
+pub struct yaml_owned_emitter_t {
+    emitter: OwnedEmitter,
+}
+
+/// An emitter whose stream is opened, made by yaml_owned_emitter_t::open().
+pub struct yaml_opened_emitter_t {
+    emitter: OwnedEmitter,
+}
+
+struct OwnedEmitter(Box<MaybeUninit<yaml_emitter_t>>);
+
+impl OwnedEmitter {
+    fn as_mut_ptr(&mut self) -> *mut yaml_emitter_t {
+        self.0.as_mut_ptr()
+    }
+}
+
+impl Drop for OwnedEmitter {
+    fn drop(&mut self) {
+        // SAFETY: the emitter was initialized by yaml_owned_emitter_t::new().
+        unsafe { yaml_emitter_delete(self.as_mut_ptr()) };
+    }
+}
+
+impl yaml_owned_emitter_t {
+    /// Create an emitter with the default settings.
+    pub fn new() -> Self {
+        let mut emitter = Box::new(MaybeUninit::<yaml_emitter_t>::uninit());
+        // SAFETY: the emitter is initialized before its output is set.
+        unsafe {
+            let _ = yaml_emitter_initialize(emitter.as_mut_ptr());
+            yaml_emitter_set_output_owned(emitter.as_mut_ptr());
+        }
+        yaml_owned_emitter_t {
+            emitter: OwnedEmitter(emitter),
+        }
+    }
+
+    /// Configure the emitter, see yaml_emitter_set_options().
+    pub fn set_options(&mut self, options: &yaml_emitter_options_t) {
+        // SAFETY: the emitter was initialized by new().
+        unsafe { yaml_emitter_set_options(self.emitter.as_mut_ptr(), options) };
+    }
+
+    /// Start the YAML stream.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the STREAM-START event cannot be emitted.
+    pub fn open(mut self) -> Result<yaml_opened_emitter_t, yaml_error_t> {
+        // SAFETY: the emitter was initialized by new() and is not opened yet.
+        unsafe {
+            if yaml_emitter_open(self.emitter.as_mut_ptr()).fail {
+                return Err(yaml_emitter_error(self.emitter.as_mut_ptr()));
+            }
+        }
+        Ok(yaml_opened_emitter_t {
+            emitter: self.emitter,
+        })
+    }
+}
+
+impl Default for yaml_owned_emitter_t {
+    fn default() -> Self {
+        yaml_owned_emitter_t::new()
+    }
+}
+
+impl yaml_opened_emitter_t {
+    /// Emit a YAML document.
+    ///
+    /// As with yaml_emitter_dump(), the emitter destroys the document, even if
+    /// the function fails. A document without nodes is emitted as a document
+    /// holding an empty scalar, so the stream is only closed by close().
+    ///
+    /// # Errors
+    ///
+    /// Fails if the document cannot be emitted or written.
+    pub unsafe fn dump(&mut self, document: *mut yaml_document_t) -> Result<(), yaml_error_t> {
+        let emitter = self.emitter.as_mut_ptr();
+        if yaml_emitter_dump_document(emitter, document, true).fail {
+            return Err(yaml_emitter_error(emitter));
+        }
+        Ok(())
+    }
+
+    /// Finish the YAML stream and return the output.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the STREAM-END event cannot be emitted or written.
+    pub fn close(mut self) -> Result<Vec<u8>, yaml_error_t> {
+        let emitter = self.emitter.as_mut_ptr();
+        // SAFETY: the emitter was opened by yaml_owned_emitter_t::open() and
+        // its output was set with yaml_emitter_set_output_owned().
+        unsafe {
+            if yaml_emitter_close(emitter).fail {
+                return Err(yaml_emitter_error(emitter));
+            }
+            let mut output = ptr::null_mut::<yaml_char_t>();
+            let mut size: size_t = 0;
+            yaml_emitter_take_output(emitter, &mut output, &mut size);
+            let bytes = slice::from_raw_parts(output, size as usize).to_vec();
+            yaml_output_delete(output);
+            Ok(bytes)
+        }
+    }
+}
+
+/// Describe the error of an emitter.
+unsafe fn yaml_emitter_error(emitter: *mut yaml_emitter_t) -> yaml_error_t {
+    let mut error = MaybeUninit::<yaml_error_t>::zeroed();
+    let fresh0 = addr_of_mut!((*error.as_mut_ptr()).error);
+    *fresh0 = (*emitter).error;
+    let fresh1 = addr_of_mut!((*error.as_mut_ptr()).problem);
+    *fresh1 = (*emitter).problem;
+    error.assume_init()
+}
+
+/// A document or the subtree of a node written as YAML, made by
+/// yaml_document_display() or yaml_node_ref_t::display().
+pub struct yaml_document_display_t<'a> {
+    document: *mut yaml_document_t,
+    node: Option<yaml_node_ref_t<'a>>,
+    marker: PhantomData<&'a yaml_document_t>,
+}
+
+/// Write a document with Display as yaml_document_dump_string() does.
+///
+/// If the document cannot be dumped, the problem is written in angle brackets
+/// instead. The document must remain valid and unmodified for `'a`.
+pub unsafe fn yaml_document_display<'a>(
+    document: *mut yaml_document_t,
+) -> yaml_document_display_t<'a> {
+    __assert!(!document.is_null());
+    yaml_document_display_t {
+        document,
+        node: None,
+        marker: PhantomData,
+    }
+}
+
+impl<'a> yaml_node_ref_t<'a> {
+    /// Write the subtree of the node with Display as a document of its own.
+    pub fn display(self) -> yaml_document_display_t<'a> {
+        yaml_document_display_t {
+            document: self.document(),
+            node: Some(self),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl Display for yaml_document_display_t<'_> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let mut output = ptr::null_mut::<yaml_char_t>();
+        let mut size: size_t = 0;
+        let mut error = MaybeUninit::<yaml_error_t>::zeroed();
+        // SAFETY: yaml_document_display() and yaml_node_ref_t require the
+        // document to stay valid and unmodified for 'a, and dumping leaves it
+        // intact.
+        let success = unsafe {
+            match self.node {
+                None => yaml_document_dump_string(
+                    self.document,
+                    &mut output,
+                    &mut size,
+                    error.as_mut_ptr(),
+                ),
+                Some(node) => {
+                    yaml_node_dump_string(node, &mut output, &mut size, error.as_mut_ptr())
+                }
+            }
+        };
+        if success.ok {
+            // SAFETY: the dump succeeded, so `output` holds `size` bytes and
+            // is freed once they are written.
+            unsafe {
+                let result = formatter.write_str(&String::from_utf8_lossy(slice::from_raw_parts(
+                    output,
+                    size as usize,
+                )));
+                yaml_output_delete(output);
+                return result;
+            }
+        }
+        // SAFETY: the dump failed, so `error` was filled in, and its problem is
+        // NULL or a static NUL-terminated string.
+        let problem = unsafe {
+            let problem = (*error.as_ptr()).problem;
+            if problem.is_null() {
+                "dump failed"
+            } else {
+                str::from_utf8(slice::from_raw_parts(
+                    problem.cast::<u8>(),
+                    strlen(problem) as usize,
+                ))
+                .unwrap_or("dump failed")
+            }
+        };
+        write!(formatter, "<{}>", problem)
+    }
+}
+
+unsafe fn yaml_node_dump_string(
+    node: yaml_node_ref_t,
+    output: *mut *mut yaml_char_t,
+    size: *mut size_t,
+    error: *mut yaml_error_t,
+) -> Success {
+    let mut document = MaybeUninit::<yaml_document_t>::uninit();
+    let document = document.as_mut_ptr();
+    if yaml_document_initialize(
+        document,
+        ptr::null_mut(),
+        ptr::null_mut(),
+        ptr::null_mut(),
+        true,
+        true,
+    )
+    .fail
+    {
+        (*error).error = YAML_MEMORY_ERROR;
+        return FAIL;
+    }
+    let success = if yaml_document_copy_ref(document, node) == 0 {
+        (*error).error = YAML_MEMORY_ERROR;
+        FAIL
+    } else {
+        yaml_document_dump_string(document, output, size, error)
+    };
+    yaml_document_delete(document);
+    success
+}
+
+unsafe fn yaml_emitter_dump_event(
+    emitter: *mut yaml_emitter_t,
+    event: *mut yaml_event_t,
+) -> Success {
+    match (*emitter).event_handler {
+        None => yaml_emitter_emit(emitter, event),
+        Some(handler) => {
+            if handler((*emitter).event_handler_data, event) != 0 {
+                OK
+            } else {
+                (*emitter).error = YAML_WRITER_ERROR;
+                (*emitter).problem = b"event handler error\0" as *const u8 as *const libc::c_char;
+                FAIL
+            }
+        }
+    }
+}
+
 unsafe fn yaml_emitter_delete_document_and_anchors(emitter: *mut yaml_emitter_t) {
     let mut index: libc::c_int;
     if (*emitter).anchors.is_null() {
@@ -185,10 +667,17 @@ unsafe fn yaml_emitter_delete_document_and_anchors(emitter: *mut yaml_emitter_t)
             .nodes
             .start
             .wrapping_offset(index as isize);
+        yaml_free(node.anchor as *mut libc::c_void);
+        yaml_free(node.leading_comment as *mut libc::c_void);
+        yaml_free(node.trailing_comment as *mut libc::c_void);
+        yaml_free(
+            (*(*emitter).anchors.wrapping_offset(index as isize)).duplicates as *mut libc::c_void,
+        );
         if !(*(*emitter).anchors.wrapping_offset(index as isize)).serialized {
             yaml_free(node.tag as *mut libc::c_void);
             if node.type_ == YAML_SCALAR_NODE {
                 yaml_free(node.data.scalar.value as *mut libc::c_void);
+                yaml_free(node.data.scalar.source as *mut libc::c_void);
             }
         }
         if node.type_ == YAML_SEQUENCE_NODE {
@@ -200,6 +689,7 @@ unsafe fn yaml_emitter_delete_document_and_anchors(emitter: *mut yaml_emitter_t)
         index += 1;
     }
     STACK_DEL!((*(*emitter).document).nodes);
+    yaml_free((*(*emitter).document).end_comment as *mut libc::c_void);
     yaml_free((*emitter).anchors as *mut libc::c_void);
     let fresh6 = addr_of_mut!((*emitter).anchors);
     *fresh6 = ptr::null_mut::<yaml_anchors_t>();
@@ -208,11 +698,247 @@ unsafe fn yaml_emitter_delete_document_and_anchors(emitter: *mut yaml_emitter_t)
     *fresh7 = ptr::null_mut::<yaml_document_t>();
 }
 
-unsafe fn yaml_emitter_anchor_node_sub(emitter: *mut yaml_emitter_t, index: libc::c_int) {
-    (*((*emitter).anchors).offset((index - 1) as isize)).references += 1;
-    if (*(*emitter).anchors.offset((index - 1) as isize)).references == 2 {
-        (*emitter).last_anchor_id += 1;
-        (*(*emitter).anchors.offset((index - 1) as isize)).anchor = (*emitter).last_anchor_id;
+/// Allocate the anchors of the document, sort the mapping keys and look for
+/// duplicate keys, before anything of the document is emitted.
+unsafe fn yaml_emitter_prepare_document(emitter: *mut yaml_emitter_t) -> Success {
+    let nodes = (*(*emitter).document).nodes;
+    let size = (size_of::<yaml_anchors_t>() as libc::c_ulong)
+        .force_mul(nodes.top.c_offset_from(nodes.start) as libc::c_ulong);
+    let fresh1 = addr_of_mut!((*emitter).anchors);
+    *fresh1 = yaml_malloc(size) as *mut yaml_anchors_t;
+    memset((*emitter).anchors as *mut libc::c_void, 0, size);
+    yaml_emitter_sort_keys(emitter);
+    yaml_emitter_check_duplicate_keys(emitter)
+}
+
+/// Find the mapping pairs whose key is repeated by a later pair.
+///
+/// The document is left as it is: with YAML_KEEP_LAST_DUPLICATE_KEY, the pairs
+/// to leave out are flagged in the `duplicates` of the anchors of the mapping.
+/// Scalar keys are sorted by tag and value and compared with their neighbours,
+/// other keys are compared as subtrees, ignoring styles, marks and anchors.
+unsafe fn yaml_emitter_check_duplicate_keys(emitter: *mut yaml_emitter_t) -> Success {
+    if (*emitter).duplicate_keys == YAML_ALLOW_DUPLICATE_KEYS {
+        return OK;
+    }
+    let document = (*emitter).document;
+    let mut options = MaybeUninit::<yaml_compare_options_t>::uninit();
+    let options = options.as_mut_ptr();
+    yaml_compare_options_initialize(options);
+    (*options).ignore_styles = true;
+    (*options).ignore_marks = true;
+    (*options).ignore_anchors = true;
+    let mut node: *mut yaml_node_t = (*document).nodes.start;
+    let mut anchors: *mut yaml_anchors_t = (*emitter).anchors;
+    while node < (*document).nodes.top {
+        if (*node).type_ == YAML_MAPPING_NODE {
+            let pairs = (*node).data.mapping.pairs;
+            let pairs =
+                slice::from_raw_parts(pairs.start, pairs.top.c_offset_from(pairs.start) as usize);
+            let mut duplicates = vec![false; pairs.len()];
+            let mut scalars = Vec::new();
+            let mut others = Vec::new();
+            for (index, pair) in pairs.iter().enumerate() {
+                let key = (*document).nodes.start.add((pair.key - 1) as usize);
+                if (*key).type_ == YAML_SCALAR_NODE {
+                    scalars.push((
+                        yaml_emitter_key_tag(key),
+                        yaml_emitter_key_value(key),
+                        index,
+                    ));
+                } else {
+                    others.push(index);
+                }
+            }
+            scalars.sort_unstable();
+            for (scalar, next) in scalars.iter().zip(scalars.iter().skip(1)) {
+                if scalar.0 == next.0 && scalar.1 == next.1 {
+                    duplicates[scalar.2] = true;
+                }
+            }
+            for (position, &index) in others.iter().enumerate() {
+                for &later in &others[position + 1..] {
+                    let mut equal = false;
+                    if yaml_document_compare_subtrees(
+                        document,
+                        pairs[index].key,
+                        document,
+                        pairs[later].key,
+                        options,
+                        &mut equal,
+                    )
+                    .fail
+                    {
+                        (*emitter).error = YAML_MEMORY_ERROR;
+                        return FAIL;
+                    }
+                    if equal {
+                        duplicates[index] = true;
+                        break;
+                    }
+                }
+            }
+            if duplicates.contains(&true) {
+                if (*emitter).duplicate_keys == YAML_REJECT_DUPLICATE_KEYS {
+                    return yaml_emitter_set_emitter_error(
+                        emitter,
+                        b"found duplicate mapping key\0" as *const u8 as *const libc::c_char,
+                    );
+                }
+                let size = (size_of::<bool>() as libc::c_ulong)
+                    .force_mul(duplicates.len() as libc::c_ulong);
+                (*anchors).duplicates = yaml_malloc(size) as *mut bool;
+                memcpy(
+                    (*anchors).duplicates as *mut libc::c_void,
+                    duplicates.as_ptr() as *const libc::c_void,
+                    size,
+                );
+            }
+        }
+        node = node.add(1);
+        anchors = anchors.add(1);
+    }
+    OK
+}
+
+unsafe fn yaml_emitter_key_tag<'a>(node: *const yaml_node_t) -> Option<&'a [u8]> {
+    if (*node).tag.is_null() {
+        return None;
+    }
+    Some(slice::from_raw_parts(
+        (*node).tag,
+        strlen((*node).tag as *const libc::c_char) as usize,
+    ))
+}
+
+unsafe fn yaml_emitter_key_value<'a>(node: *const yaml_node_t) -> &'a [u8] {
+    slice::from_raw_parts(
+        (*node).data.scalar.value,
+        (*node).data.scalar.length as usize,
+    )
+}
+
+/// Is the pair of the mapping `node` left out as a duplicate key?
+unsafe fn yaml_emitter_is_duplicate(
+    emitter: *mut yaml_emitter_t,
+    node: *const yaml_node_t,
+    pair: *const yaml_node_pair_t,
+) -> bool {
+    let index = node.offset_from((*(*emitter).document).nodes.start);
+    let duplicates = (*(*emitter).anchors.offset(index)).duplicates;
+    !duplicates.is_null() && *duplicates.offset(pair.offset_from((*node).data.mapping.pairs.start))
+}
+
+unsafe fn yaml_emitter_sort_keys(emitter: *mut yaml_emitter_t) {
+    if !(*emitter).sort_keys {
+        return;
+    }
+    let mut node: *mut yaml_node_t = (*(*emitter).document).nodes.start;
+    while node < (*(*emitter).document).nodes.top {
+        if (*node).type_ == YAML_MAPPING_NODE {
+            let pairs = (*node).data.mapping.pairs;
+            let length = pairs.top.c_offset_from(pairs.start) as usize;
+            if length > 1 {
+                yaml_emitter_sort_pairs(emitter, slice::from_raw_parts_mut(pairs.start, length));
+            }
+        }
+        node = node.add(1);
+    }
+    yaml_emitter_drop_redefined_anchors(emitter);
+}
+
+/// Sort the pairs of a mapping by key with a bottom-up merge sort.
+///
+/// A pair only goes before a pair on its left if the comparator says so, which
+/// keeps the sort stable. slice::sort_by() may panic if a key comparator is not
+/// a total order, while this sort only puts the pairs in an unspecified order.
+unsafe fn yaml_emitter_sort_pairs(emitter: *mut yaml_emitter_t, pairs: &mut [yaml_node_pair_t]) {
+    let length = pairs.len();
+    let mut merged = pairs.to_vec();
+    let mut width = 1;
+    while width < length {
+        let mut start = 0;
+        while start < length {
+            let middle = cmp::min(start + width, length);
+            let end = cmp::min(middle + width, length);
+            let (mut left, mut right) = (start, middle);
+            let mut index = start;
+            while left < middle && right < end {
+                if yaml_emitter_compare_keys(emitter, pairs[right].key, pairs[left].key)
+                    == Ordering::Less
+                {
+                    merged[index] = pairs[right];
+                    right += 1;
+                } else {
+                    merged[index] = pairs[left];
+                    left += 1;
+                }
+                index += 1;
+            }
+            merged[index..index + middle - left].copy_from_slice(&pairs[left..middle]);
+            index += middle - left;
+            merged[index..end].copy_from_slice(&pairs[right..end]);
+            start = end;
+        }
+        pairs.copy_from_slice(&merged);
+        width *= 2;
+    }
+}
+
+/// Forget the anchors that name more than one node, as sorting may move an
+/// alias past a redefinition of its anchor. The nodes that need an anchor get
+/// a generated one instead.
+unsafe fn yaml_emitter_drop_redefined_anchors(emitter: *mut yaml_emitter_t) {
+    let nodes = (*(*emitter).document).nodes;
+    let mut counts = BTreeMap::<&[u8], usize>::new();
+    let mut node: *mut yaml_node_t = nodes.start;
+    while node < nodes.top {
+        if !(*node).anchor.is_null() {
+            *counts.entry(yaml_emitter_anchor_name(node)).or_insert(0) += 1;
+        }
+        node = node.add(1);
+    }
+    let mut redefined = Vec::new();
+    node = nodes.start;
+    while node < nodes.top {
+        if !(*node).anchor.is_null() && counts[yaml_emitter_anchor_name(node)] > 1 {
+            redefined.push(node);
+        }
+        node = node.add(1);
+    }
+    drop(counts);
+    for node in redefined {
+        yaml_free((*node).anchor as *mut libc::c_void);
+        (*node).anchor = ptr::null_mut::<yaml_char_t>();
+    }
+}
+
+unsafe fn yaml_emitter_anchor_name<'a>(node: *const yaml_node_t) -> &'a [u8] {
+    slice::from_raw_parts(
+        (*node).anchor,
+        strlen((*node).anchor as *const libc::c_char) as usize,
+    )
+}
+
+unsafe fn yaml_emitter_compare_keys(
+    emitter: *mut yaml_emitter_t,
+    a: libc::c_int,
+    b: libc::c_int,
+) -> Ordering {
+    let a: *const yaml_node_t = (*(*emitter).document).nodes.start.add((a - 1) as usize);
+    let b: *const yaml_node_t = (*(*emitter).document).nodes.start.add((b - 1) as usize);
+    if let Some(comparator) = (*emitter).key_comparator {
+        return comparator((*emitter).key_comparator_data, a, b).cmp(&0);
+    }
+    match ((*a).type_, (*b).type_) {
+        (YAML_SCALAR_NODE, YAML_SCALAR_NODE) => {
+            let a = slice::from_raw_parts((*a).data.scalar.value, (*a).data.scalar.length as usize);
+            let b = slice::from_raw_parts((*b).data.scalar.value, (*b).data.scalar.length as usize);
+            a.cmp(b)
+        }
+        (YAML_SCALAR_NODE, _) => Ordering::Less,
+        (_, YAML_SCALAR_NODE) => Ordering::Greater,
+        _ => Ordering::Equal,
     }
 }
 
@@ -232,24 +958,65 @@ unsafe fn yaml_emitter_anchor_node(emitter: *mut yaml_emitter_t, index: libc::c_
             YAML_SEQUENCE_NODE => {
                 item = (*node).data.sequence.items.start;
                 while item < (*node).data.sequence.items.top {
-                    yaml_emitter_anchor_node_sub(emitter, *item);
+                    yaml_emitter_anchor_node(emitter, *item);
                     item = item.wrapping_offset(1);
                 }
             }
             YAML_MAPPING_NODE => {
                 pair = (*node).data.mapping.pairs.start;
                 while pair < (*node).data.mapping.pairs.top {
-                    yaml_emitter_anchor_node_sub(emitter, (*pair).key);
-                    yaml_emitter_anchor_node_sub(emitter, (*pair).value);
+                    if !yaml_emitter_is_duplicate(emitter, node, pair) {
+                        yaml_emitter_anchor_node(emitter, (*pair).key);
+                        yaml_emitter_anchor_node(emitter, (*pair).value);
+                    }
                     pair = pair.wrapping_offset(1);
                 }
             }
+            YAML_ALIAS_NODE => {
+                yaml_emitter_anchor_node(emitter, (*node).data.alias.target);
+            }
             _ => {}
         }
-    } else if (*(*emitter).anchors.wrapping_offset((index - 1) as isize)).references == 2 {
-        let fresh9 = addr_of_mut!((*emitter).last_anchor_id);
-        *fresh9 += 1;
-        (*(*emitter).anchors.wrapping_offset((index - 1) as isize)).anchor = *fresh9;
+    } else if (*(*emitter).anchors.wrapping_offset((index - 1) as isize)).references == 2
+        && (*(*emitter).anchors.wrapping_offset((index - 1) as isize)).anchor == 0
+    {
+        (*(*emitter).anchors.wrapping_offset((index - 1) as isize)).anchor =
+            yaml_emitter_next_anchor_id(emitter);
+    }
+}
+
+unsafe fn yaml_emitter_anchor_all_nodes(emitter: *mut yaml_emitter_t) {
+    let mut node: *mut yaml_node_t = (*(*emitter).document).nodes.start;
+    let mut anchor: *mut yaml_anchors_t = (*emitter).anchors;
+    while node < (*(*emitter).document).nodes.top {
+        if (*node).anchor.is_null() {
+            (*anchor).anchor = yaml_emitter_next_anchor_id(emitter);
+        }
+        node = node.add(1);
+        anchor = anchor.add(1);
+    }
+}
+
+unsafe fn yaml_emitter_next_anchor_id(emitter: *mut yaml_emitter_t) -> libc::c_int {
+    loop {
+        (*emitter).last_anchor_id += 1;
+        let anchor = yaml_emitter_generate_anchor(emitter, (*emitter).last_anchor_id);
+        let mut node: *mut yaml_node_t = (*(*emitter).document).nodes.start;
+        while node < (*(*emitter).document).nodes.top {
+            if !(*node).anchor.is_null()
+                && strcmp(
+                    (*node).anchor as *mut libc::c_char,
+                    anchor as *mut libc::c_char,
+                ) == 0
+            {
+                break;
+            }
+            node = node.add(1);
+        }
+        yaml_free(anchor as *mut libc::c_void);
+        if node == (*(*emitter).document).nodes.top {
+            return (*emitter).last_anchor_id;
+        }
     }
 }
 
@@ -268,12 +1035,24 @@ unsafe fn yaml_emitter_dump_node(emitter: *mut yaml_emitter_t, index: libc::c_in
         .start
         .wrapping_offset(index as isize)
         .wrapping_offset(-1_isize);
+    if (*node).type_ == YAML_ALIAS_NODE {
+        // The target is written in full if the alias comes before it.
+        return yaml_emitter_dump_node(emitter, (*node).data.alias.target);
+    }
     let anchor_id: libc::c_int = (*(*emitter).anchors.wrapping_offset((index - 1) as isize)).anchor;
     let mut anchor: *mut yaml_char_t = ptr::null_mut::<yaml_char_t>();
-    if anchor_id != 0 {
+    if !(*node).anchor.is_null() {
+        anchor = yaml_strdup((*node).anchor);
+    } else if anchor_id != 0 {
         anchor = yaml_emitter_generate_anchor(emitter, anchor_id);
     }
     if (*(*emitter).anchors.wrapping_offset((index - 1) as isize)).serialized {
+        if anchor.is_null() {
+            return yaml_emitter_set_emitter_error(
+                emitter,
+                b"node is referenced again but has no anchor\0" as *const u8 as *const libc::c_char,
+            );
+        }
         return yaml_emitter_dump_alias(emitter, anchor);
     }
     (*(*emitter).anchors.wrapping_offset((index - 1) as isize)).serialized = true;
@@ -285,6 +1064,30 @@ unsafe fn yaml_emitter_dump_node(emitter: *mut yaml_emitter_t, index: libc::c_in
     }
 }
 
+unsafe fn yaml_emitter_dump_empty_node(emitter: *mut yaml_emitter_t) -> Success {
+    let mut event = MaybeUninit::<yaml_event_t>::uninit();
+    let event = event.as_mut_ptr();
+    let mark = yaml_mark_t {
+        index: 0_u64,
+        line: 0_u64,
+        column: 0_u64,
+    };
+    memset(
+        event as *mut libc::c_void,
+        0,
+        size_of::<yaml_event_t>() as libc::c_ulong,
+    );
+    (*event).type_ = YAML_SCALAR_EVENT;
+    (*event).start_mark = mark;
+    (*event).end_mark = mark;
+    (*event).data.scalar.value = yaml_strdup(b"\0" as *const u8 as *mut yaml_char_t);
+    (*event).data.scalar.length = 0_u64;
+    (*event).data.scalar.plain_implicit = true;
+    (*event).data.scalar.quoted_implicit = true;
+    (*event).data.scalar.style = YAML_ANY_SCALAR_STYLE;
+    yaml_emitter_dump_event(emitter, event)
+}
+
 unsafe fn yaml_emitter_dump_alias(
     emitter: *mut yaml_emitter_t,
     anchor: *mut yaml_char_t,
@@ -305,7 +1108,14 @@ unsafe fn yaml_emitter_dump_alias(
     (*event).start_mark = mark;
     (*event).end_mark = mark;
     (*event).data.alias.anchor = anchor;
-    yaml_emitter_emit(emitter, event)
+    yaml_emitter_dump_event(emitter, event)
+}
+
+unsafe fn yaml_emitter_take_comments(event: *mut yaml_event_t, node: *mut yaml_node_t) {
+    (*event).leading_comment = (*node).leading_comment;
+    (*event).trailing_comment = (*node).trailing_comment;
+    (*node).leading_comment = ptr::null_mut::<yaml_char_t>();
+    (*node).trailing_comment = ptr::null_mut::<yaml_char_t>();
 }
 
 unsafe fn yaml_emitter_dump_scalar(
@@ -337,13 +1147,17 @@ unsafe fn yaml_emitter_dump_scalar(
     (*event).start_mark = mark;
     (*event).end_mark = mark;
     (*event).data.scalar.anchor = anchor;
+    yaml_emitter_take_comments(event, node);
     (*event).data.scalar.tag = (*node).tag;
     (*event).data.scalar.value = (*node).data.scalar.value;
     (*event).data.scalar.length = (*node).data.scalar.length;
     (*event).data.scalar.plain_implicit = plain_implicit;
     (*event).data.scalar.quoted_implicit = quoted_implicit;
     (*event).data.scalar.style = (*node).data.scalar.style;
-    yaml_emitter_emit(emitter, event)
+    (*event).data.scalar.chomping = (*node).data.scalar.chomping;
+    (*event).data.scalar.indentation_indicator = (*node).data.scalar.indentation_indicator;
+    (*event).data.scalar.source = (*node).data.scalar.source;
+    yaml_emitter_dump_event(emitter, event)
 }
 
 unsafe fn yaml_emitter_dump_sequence(
@@ -372,10 +1186,11 @@ unsafe fn yaml_emitter_dump_sequence(
     (*event).start_mark = mark;
     (*event).end_mark = mark;
     (*event).data.sequence_start.anchor = anchor;
+    yaml_emitter_take_comments(event, node);
     (*event).data.sequence_start.tag = (*node).tag;
     (*event).data.sequence_start.implicit = implicit;
     (*event).data.sequence_start.style = (*node).data.sequence.style;
-    if yaml_emitter_emit(emitter, event).fail {
+    if yaml_emitter_dump_event(emitter, event).fail {
         return FAIL;
     }
     item = (*node).data.sequence.items.start;
@@ -393,7 +1208,7 @@ unsafe fn yaml_emitter_dump_sequence(
     (*event).type_ = YAML_SEQUENCE_END_EVENT;
     (*event).start_mark = mark;
     (*event).end_mark = mark;
-    yaml_emitter_emit(emitter, event)
+    yaml_emitter_dump_event(emitter, event)
 }
 
 unsafe fn yaml_emitter_dump_mapping(
@@ -422,19 +1237,22 @@ unsafe fn yaml_emitter_dump_mapping(
     (*event).start_mark = mark;
     (*event).end_mark = mark;
     (*event).data.mapping_start.anchor = anchor;
+    yaml_emitter_take_comments(event, node);
     (*event).data.mapping_start.tag = (*node).tag;
     (*event).data.mapping_start.implicit = implicit;
     (*event).data.mapping_start.style = (*node).data.mapping.style;
-    if yaml_emitter_emit(emitter, event).fail {
+    if yaml_emitter_dump_event(emitter, event).fail {
         return FAIL;
     }
     pair = (*node).data.mapping.pairs.start;
     while pair < (*node).data.mapping.pairs.top {
-        if yaml_emitter_dump_node(emitter, (*pair).key).fail {
-            return FAIL;
-        }
-        if yaml_emitter_dump_node(emitter, (*pair).value).fail {
-            return FAIL;
+        if !yaml_emitter_is_duplicate(emitter, node, pair) {
+            if yaml_emitter_dump_node(emitter, (*pair).key).fail {
+                return FAIL;
+            }
+            if yaml_emitter_dump_node(emitter, (*pair).value).fail {
+                return FAIL;
+            }
         }
         pair = pair.wrapping_offset(1);
     }
@@ -446,5 +1264,5 @@ unsafe fn yaml_emitter_dump_mapping(
     (*event).type_ = YAML_MAPPING_END_EVENT;
     (*event).start_mark = mark;
     (*event).end_mark = mark;
-    yaml_emitter_emit(emitter, event)
+    yaml_emitter_dump_event(emitter, event)
 }
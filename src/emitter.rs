@@ -1,28 +1,34 @@
-use crate::api::{yaml_free, yaml_queue_extend, yaml_stack_extend, yaml_strdup};
-use crate::externs::{strcmp, strlen, strncmp};
+use crate::api::{
+    yaml_check_utf8, yaml_free, yaml_malloc, yaml_queue_extend, yaml_stack_extend, yaml_strdup,
+};
+use crate::externs::{memcpy, strcmp, strlen, strncmp};
 use crate::ops::{ForceAdd as _, ForceMul as _};
 use crate::success::{Success, FAIL, OK};
-use crate::yaml::{size_t, yaml_char_t, yaml_string_t};
+use crate::yaml::{size_t, yaml_char_t, yaml_scalar_source_t, yaml_string_t};
 use crate::{
-    libc, yaml_emitter_flush, yaml_emitter_t, yaml_event_delete, yaml_event_t, yaml_scalar_style_t,
-    yaml_tag_directive_t, yaml_version_directive_t, PointerExt, YAML_ALIAS_EVENT, YAML_ANY_BREAK,
-    YAML_ANY_ENCODING, YAML_ANY_SCALAR_STYLE, YAML_CRLN_BREAK, YAML_CR_BREAK,
-    YAML_DOCUMENT_END_EVENT, YAML_DOCUMENT_START_EVENT, YAML_DOUBLE_QUOTED_SCALAR_STYLE,
-    YAML_EMITTER_ERROR, YAML_EMIT_BLOCK_MAPPING_FIRST_KEY_STATE, YAML_EMIT_BLOCK_MAPPING_KEY_STATE,
-    YAML_EMIT_BLOCK_MAPPING_SIMPLE_VALUE_STATE, YAML_EMIT_BLOCK_MAPPING_VALUE_STATE,
-    YAML_EMIT_BLOCK_SEQUENCE_FIRST_ITEM_STATE, YAML_EMIT_BLOCK_SEQUENCE_ITEM_STATE,
-    YAML_EMIT_DOCUMENT_CONTENT_STATE, YAML_EMIT_DOCUMENT_END_STATE, YAML_EMIT_DOCUMENT_START_STATE,
-    YAML_EMIT_END_STATE, YAML_EMIT_FIRST_DOCUMENT_START_STATE,
-    YAML_EMIT_FLOW_MAPPING_FIRST_KEY_STATE, YAML_EMIT_FLOW_MAPPING_KEY_STATE,
-    YAML_EMIT_FLOW_MAPPING_SIMPLE_VALUE_STATE, YAML_EMIT_FLOW_MAPPING_VALUE_STATE,
-    YAML_EMIT_FLOW_SEQUENCE_FIRST_ITEM_STATE, YAML_EMIT_FLOW_SEQUENCE_ITEM_STATE,
-    YAML_EMIT_STREAM_START_STATE, YAML_FLOW_MAPPING_STYLE, YAML_FLOW_SEQUENCE_STYLE,
-    YAML_FOLDED_SCALAR_STYLE, YAML_LITERAL_SCALAR_STYLE, YAML_LN_BREAK, YAML_MAPPING_END_EVENT,
-    YAML_MAPPING_START_EVENT, YAML_PLAIN_SCALAR_STYLE, YAML_SCALAR_EVENT, YAML_SEQUENCE_END_EVENT,
-    YAML_SEQUENCE_START_EVENT, YAML_SINGLE_QUOTED_SCALAR_STYLE, YAML_STREAM_END_EVENT,
-    YAML_STREAM_START_EVENT, YAML_UTF8_ENCODING,
+    libc, yaml_emitter_flush, yaml_emitter_t, yaml_event_delete, yaml_event_t,
+    yaml_scalar_handler_t, yaml_scalar_style_t, yaml_tag_directive_t, yaml_version_directive_t,
+    PointerExt, YAML_ALIAS_EVENT, YAML_ALWAYS_DOCUMENT_END_MARKER, YAML_ANY_BREAK,
+    YAML_ANY_ENCODING, YAML_ANY_SCALAR_STYLE, YAML_BETWEEN_DOCUMENT_START_MARKER, YAML_CRLN_BREAK,
+    YAML_CR_BREAK, YAML_DOCUMENT_END_EVENT, YAML_DOCUMENT_START_EVENT,
+    YAML_DOUBLE_QUOTED_SCALAR_STYLE, YAML_EMITTER_ERROR, YAML_EMIT_BLOCK_MAPPING_FIRST_KEY_STATE,
+    YAML_EMIT_BLOCK_MAPPING_KEY_STATE, YAML_EMIT_BLOCK_MAPPING_SIMPLE_VALUE_STATE,
+    YAML_EMIT_BLOCK_MAPPING_VALUE_STATE, YAML_EMIT_BLOCK_SEQUENCE_FIRST_ITEM_STATE,
+    YAML_EMIT_BLOCK_SEQUENCE_ITEM_STATE, YAML_EMIT_DOCUMENT_CONTENT_STATE,
+    YAML_EMIT_DOCUMENT_END_STATE, YAML_EMIT_DOCUMENT_START_STATE, YAML_EMIT_END_STATE,
+    YAML_EMIT_FIRST_DOCUMENT_START_STATE, YAML_EMIT_FLOW_MAPPING_FIRST_KEY_STATE,
+    YAML_EMIT_FLOW_MAPPING_KEY_STATE, YAML_EMIT_FLOW_MAPPING_SIMPLE_VALUE_STATE,
+    YAML_EMIT_FLOW_MAPPING_VALUE_STATE, YAML_EMIT_FLOW_SEQUENCE_FIRST_ITEM_STATE,
+    YAML_EMIT_FLOW_SEQUENCE_ITEM_STATE, YAML_EMIT_STREAM_START_STATE, YAML_FLOW_MAPPING_STYLE,
+    YAML_FLOW_SEQUENCE_STYLE, YAML_FOLDED_SCALAR_STYLE, YAML_KEEP_CHOMPING,
+    YAML_LITERAL_SCALAR_STYLE, YAML_LN_BREAK, YAML_MAPPING_END_EVENT, YAML_MAPPING_START_EVENT,
+    YAML_NEVER_DOCUMENT_END_MARKER, YAML_NEVER_WRAP, YAML_OMIT_FIRST_DOCUMENT_START_MARKER,
+    YAML_PLAIN_SCALAR_STYLE, YAML_SCALAR_EVENT, YAML_SEQUENCE_END_EVENT, YAML_SEQUENCE_START_EVENT,
+    YAML_SINGLE_QUOTED_SCALAR_STYLE, YAML_STREAM_END_EVENT, YAML_STREAM_START_EVENT,
+    YAML_UTF8_ENCODING, YAML_WRAP_AT_WHITESPACE, YAML_WRAP_AT_WIDTH,
 };
 use core::ptr::{self, addr_of_mut};
+use core::slice;
 
 unsafe fn FLUSH(emitter: *mut yaml_emitter_t) -> Success {
     if (*emitter).buffer.pointer.wrapping_offset(5_isize) < (*emitter).buffer.end {
@@ -45,6 +51,13 @@ unsafe fn PUT(emitter: *mut yaml_emitter_t, value: u8) -> Success {
     OK
 }
 
+unsafe fn COUNT_LINE(emitter: *mut yaml_emitter_t) {
+    if (*emitter).column > (*emitter).max_column {
+        (*emitter).max_column = (*emitter).column;
+    }
+    (*emitter).lines_written = (*emitter).lines_written.force_add(1);
+}
+
 unsafe fn PUT_BREAK(emitter: *mut yaml_emitter_t) -> Success {
     if FLUSH(emitter).fail {
         return FAIL;
@@ -69,6 +82,7 @@ unsafe fn PUT_BREAK(emitter: *mut yaml_emitter_t) -> Success {
         *fresh68 = (*fresh68).wrapping_offset(1);
         *fresh69 = b'\n';
     };
+    COUNT_LINE(emitter);
     (*emitter).column = 0;
     let fresh70 = addr_of_mut!((*emitter).line);
     *fresh70 += 1;
@@ -94,6 +108,7 @@ unsafe fn WRITE_BREAK(emitter: *mut yaml_emitter_t, string: *mut yaml_string_t)
         (*string).pointer = (*string).pointer.wrapping_offset(1);
     } else {
         COPY!((*emitter).buffer, *string);
+        COUNT_LINE(emitter);
         (*emitter).column = 0;
         let fresh300 = addr_of_mut!((*emitter).line);
         *fresh300 += 1;
@@ -101,6 +116,31 @@ unsafe fn WRITE_BREAK(emitter: *mut yaml_emitter_t, string: *mut yaml_string_t)
     OK
 }
 
+unsafe fn yaml_emitter_write_scalar_break(
+    emitter: *mut yaml_emitter_t,
+    string: *mut yaml_string_t,
+) -> Success {
+    if (*emitter).preserve_scalar_breaks {
+        if FLUSH(emitter).fail {
+            return FAIL;
+        }
+        COPY!((*emitter).buffer, *string);
+        COUNT_LINE(emitter);
+        (*emitter).column = 0;
+        (*emitter).line += 1;
+        return OK;
+    }
+    if (*emitter).scalar_break == YAML_ANY_BREAK {
+        return WRITE_BREAK(emitter, string);
+    }
+    if (*string).pointer.add(1) != (*string).end && IS_CRLF!(*string) {
+        (*string).pointer = (*string).pointer.add(2);
+    } else {
+        MOVE!(*string);
+    }
+    PUT_BREAK(emitter)
+}
+
 macro_rules! WRITE {
     ($emitter:expr, $string:expr) => {
         WRITE($emitter, addr_of_mut!($string))
@@ -113,7 +153,7 @@ macro_rules! WRITE_BREAK {
     };
 }
 
-unsafe fn yaml_emitter_set_emitter_error(
+pub(crate) unsafe fn yaml_emitter_set_emitter_error(
     emitter: *mut yaml_emitter_t,
     problem: *const libc::c_char,
 ) -> Success {
@@ -143,12 +183,97 @@ pub unsafe fn yaml_emitter_emit(emitter: *mut yaml_emitter_t, event: *mut yaml_e
     OK
 }
 
+/// Write pre-formatted text to the output.
+///
+/// The text is copied to the output as it is, between the events emitted so
+/// far and the following ones, which makes it possible to splice comments or
+/// pre-rendered fragments into a stream. The text must be valid UTF-8 and end
+/// with a line break, so that the emitter continues on a fresh line. If the
+/// emitter is in the middle of a line, a line break is written first.
+///
+/// The function may only be called between documents, or between the entries
+/// of a block sequence or the pairs of a block mapping, and only when the
+/// emitter does not hold any events back. Events are held back after a
+/// DOCUMENT-START, SEQUENCE-START or MAPPING-START event until the emitter has
+/// seen enough of the following events to choose a style.
+pub unsafe fn yaml_emitter_write_raw(
+    emitter: *mut yaml_emitter_t,
+    text: *const yaml_char_t,
+    length: size_t,
+) -> Success {
+    __assert!(!emitter.is_null());
+    __assert!(!text.is_null() || length == 0);
+    if !matches!(
+        (*emitter).state,
+        YAML_EMIT_FIRST_DOCUMENT_START_STATE
+            | YAML_EMIT_DOCUMENT_START_STATE
+            | YAML_EMIT_DOCUMENT_CONTENT_STATE
+            | YAML_EMIT_DOCUMENT_END_STATE
+            | YAML_EMIT_BLOCK_SEQUENCE_FIRST_ITEM_STATE
+            | YAML_EMIT_BLOCK_SEQUENCE_ITEM_STATE
+            | YAML_EMIT_BLOCK_MAPPING_FIRST_KEY_STATE
+            | YAML_EMIT_BLOCK_MAPPING_KEY_STATE
+    ) {
+        return yaml_emitter_set_emitter_error(
+            emitter,
+            b"raw text is only allowed between documents or block collection entries\0" as *const u8
+                as *const libc::c_char,
+        );
+    }
+    if !QUEUE_EMPTY!((*emitter).events) {
+        return yaml_emitter_set_emitter_error(
+            emitter,
+            b"raw text is not allowed while events are pending\0" as *const u8
+                as *const libc::c_char,
+        );
+    }
+    if length == 0 {
+        return OK;
+    }
+    if yaml_check_utf8(text, length).fail {
+        return yaml_emitter_set_emitter_error(
+            emitter,
+            b"raw text is not valid UTF-8\0" as *const u8 as *const libc::c_char,
+        );
+    }
+    if *text.add(length as usize - 1) != b'\n' {
+        return yaml_emitter_set_emitter_error(
+            emitter,
+            b"raw text must end with a line break\0" as *const u8 as *const libc::c_char,
+        );
+    }
+    if (*emitter).column != 0 && PUT_BREAK(emitter).fail {
+        return FAIL;
+    }
+    let mut string = STRING_ASSIGN!(text as *mut yaml_char_t, length);
+    while string.pointer != string.end {
+        if IS_BREAK!(string) {
+            if WRITE_BREAK!(emitter, string).fail {
+                return FAIL;
+            }
+        } else if WRITE!(emitter, string).fail {
+            return FAIL;
+        }
+    }
+    (*emitter).whitespace = true;
+    (*emitter).indention = true;
+    OK
+}
+
 unsafe fn yaml_emitter_need_more_events(emitter: *mut yaml_emitter_t) -> Success {
     let mut level: libc::c_int = 0;
     let mut event: *mut yaml_event_t;
     if QUEUE_EMPTY!((*emitter).events) {
         return OK;
     }
+    if yaml_emitter_wants_compact_flow(emitter) {
+        match yaml_emitter_measure_compact_flow(emitter) {
+            Some(true) => return FAIL,
+            None => return OK,
+            // Still look ahead for the end event of an empty collection.
+            Some(false) => {}
+        }
+    }
     let accumulate = match (*(*emitter).events.head).type_ {
         YAML_DOCUMENT_START_EVENT => 1,
         YAML_SEQUENCE_START_EVENT => 2,
@@ -185,6 +310,133 @@ unsafe fn yaml_emitter_need_more_events(emitter: *mut yaml_emitter_t) -> Success
     OK
 }
 
+unsafe fn yaml_emitter_wants_compact_flow(emitter: *mut yaml_emitter_t) -> bool {
+    if (*emitter).compact_flow_threshold == 0
+        || (*emitter).flow_level != 0
+        || (*emitter).canonical
+        || QUEUE_EMPTY!((*emitter).events)
+    {
+        return false;
+    }
+    let event: *mut yaml_event_t = (*emitter).events.head;
+    match (*event).type_ {
+        YAML_SEQUENCE_START_EVENT => (*event).data.sequence_start.style != YAML_FLOW_SEQUENCE_STYLE,
+        YAML_MAPPING_START_EVENT => (*event).data.mapping_start.style != YAML_FLOW_MAPPING_STYLE,
+        _ => false,
+    }
+}
+
+unsafe fn yaml_emitter_properties_length(
+    anchor: *mut yaml_char_t,
+    tag: *mut yaml_char_t,
+    tagged: bool,
+) -> size_t {
+    let mut length: size_t = 0_u64;
+    if !anchor.is_null() {
+        length = length
+            .force_add(strlen(anchor as *mut libc::c_char))
+            .force_add(2);
+    }
+    if tagged && !tag.is_null() {
+        length = length
+            .force_add(strlen(tag as *mut libc::c_char))
+            .force_add(2);
+    }
+    length
+}
+
+/// Measure the flow form of the collection at the head of the event queue.
+///
+/// Returns None until the queue holds the whole collection, unless it is
+/// already known not to fit. Otherwise returns if the collection fits under
+/// the compact flow threshold.
+unsafe fn yaml_emitter_measure_compact_flow(emitter: *mut yaml_emitter_t) -> Option<bool> {
+    let threshold = (*emitter).compact_flow_threshold as size_t;
+    let always = (*emitter).always_emit_tags;
+    let mut length: size_t = 0_u64;
+    let mut level: libc::c_int = 0;
+    let mut first = true;
+    let mut event: *mut yaml_event_t = (*emitter).events.head;
+    while event != (*emitter).events.tail {
+        match (*event).type_ {
+            YAML_SEQUENCE_END_EVENT | YAML_MAPPING_END_EVENT => {}
+            _ if !first => length = length.force_add(2),
+            _ => {}
+        }
+        match (*event).type_ {
+            YAML_SEQUENCE_START_EVENT => {
+                let data = (*event).data.sequence_start;
+                length = length
+                    .force_add(1)
+                    .force_add(yaml_emitter_properties_length(
+                        data.anchor,
+                        data.tag,
+                        always || !data.implicit,
+                    ));
+                level += 1;
+                first = true;
+            }
+            YAML_MAPPING_START_EVENT => {
+                let data = (*event).data.mapping_start;
+                length = length
+                    .force_add(1)
+                    .force_add(yaml_emitter_properties_length(
+                        data.anchor,
+                        data.tag,
+                        always || !data.implicit,
+                    ));
+                level += 1;
+                first = true;
+            }
+            YAML_SEQUENCE_END_EVENT | YAML_MAPPING_END_EVENT => {
+                length = length.force_add(1);
+                level -= 1;
+                first = false;
+                if level == 0 {
+                    return Some(length < threshold);
+                }
+            }
+            YAML_SCALAR_EVENT => {
+                let data = (*event).data.scalar;
+                let value = slice::from_raw_parts(data.value, data.length as usize);
+                if data.style == YAML_LITERAL_SCALAR_STYLE
+                    || data.style == YAML_FOLDED_SCALAR_STYLE
+                    || value.iter().any(|&ch| ch == b'\n' || ch == b'\r')
+                {
+                    return Some(false);
+                }
+                // The threshold counts characters, not the bytes of UTF-8.
+                let characters = value.iter().filter(|&&ch| ch & 0xC0 != 0x80).count();
+                length = length.force_add(characters as size_t).force_add(
+                    yaml_emitter_properties_length(
+                        data.anchor,
+                        data.tag,
+                        always || !data.plain_implicit && !data.quoted_implicit,
+                    ),
+                );
+                if data.style == YAML_SINGLE_QUOTED_SCALAR_STYLE
+                    || data.style == YAML_DOUBLE_QUOTED_SCALAR_STYLE
+                {
+                    length = length.force_add(2);
+                }
+                first = false;
+            }
+            YAML_ALIAS_EVENT => {
+                length = length
+                    .force_add(strlen((*event).data.alias.anchor as *mut libc::c_char))
+                    .force_add(1);
+                first = false;
+            }
+            _ => return Some(false),
+        }
+        if length >= threshold {
+            return Some(false);
+        }
+        event = event.add(1);
+    }
+    None
+}
+
 unsafe fn yaml_emitter_append_tag_directive(
     emitter: *mut yaml_emitter_t,
     value: yaml_tag_directive_t,
@@ -220,10 +472,15 @@ unsafe fn yaml_emitter_append_tag_directive(
 
 unsafe fn yaml_emitter_increase_indent(emitter: *mut yaml_emitter_t, flow: bool, indentless: bool) {
     PUSH!((*emitter).indents, (*emitter).indent);
+    let best_indent = if flow && (*emitter).canonical && (*emitter).canonical_indent != 0 {
+        (*emitter).canonical_indent
+    } else {
+        (*emitter).best_indent
+    };
     if (*emitter).indent < 0 {
-        (*emitter).indent = if flow { (*emitter).best_indent } else { 0 };
+        (*emitter).indent = if flow { best_indent } else { 0 };
     } else if !indentless {
-        (*emitter).indent += (*emitter).best_indent;
+        (*emitter).indent += best_indent;
     }
 }
 
@@ -323,7 +580,7 @@ unsafe fn yaml_emitter_emit_stream_start(
     }
     yaml_emitter_set_emitter_error(
         emitter,
-        b"expected STREAM-START\0" as *const u8 as *const libc::c_char,
+        b"expected STREAM-START at the start of the stream\0" as *const u8 as *const libc::c_char,
     )
 }
 
@@ -333,6 +590,9 @@ unsafe fn yaml_emitter_emit_document_start(
     first: bool,
 ) -> Success {
     if (*event).type_ == YAML_DOCUMENT_START_EVENT {
+        if (*emitter).column != 0 && yaml_emitter_write_indent(emitter).fail {
+            return FAIL;
+        }
         let mut default_tag_directives: [yaml_tag_directive_t; 3] = [
             yaml_tag_directive_t {
                 handle: b"!\0" as *const u8 as *const libc::c_char as *mut yaml_char_t,
@@ -350,13 +610,17 @@ unsafe fn yaml_emitter_emit_document_start(
         ];
         let mut tag_directive: *mut yaml_tag_directive_t;
         let mut implicit;
-        if !(*event).data.document_start.version_directive.is_null() {
-            if yaml_emitter_analyze_version_directive(
-                emitter,
-                *(*event).data.document_start.version_directive,
-            )
-            .fail
-            {
+        let directives = (*emitter).canonical && (*emitter).canonical_directives;
+        let mut secondary_handle = false;
+        let version_directive = if !(*event).data.document_start.version_directive.is_null() {
+            (*event).data.document_start.version_directive
+        } else if (*emitter).version_directive.major != 0 {
+            addr_of_mut!((*emitter).version_directive)
+        } else {
+            ptr::null_mut::<yaml_version_directive_t>()
+        };
+        if !version_directive.is_null() {
+            if yaml_emitter_analyze_version_directive(emitter, *version_directive).fail {
                 return FAIL;
             }
         }
@@ -370,6 +634,17 @@ unsafe fn yaml_emitter_emit_document_start(
             }
             tag_directive = tag_directive.wrapping_offset(1);
         }
+        tag_directive = (*emitter).document_tag_directives.start;
+        while tag_directive != (*emitter).document_tag_directives.top {
+            if yaml_emitter_analyze_tag_directive(emitter, *tag_directive).fail {
+                return FAIL;
+            }
+            if yaml_emitter_append_tag_directive(emitter, *tag_directive, true).fail {
+                return FAIL;
+            }
+            tag_directive = tag_directive.add(1);
+        }
+        let tag_directives_end = (*emitter).tag_directives.top;
         tag_directive = default_tag_directives.as_mut_ptr();
         while !(*tag_directive).handle.is_null() {
             if yaml_emitter_append_tag_directive(emitter, *tag_directive, true).fail {
@@ -377,13 +652,14 @@ unsafe fn yaml_emitter_emit_document_start(
             }
             tag_directive = tag_directive.wrapping_offset(1);
         }
-        implicit = (*event).data.document_start.implicit;
-        if !first || (*emitter).canonical {
-            implicit = false;
-        }
-        if (!(*event).data.document_start.version_directive.is_null()
-            || (*event).data.document_start.tag_directives.start
-                != (*event).data.document_start.tag_directives.end)
+        implicit = match (*emitter).document_start_marker {
+            YAML_OMIT_FIRST_DOCUMENT_START_MARKER => first && (*event).data.document_start.implicit,
+            YAML_BETWEEN_DOCUMENT_START_MARKER => first,
+            _ => first && !(*emitter).canonical && (*event).data.document_start.implicit,
+        };
+        if (directives
+            || !version_directive.is_null()
+            || (*emitter).tag_directives.start != tag_directives_end)
             && (*emitter).open_ended != 0
         {
             if yaml_emitter_write_indicator(
@@ -402,7 +678,10 @@ unsafe fn yaml_emitter_emit_document_start(
             }
         }
         (*emitter).open_ended = 0;
-        if !(*event).data.document_start.version_directive.is_null() {
+        if yaml_emitter_write_banner(emitter).fail {
+            return FAIL;
+        }
+        if directives || !version_directive.is_null() {
             implicit = false;
             if yaml_emitter_write_indicator(
                 emitter,
@@ -415,7 +694,7 @@ unsafe fn yaml_emitter_emit_document_start(
             {
                 return FAIL;
             }
-            if (*(*event).data.document_start.version_directive).minor == 1 {
+            if version_directive.is_null() || (*version_directive).minor == 1 {
                 if yaml_emitter_write_indicator(
                     emitter,
                     b"1.1\0" as *const u8 as *const libc::c_char,
@@ -442,46 +721,25 @@ unsafe fn yaml_emitter_emit_document_start(
                 return FAIL;
             }
         }
-        if (*event).data.document_start.tag_directives.start
-            != (*event).data.document_start.tag_directives.end
-        {
+        tag_directive = (*emitter).tag_directives.start;
+        while tag_directive != tag_directives_end {
             implicit = false;
-            tag_directive = (*event).data.document_start.tag_directives.start;
-            while tag_directive != (*event).data.document_start.tag_directives.end {
-                if yaml_emitter_write_indicator(
-                    emitter,
-                    b"%TAG\0" as *const u8 as *const libc::c_char,
-                    true,
-                    false,
-                    false,
-                )
-                .fail
-                {
-                    return FAIL;
-                }
-                if yaml_emitter_write_tag_handle(
-                    emitter,
-                    (*tag_directive).handle,
-                    strlen((*tag_directive).handle as *mut libc::c_char),
-                )
-                .fail
-                {
-                    return FAIL;
-                }
-                if yaml_emitter_write_tag_content(
-                    emitter,
-                    (*tag_directive).prefix,
-                    strlen((*tag_directive).prefix as *mut libc::c_char),
-                    true,
-                )
-                .fail
-                {
-                    return FAIL;
-                }
-                if yaml_emitter_write_indent(emitter).fail {
-                    return FAIL;
-                }
-                tag_directive = tag_directive.wrapping_offset(1);
+            if yaml_emitter_write_tag_directive(emitter, *tag_directive).fail {
+                return FAIL;
+            }
+            if strcmp(
+                (*tag_directive).handle as *mut libc::c_char,
+                b"!!\0" as *const u8 as *const libc::c_char,
+            ) == 0
+            {
+                secondary_handle = true;
+            }
+            tag_directive = tag_directive.add(1);
+        }
+        if directives && !secondary_handle {
+            implicit = false;
+            if yaml_emitter_write_tag_directive(emitter, default_tag_directives[1]).fail {
+                return FAIL;
             }
         }
         if yaml_emitter_check_empty_document(emitter) {
@@ -537,15 +795,214 @@ unsafe fn yaml_emitter_emit_document_start(
     }
     yaml_emitter_set_emitter_error(
         emitter,
-        b"expected DOCUMENT-START or STREAM-END\0" as *const u8 as *const libc::c_char,
+        if first {
+            b"expected DOCUMENT-START or STREAM-END after STREAM-START\0" as *const u8
+                as *const libc::c_char
+        } else {
+            b"expected DOCUMENT-START or STREAM-END after DOCUMENT-END\0" as *const u8
+                as *const libc::c_char
+        },
     )
 }
 
+unsafe fn yaml_emitter_write_banner(emitter: *mut yaml_emitter_t) -> Success {
+    let index = (*emitter).document_index;
+    (*emitter).document_index = index.force_add(1);
+    let handler = match (*emitter).banner_handler {
+        Some(handler) => handler,
+        None => return OK,
+    };
+    let mut banner: *const yaml_char_t = ptr::null();
+    let mut length: size_t = 0;
+    if handler(
+        (*emitter).banner_handler_data,
+        index,
+        addr_of_mut!(banner),
+        addr_of_mut!(length),
+    ) == 0
+    {
+        return yaml_emitter_set_emitter_error(
+            emitter,
+            b"document banner handler failed\0" as *const u8 as *const libc::c_char,
+        );
+    }
+    if banner.is_null() || length == 0 {
+        return OK;
+    }
+    if yaml_check_utf8(banner, length).fail {
+        return yaml_emitter_set_emitter_error(
+            emitter,
+            b"document banner is not valid UTF-8\0" as *const u8 as *const libc::c_char,
+        );
+    }
+    if (*emitter).column != 0 && PUT_BREAK(emitter).fail {
+        return FAIL;
+    }
+    let mut string = STRING_ASSIGN!(banner as *mut yaml_char_t, length);
+    let mut line_start = true;
+    while string.pointer != string.end {
+        if line_start {
+            if PUT(emitter, b'#').fail {
+                return FAIL;
+            }
+            if !IS_BREAK!(string) && PUT(emitter, b' ').fail {
+                return FAIL;
+            }
+            line_start = false;
+        }
+        if IS_BREAK!(string) {
+            if IS_CRLF!(string) {
+                string.pointer = string.pointer.add(2);
+            } else {
+                MOVE!(string);
+            }
+            if PUT_BREAK(emitter).fail {
+                return FAIL;
+            }
+            line_start = true;
+        } else if (*emitter).wrap_comments
+            && IS_SPACE!(string)
+            && (*emitter).column > (*emitter).best_width
+            && string.pointer.add(1) != string.end
+            && !IS_BLANKZ_AT!(string, 1)
+        {
+            MOVE!(string);
+            if PUT_BREAK(emitter).fail {
+                return FAIL;
+            }
+            line_start = true;
+        } else if WRITE!(emitter, string).fail {
+            return FAIL;
+        }
+    }
+    if !line_start && PUT_BREAK(emitter).fail {
+        return FAIL;
+    }
+    (*emitter).whitespace = true;
+    (*emitter).indention = true;
+    OK
+}
+
+unsafe fn yaml_emitter_write_leading_comment(
+    emitter: *mut yaml_emitter_t,
+    comment: *mut *mut yaml_char_t,
+) -> Success {
+    let value: *mut yaml_char_t = *comment;
+    if value.is_null() {
+        return OK;
+    }
+    *comment = ptr::null_mut::<yaml_char_t>();
+    let written = yaml_emitter_write_comment(emitter, value, false);
+    yaml_free(value as *mut libc::c_void);
+    written
+}
+
+unsafe fn yaml_emitter_write_comment(
+    emitter: *mut yaml_emitter_t,
+    comment: *const yaml_char_t,
+    mut trailing: bool,
+) -> Success {
+    let length: size_t = strlen(comment as *const libc::c_char);
+    let mut string = STRING_ASSIGN!(comment as *mut yaml_char_t, length);
+    let mut line_start = true;
+    loop {
+        if line_start {
+            if !trailing && yaml_emitter_write_indent(emitter).fail {
+                return FAIL;
+            }
+            trailing = false;
+            if yaml_emitter_write_indicator(
+                emitter,
+                b"#\0" as *const u8 as *const libc::c_char,
+                true,
+                false,
+                false,
+            )
+            .fail
+            {
+                return FAIL;
+            }
+            if string.pointer != string.end && !IS_BREAK!(string) && PUT(emitter, b' ').fail {
+                return FAIL;
+            }
+            line_start = false;
+        }
+        if string.pointer == string.end {
+            break;
+        }
+        if IS_BREAK!(string) {
+            if IS_CRLF!(string) {
+                string.pointer = string.pointer.add(2);
+            } else {
+                MOVE!(string);
+            }
+            line_start = true;
+        } else if (*emitter).wrap_comments
+            && IS_SPACE!(string)
+            && (*emitter).column > (*emitter).best_width
+            && string.pointer.add(1) != string.end
+            && !IS_BLANKZ_AT!(string, 1)
+        {
+            MOVE!(string);
+            line_start = true;
+        } else if WRITE!(emitter, string).fail {
+            return FAIL;
+        }
+    }
+    (*emitter).whitespace = false;
+    (*emitter).indention = false;
+    OK
+}
+
+unsafe fn yaml_emitter_write_tag_directive(
+    emitter: *mut yaml_emitter_t,
+    tag_directive: yaml_tag_directive_t,
+) -> Success {
+    if yaml_emitter_write_indicator(
+        emitter,
+        b"%TAG\0" as *const u8 as *const libc::c_char,
+        true,
+        false,
+        false,
+    )
+    .fail
+    {
+        return FAIL;
+    }
+    if yaml_emitter_write_tag_handle(
+        emitter,
+        tag_directive.handle,
+        strlen(tag_directive.handle as *mut libc::c_char),
+    )
+    .fail
+    {
+        return FAIL;
+    }
+    if yaml_emitter_write_tag_content(
+        emitter,
+        tag_directive.prefix,
+        strlen(tag_directive.prefix as *mut libc::c_char),
+        true,
+    )
+    .fail
+    {
+        return FAIL;
+    }
+    yaml_emitter_write_indent(emitter)
+}
+
 unsafe fn yaml_emitter_emit_document_content(
     emitter: *mut yaml_emitter_t,
     event: *mut yaml_event_t,
 ) -> Success {
     PUSH!((*emitter).states, YAML_EMIT_DOCUMENT_END_STATE);
+    if !(*event).leading_comment.is_null()
+        && (yaml_emitter_write_leading_comment(emitter, addr_of_mut!((*event).leading_comment))
+            .fail
+            || yaml_emitter_write_indent(emitter).fail)
+    {
+        return FAIL;
+    }
     yaml_emitter_emit_node(emitter, event, true, false, false, false)
 }
 
@@ -554,10 +1011,19 @@ unsafe fn yaml_emitter_emit_document_end(
     event: *mut yaml_event_t,
 ) -> Success {
     if (*event).type_ == YAML_DOCUMENT_END_EVENT {
-        if yaml_emitter_write_indent(emitter).fail {
+        let implicit = match (*emitter).document_end_marker {
+            YAML_ALWAYS_DOCUMENT_END_MARKER => false,
+            YAML_NEVER_DOCUMENT_END_MARKER => true,
+            _ => (*event).data.document_end.implicit,
+        };
+        if yaml_emitter_write_leading_comment(emitter, addr_of_mut!((*event).leading_comment)).fail
+        {
             return FAIL;
         }
-        if !(*event).data.document_end.implicit {
+        if (!implicit || !(*emitter).omit_final_break) && yaml_emitter_write_indent(emitter).fail {
+            return FAIL;
+        }
+        if !implicit {
             if yaml_emitter_write_indicator(
                 emitter,
                 b"...\0" as *const u8 as *const libc::c_char,
@@ -570,7 +1036,7 @@ unsafe fn yaml_emitter_emit_document_end(
                 return FAIL;
             }
             (*emitter).open_ended = 0;
-            if yaml_emitter_write_indent(emitter).fail {
+            if !(*emitter).omit_final_break && yaml_emitter_write_indent(emitter).fail {
                 return FAIL;
             }
         } else if (*emitter).open_ended == 0 {
@@ -585,11 +1051,14 @@ unsafe fn yaml_emitter_emit_document_end(
             yaml_free(tag_directive.handle as *mut libc::c_void);
             yaml_free(tag_directive.prefix as *mut libc::c_void);
         }
+        while !STACK_EMPTY!((*emitter).emitted_anchors) {
+            yaml_free(POP!((*emitter).emitted_anchors) as *mut libc::c_void);
+        }
         return OK;
     }
     yaml_emitter_set_emitter_error(
         emitter,
-        b"expected DOCUMENT-END\0" as *const u8 as *const libc::c_char,
+        b"expected DOCUMENT-END after the document content\0" as *const u8 as *const libc::c_char,
     )
 }
 
@@ -805,6 +1274,20 @@ unsafe fn yaml_emitter_emit_flow_mapping_value(
     yaml_emitter_emit_node(emitter, event, false, false, true, false)
 }
 
+unsafe fn yaml_emitter_check_expanded(emitter: *mut yaml_emitter_t) {
+    if (*emitter).expanded && (*emitter).indention && !(*emitter).whitespace {
+        (*emitter).indention = false;
+    }
+}
+
+unsafe fn yaml_emitter_owe_indicator_spaces(emitter: *mut yaml_emitter_t) {
+    (*emitter).pending_spaces = if (*emitter).indicator_spaces > 1 {
+        (*emitter).indicator_spaces - 1
+    } else {
+        0
+    };
+}
+
 unsafe fn yaml_emitter_emit_block_sequence_item(
     emitter: *mut yaml_emitter_t,
     event: *mut yaml_event_t,
@@ -814,14 +1297,23 @@ unsafe fn yaml_emitter_emit_block_sequence_item(
         yaml_emitter_increase_indent(
             emitter,
             false,
-            (*emitter).mapping_context && !(*emitter).indention,
+            (*emitter).mapping_context && !(*emitter).indention && !(*emitter).indent_sequences,
         );
+        yaml_emitter_check_expanded(emitter);
+        if yaml_emitter_write_leading_comment(emitter, addr_of_mut!((*emitter).pending_comment))
+            .fail
+        {
+            return FAIL;
+        }
     }
     if (*event).type_ == YAML_SEQUENCE_END_EVENT {
         (*emitter).indent = POP!((*emitter).indents);
         (*emitter).state = POP!((*emitter).states);
         return OK;
     }
+    if yaml_emitter_write_leading_comment(emitter, addr_of_mut!((*event).leading_comment)).fail {
+        return FAIL;
+    }
     if yaml_emitter_write_indent(emitter).fail {
         return FAIL;
     }
@@ -836,6 +1328,7 @@ unsafe fn yaml_emitter_emit_block_sequence_item(
     {
         return FAIL;
     }
+    yaml_emitter_owe_indicator_spaces(emitter);
     PUSH!((*emitter).states, YAML_EMIT_BLOCK_SEQUENCE_ITEM_STATE);
     yaml_emitter_emit_node(emitter, event, false, true, false, false)
 }
@@ -847,12 +1340,21 @@ unsafe fn yaml_emitter_emit_block_mapping_key(
 ) -> Success {
     if first {
         yaml_emitter_increase_indent(emitter, false, false);
+        yaml_emitter_check_expanded(emitter);
+        if yaml_emitter_write_leading_comment(emitter, addr_of_mut!((*emitter).pending_comment))
+            .fail
+        {
+            return FAIL;
+        }
     }
     if (*event).type_ == YAML_MAPPING_END_EVENT {
         (*emitter).indent = POP!((*emitter).indents);
         (*emitter).state = POP!((*emitter).states);
         return OK;
     }
+    if yaml_emitter_write_leading_comment(emitter, addr_of_mut!((*event).leading_comment)).fail {
+        return FAIL;
+    }
     if yaml_emitter_write_indent(emitter).fail {
         return FAIL;
     }
@@ -874,6 +1376,7 @@ unsafe fn yaml_emitter_emit_block_mapping_key(
         {
             return FAIL;
         }
+        yaml_emitter_owe_indicator_spaces(emitter);
         PUSH!((*emitter).states, YAML_EMIT_BLOCK_MAPPING_VALUE_STATE);
         yaml_emitter_emit_node(emitter, event, false, false, true, false)
     }
@@ -912,6 +1415,7 @@ unsafe fn yaml_emitter_emit_block_mapping_value(
             return FAIL;
         }
     }
+    yaml_emitter_owe_indicator_spaces(emitter);
     PUSH!((*emitter).states, YAML_EMIT_BLOCK_MAPPING_KEY_STATE);
     yaml_emitter_emit_node(emitter, event, false, false, true, false)
 }
@@ -935,8 +1439,35 @@ unsafe fn yaml_emitter_emit_node(
         YAML_MAPPING_START_EVENT => yaml_emitter_emit_mapping_start(emitter, event),
         _ => yaml_emitter_set_emitter_error(
             emitter,
-            b"expected SCALAR, SEQUENCE-START, MAPPING-START, or ALIAS\0" as *const u8
-                as *const libc::c_char,
+            match (*emitter).state {
+                YAML_EMIT_DOCUMENT_CONTENT_STATE => {
+                    b"expected SCALAR, SEQUENCE-START, MAPPING-START, or ALIAS as the document content\0"
+                        as *const u8 as *const libc::c_char
+                }
+                YAML_EMIT_FLOW_SEQUENCE_FIRST_ITEM_STATE
+                | YAML_EMIT_FLOW_SEQUENCE_ITEM_STATE
+                | YAML_EMIT_BLOCK_SEQUENCE_FIRST_ITEM_STATE
+                | YAML_EMIT_BLOCK_SEQUENCE_ITEM_STATE => {
+                    b"expected SCALAR, SEQUENCE-START, MAPPING-START, ALIAS, or SEQUENCE-END in a sequence\0"
+                        as *const u8 as *const libc::c_char
+                }
+                YAML_EMIT_FLOW_MAPPING_FIRST_KEY_STATE
+                | YAML_EMIT_FLOW_MAPPING_KEY_STATE
+                | YAML_EMIT_BLOCK_MAPPING_FIRST_KEY_STATE
+                | YAML_EMIT_BLOCK_MAPPING_KEY_STATE => {
+                    b"expected SCALAR, SEQUENCE-START, MAPPING-START, ALIAS, or MAPPING-END for a mapping key\0"
+                        as *const u8 as *const libc::c_char
+                }
+                YAML_EMIT_FLOW_MAPPING_SIMPLE_VALUE_STATE
+                | YAML_EMIT_FLOW_MAPPING_VALUE_STATE
+                | YAML_EMIT_BLOCK_MAPPING_SIMPLE_VALUE_STATE
+                | YAML_EMIT_BLOCK_MAPPING_VALUE_STATE => {
+                    b"expected SCALAR, SEQUENCE-START, MAPPING-START, or ALIAS after a mapping key\0"
+                        as *const u8 as *const libc::c_char
+                }
+                _ => b"expected SCALAR, SEQUENCE-START, MAPPING-START, or ALIAS\0" as *const u8
+                    as *const libc::c_char,
+            },
         ),
     }
 }
@@ -970,8 +1501,29 @@ unsafe fn yaml_emitter_emit_scalar(
     if yaml_emitter_process_tag(emitter).fail {
         return FAIL;
     }
+    let source = (*event).data.scalar.source;
+    let verbatim = !source.is_null() && yaml_emitter_check_source(emitter, event, source);
     yaml_emitter_increase_indent(emitter, true, false);
-    if yaml_emitter_process_scalar(emitter).fail {
+    let line_break = (*emitter).line_break;
+    if (*emitter).scalar_break != YAML_ANY_BREAK {
+        (*emitter).line_break = (*emitter).scalar_break;
+    }
+    let processed = if verbatim {
+        yaml_emitter_write_source(emitter, source)
+    } else {
+        yaml_emitter_process_scalar(emitter)
+    };
+    (*emitter).line_break = line_break;
+    if processed.fail {
+        return FAIL;
+    }
+    if !(*event).trailing_comment.is_null()
+        && (*emitter).flow_level == 0
+        && !(*emitter).simple_key_context
+        && (*emitter).scalar_data.style != YAML_LITERAL_SCALAR_STYLE
+        && (*emitter).scalar_data.style != YAML_FOLDED_SCALAR_STYLE
+        && yaml_emitter_write_comment(emitter, (*event).trailing_comment, true).fail
+    {
         return FAIL;
     }
     (*emitter).indent = POP!((*emitter).indents);
@@ -993,10 +1545,15 @@ unsafe fn yaml_emitter_emit_sequence_start(
         || (*emitter).canonical
         || (*event).data.sequence_start.style == YAML_FLOW_SEQUENCE_STYLE
         || yaml_emitter_check_empty_sequence(emitter)
+        || yaml_emitter_wants_compact_flow(emitter)
+            && yaml_emitter_measure_compact_flow(emitter) == Some(true)
     {
         (*emitter).state = YAML_EMIT_FLOW_SEQUENCE_FIRST_ITEM_STATE;
     } else {
         (*emitter).state = YAML_EMIT_BLOCK_SEQUENCE_FIRST_ITEM_STATE;
+        yaml_free((*emitter).pending_comment as *mut libc::c_void);
+        (*emitter).pending_comment = (*event).leading_comment;
+        (*event).leading_comment = ptr::null_mut::<yaml_char_t>();
     }
     OK
 }
@@ -1015,10 +1572,15 @@ unsafe fn yaml_emitter_emit_mapping_start(
         || (*emitter).canonical
         || (*event).data.mapping_start.style == YAML_FLOW_MAPPING_STYLE
         || yaml_emitter_check_empty_mapping(emitter)
+        || yaml_emitter_wants_compact_flow(emitter)
+            && yaml_emitter_measure_compact_flow(emitter) == Some(true)
     {
         (*emitter).state = YAML_EMIT_FLOW_MAPPING_FIRST_KEY_STATE;
     } else {
         (*emitter).state = YAML_EMIT_BLOCK_MAPPING_FIRST_KEY_STATE;
+        yaml_free((*emitter).pending_comment as *mut libc::c_void);
+        (*emitter).pending_comment = (*event).leading_comment;
+        (*event).leading_comment = ptr::null_mut::<yaml_char_t>();
     }
     OK
 }
@@ -1102,6 +1664,7 @@ unsafe fn yaml_emitter_select_scalar_style(
     if style == YAML_ANY_SCALAR_STYLE {
         style = YAML_PLAIN_SCALAR_STYLE;
     }
+    let plain_requested = style == YAML_PLAIN_SCALAR_STYLE && !(*emitter).canonical;
     if (*emitter).canonical {
         style = YAML_DOUBLE_QUOTED_SCALAR_STYLE;
     }
@@ -1109,18 +1672,23 @@ unsafe fn yaml_emitter_select_scalar_style(
         style = YAML_DOUBLE_QUOTED_SCALAR_STYLE;
     }
     if style == YAML_PLAIN_SCALAR_STYLE {
+        let quote_style = if (*emitter).quote_style == YAML_DOUBLE_QUOTED_SCALAR_STYLE {
+            YAML_DOUBLE_QUOTED_SCALAR_STYLE
+        } else {
+            YAML_SINGLE_QUOTED_SCALAR_STYLE
+        };
         if (*emitter).flow_level != 0 && !(*emitter).scalar_data.flow_plain_allowed
             || (*emitter).flow_level == 0 && !(*emitter).scalar_data.block_plain_allowed
         {
-            style = YAML_SINGLE_QUOTED_SCALAR_STYLE;
+            style = quote_style;
         }
         if (*emitter).scalar_data.length == 0
             && ((*emitter).flow_level != 0 || (*emitter).simple_key_context)
         {
-            style = YAML_SINGLE_QUOTED_SCALAR_STYLE;
+            style = quote_style;
         }
         if no_tag && !(*event).data.scalar.plain_implicit {
-            style = YAML_SINGLE_QUOTED_SCALAR_STYLE;
+            style = quote_style;
         }
     }
     if style == YAML_SINGLE_QUOTED_SCALAR_STYLE {
@@ -1129,10 +1697,12 @@ unsafe fn yaml_emitter_select_scalar_style(
         }
     }
     if style == YAML_LITERAL_SCALAR_STYLE || style == YAML_FOLDED_SCALAR_STYLE {
-        if !(*emitter).scalar_data.block_allowed
-            || (*emitter).flow_level != 0
-            || (*emitter).simple_key_context
-        {
+        let block_allowed = if (*emitter).strict_styles && style == YAML_LITERAL_SCALAR_STYLE {
+            (*emitter).scalar_data.literal_allowed
+        } else {
+            (*emitter).scalar_data.block_allowed
+        };
+        if !block_allowed || (*emitter).flow_level != 0 || (*emitter).simple_key_context {
             style = YAML_DOUBLE_QUOTED_SCALAR_STYLE;
         }
     }
@@ -1141,6 +1711,9 @@ unsafe fn yaml_emitter_select_scalar_style(
         *fresh46 = b"!\0" as *const u8 as *const libc::c_char as *mut yaml_char_t;
         (*emitter).tag_data.handle_length = 1_u64;
     }
+    if plain_requested && style != YAML_PLAIN_SCALAR_STYLE {
+        (*emitter).quoted_scalars = (*emitter).quoted_scalars.force_add(1);
+    }
     (*emitter).scalar_data.style = style;
     OK
 }
@@ -1234,6 +1807,88 @@ unsafe fn yaml_emitter_process_tag(emitter: *mut yaml_emitter_t) -> Success {
     OK
 }
 
+unsafe fn yaml_emitter_check_source(
+    emitter: *mut yaml_emitter_t,
+    event: *mut yaml_event_t,
+    source: *mut yaml_scalar_source_t,
+) -> bool {
+    if (*emitter).canonical
+        || (*source).style != (*emitter).scalar_data.style
+        || (*source).value_length != (*event).data.scalar.length
+        || slice::from_raw_parts((*source).value, (*source).value_length as usize)
+            != slice::from_raw_parts(
+                (*event).data.scalar.value,
+                (*event).data.scalar.length as usize,
+            )
+    {
+        return false;
+    }
+    let text = slice::from_raw_parts((*source).text, (*source).length as usize);
+    let mut lines = text.split(|ch| matches!(ch, b'\r' | b'\n'));
+    let header = lines.next().unwrap_or_default();
+    if (*emitter).simple_key_context {
+        return text.len() <= 1024 && header.len() == text.len();
+    }
+    if header.iter().any(u8::is_ascii_digit)
+        && ((*source).style == YAML_LITERAL_SCALAR_STYLE
+            || (*source).style == YAML_FOLDED_SCALAR_STYLE)
+    {
+        return false;
+    }
+    // The lines after the first one must be indented further than the
+    // collection holding the scalar.
+    let indent = ((*emitter).indent + 1) as usize;
+    lines.all(|line| {
+        let spaces = line.iter().take_while(|ch| **ch == b' ').count();
+        spaces >= indent || line[spaces..].iter().all(|ch| matches!(ch, b' ' | b'\t'))
+    })
+}
+
+unsafe fn yaml_emitter_write_source(
+    emitter: *mut yaml_emitter_t,
+    source: *mut yaml_scalar_source_t,
+) -> Success {
+    let mut string = STRING_ASSIGN!((*source).text, (*source).length);
+    if !(*emitter).whitespace
+        && string.start != string.end
+        && yaml_emitter_write_separator(emitter).fail
+    {
+        return FAIL;
+    }
+    let mut breaks = false;
+    while string.pointer != string.end {
+        if IS_BREAK!(string) {
+            if string.pointer.add(1) != string.end && IS_CRLF!(string) {
+                string.pointer = string.pointer.add(1);
+            }
+            if WRITE_BREAK!(emitter, string).fail {
+                return FAIL;
+            }
+            breaks = true;
+        } else {
+            if WRITE!(emitter, string).fail {
+                return FAIL;
+            }
+            breaks = false;
+        }
+    }
+    (*emitter).whitespace = breaks;
+    (*emitter).indention = breaks;
+    if (*source).style == YAML_LITERAL_SCALAR_STYLE || (*source).style == YAML_FOLDED_SCALAR_STYLE {
+        let text = slice::from_raw_parts((*source).text, (*source).length as usize);
+        (*emitter).open_ended = if text
+            .iter()
+            .take_while(|ch| !matches!(ch, b'\r' | b'\n'))
+            .any(|ch| *ch == b'+')
+        {
+            2
+        } else {
+            0
+        };
+    }
+    OK
+}
+
 unsafe fn yaml_emitter_process_scalar(emitter: *mut yaml_emitter_t) -> Success {
     match (*emitter).scalar_data.style {
         YAML_PLAIN_SCALAR_STYLE => {
@@ -1241,7 +1896,7 @@ unsafe fn yaml_emitter_process_scalar(emitter: *mut yaml_emitter_t) -> Success {
                 emitter,
                 (*emitter).scalar_data.value,
                 (*emitter).scalar_data.length,
-                !(*emitter).simple_key_context,
+                !(*emitter).simple_key_context && !(*emitter).scalar_data.unfolded,
             );
         }
         YAML_SINGLE_QUOTED_SCALAR_STYLE => {
@@ -1249,7 +1904,7 @@ unsafe fn yaml_emitter_process_scalar(emitter: *mut yaml_emitter_t) -> Success {
                 emitter,
                 (*emitter).scalar_data.value,
                 (*emitter).scalar_data.length,
-                !(*emitter).simple_key_context,
+                !(*emitter).simple_key_context && !(*emitter).scalar_data.unfolded,
             );
         }
         YAML_DOUBLE_QUOTED_SCALAR_STYLE => {
@@ -1257,7 +1912,7 @@ unsafe fn yaml_emitter_process_scalar(emitter: *mut yaml_emitter_t) -> Success {
                 emitter,
                 (*emitter).scalar_data.value,
                 (*emitter).scalar_data.length,
-                !(*emitter).simple_key_context,
+                !(*emitter).simple_key_context && !(*emitter).scalar_data.unfolded,
             );
         }
         YAML_LITERAL_SCALAR_STYLE => {
@@ -1371,6 +2026,29 @@ unsafe fn yaml_emitter_analyze_anchor(
         }
         MOVE!(string);
     }
+    if (*emitter).check_aliases {
+        if alias {
+            let mut emitted_anchor = (*emitter).emitted_anchors.start;
+            while emitted_anchor != (*emitter).emitted_anchors.top {
+                if strcmp(
+                    *emitted_anchor as *mut libc::c_char,
+                    anchor as *mut libc::c_char,
+                ) == 0
+                {
+                    break;
+                }
+                emitted_anchor = emitted_anchor.add(1);
+            }
+            if emitted_anchor == (*emitter).emitted_anchors.top {
+                return yaml_emitter_set_emitter_error(
+                    emitter,
+                    b"found undefined alias\0" as *const u8 as *const libc::c_char,
+                );
+            }
+        } else {
+            PUSH!((*emitter).emitted_anchors, yaml_strdup(anchor));
+        }
+    }
     let fresh47 = addr_of_mut!((*emitter).anchor_data.anchor);
     *fresh47 = string.start;
     (*emitter).anchor_data.anchor_length = string.end.c_offset_from(string.start) as size_t;
@@ -1380,6 +2058,8 @@ unsafe fn yaml_emitter_analyze_anchor(
 
 unsafe fn yaml_emitter_analyze_tag(emitter: *mut yaml_emitter_t, tag: *mut yaml_char_t) -> Success {
     let mut tag_directive: *mut yaml_tag_directive_t;
+    let mut best_directive: *mut yaml_tag_directive_t = ptr::null_mut();
+    let mut best_length: size_t = 0_u64;
     let tag_length: size_t = strlen(tag as *mut libc::c_char);
     let string = STRING_ASSIGN!(tag, tag_length);
     if string.start == string.end {
@@ -1389,28 +2069,31 @@ unsafe fn yaml_emitter_analyze_tag(emitter: *mut yaml_emitter_t, tag: *mut yaml_
         );
     }
     tag_directive = (*emitter).tag_directives.start;
-    while tag_directive != (*emitter).tag_directives.top {
+    while !(*emitter).verbatim_tags && tag_directive != (*emitter).tag_directives.top {
         let prefix_length: size_t = strlen((*tag_directive).prefix as *mut libc::c_char);
-        if prefix_length < string.end.c_offset_from(string.start) as size_t
+        if prefix_length > best_length
+            && prefix_length < string.end.c_offset_from(string.start) as size_t
             && strncmp(
                 (*tag_directive).prefix as *mut libc::c_char,
                 string.start as *mut libc::c_char,
                 prefix_length,
             ) == 0
         {
-            let fresh48 = addr_of_mut!((*emitter).tag_data.handle);
-            *fresh48 = (*tag_directive).handle;
-            (*emitter).tag_data.handle_length =
-                strlen((*tag_directive).handle as *mut libc::c_char);
-            let fresh49 = addr_of_mut!((*emitter).tag_data.suffix);
-            *fresh49 = string.start.wrapping_offset(prefix_length as isize);
-            (*emitter).tag_data.suffix_length = (string.end.c_offset_from(string.start)
-                as libc::c_ulong)
-                .wrapping_sub(prefix_length);
-            return OK;
+            best_directive = tag_directive;
+            best_length = prefix_length;
         }
         tag_directive = tag_directive.wrapping_offset(1);
     }
+    if !best_directive.is_null() {
+        let fresh48 = addr_of_mut!((*emitter).tag_data.handle);
+        *fresh48 = (*best_directive).handle;
+        (*emitter).tag_data.handle_length = strlen((*best_directive).handle as *mut libc::c_char);
+        let fresh49 = addr_of_mut!((*emitter).tag_data.suffix);
+        *fresh49 = string.start.wrapping_offset(best_length as isize);
+        (*emitter).tag_data.suffix_length =
+            (string.end.c_offset_from(string.start) as libc::c_ulong).wrapping_sub(best_length);
+        return OK;
+    }
     let fresh50 = addr_of_mut!((*emitter).tag_data.suffix);
     *fresh50 = string.start;
     (*emitter).tag_data.suffix_length = string.end.c_offset_from(string.start) as size_t;
@@ -1446,6 +2129,7 @@ unsafe fn yaml_emitter_analyze_scalar(
         (*emitter).scalar_data.block_plain_allowed = true;
         (*emitter).scalar_data.single_quoted_allowed = true;
         (*emitter).scalar_data.block_allowed = false;
+        (*emitter).scalar_data.literal_allowed = false;
         return OK;
     }
     if CHECK_AT!(string, b'-', 0) && CHECK_AT!(string, b'-', 1) && CHECK_AT!(string, b'-', 2)
@@ -1509,7 +2193,7 @@ unsafe fn yaml_emitter_analyze_scalar(
                 block_indicators = true;
             }
         }
-        if !IS_PRINTABLE!(string) || !IS_ASCII!(string) && !(*emitter).unicode {
+        if !IS_PRINTABLE!(string) || !IS_ASCII!(string) && !(*emitter).scalar_data.unicode {
             special_characters = true;
         }
         if IS_BREAK!(string) {
@@ -1554,6 +2238,7 @@ unsafe fn yaml_emitter_analyze_scalar(
     (*emitter).scalar_data.block_plain_allowed = true;
     (*emitter).scalar_data.single_quoted_allowed = true;
     (*emitter).scalar_data.block_allowed = true;
+    (*emitter).scalar_data.literal_allowed = !special_characters;
     if leading_space || leading_break || trailing_space || trailing_break {
         (*emitter).scalar_data.flow_plain_allowed = false;
         (*emitter).scalar_data.block_plain_allowed = false;
@@ -1585,10 +2270,89 @@ unsafe fn yaml_emitter_analyze_scalar(
     OK
 }
 
+unsafe fn yaml_emitter_apply_scalar_handler(
+    emitter: *mut yaml_emitter_t,
+    event: *mut yaml_event_t,
+    handler: yaml_scalar_handler_t,
+) -> Success {
+    let key_context = matches!(
+        (*emitter).state,
+        YAML_EMIT_FLOW_MAPPING_FIRST_KEY_STATE
+            | YAML_EMIT_FLOW_MAPPING_KEY_STATE
+            | YAML_EMIT_BLOCK_MAPPING_FIRST_KEY_STATE
+            | YAML_EMIT_BLOCK_MAPPING_KEY_STATE
+    );
+    let value_context = matches!(
+        (*emitter).state,
+        YAML_EMIT_FLOW_MAPPING_SIMPLE_VALUE_STATE
+            | YAML_EMIT_FLOW_MAPPING_VALUE_STATE
+            | YAML_EMIT_BLOCK_MAPPING_SIMPLE_VALUE_STATE
+            | YAML_EMIT_BLOCK_MAPPING_VALUE_STATE
+    );
+    let key = (*emitter).scalar_key;
+    let key_length = (*emitter).scalar_key_length;
+    let fresh301 = addr_of_mut!((*emitter).scalar_key);
+    *fresh301 = ptr::null_mut::<yaml_char_t>();
+    (*emitter).scalar_key_length = 0;
+    if (*event).type_ == YAML_SCALAR_EVENT {
+        if key_context {
+            let fresh302 = addr_of_mut!((*emitter).scalar_key);
+            *fresh302 = yaml_malloc((*event).data.scalar.length.force_add(1)) as *mut yaml_char_t;
+            memcpy(
+                (*emitter).scalar_key as *mut libc::c_void,
+                (*event).data.scalar.value as *const libc::c_void,
+                (*event).data.scalar.length,
+            );
+            *(*emitter)
+                .scalar_key
+                .add((*event).data.scalar.length as usize) = b'\0';
+            (*emitter).scalar_key_length = (*event).data.scalar.length;
+        }
+        let mut value = (*event).data.scalar.value as *const yaml_char_t;
+        let mut length = (*event).data.scalar.length;
+        let result = handler(
+            (*emitter).scalar_handler_data,
+            addr_of_mut!(value),
+            addr_of_mut!(length),
+            (*event).data.scalar.tag,
+            if value_context { key } else { ptr::null() },
+            if value_context { key_length } else { 0 },
+        );
+        yaml_free(key as *mut libc::c_void);
+        if result == 0 {
+            return yaml_emitter_set_emitter_error(
+                emitter,
+                b"scalar handler failed\0" as *const u8 as *const libc::c_char,
+            );
+        }
+        if !ptr::eq(value, (*event).data.scalar.value) || length != (*event).data.scalar.length {
+            let copy = yaml_malloc(length.force_add(1)) as *mut yaml_char_t;
+            memcpy(
+                copy as *mut libc::c_void,
+                value as *const libc::c_void,
+                length,
+            );
+            *copy.add(length as usize) = b'\0';
+            yaml_free((*event).data.scalar.value as *mut libc::c_void);
+            let fresh303 = addr_of_mut!((*event).data.scalar.value);
+            *fresh303 = copy;
+            (*event).data.scalar.length = length;
+        }
+        return OK;
+    }
+    yaml_free(key as *mut libc::c_void);
+    OK
+}
+
 unsafe fn yaml_emitter_analyze_event(
     emitter: *mut yaml_emitter_t,
     event: *mut yaml_event_t,
 ) -> Success {
+    if let Some(handler) = (*emitter).scalar_handler {
+        if yaml_emitter_apply_scalar_handler(emitter, event, handler).fail {
+            return FAIL;
+        }
+    }
     let fresh52 = addr_of_mut!((*emitter).anchor_data.anchor);
     *fresh52 = ptr::null_mut::<yaml_char_t>();
     (*emitter).anchor_data.anchor_length = 0_u64;
@@ -1611,6 +2375,7 @@ unsafe fn yaml_emitter_analyze_event(
             }
             if !(*event).data.scalar.tag.is_null()
                 && ((*emitter).canonical
+                    || (*emitter).always_emit_tags
                     || !(*event).data.scalar.plain_implicit
                         && !(*event).data.scalar.quoted_implicit)
             {
@@ -1618,6 +2383,21 @@ unsafe fn yaml_emitter_analyze_event(
                     return FAIL;
                 }
             }
+            (*emitter).scalar_data.unfolded = (*event).data.scalar.unfolded;
+            (*emitter).scalar_data.chomping = (*event).data.scalar.chomping;
+            (*emitter).scalar_data.indentation_indicator =
+                (*event).data.scalar.indentation_indicator;
+            (*emitter).scalar_data.unicode = match (*emitter).unicode_handler {
+                Some(handler) => {
+                    handler(
+                        (*emitter).unicode_handler_data,
+                        (*event).data.scalar.value,
+                        (*event).data.scalar.length,
+                        (*event).data.scalar.tag,
+                    ) != 0
+                }
+                None => (*emitter).unicode,
+            };
             yaml_emitter_analyze_scalar(
                 emitter,
                 (*event).data.scalar.value,
@@ -1633,7 +2413,9 @@ unsafe fn yaml_emitter_analyze_event(
                 }
             }
             if !(*event).data.sequence_start.tag.is_null()
-                && ((*emitter).canonical || !(*event).data.sequence_start.implicit)
+                && ((*emitter).canonical
+                    || (*emitter).always_emit_tags
+                    || !(*event).data.sequence_start.implicit)
             {
                 if yaml_emitter_analyze_tag(emitter, (*event).data.sequence_start.tag).fail {
                     return FAIL;
@@ -1650,7 +2432,9 @@ unsafe fn yaml_emitter_analyze_event(
                 }
             }
             if !(*event).data.mapping_start.tag.is_null()
-                && ((*emitter).canonical || !(*event).data.mapping_start.implicit)
+                && ((*emitter).canonical
+                    || (*emitter).always_emit_tags
+                    || !(*event).data.mapping_start.implicit)
             {
                 if yaml_emitter_analyze_tag(emitter, (*event).data.mapping_start.tag).fail {
                     return FAIL;
@@ -1702,6 +2486,20 @@ unsafe fn yaml_emitter_write_indent(emitter: *mut yaml_emitter_t) -> Success {
     }
     (*emitter).whitespace = true;
     (*emitter).indention = true;
+    (*emitter).pending_spaces = 0;
+    OK
+}
+
+unsafe fn yaml_emitter_write_separator(emitter: *mut yaml_emitter_t) -> Success {
+    if PUT(emitter, b' ').fail {
+        return FAIL;
+    }
+    while (*emitter).pending_spaces > 0 {
+        if PUT(emitter, b' ').fail {
+            return FAIL;
+        }
+        (*emitter).pending_spaces -= 1;
+    }
     OK
 }
 
@@ -1715,7 +2513,7 @@ unsafe fn yaml_emitter_write_indicator(
     let indicator_length: size_t = strlen(indicator);
     let mut string = STRING_ASSIGN!(indicator as *mut yaml_char_t, indicator_length);
     if need_whitespace && !(*emitter).whitespace {
-        if PUT(emitter, b' ').fail {
+        if yaml_emitter_write_separator(emitter).fail {
             return FAIL;
         }
     }
@@ -1752,7 +2550,7 @@ unsafe fn yaml_emitter_write_tag_handle(
 ) -> Success {
     let mut string = STRING_ASSIGN!(value, length);
     if !(*emitter).whitespace {
-        if PUT(emitter, b' ').fail {
+        if yaml_emitter_write_separator(emitter).fail {
             return FAIL;
         }
     }
@@ -1774,7 +2572,7 @@ unsafe fn yaml_emitter_write_tag_content(
 ) -> Success {
     let mut string = STRING_ASSIGN!(value, length);
     if need_whitespace && !(*emitter).whitespace {
-        if PUT(emitter, b' ').fail {
+        if yaml_emitter_write_separator(emitter).fail {
             return FAIL;
         }
     }
@@ -1841,6 +2639,55 @@ unsafe fn yaml_emitter_write_tag_content(
     OK
 }
 
+unsafe fn yaml_emitter_count_characters(value: *const yaml_char_t, length: size_t) -> libc::c_int {
+    let bytes = slice::from_raw_parts(value, length as usize);
+    bytes.iter().filter(|&&octet| octet & 0xC0 != 0x80).count() as libc::c_int
+}
+
+unsafe fn yaml_emitter_write_run(
+    emitter: *mut yaml_emitter_t,
+    value: *const yaml_char_t,
+    length: size_t,
+    width: libc::c_int,
+) -> Success {
+    let mut pointer = value;
+    let end = value.add(length as usize);
+    while pointer < end {
+        if FLUSH(emitter).fail {
+            return FAIL;
+        }
+        let room = (*emitter)
+            .buffer
+            .end
+            .c_offset_from((*emitter).buffer.pointer) as usize;
+        let mut size = room.min(end.c_offset_from(pointer) as usize);
+        while pointer.add(size) < end && *pointer.add(size) & 0xC0 == 0x80 {
+            size -= 1;
+        }
+        ptr::copy_nonoverlapping(pointer, (*emitter).buffer.pointer, size);
+        (*emitter).buffer.pointer = (*emitter).buffer.pointer.add(size);
+        pointer = pointer.add(size);
+    }
+    (*emitter).column = (*emitter).column.force_add(width);
+    OK
+}
+
+unsafe fn yaml_emitter_check_wrap(emitter: *mut yaml_emitter_t, mut string: yaml_string_t) -> bool {
+    match (*emitter).wrap_policy {
+        YAML_WRAP_AT_WHITESPACE => (*emitter).column > (*emitter).best_width,
+        YAML_WRAP_AT_WIDTH => {
+            let mut column = (*emitter).column.force_add(1);
+            MOVE!(string);
+            while string.pointer != string.end && !IS_BLANK!(string) && !IS_BREAK!(string) {
+                column = column.force_add(1);
+                MOVE!(string);
+            }
+            column > (*emitter).best_width
+        }
+        YAML_NEVER_WRAP => false,
+    }
+}
+
 unsafe fn yaml_emitter_write_plain_scalar(
     emitter: *mut yaml_emitter_t,
     value: *mut yaml_char_t,
@@ -1851,15 +2698,26 @@ unsafe fn yaml_emitter_write_plain_scalar(
     let mut breaks = false;
     let mut string = STRING_ASSIGN!(value, length);
     if !(*emitter).whitespace && (length != 0 || (*emitter).flow_level != 0) {
-        if PUT(emitter, b' ').fail {
+        if yaml_emitter_write_separator(emitter).fail {
             return FAIL;
         }
     }
+    if !(*emitter).scalar_data.multiline {
+        let width = yaml_emitter_count_characters(value, length);
+        if !allow_breaks || (*emitter).column.force_add(width) <= (*emitter).best_width {
+            if yaml_emitter_write_run(emitter, value, length, width).fail {
+                return FAIL;
+            }
+            (*emitter).whitespace = false;
+            (*emitter).indention = false;
+            return OK;
+        }
+    }
     while string.pointer != string.end {
         if IS_SPACE!(string) {
             if allow_breaks
                 && !spaces
-                && (*emitter).column > (*emitter).best_width
+                && yaml_emitter_check_wrap(emitter, string)
                 && !IS_SPACE_AT!(string, 1)
             {
                 if yaml_emitter_write_indent(emitter).fail {
@@ -1876,7 +2734,7 @@ unsafe fn yaml_emitter_write_plain_scalar(
                     return FAIL;
                 }
             }
-            if WRITE_BREAK!(emitter, string).fail {
+            if yaml_emitter_write_scalar_break(emitter, addr_of_mut!(string)).fail {
                 return FAIL;
             }
             (*emitter).indention = true;
@@ -1924,7 +2782,7 @@ unsafe fn yaml_emitter_write_single_quoted_scalar(
         if IS_SPACE!(string) {
             if allow_breaks
                 && !spaces
-                && (*emitter).column > (*emitter).best_width
+                && yaml_emitter_check_wrap(emitter, string)
                 && string.pointer != string.start
                 && string.pointer != string.end.wrapping_offset(-1_isize)
                 && !IS_SPACE_AT!(string, 1)
@@ -1943,7 +2801,7 @@ unsafe fn yaml_emitter_write_single_quoted_scalar(
                     return FAIL;
                 }
             }
-            if WRITE_BREAK!(emitter, string).fail {
+            if yaml_emitter_write_scalar_break(emitter, addr_of_mut!(string)).fail {
                 return FAIL;
             }
             (*emitter).indention = true;
@@ -1988,12 +2846,28 @@ unsafe fn yaml_emitter_write_single_quoted_scalar(
     OK
 }
 
+unsafe fn yaml_emitter_write_escaped_break(
+    emitter: *mut yaml_emitter_t,
+    width: libc::c_int,
+) -> Success {
+    if (*emitter).column <= (*emitter).indent
+        || (*emitter).column.force_add(width).force_add(1) <= (*emitter).best_width
+    {
+        return OK;
+    }
+    if PUT(emitter, b'\\').fail {
+        return FAIL;
+    }
+    yaml_emitter_write_indent(emitter)
+}
+
 unsafe fn yaml_emitter_write_double_quoted_scalar(
     emitter: *mut yaml_emitter_t,
     value: *mut yaml_char_t,
     length: size_t,
     allow_breaks: bool,
 ) -> Success {
+    let strict = allow_breaks && (*emitter).strict_width;
     let mut spaces = false;
     let mut string = STRING_ASSIGN!(value, length);
     if yaml_emitter_write_indicator(
@@ -2009,7 +2883,7 @@ unsafe fn yaml_emitter_write_double_quoted_scalar(
     }
     while string.pointer != string.end {
         if !IS_PRINTABLE!(string)
-            || !(*emitter).unicode && !IS_ASCII!(string)
+            || !(*emitter).scalar_data.unicode && !IS_ASCII!(string)
             || IS_BOM!(string)
             || IS_BREAK!(string)
             || CHECK!(string, b'"')
@@ -2049,6 +2923,18 @@ unsafe fn yaml_emitter_write_double_quoted_scalar(
                 k += 1;
             }
             string.pointer = string.pointer.wrapping_offset(width as isize);
+            if strict {
+                let escape_width = match value_0 {
+                    0x00 | 0x07 | 0x08 | 0x09 | 0x0A | 0x0B | 0x0C | 0x0D | 0x1B | 0x22 | 0x5C
+                    | 0x85 | 0xA0 | 0x2028 | 0x2029 => 2,
+                    _ if value_0 <= 0xFF => 4,
+                    _ if value_0 <= 0xFFFF => 6,
+                    _ => 10,
+                };
+                if yaml_emitter_write_escaped_break(emitter, escape_width).fail {
+                    return FAIL;
+                }
+            }
             if PUT(emitter, b'\\').fail {
                 return FAIL;
             }
@@ -2162,7 +3048,20 @@ unsafe fn yaml_emitter_write_double_quoted_scalar(
             }
             spaces = false;
         } else if IS_SPACE!(string) {
-            if allow_breaks
+            if strict {
+                let column = (*emitter).column;
+                if yaml_emitter_write_escaped_break(emitter, 1).fail {
+                    return FAIL;
+                }
+                if (*emitter).column < column {
+                    if PUT(emitter, b'\\').fail {
+                        return FAIL;
+                    }
+                }
+                if WRITE!(emitter, string).fail {
+                    return FAIL;
+                }
+            } else if allow_breaks
                 && !spaces
                 && (*emitter).column > (*emitter).best_width
                 && string.pointer != string.start
@@ -2182,6 +3081,9 @@ unsafe fn yaml_emitter_write_double_quoted_scalar(
             }
             spaces = true;
         } else {
+            if strict && yaml_emitter_write_escaped_break(emitter, 1).fail {
+                return FAIL;
+            }
             if WRITE!(emitter, string).fail {
                 return FAIL;
             }
@@ -2210,7 +3112,7 @@ unsafe fn yaml_emitter_write_block_scalar_hints(
 ) -> Success {
     let mut indent_hint: [libc::c_char; 2] = [0; 2];
     let mut chomp_hint: *const libc::c_char = ptr::null::<libc::c_char>();
-    if IS_SPACE!(string) || IS_BREAK!(string) {
+    if (*emitter).scalar_data.indentation_indicator || IS_SPACE!(string) || IS_BREAK!(string) {
         indent_hint[0] = (b'0' as libc::c_int + (*emitter).best_indent) as libc::c_char;
         indent_hint[1] = '\0' as libc::c_char;
         if yaml_emitter_write_indicator(emitter, indent_hint.as_mut_ptr(), false, false, false).fail
@@ -2241,7 +3143,7 @@ unsafe fn yaml_emitter_write_block_scalar_hints(
                     break;
                 }
             }
-            if IS_BREAK!(string) {
+            if IS_BREAK!(string) || (*emitter).scalar_data.chomping == YAML_KEEP_CHOMPING {
                 chomp_hint = b"+\0" as *const u8 as *const libc::c_char;
                 (*emitter).open_ended = 2;
             }
@@ -2283,7 +3185,7 @@ unsafe fn yaml_emitter_write_literal_scalar(
     (*emitter).whitespace = true;
     while string.pointer != string.end {
         if IS_BREAK!(string) {
-            if WRITE_BREAK!(emitter, string).fail {
+            if yaml_emitter_write_scalar_break(emitter, addr_of_mut!(string)).fail {
                 return FAIL;
             }
             (*emitter).indention = true;
@@ -2344,7 +3246,7 @@ unsafe fn yaml_emitter_write_folded_scalar(
                     }
                 }
             }
-            if WRITE_BREAK!(emitter, string).fail {
+            if yaml_emitter_write_scalar_break(emitter, addr_of_mut!(string)).fail {
                 return FAIL;
             }
             (*emitter).indention = true;
@@ -2357,6 +3259,7 @@ unsafe fn yaml_emitter_write_folded_scalar(
                 leading_spaces = IS_BLANK!(string);
             }
             if !breaks
+                && !(*emitter).scalar_data.unfolded
                 && IS_SPACE!(string)
                 && !IS_SPACE_AT!(string, 1)
                 && (*emitter).column > (*emitter).best_width
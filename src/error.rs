@@ -0,0 +1,433 @@
+use crate::externs::strlen;
+use crate::{libc, yaml_emitter_t, yaml_error_type_t, yaml_mark_t, yaml_parser_t};
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Display, Write as _};
+use core::slice;
+use serde::{de, ser};
+
+/// An error that occurred while serializing or deserializing YAML.
+pub struct Error {
+    kind: ErrorKind,
+    message: String,
+    mark: Option<yaml_mark_t>,
+    context: Option<(String, yaml_mark_t)>,
+    source: Option<Box<OutputError>>,
+}
+
+/// The class of failure behind an [`Error`].
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// Memory could not be allocated.
+    Memory,
+    /// The input could not be read or decoded.
+    Reader,
+    /// The input could not be split into tokens.
+    Scanner,
+    /// The tokens do not form a valid YAML stream.
+    Parser,
+    /// The events could not be composed into nodes, as for an undefined alias.
+    Composer,
+    /// The output could not be written.
+    Writer,
+    /// The value could not be emitted as YAML.
+    Emitter,
+    /// The input is nested or aliased beyond what is allowed.
+    Limit,
+    /// The YAML does not fit the type being deserialized, or the value being
+    /// serialized was rejected.
+    Data,
+    /// The output could not accept the bytes right now and nothing was
+    /// written, so writing them again later may succeed.
+    WouldBlock,
+}
+
+impl ErrorKind {
+    fn from_error_type(error: yaml_error_type_t) -> Self {
+        match error {
+            yaml_error_type_t::YAML_READER_ERROR => ErrorKind::Reader,
+            yaml_error_type_t::YAML_SCANNER_ERROR => ErrorKind::Scanner,
+            yaml_error_type_t::YAML_PARSER_ERROR => ErrorKind::Parser,
+            yaml_error_type_t::YAML_COMPOSER_ERROR => ErrorKind::Composer,
+            yaml_error_type_t::YAML_WRITER_ERROR => ErrorKind::Writer,
+            yaml_error_type_t::YAML_EMITTER_ERROR => ErrorKind::Emitter,
+            yaml_error_type_t::YAML_NO_ERROR | yaml_error_type_t::YAML_MEMORY_ERROR => {
+                ErrorKind::Memory
+            }
+        }
+    }
+}
+
+impl Error {
+    pub(crate) fn new(kind: ErrorKind, message: &str, mark: Option<yaml_mark_t>) -> Self {
+        Error {
+            kind,
+            message: message.to_string(),
+            mark,
+            context: None,
+            source: None,
+        }
+    }
+
+    pub(crate) unsafe fn from_parser(parser: *mut yaml_parser_t) -> Self {
+        let message = yaml_error_cstr((*parser).problem);
+        let context = if (*parser).context.is_null() {
+            None
+        } else {
+            let context = yaml_error_cstr((*parser).context);
+            Some((
+                String::from_utf8_lossy(context).into_owned(),
+                (*parser).context_mark,
+            ))
+        };
+        Error {
+            kind: ErrorKind::from_error_type((*parser).error),
+            message: String::from_utf8_lossy(message).into_owned(),
+            mark: Some((*parser).problem_mark),
+            context,
+            source: None,
+        }
+    }
+
+    pub(crate) unsafe fn from_emitter(emitter: *mut yaml_emitter_t) -> Self {
+        let message = if (*emitter).problem.is_null() {
+            &b"out of memory"[..]
+        } else {
+            yaml_error_cstr((*emitter).problem)
+        };
+        Error {
+            kind: ErrorKind::from_error_type((*emitter).error),
+            message: String::from_utf8_lossy(message).into_owned(),
+            mark: None,
+            context: None,
+            source: None,
+        }
+    }
+
+    pub(crate) fn from_output(error: OutputError) -> Self {
+        Error {
+            kind: if error.would_block {
+                ErrorKind::WouldBlock
+            } else {
+                ErrorKind::Writer
+            },
+            message: error.to_string(),
+            mark: None,
+            context: None,
+            source: Some(Box::new(error)),
+        }
+    }
+
+    pub(crate) fn with_mark(mut self, mark: yaml_mark_t) -> Self {
+        if self.mark.is_none() {
+            self.mark = Some(mark);
+        }
+        self
+    }
+
+    /// The class of failure, for programs that need to tell errors apart.
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// The position in the input where the error occurred, if known.
+    pub fn mark(&self) -> Option<yaml_mark_t> {
+        self.mark
+    }
+
+    /// Show the error with the lines of the input it refers to.
+    ///
+    /// The message is followed by the line of the context, if any, and the line
+    /// of the problem, each with a `^` under the column of its mark, as a
+    /// compiler shows its diagnostics. `source` must be the input the error was
+    /// found in.
+    pub fn display_with_source<'a>(&'a self, source: &'a str) -> impl Display + 'a {
+        ErrorWithSource {
+            error: self,
+            source,
+        }
+    }
+}
+
+struct ErrorWithSource<'a> {
+    error: &'a Error,
+    source: &'a str,
+}
+
+impl Display for ErrorWithSource<'_> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        Display::fmt(self.error, formatter)?;
+        let mut snippets = Vec::new();
+        if let Some((context, mark)) = &self.error.context {
+            snippets.push((context.as_str(), *mark));
+        }
+        if let Some(mark) = self.error.mark {
+            snippets.push((self.error.message.as_str(), mark));
+        }
+        let width = snippets
+            .iter()
+            .map(|(_, mark)| (mark.line + 1).to_string().len())
+            .max()
+            .unwrap_or(0);
+        for (label, mark) in snippets {
+            let line = yaml_error_source_line(self.source, mark.line);
+            write!(formatter, "\n{:width$} |", "", width = width)?;
+            write!(formatter, "\n{:width$} |", mark.line + 1, width = width)?;
+            if !line.is_empty() {
+                write!(formatter, " {}", line)?;
+            }
+            write!(formatter, "\n{:width$} | ", "", width = width)?;
+            // Tabs are repeated so that the caret lines up with the column.
+            let mut chars = line.chars();
+            for _ in 0..mark.column {
+                let ch = if chars.next() == Some('\t') {
+                    '\t'
+                } else {
+                    ' '
+                };
+                formatter.write_char(ch)?;
+            }
+            write!(formatter, "^ {}", label)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(&self.message)?;
+        if let Some(mark) = self.mark {
+            write!(
+                formatter,
+                " at line {} column {}",
+                mark.line + 1,
+                mark.column + 1,
+            )?;
+        }
+        if let Some((context, mark)) = &self.context {
+            write!(
+                formatter,
+                ", {} at line {} column {}",
+                context,
+                mark.line + 1,
+                mark.column + 1,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Debug for Error {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let mut debug = formatter.debug_struct("Error");
+        debug.field("kind", &self.kind);
+        debug.field("message", &self.message);
+        if let Some(mark) = self.mark {
+            debug.field("line", &(mark.line + 1));
+            debug.field("column", &(mark.column + 1));
+        }
+        if let Some((context, _)) = &self.context {
+            debug.field("context", context);
+        }
+        if let Some(source) = &self.source {
+            debug.field("source", source);
+        }
+        debug.finish()
+    }
+}
+
+impl de::StdError for Error {
+    fn source(&self) -> Option<&(dyn de::StdError + 'static)> {
+        self.source
+            .as_ref()
+            .map(|source| &**source as &(dyn de::StdError + 'static))
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T: Display>(message: T) -> Self {
+        Error {
+            kind: ErrorKind::Data,
+            message: message.to_string(),
+            mark: None,
+            context: None,
+            source: None,
+        }
+    }
+}
+
+/// The code names the [`ErrorKind`], as in `unsafe_libyaml::scanner`. The
+/// problem and the context are labeled at their marks, which are byte
+/// offsets into the input past any byte order mark, so that a
+/// [`miette::Report`] given the input with `with_source_code` shows the lines
+/// they point at.
+#[cfg(feature = "miette")]
+impl miette::Diagnostic for Error {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        let code = match self.kind {
+            ErrorKind::Memory => "unsafe_libyaml::memory",
+            ErrorKind::Reader => "unsafe_libyaml::reader",
+            ErrorKind::Scanner => "unsafe_libyaml::scanner",
+            ErrorKind::Parser => "unsafe_libyaml::parser",
+            ErrorKind::Composer => "unsafe_libyaml::composer",
+            ErrorKind::Writer => "unsafe_libyaml::writer",
+            ErrorKind::Emitter => "unsafe_libyaml::emitter",
+            ErrorKind::Limit => "unsafe_libyaml::limit",
+            ErrorKind::Data => "unsafe_libyaml::data",
+            ErrorKind::WouldBlock => "unsafe_libyaml::would_block",
+        };
+        Some(Box::new(code))
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        let mut labels = Vec::new();
+        if let Some((context, mark)) = &self.context {
+            labels.push(miette::LabeledSpan::at(
+                (mark.index as usize, 0),
+                context.as_str(),
+            ));
+        }
+        if let Some(mark) = self.mark {
+            labels.push(miette::LabeledSpan::new_primary_with_span(
+                Some(self.message.clone()),
+                (mark.index as usize, 0),
+            ));
+        }
+        if labels.is_empty() {
+            None
+        } else {
+            Some(Box::new(labels.into_iter()))
+        }
+    }
+}
+
+impl ser::Error for Error {
+    fn custom<T: Display>(message: T) -> Self {
+        de::Error::custom(message)
+    }
+}
+
+/// A failure of an [`Output`](crate::Output), which keeps its cause.
+pub struct OutputError {
+    would_block: bool,
+    written: Option<usize>,
+    source: Option<Box<dyn de::StdError + Send + Sync>>,
+}
+
+impl OutputError {
+    /// An error caused by `source`, which is kept as the source of the
+    /// [`Error`] it becomes.
+    pub fn new<E>(source: E) -> Self
+    where
+        E: de::StdError + Send + Sync + 'static,
+    {
+        OutputError {
+            would_block: false,
+            written: None,
+            source: Some(Box::new(source)),
+        }
+    }
+
+    /// The output is full after `written` bytes of the ones given to it.
+    pub fn buffer_full(written: usize) -> Self {
+        OutputError {
+            would_block: false,
+            written: Some(written),
+            source: None,
+        }
+    }
+
+    /// The output cannot accept the bytes right now, and nothing was written.
+    pub fn would_block() -> Self {
+        OutputError {
+            would_block: true,
+            written: None,
+            source: None,
+        }
+    }
+
+    /// Whether the output would block, so that writing again later may
+    /// succeed.
+    pub fn is_would_block(&self) -> bool {
+        self.would_block
+    }
+
+    /// The number of bytes written before a full output gave up, or None if
+    /// the output is not full.
+    pub fn bytes_written(&self) -> Option<usize> {
+        self.written
+    }
+}
+
+impl Display for OutputError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        if self.would_block {
+            formatter.write_str("the output would block")
+        } else if let Some(written) = self.written {
+            write!(
+                formatter,
+                "the output buffer is full after {} bytes",
+                written
+            )
+        } else {
+            formatter.write_str("failed to write the output")?;
+            if let Some(source) = &self.source {
+                write!(formatter, ": {}", source)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+impl Debug for OutputError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let mut debug = formatter.debug_struct("OutputError");
+        debug.field("would_block", &self.would_block);
+        if let Some(written) = self.written {
+            debug.field("written", &written);
+        }
+        if let Some(source) = &self.source {
+            debug.field("source", source);
+        }
+        debug.finish()
+    }
+}
+
+impl de::StdError for OutputError {
+    fn source(&self) -> Option<&(dyn de::StdError + 'static)> {
+        self.source
+            .as_ref()
+            .map(|source| &**source as &(dyn de::StdError + 'static))
+    }
+}
+
+/// Find a line of the input, counting line breaks as the scanner does.
+fn yaml_error_source_line(source: &str, line: u64) -> &str {
+    let mut current = 0;
+    let mut start = 0;
+    let mut chars = source.char_indices().peekable();
+    while let Some((index, ch)) = chars.next() {
+        if !matches!(ch, '\r' | '\n' | '\u{85}' | '\u{2028}' | '\u{2029}') {
+            continue;
+        }
+        if current == line {
+            return &source[start..index];
+        }
+        if ch == '\r' && chars.peek().map(|&(_, ch)| ch) == Some('\n') {
+            chars.next();
+        }
+        current += 1;
+        start = chars.peek().map_or(source.len(), |&(index, _)| index);
+    }
+    if current == line {
+        &source[start..]
+    } else {
+        ""
+    }
+}
+
+unsafe fn yaml_error_cstr<'a>(string: *const libc::c_char) -> &'a [u8] {
+    slice::from_raw_parts(string as *const u8, strlen(string) as usize)
+}
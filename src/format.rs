@@ -0,0 +1,234 @@
+use crate::externs::strlen;
+use crate::success::{Success, FAIL, OK};
+use crate::yaml::{size_t, yaml_char_t};
+use crate::{
+    libc, yaml_event_t, yaml_token_t, yaml_write_handler_t, YAML_ALIAS_EVENT, YAML_ALIAS_TOKEN,
+    YAML_ANCHOR_TOKEN, YAML_BLOCK_END_TOKEN, YAML_BLOCK_ENTRY_TOKEN,
+    YAML_BLOCK_MAPPING_START_TOKEN, YAML_BLOCK_SEQUENCE_START_TOKEN, YAML_DOCUMENT_END_EVENT,
+    YAML_DOCUMENT_END_TOKEN, YAML_DOCUMENT_START_EVENT, YAML_DOCUMENT_START_TOKEN,
+    YAML_DOUBLE_QUOTED_SCALAR_STYLE, YAML_FLOW_ENTRY_TOKEN, YAML_FLOW_MAPPING_END_TOKEN,
+    YAML_FLOW_MAPPING_START_TOKEN, YAML_FLOW_SEQUENCE_END_TOKEN, YAML_FLOW_SEQUENCE_START_TOKEN,
+    YAML_FOLDED_SCALAR_STYLE, YAML_KEY_TOKEN, YAML_LITERAL_SCALAR_STYLE, YAML_MAPPING_END_EVENT,
+    YAML_MAPPING_START_EVENT, YAML_NO_EVENT, YAML_NO_TOKEN, YAML_PLAIN_SCALAR_STYLE,
+    YAML_SCALAR_EVENT, YAML_SCALAR_TOKEN, YAML_SEQUENCE_END_EVENT, YAML_SEQUENCE_START_EVENT,
+    YAML_SINGLE_QUOTED_SCALAR_STYLE, YAML_STREAM_END_EVENT, YAML_STREAM_END_TOKEN,
+    YAML_STREAM_START_EVENT, YAML_STREAM_START_TOKEN, YAML_TAG_DIRECTIVE_TOKEN, YAML_TAG_TOKEN,
+    YAML_VALUE_TOKEN, YAML_VERSION_DIRECTIVE_TOKEN,
+};
+use core::slice;
+
+/// Format a token as an entry of the PyYAML token listing.
+///
+/// The listing is the one of the `.tokens` files of the PyYAML test data,
+/// which are checked against the libyaml scanner as well. Each token is
+/// written as a mnemonic of its type: `%` for directives, `---` and `...` for
+/// document markers, `*` for aliases, `&` for anchors, `!` for tags, `_` for
+/// scalars, `[[`, `{{` and `]}` for the start and end of block collections,
+/// `[`, `]`, `{` and `}` for flow collections, `,` for block and flow entries,
+/// `?` for keys and `:` for values. The token data is not part of the
+/// listing. Nothing is written for STREAM-START, STREAM-END and NO-TOKEN,
+/// which the listing leaves out. The caller separates the entries, usually
+/// with a space.
+///
+/// The entry is passed to `handler` with `data`. The handler should return 1
+/// on success; if it returns 0 or -1, FAIL is returned.
+pub unsafe fn yaml_token_format(
+    token: *const yaml_token_t,
+    handler: yaml_write_handler_t,
+    data: *mut libc::c_void,
+) -> Success {
+    __assert!(!token.is_null());
+    let mut output = yaml_format_output_t {
+        handler,
+        data,
+        buffer: [0; 256],
+        length: 0,
+        error: false,
+    };
+    output.write(match (*token).type_ {
+        YAML_NO_TOKEN | YAML_STREAM_START_TOKEN | YAML_STREAM_END_TOKEN => b"",
+        YAML_VERSION_DIRECTIVE_TOKEN | YAML_TAG_DIRECTIVE_TOKEN => b"%",
+        YAML_DOCUMENT_START_TOKEN => b"---",
+        YAML_DOCUMENT_END_TOKEN => b"...",
+        YAML_BLOCK_SEQUENCE_START_TOKEN => b"[[",
+        YAML_BLOCK_MAPPING_START_TOKEN => b"{{",
+        YAML_BLOCK_END_TOKEN => b"]}",
+        YAML_FLOW_SEQUENCE_START_TOKEN => b"[",
+        YAML_FLOW_SEQUENCE_END_TOKEN => b"]",
+        YAML_FLOW_MAPPING_START_TOKEN => b"{",
+        YAML_FLOW_MAPPING_END_TOKEN => b"}",
+        YAML_BLOCK_ENTRY_TOKEN | YAML_FLOW_ENTRY_TOKEN => b",",
+        YAML_KEY_TOKEN => b"?",
+        YAML_VALUE_TOKEN => b":",
+        YAML_ALIAS_TOKEN => b"*",
+        YAML_ANCHOR_TOKEN => b"&",
+        YAML_TAG_TOKEN => b"!",
+        YAML_SCALAR_TOKEN => b"_",
+    });
+    output.flush()
+}
+
+/// Format an event as a single line of the yaml-test-suite event listing.
+///
+/// Events are printed as `+STR`, `-STR`, `+DOC`, `-DOC`, `+MAP`, `-MAP`,
+/// `+SEQ`, `-SEQ`, `=VAL` and `=ALI`. Explicit document markers are shown as
+/// ` ---` and ` ...`, anchors as ` &anchor` and tags as ` <tag>`. Scalars
+/// carry the style indicator (`:`, `'`, `"`, `|` or `>`) and the value with
+/// `\\`, `\0`, `\b`, `\n`, `\r` and `\t` escaped. All other bytes are
+/// written as they are, so the listing of a scalar that is not valid UTF-8 is
+/// not valid UTF-8 either. No line break is written after the event.
+///
+/// The listing is passed to `handler` with `data` in one or more pieces. The
+/// handler should return 1 on success; if it returns 0 or -1, formatting stops
+/// and FAIL is returned.
+pub unsafe fn yaml_event_format(
+    event: *const yaml_event_t,
+    handler: yaml_write_handler_t,
+    data: *mut libc::c_void,
+) -> Success {
+    __assert!(!event.is_null());
+    let mut output = yaml_format_output_t {
+        handler,
+        data,
+        buffer: [0; 256],
+        length: 0,
+        error: false,
+    };
+    match (*event).type_ {
+        YAML_NO_EVENT => output.write(b"???"),
+        YAML_STREAM_START_EVENT => output.write(b"+STR"),
+        YAML_STREAM_END_EVENT => output.write(b"-STR"),
+        YAML_DOCUMENT_START_EVENT => {
+            output.write(b"+DOC");
+            if !(*event).data.document_start.implicit {
+                output.write(b" ---");
+            }
+        }
+        YAML_DOCUMENT_END_EVENT => {
+            output.write(b"-DOC");
+            if !(*event).data.document_end.implicit {
+                output.write(b" ...");
+            }
+        }
+        YAML_ALIAS_EVENT => {
+            output.write(b"=ALI *");
+            output.write_cstr((*event).data.alias.anchor);
+        }
+        YAML_SCALAR_EVENT => {
+            output.write(b"=VAL");
+            output.write_properties((*event).data.scalar.anchor, (*event).data.scalar.tag);
+            output.write(match (*event).data.scalar.style {
+                YAML_PLAIN_SCALAR_STYLE => b" :",
+                YAML_SINGLE_QUOTED_SCALAR_STYLE => b" '",
+                YAML_DOUBLE_QUOTED_SCALAR_STYLE => b" \"",
+                YAML_LITERAL_SCALAR_STYLE => b" |",
+                YAML_FOLDED_SCALAR_STYLE => b" >",
+                _ => b" ",
+            });
+            output.write_escaped((*event).data.scalar.value, (*event).data.scalar.length);
+        }
+        YAML_SEQUENCE_START_EVENT => {
+            output.write(b"+SEQ");
+            output.write_properties(
+                (*event).data.sequence_start.anchor,
+                (*event).data.sequence_start.tag,
+            );
+        }
+        YAML_SEQUENCE_END_EVENT => output.write(b"-SEQ"),
+        YAML_MAPPING_START_EVENT => {
+            output.write(b"+MAP");
+            output.write_properties(
+                (*event).data.mapping_start.anchor,
+                (*event).data.mapping_start.tag,
+            );
+        }
+        YAML_MAPPING_END_EVENT => output.write(b"-MAP"),
+    }
+    output.flush()
+}
+
+/// The bytes of a listing that are not yet passed to the handler.
+struct yaml_format_output_t {
+    handler: yaml_write_handler_t,
+    data: *mut libc::c_void,
+    buffer: [u8; 256],
+    length: usize,
+    error: bool,
+}
+
+impl yaml_format_output_t {
+    unsafe fn write(&mut self, mut bytes: &[u8]) {
+        while !self.error && !bytes.is_empty() {
+            if self.length == self.buffer.len() && self.flush().fail {
+                return;
+            }
+            let size = bytes.len().min(self.buffer.len() - self.length);
+            self.buffer[self.length..self.length + size].copy_from_slice(&bytes[..size]);
+            self.length += size;
+            bytes = &bytes[size..];
+        }
+    }
+
+    unsafe fn flush(&mut self) -> Success {
+        if self.error {
+            return FAIL;
+        }
+        if self.length == 0 {
+            return OK;
+        }
+        let result = (self.handler)(self.data, self.buffer.as_mut_ptr(), self.length as size_t);
+        self.length = 0;
+        if result > 0 {
+            OK
+        } else {
+            self.error = true;
+            FAIL
+        }
+    }
+
+    unsafe fn write_properties(&mut self, anchor: *const yaml_char_t, tag: *const yaml_char_t) {
+        if !anchor.is_null() {
+            self.write(b" &");
+            self.write_cstr(anchor);
+        }
+        if !tag.is_null() {
+            self.write(b" <");
+            self.write_cstr(tag);
+            self.write(b">");
+        }
+    }
+
+    unsafe fn write_cstr(&mut self, string: *const yaml_char_t) {
+        if string.is_null() {
+            return;
+        }
+        let length = strlen(string as *const libc::c_char);
+        self.write(slice::from_raw_parts(string, length as usize));
+    }
+
+    unsafe fn write_escaped(&mut self, string: *const yaml_char_t, length: size_t) {
+        if string.is_null() {
+            return;
+        }
+        let bytes = slice::from_raw_parts(string, length as usize);
+        for chunk in bytes
+            .split_inclusive(|&ch| matches!(ch, b'\\' | b'\0' | b'\x08' | b'\n' | b'\r' | b'\t'))
+        {
+            let (last, rest) = chunk.split_last().unwrap();
+            let repr: &[u8] = match last {
+                b'\\' => b"\\\\",
+                b'\0' => b"\\0",
+                b'\x08' => b"\\b",
+                b'\n' => b"\\n",
+                b'\r' => b"\\r",
+                b'\t' => b"\\t",
+                _ => {
+                    self.write(chunk);
+                    continue;
+                }
+            };
+            self.write(rest);
+            self.write(repr);
+        }
+    }
+}
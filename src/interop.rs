@@ -0,0 +1,302 @@
+use crate::document::yaml_node_ref_t;
+use crate::externs::strcmp;
+use crate::schema::{yaml_core_resolve, Resolved};
+use crate::success::{Success, FAIL, OK};
+use crate::{
+    libc, yaml_document_add_mapping, yaml_document_add_scalar, yaml_document_add_sequence,
+    yaml_document_append_mapping_pair, yaml_document_append_sequence_item, yaml_document_delete,
+    yaml_document_initialize, yaml_document_root_ref, yaml_document_t, yaml_error_t, yaml_mark_t,
+    yaml_scalar_style_t, YAML_ANY_MAPPING_STYLE, YAML_ANY_SCALAR_STYLE, YAML_ANY_SEQUENCE_STYLE,
+    YAML_COMPOSER_ERROR, YAML_DOUBLE_QUOTED_SCALAR_STYLE, YAML_MAPPING_NODE,
+    YAML_PLAIN_SCALAR_STYLE, YAML_SCALAR_NODE, YAML_SEQUENCE_NODE,
+};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::mem::MaybeUninit;
+use core::ptr;
+use yaml_rust2::yaml::Hash;
+use yaml_rust2::Yaml;
+
+/// Convert a YAML document to a yaml-rust2 value.
+///
+/// The scalars are resolved as the yaml-rust2 loader resolves them: quoted
+/// scalars are strings, plain scalars tagged `!!null`, `!!bool`, `!!int` or
+/// `!!float` are checked against the tag and become a bad value if it does not
+/// accept them, and the other plain scalars go through `Yaml::from_str()`,
+/// except for the ones with a tag outside of the core schema, which are
+/// strings. Shared nodes are copied for each reference, and a document without
+/// nodes converts to a bad value, as yaml-rust2 loads an empty document.
+///
+/// The function fails for documents that contain themselves.
+pub unsafe fn yaml_document_to_yaml_rust2(
+    document: *mut yaml_document_t,
+    value: &mut Yaml,
+) -> Success {
+    __assert!(!document.is_null());
+    match yaml_document_root_ref(document) {
+        Some(root) => match Yaml::try_from(root) {
+            Ok(converted) => {
+                *value = converted;
+                OK
+            }
+            Err(_) => FAIL,
+        },
+        None => {
+            *value = Yaml::BadValue;
+            OK
+        }
+    }
+}
+
+/// Create a YAML document from a yaml-rust2 value.
+///
+/// Nulls, booleans, integers and reals become plain scalars that resolve back
+/// to the same values, with a `!!float` tag for the reals that would not be
+/// read as reals. Strings that would resolve to something else are
+/// double-quoted. A bad value gives a document without nodes. The document has
+/// no directives and implicit start and end markers.
+///
+/// The function fails for aliases and nested bad values. An application is
+/// responsible for freeing the produced document object using the
+/// yaml_document_delete() function.
+pub unsafe fn yaml_document_from_yaml_rust2(
+    document: *mut yaml_document_t,
+    value: &Yaml,
+) -> Success {
+    __assert!(!document.is_null());
+    if yaml_document_initialize(
+        document,
+        ptr::null_mut(),
+        ptr::null_mut(),
+        ptr::null_mut(),
+        true,
+        true,
+    )
+    .fail
+    {
+        return FAIL;
+    }
+    if *value != Yaml::BadValue && yaml_rust2_add_node(document, value) == 0 {
+        yaml_document_delete(document);
+        return FAIL;
+    }
+    OK
+}
+
+impl TryFrom<yaml_node_ref_t<'_>> for Yaml {
+    type Error = yaml_error_t;
+
+    /// Convert a node as yaml_document_to_yaml_rust2() converts a document.
+    fn try_from(node: yaml_node_ref_t) -> Result<Yaml, yaml_error_t> {
+        let mut visiting = Vec::new();
+        yaml_rust2_from_node(node, &mut visiting)
+    }
+}
+
+impl TryFrom<&Yaml> for yaml_document_t {
+    type Error = yaml_error_t;
+
+    /// Create a document with yaml_document_from_yaml_rust2(), which must be
+    /// freed with yaml_document_delete().
+    fn try_from(value: &Yaml) -> Result<yaml_document_t, yaml_error_t> {
+        let mut document = MaybeUninit::<yaml_document_t>::uninit();
+        unsafe {
+            if yaml_document_from_yaml_rust2(document.as_mut_ptr(), value).fail {
+                return Err(yaml_rust2_error(
+                    b"cannot convert an alias or a bad value\0",
+                    MaybeUninit::zeroed().assume_init(),
+                ));
+            }
+            Ok(document.assume_init())
+        }
+    }
+}
+
+fn yaml_rust2_error(problem: &'static [u8], mark: yaml_mark_t) -> yaml_error_t {
+    yaml_error_t {
+        error: YAML_COMPOSER_ERROR,
+        problem: problem.as_ptr() as *const libc::c_char,
+        problem_mark: mark,
+        context: ptr::null::<libc::c_char>(),
+        context_mark: unsafe { MaybeUninit::zeroed().assume_init() },
+    }
+}
+
+fn yaml_rust2_from_node(
+    node: yaml_node_ref_t,
+    visiting: &mut Vec<libc::c_int>,
+) -> Result<Yaml, yaml_error_t> {
+    if visiting.contains(&node.id()) {
+        return Err(yaml_rust2_error(
+            b"found a node that contains itself\0",
+            node.node().start_mark,
+        ));
+    }
+    visiting.push(node.id());
+    let value = match node.node().type_ {
+        YAML_SCALAR_NODE => yaml_rust2_from_scalar(node),
+        YAML_SEQUENCE_NODE => {
+            let items = unsafe { node.node().data.sequence.items };
+            let mut array = Vec::new();
+            let mut item = items.start;
+            while item < items.top {
+                // SAFETY: the items of a sequence are ids of nodes of the document.
+                unsafe {
+                    array.push(yaml_rust2_from_node(node.child(*item), visiting)?);
+                    item = item.add(1);
+                }
+            }
+            Yaml::Array(array)
+        }
+        YAML_MAPPING_NODE => {
+            let pairs = unsafe { node.node().data.mapping.pairs };
+            let mut hash = Hash::new();
+            let mut pair = pairs.start;
+            while pair < pairs.top {
+                // SAFETY: the pairs of a mapping hold ids of nodes of the document.
+                unsafe {
+                    let key = yaml_rust2_from_node(node.child((*pair).key), visiting)?;
+                    let value = yaml_rust2_from_node(node.child((*pair).value), visiting)?;
+                    hash.insert(key, value);
+                    pair = pair.add(1);
+                }
+            }
+            Yaml::Hash(hash)
+        }
+        _ => Yaml::BadValue,
+    };
+    visiting.pop();
+    Ok(value)
+}
+
+fn yaml_rust2_has_tag(node: yaml_node_ref_t, tag: &[u8]) -> bool {
+    let node_tag = node.node().tag;
+    !node_tag.is_null()
+        && unsafe {
+            strcmp(
+                node_tag as *const libc::c_char,
+                tag.as_ptr() as *const libc::c_char,
+            ) == 0
+        }
+}
+
+fn yaml_rust2_from_scalar(node: yaml_node_ref_t) -> Yaml {
+    let value = String::from_utf8_lossy(node.as_bytes().unwrap_or_default()).into_owned();
+    let style = unsafe { node.node().data.scalar.style };
+    if style != YAML_PLAIN_SCALAR_STYLE && style != YAML_ANY_SCALAR_STYLE {
+        Yaml::String(value)
+    } else if node.node().tag.is_null() || yaml_rust2_has_tag(node, b"tag:yaml.org,2002:str\0") {
+        Yaml::from_str(&value)
+    } else if yaml_rust2_has_tag(node, b"tag:yaml.org,2002:null\0") {
+        match value.as_str() {
+            "~" | "null" => Yaml::Null,
+            _ => Yaml::BadValue,
+        }
+    } else if yaml_rust2_has_tag(node, b"tag:yaml.org,2002:bool\0") {
+        match value.as_str() {
+            "true" | "True" | "TRUE" => Yaml::Boolean(true),
+            "false" | "False" | "FALSE" => Yaml::Boolean(false),
+            _ => Yaml::BadValue,
+        }
+    } else if yaml_rust2_has_tag(node, b"tag:yaml.org,2002:int\0") {
+        match value.parse::<i64>() {
+            Ok(int) => Yaml::Integer(int),
+            Err(_) => Yaml::BadValue,
+        }
+    } else if yaml_rust2_has_tag(node, b"tag:yaml.org,2002:float\0") {
+        let real = Yaml::Real(value);
+        if real.as_f64().is_some() {
+            real
+        } else {
+            Yaml::BadValue
+        }
+    } else {
+        Yaml::String(value)
+    }
+}
+
+unsafe fn yaml_rust2_add_scalar(
+    document: *mut yaml_document_t,
+    tag: *const u8,
+    value: &str,
+    style: yaml_scalar_style_t,
+) -> libc::c_int {
+    yaml_document_add_scalar(
+        document,
+        tag,
+        value.as_ptr(),
+        value.len() as libc::c_int,
+        style,
+    )
+}
+
+unsafe fn yaml_rust2_add_node(document: *mut yaml_document_t, value: &Yaml) -> libc::c_int {
+    match value {
+        Yaml::Null => yaml_rust2_add_scalar(document, ptr::null(), "null", YAML_PLAIN_SCALAR_STYLE),
+        Yaml::Boolean(value) => yaml_rust2_add_scalar(
+            document,
+            ptr::null(),
+            if *value { "true" } else { "false" },
+            YAML_PLAIN_SCALAR_STYLE,
+        ),
+        Yaml::Integer(int) => yaml_rust2_add_scalar(
+            document,
+            ptr::null(),
+            &int.to_string(),
+            YAML_PLAIN_SCALAR_STYLE,
+        ),
+        Yaml::Real(real) => {
+            let tag = if let Yaml::Real(_) = Yaml::from_str(real) {
+                ptr::null()
+            } else {
+                b"tag:yaml.org,2002:float\0".as_ptr()
+            };
+            yaml_rust2_add_scalar(document, tag, real, YAML_PLAIN_SCALAR_STYLE)
+        }
+        Yaml::String(string) => {
+            let style = if yaml_core_resolve(string.as_bytes()) != Resolved::Str
+                || !matches!(Yaml::from_str(string), Yaml::String(_))
+            {
+                YAML_DOUBLE_QUOTED_SCALAR_STYLE
+            } else {
+                YAML_ANY_SCALAR_STYLE
+            };
+            yaml_rust2_add_scalar(document, ptr::null(), string, style)
+        }
+        Yaml::Array(array) => {
+            let sequence =
+                yaml_document_add_sequence(document, ptr::null(), YAML_ANY_SEQUENCE_STYLE);
+            if sequence == 0 {
+                return 0;
+            }
+            for item in array {
+                let item = yaml_rust2_add_node(document, item);
+                if item == 0 || yaml_document_append_sequence_item(document, sequence, item).fail {
+                    return 0;
+                }
+            }
+            sequence
+        }
+        Yaml::Hash(hash) => {
+            let mapping = yaml_document_add_mapping(document, ptr::null(), YAML_ANY_MAPPING_STYLE);
+            if mapping == 0 {
+                return 0;
+            }
+            for (key, value) in hash {
+                let key = yaml_rust2_add_node(document, key);
+                if key == 0 {
+                    return 0;
+                }
+                let value = yaml_rust2_add_node(document, value);
+                if value == 0
+                    || yaml_document_append_mapping_pair(document, mapping, key, value).fail
+                {
+                    return 0;
+                }
+            }
+            mapping
+        }
+        Yaml::Alias(_) | Yaml::BadValue => 0,
+    }
+}
@@ -0,0 +1,270 @@
+use crate::externs::strcmp;
+use crate::limits::{yaml_expansion_limit, RECURSION_LIMIT};
+use crate::schema::{yaml_core_float, yaml_core_resolve, Resolved};
+use crate::success::{Success, FAIL, OK};
+use crate::{
+    libc, yaml_document_add_mapping, yaml_document_add_scalar, yaml_document_add_sequence,
+    yaml_document_append_mapping_pair, yaml_document_append_sequence_item, yaml_document_delete,
+    yaml_document_initialize, yaml_document_t, yaml_node_t, yaml_scalar_style_t, PointerExt,
+    YAML_ALIAS_NODE, YAML_ANY_MAPPING_STYLE, YAML_ANY_SCALAR_STYLE, YAML_ANY_SEQUENCE_STYLE,
+    YAML_DOUBLE_QUOTED_SCALAR_STYLE, YAML_MAPPING_NODE, YAML_PLAIN_SCALAR_STYLE, YAML_SCALAR_NODE,
+    YAML_SEQUENCE_NODE,
+};
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ptr;
+use core::slice;
+use serde_json::{Map, Number, Value};
+
+/// Convert a YAML document to a JSON value.
+///
+/// Plain scalars with the default `tag:yaml.org,2002:str` tag are resolved with
+/// the YAML 1.2 core schema, so `~`, `true`, `0x1F` and `.5` become null, a
+/// boolean and numbers. Scalars tagged `!!null`, `!!bool`, `!!int` or `!!float`
+/// are converted to the tagged type, and all other scalars become strings.
+/// Mapping keys must be scalars and are taken as strings. Shared nodes are
+/// copied for each reference.
+///
+/// The function fails for non-scalar keys, values the tag does not accept,
+/// infinite and NaN floats, which JSON cannot represent, and documents that
+/// contain themselves. It also fails for documents nested too deeply, and for
+/// documents whose shared nodes expand to far more nodes than they hold, as in
+/// the "billion laughs" attack. A document without nodes converts to null.
+pub unsafe fn yaml_document_to_json(document: *mut yaml_document_t, value: &mut Value) -> Success {
+    __assert!(!document.is_null());
+    let nodes = (*document).nodes.top.c_offset_from((*document).nodes.start) as usize;
+    if nodes == 0 {
+        *value = Value::Null;
+        return OK;
+    }
+    let mut converter = Converter {
+        visiting: vec![false; nodes],
+        expanded: 0,
+        limit: yaml_expansion_limit(nodes),
+    };
+    match yaml_json_from_node(document, 1, &mut converter, 0) {
+        Some(converted) => {
+            *value = converted;
+            OK
+        }
+        None => FAIL,
+    }
+}
+
+/// Create a YAML document from a JSON value.
+///
+/// Null, booleans and numbers become plain scalars that resolve back to the
+/// same values with the core schema. Strings that would resolve to something
+/// else are double-quoted. The document has no directives and implicit start
+/// and end markers.
+///
+/// An application is responsible for freeing the produced document object using
+/// the yaml_document_delete() function.
+pub unsafe fn yaml_document_from_json(document: *mut yaml_document_t, value: &Value) -> Success {
+    __assert!(!document.is_null());
+    if yaml_document_initialize(
+        document,
+        ptr::null_mut(),
+        ptr::null_mut(),
+        ptr::null_mut(),
+        true,
+        true,
+    )
+    .fail
+    {
+        return FAIL;
+    }
+    if yaml_json_add_node(document, value) == 0 {
+        yaml_document_delete(document);
+        return FAIL;
+    }
+    OK
+}
+
+/// The state of a conversion to JSON.
+struct Converter {
+    /// The nodes being converted, to detect documents that contain themselves.
+    visiting: Vec<bool>,
+    /// The number of nodes converted so far, counting shared nodes each time.
+    expanded: usize,
+    limit: usize,
+}
+
+unsafe fn yaml_json_from_node(
+    document: *mut yaml_document_t,
+    index: libc::c_int,
+    converter: &mut Converter,
+    depth: usize,
+) -> Option<Value> {
+    let node = (*document).nodes.start.add(index as usize - 1);
+    if converter.visiting[index as usize - 1] {
+        return None;
+    }
+    converter.expanded += 1;
+    if converter.expanded > converter.limit {
+        return None;
+    }
+    if (*node).type_ != YAML_SCALAR_NODE && depth >= RECURSION_LIMIT {
+        return None;
+    }
+    converter.visiting[index as usize - 1] = true;
+    let value = match (*node).type_ {
+        YAML_SCALAR_NODE => yaml_json_from_scalar(node)?,
+        YAML_SEQUENCE_NODE => {
+            let items = (*node).data.sequence.items;
+            let mut array = Vec::new();
+            let mut item = items.start;
+            while item < items.top {
+                array.push(yaml_json_from_node(document, *item, converter, depth + 1)?);
+                item = item.add(1);
+            }
+            Value::Array(array)
+        }
+        YAML_MAPPING_NODE => {
+            let pairs = (*node).data.mapping.pairs;
+            let mut object = Map::new();
+            let mut pair = pairs.start;
+            while pair < pairs.top {
+                let key = (*document).nodes.start.add((*pair).key as usize - 1);
+                if (*key).type_ != YAML_SCALAR_NODE {
+                    return None;
+                }
+                let key = String::from_utf8_lossy(yaml_json_scalar_value(key)).into_owned();
+                let value = yaml_json_from_node(document, (*pair).value, converter, depth + 1)?;
+                object.insert(key, value);
+                pair = pair.add(1);
+            }
+            Value::Object(object)
+        }
+        YAML_ALIAS_NODE => {
+            yaml_json_from_node(document, (*node).data.alias.target, converter, depth + 1)?
+        }
+        _ => return None,
+    };
+    converter.visiting[index as usize - 1] = false;
+    Some(value)
+}
+
+unsafe fn yaml_json_scalar_value<'a>(node: *mut yaml_node_t) -> &'a [u8] {
+    slice::from_raw_parts(
+        (*node).data.scalar.value,
+        (*node).data.scalar.length as usize,
+    )
+}
+
+unsafe fn yaml_json_has_tag(node: *mut yaml_node_t, tag: &[u8]) -> bool {
+    !(*node).tag.is_null()
+        && strcmp(
+            (*node).tag as *const libc::c_char,
+            tag.as_ptr() as *const libc::c_char,
+        ) == 0
+}
+
+unsafe fn yaml_json_from_scalar(node: *mut yaml_node_t) -> Option<Value> {
+    let value = yaml_json_scalar_value(node);
+    let resolved = yaml_core_resolve(value);
+    if yaml_json_has_tag(node, b"tag:yaml.org,2002:str\0") {
+        if (*node).data.scalar.style == YAML_PLAIN_SCALAR_STYLE && resolved != Resolved::Str {
+            return yaml_json_from_resolved(resolved);
+        }
+    } else if yaml_json_has_tag(node, b"tag:yaml.org,2002:null\0") {
+        return Some(Value::Null);
+    } else if yaml_json_has_tag(node, b"tag:yaml.org,2002:bool\0") {
+        return match resolved {
+            Resolved::Bool(value) => Some(Value::Bool(value)),
+            _ => None,
+        };
+    } else if yaml_json_has_tag(node, b"tag:yaml.org,2002:int\0") {
+        return match resolved {
+            Resolved::Int(_) | Resolved::UInt(_) => yaml_json_from_resolved(resolved),
+            _ => None,
+        };
+    } else if yaml_json_has_tag(node, b"tag:yaml.org,2002:float\0") {
+        return yaml_json_from_resolved(Resolved::Float(yaml_core_float(resolved)?));
+    }
+    Some(Value::String(String::from_utf8_lossy(value).into_owned()))
+}
+
+/// Infinite and NaN floats have no JSON representation.
+fn yaml_json_from_resolved(resolved: Resolved) -> Option<Value> {
+    Some(match resolved {
+        Resolved::Null => Value::Null,
+        Resolved::Bool(value) => Value::Bool(value),
+        Resolved::Int(int) => Value::Number(Number::from(int)),
+        Resolved::UInt(int) => Value::Number(Number::from(int)),
+        Resolved::Float(float) => Value::Number(Number::from_f64(float)?),
+        Resolved::Str => return None,
+    })
+}
+
+unsafe fn yaml_json_add_scalar(
+    document: *mut yaml_document_t,
+    value: &str,
+    style: yaml_scalar_style_t,
+) -> libc::c_int {
+    yaml_document_add_scalar(
+        document,
+        ptr::null(),
+        value.as_ptr(),
+        value.len() as libc::c_int,
+        style,
+    )
+}
+
+unsafe fn yaml_json_add_node(document: *mut yaml_document_t, value: &Value) -> libc::c_int {
+    match value {
+        Value::Null => yaml_json_add_scalar(document, "null", YAML_PLAIN_SCALAR_STYLE),
+        Value::Bool(value) => yaml_json_add_scalar(
+            document,
+            if *value { "true" } else { "false" },
+            YAML_PLAIN_SCALAR_STYLE,
+        ),
+        Value::Number(number) => {
+            yaml_json_add_scalar(document, &number.to_string(), YAML_PLAIN_SCALAR_STYLE)
+        }
+        Value::String(string) => yaml_json_add_string(document, string),
+        Value::Array(array) => {
+            let sequence =
+                yaml_document_add_sequence(document, ptr::null(), YAML_ANY_SEQUENCE_STYLE);
+            if sequence == 0 {
+                return 0;
+            }
+            for item in array {
+                let item = yaml_json_add_node(document, item);
+                if item == 0 || yaml_document_append_sequence_item(document, sequence, item).fail {
+                    return 0;
+                }
+            }
+            sequence
+        }
+        Value::Object(object) => {
+            let mapping = yaml_document_add_mapping(document, ptr::null(), YAML_ANY_MAPPING_STYLE);
+            if mapping == 0 {
+                return 0;
+            }
+            for (key, value) in object {
+                let key = yaml_json_add_string(document, key);
+                if key == 0 {
+                    return 0;
+                }
+                let value = yaml_json_add_node(document, value);
+                if value == 0
+                    || yaml_document_append_mapping_pair(document, mapping, key, value).fail
+                {
+                    return 0;
+                }
+            }
+            mapping
+        }
+    }
+}
+
+unsafe fn yaml_json_add_string(document: *mut yaml_document_t, string: &str) -> libc::c_int {
+    let style = if yaml_core_resolve(string.as_bytes()) != Resolved::Str {
+        YAML_DOUBLE_QUOTED_SCALAR_STYLE
+    } else {
+        YAML_ANY_SCALAR_STYLE
+    };
+    yaml_json_add_scalar(document, string, style)
+}
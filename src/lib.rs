@@ -5,7 +5,7 @@
 //! [docs-rs]: https://img.shields.io/badge/docs.rs-66c2a5?style=for-the-badge&labelColor=555555&logo=docs.rs
 
 #![no_std]
-#![doc(html_root_url = "https://docs.rs/unsafe-libyaml/0.2.11")]
+#![doc(html_root_url = "https://docs.rs/unsafe-libyaml/0.3.0")]
 #![allow(non_camel_case_types, non_snake_case, unsafe_op_in_unsafe_fn)]
 #![warn(clippy::pedantic)]
 #![allow(
@@ -115,6 +115,11 @@ mod externs {
         rust::dealloc(memory, layout);
     }
 
+    pub unsafe fn malloc_usable_size(ptr: *mut libc::c_void) -> libc::c_ulong {
+        let memory = ptr.cast::<u8>().sub(HEADER);
+        (memory.cast::<usize>().read() - HEADER) as libc::c_ulong
+    }
+
     pub unsafe fn memcmp(
         lhs: *const libc::c_void,
         rhs: *const libc::c_void,
@@ -277,49 +282,148 @@ impl<T> PointerExt for *mut T {
 mod macros;
 
 mod api;
+mod canonical;
+#[cfg(feature = "serde")]
+mod de;
+mod diff;
+mod document;
 mod dumper;
 mod emitter;
+#[cfg(feature = "serde")]
+mod error;
+mod format;
+#[cfg(feature = "yaml-rust2")]
+mod interop;
+#[cfg(feature = "serde_json")]
+mod json;
+#[cfg(any(feature = "serde", feature = "serde_json"))]
+mod limits;
 mod loader;
 mod ops;
 mod parser;
+mod patch;
 mod reader;
 mod scanner;
+mod schema;
+#[cfg(feature = "serde")]
+mod ser;
 mod success;
+mod tag;
+mod validate;
 mod writer;
 mod yaml;
 
+#[cfg(feature = "async")]
+pub use crate::api::yaml_emitter_set_output_async;
 pub use crate::api::{
     yaml_alias_event_initialize, yaml_document_add_mapping, yaml_document_add_scalar,
     yaml_document_add_sequence, yaml_document_append_mapping_pair,
     yaml_document_append_sequence_item, yaml_document_delete, yaml_document_end_event_initialize,
     yaml_document_get_node, yaml_document_get_root_node, yaml_document_initialize,
-    yaml_document_start_event_initialize, yaml_emitter_delete, yaml_emitter_initialize,
-    yaml_emitter_set_break, yaml_emitter_set_canonical, yaml_emitter_set_encoding,
-    yaml_emitter_set_indent, yaml_emitter_set_output, yaml_emitter_set_output_string,
-    yaml_emitter_set_unicode, yaml_emitter_set_width, yaml_event_delete,
-    yaml_mapping_end_event_initialize, yaml_mapping_start_event_initialize, yaml_parser_delete,
-    yaml_parser_initialize, yaml_parser_set_encoding, yaml_parser_set_input,
-    yaml_parser_set_input_string, yaml_scalar_event_initialize, yaml_sequence_end_event_initialize,
-    yaml_sequence_start_event_initialize, yaml_stream_end_event_initialize,
-    yaml_stream_start_event_initialize, yaml_token_delete,
+    yaml_document_start_event_initialize, yaml_emitter_add_tag_directive, yaml_emitter_delete,
+    yaml_emitter_get_options, yaml_emitter_initialize, yaml_emitter_options_initialize,
+    yaml_emitter_reset, yaml_emitter_set_always_emit_tags, yaml_emitter_set_anchor_all,
+    yaml_emitter_set_break, yaml_emitter_set_canonical, yaml_emitter_set_canonical_directives,
+    yaml_emitter_set_canonical_indent, yaml_emitter_set_check_aliases, yaml_emitter_set_compact,
+    yaml_emitter_set_compact_flow_threshold, yaml_emitter_set_document_banner,
+    yaml_emitter_set_document_end_marker, yaml_emitter_set_document_start_marker,
+    yaml_emitter_set_duplicate_keys, yaml_emitter_set_empty_documents, yaml_emitter_set_encoding,
+    yaml_emitter_set_indent, yaml_emitter_set_indent_sequences, yaml_emitter_set_indicator_spaces,
+    yaml_emitter_set_key_comparator, yaml_emitter_set_omit_final_break, yaml_emitter_set_options,
+    yaml_emitter_set_output, yaml_emitter_set_output_owned, yaml_emitter_set_output_string,
+    yaml_emitter_set_preserve_scalar_breaks, yaml_emitter_set_quote_style,
+    yaml_emitter_set_scalar_break, yaml_emitter_set_scalar_handler, yaml_emitter_set_sort_keys,
+    yaml_emitter_set_strict_styles, yaml_emitter_set_strict_width, yaml_emitter_set_tag_directives,
+    yaml_emitter_set_unicode, yaml_emitter_set_unicode_handler, yaml_emitter_set_verbatim_tags,
+    yaml_emitter_set_version_directive, yaml_emitter_set_width, yaml_emitter_set_wrap_comments,
+    yaml_emitter_set_wrap_policy, yaml_emitter_take_output, yaml_event_delete,
+    yaml_get_capabilities, yaml_mapping_end_event_initialize, yaml_mapping_start_event_initialize,
+    yaml_output_delete, yaml_parser_delete, yaml_parser_initialize, yaml_parser_set_alias_nodes,
+    yaml_parser_set_comments, yaml_parser_set_encoding, yaml_parser_set_implicit_resolver,
+    yaml_parser_set_input, yaml_parser_set_input_string, yaml_parser_set_input_string_with_origin,
+    yaml_parser_set_scalar_source, yaml_parser_set_schema, yaml_parser_set_schema_options,
+    yaml_parser_source_slice, yaml_scalar_event_initialize, yaml_sequence_end_event_initialize,
+    yaml_sequence_start_event_initialize, yaml_stream_append_document, yaml_stream_delete,
+    yaml_stream_end_event_initialize, yaml_stream_initialize, yaml_stream_start_event_initialize,
+    yaml_token_delete,
+};
+pub use crate::canonical::{yaml_canonicalize_options_initialize, yaml_document_canonicalize};
+#[cfg(feature = "serde")]
+pub use crate::de::{from_document, from_node, from_slice, from_str, Deserializer};
+pub use crate::diff::yaml_document_diff;
+pub use crate::document::{
+    yaml_compare_options_initialize, yaml_document_add_child_mapping,
+    yaml_document_add_child_scalar, yaml_document_add_child_sequence, yaml_document_add_path,
+    yaml_document_anchor_iter, yaml_document_append_unique_mapping_pair, yaml_document_compare,
+    yaml_document_detach_subtree, yaml_document_extract_subtree, yaml_document_get_alias_graph,
+    yaml_document_get_anchored_node, yaml_document_get_mapping_value,
+    yaml_document_get_mapping_value_by_node, yaml_document_get_node_data,
+    yaml_document_get_node_source, yaml_document_mapping_iter, yaml_document_memory_usage,
+    yaml_document_merge, yaml_document_node_iter, yaml_document_node_ref, yaml_document_nodes,
+    yaml_document_nodes_mut, yaml_document_remove_node, yaml_document_replace_node,
+    yaml_document_root_mapping_iter, yaml_document_root_ref, yaml_document_sequence_iter,
+    yaml_document_set_node_comments, yaml_document_set_node_data, yaml_document_walk,
+    yaml_mapping_iter_t, yaml_node_iter_t, yaml_node_ref_t, yaml_sequence_iter_t,
+};
+pub use crate::dumper::{
+    yaml_document_display, yaml_document_display_t, yaml_document_dump_events,
+    yaml_document_dump_string, yaml_emitter_close, yaml_emitter_dump, yaml_emitter_dump_all,
+    yaml_emitter_open, yaml_opened_emitter_t, yaml_owned_emitter_t, yaml_stream_dump,
+};
+pub use crate::emitter::{yaml_emitter_emit, yaml_emitter_write_raw};
+#[cfg(feature = "serde")]
+pub use crate::error::{Error, ErrorKind, OutputError};
+pub use crate::format::{yaml_event_format, yaml_token_format};
+#[cfg(feature = "yaml-rust2")]
+pub use crate::interop::{yaml_document_from_yaml_rust2, yaml_document_to_yaml_rust2};
+#[cfg(feature = "serde_json")]
+pub use crate::json::{yaml_document_from_json, yaml_document_to_json};
+pub use crate::loader::{
+    yaml_document_load_string, yaml_parser_load, yaml_parser_load_all, yaml_stream_load,
+};
+pub use crate::parser::{
+    yaml_parser_get_consumed_span, yaml_parser_get_stream_info, yaml_parser_parse,
+};
+pub use crate::patch::yaml_document_apply_patch;
+pub use crate::scanner::{yaml_parser_scan, yaml_parser_skip_to_next_document};
+pub use crate::schema::{
+    yaml_scalar_resolve, yaml_scalar_resolve_with_options, yaml_scalar_version_dependent,
+    yaml_schema_options_initialize,
+};
+#[cfg(feature = "serde")]
+pub use crate::ser::{to_output, to_string, to_writer, Output, Serializer};
+pub use crate::tag::{
+    yaml_document_node_has_tag, yaml_document_set_node_tag, yaml_document_tag_iter, yaml_tag_iter_t,
 };
-pub use crate::dumper::{yaml_emitter_close, yaml_emitter_dump, yaml_emitter_open};
-pub use crate::emitter::yaml_emitter_emit;
-pub use crate::loader::yaml_parser_load;
-pub use crate::parser::yaml_parser_parse;
-pub use crate::scanner::yaml_parser_scan;
-pub use crate::writer::yaml_emitter_flush;
+pub use crate::validate::yaml_document_validate;
+#[cfg(feature = "async")]
+pub use crate::writer::{yaml_emitter_emit_async, yaml_emitter_flush_async};
+pub use crate::writer::{yaml_emitter_flush, yaml_emitter_get_metrics, yaml_emitter_pending};
 pub use crate::yaml::{
-    yaml_alias_data_t, yaml_break_t, yaml_document_t, yaml_emitter_state_t, yaml_emitter_t,
-    yaml_encoding_t, yaml_error_type_t, yaml_event_t, yaml_event_type_t, yaml_mapping_style_t,
-    yaml_mark_t, yaml_node_item_t, yaml_node_pair_t, yaml_node_t, yaml_node_type_t,
-    yaml_parser_state_t, yaml_parser_t, yaml_read_handler_t, yaml_scalar_style_t,
-    yaml_sequence_style_t, yaml_simple_key_t, yaml_stack_t, yaml_tag_directive_t, yaml_token_t,
-    yaml_token_type_t, yaml_version_directive_t, yaml_write_handler_t,
+    yaml_alias_data_t, yaml_alias_edge_t, yaml_banner_handler_t, yaml_break_t,
+    yaml_canonicalize_options_t, yaml_capabilities_t, yaml_chomping_t, yaml_compare_options_t,
+    yaml_diff_handler_t, yaml_diff_op_t, yaml_diff_type_t, yaml_document_end_marker_t,
+    yaml_document_start_marker_t, yaml_document_t, yaml_duplicate_keys_t, yaml_emitter_metrics_t,
+    yaml_emitter_options_t, yaml_emitter_state_t, yaml_emitter_t, yaml_encoding_t, yaml_error_t,
+    yaml_error_type_t, yaml_event_handler_t, yaml_event_t, yaml_event_type_t,
+    yaml_implicit_resolver_t, yaml_key_comparator_t, yaml_mapping_style_t, yaml_mark_t,
+    yaml_node_item_t, yaml_node_pair_t, yaml_node_t, yaml_node_type_t, yaml_node_visitor_t,
+    yaml_overflow_t, yaml_parser_state_t, yaml_parser_t, yaml_patch_op_t, yaml_patch_type_t,
+    yaml_path_step_t, yaml_read_handler_t, yaml_resolved_scalar_t, yaml_scalar_handler_t,
+    yaml_scalar_source_t, yaml_scalar_style_t, yaml_schema_options_t, yaml_schema_t,
+    yaml_sequence_merge_t, yaml_sequence_style_t, yaml_shape_field_t, yaml_shape_t,
+    yaml_simple_key_t, yaml_stack_t, yaml_stream_info_t, yaml_stream_t, yaml_tag_directive_t,
+    yaml_token_t, yaml_token_type_t, yaml_unicode_handler_t, yaml_value_type_t,
+    yaml_version_directive_t, yaml_violation_handler_t, yaml_violation_t, yaml_violation_type_t,
+    yaml_visit_type_t, yaml_wrap_policy_t, yaml_write_handler_t,
 };
 #[doc(hidden)]
 pub use crate::yaml::{
-    yaml_break_t::*, yaml_emitter_state_t::*, yaml_encoding_t::*, yaml_error_type_t::*,
-    yaml_event_type_t::*, yaml_mapping_style_t::*, yaml_node_type_t::*, yaml_parser_state_t::*,
-    yaml_scalar_style_t::*, yaml_sequence_style_t::*, yaml_token_type_t::*,
+    yaml_break_t::*, yaml_chomping_t::*, yaml_diff_type_t::*, yaml_document_end_marker_t::*,
+    yaml_document_start_marker_t::*, yaml_duplicate_keys_t::*, yaml_emitter_state_t::*,
+    yaml_encoding_t::*, yaml_error_type_t::*, yaml_event_type_t::*, yaml_mapping_style_t::*,
+    yaml_node_type_t::*, yaml_overflow_t::*, yaml_parser_state_t::*, yaml_patch_type_t::*,
+    yaml_scalar_style_t::*, yaml_schema_t::*, yaml_sequence_merge_t::*, yaml_sequence_style_t::*,
+    yaml_token_type_t::*, yaml_value_type_t::*, yaml_violation_type_t::*, yaml_visit_type_t::*,
+    yaml_wrap_policy_t::*,
 };
@@ -0,0 +1,8 @@
+/// The maximum nesting of sequences, mappings and aliases.
+pub(crate) const RECURSION_LIMIT: usize = 128;
+
+/// The number of events or nodes that aliases may expand an input of `size`
+/// events or nodes to.
+pub(crate) fn yaml_expansion_limit(size: usize) -> usize {
+    size.saturating_mul(100).max(1 << 16)
+}
@@ -1,23 +1,41 @@
-use crate::api::{yaml_free, yaml_malloc, yaml_stack_extend, yaml_strdup};
-use crate::externs::{memset, strcmp};
+use crate::api::{yaml_free, yaml_malloc, yaml_scalar_source_new, yaml_stack_extend, yaml_strdup};
+use crate::externs::{memcpy, memset, strcmp, strlen};
+use crate::ops::ForceAdd as _;
 use crate::success::{Success, FAIL, OK};
-use crate::yaml::yaml_char_t;
+use crate::yaml::{size_t, yaml_char_t, yaml_comment_t, yaml_scalar_source_t};
 use crate::{
-    libc, yaml_alias_data_t, yaml_document_delete, yaml_document_t, yaml_event_t, yaml_mark_t,
-    yaml_node_item_t, yaml_node_pair_t, yaml_node_t, yaml_parser_parse, yaml_parser_t, PointerExt,
-    YAML_ALIAS_EVENT, YAML_COMPOSER_ERROR, YAML_DOCUMENT_END_EVENT, YAML_DOCUMENT_START_EVENT,
-    YAML_MAPPING_END_EVENT, YAML_MAPPING_NODE, YAML_MAPPING_START_EVENT, YAML_MEMORY_ERROR,
-    YAML_SCALAR_EVENT, YAML_SCALAR_NODE, YAML_SEQUENCE_END_EVENT, YAML_SEQUENCE_NODE,
-    YAML_SEQUENCE_START_EVENT, YAML_STREAM_END_EVENT, YAML_STREAM_START_EVENT,
+    libc, yaml_alias_data_t, yaml_document_delete, yaml_document_t, yaml_error_t, yaml_event_t,
+    yaml_mark_t, yaml_node_item_t, yaml_node_pair_t, yaml_node_t, yaml_parser_delete,
+    yaml_parser_get_stream_info, yaml_parser_initialize, yaml_parser_parse,
+    yaml_parser_set_input_string, yaml_parser_source_slice, yaml_parser_t,
+    yaml_stream_append_document, yaml_stream_info_t, yaml_stream_initialize, yaml_stream_t,
+    PointerExt, YAML_ALIAS_EVENT, YAML_ALIAS_NODE, YAML_ANY_ENCODING, YAML_COMPOSER_ERROR,
+    YAML_DOCUMENT_END_EVENT, YAML_DOCUMENT_START_EVENT, YAML_FOLDED_SCALAR_STYLE,
+    YAML_LITERAL_SCALAR_STYLE, YAML_MAPPING_END_EVENT, YAML_MAPPING_NODE, YAML_MAPPING_START_EVENT,
+    YAML_MEMORY_ERROR, YAML_SCALAR_EVENT, YAML_SCALAR_NODE, YAML_SEQUENCE_END_EVENT,
+    YAML_SEQUENCE_NODE, YAML_SEQUENCE_START_EVENT, YAML_STREAM_END_EVENT, YAML_STREAM_START_EVENT,
 };
+use alloc::vec;
+use alloc::vec::Vec;
 use core::mem::{size_of, MaybeUninit};
 use core::ptr::{self, addr_of_mut};
+use core::slice;
 
 #[repr(C)]
 struct loader_ctx {
-    start: *mut libc::c_int,
-    end: *mut libc::c_int,
-    top: *mut libc::c_int,
+    start: *mut loader_frame,
+    end: *mut loader_frame,
+    top: *mut loader_frame,
+}
+
+/// A collection node being loaded.
+#[derive(Copy, Clone)]
+#[repr(C)]
+struct loader_frame {
+    /// The id of the node.
+    node: libc::c_int,
+    /// The key of a mapping waiting for its value, or 0.
+    key: libc::c_int,
 }
 
 /// Parse the input stream and produce the next YAML document.
@@ -85,6 +103,98 @@ pub unsafe fn yaml_parser_load(
     FAIL
 }
 
+/// Parse the input stream and produce all of its remaining YAML documents.
+///
+/// The documents are appended to `documents` until the end of the stream is
+/// reached. On error, the documents produced by this call are deleted and
+/// removed again, so `documents` is left as it was.
+///
+/// An application is responsible for freeing every produced document object
+/// using the yaml_document_delete() function.
+pub unsafe fn yaml_parser_load_all(
+    parser: *mut yaml_parser_t,
+    documents: &mut Vec<yaml_document_t>,
+) -> Success {
+    let mut document = MaybeUninit::<yaml_document_t>::uninit();
+    let document = document.as_mut_ptr();
+    let len = documents.len();
+    loop {
+        if yaml_parser_load(parser, document).fail {
+            for mut document in documents.drain(len..) {
+                yaml_document_delete(&mut document);
+            }
+            return FAIL;
+        }
+        if STACK_EMPTY!((*document).nodes) {
+            yaml_document_delete(document);
+            return OK;
+        }
+        documents.push(*document);
+    }
+}
+
+/// Parse the input stream and produce a stream of all of its remaining YAML
+/// documents.
+///
+/// The encoding and the line break of the stream are the ones detected in the
+/// input, so yaml_stream_dump() writes them back. If the function fails, the
+/// stream is left empty.
+///
+/// An application is responsible for freeing the produced stream object using
+/// the yaml_stream_delete() function.
+pub unsafe fn yaml_stream_load(stream: *mut yaml_stream_t, parser: *mut yaml_parser_t) -> Success {
+    let mut info = MaybeUninit::<yaml_stream_info_t>::uninit();
+    let info = info.as_mut_ptr();
+    __assert!(!stream.is_null());
+    __assert!(!parser.is_null());
+    yaml_stream_initialize(stream, YAML_ANY_ENCODING);
+    let mut documents = Vec::new();
+    if yaml_parser_load_all(parser, &mut documents).fail {
+        return FAIL;
+    }
+    for mut document in documents {
+        let _ = yaml_stream_append_document(stream, &mut document);
+    }
+    if yaml_parser_get_stream_info(parser, info).ok {
+        (*stream).encoding = (*info).encoding;
+        (*stream).line_break = (*info).line_break;
+    }
+    OK
+}
+
+/// Load the first YAML document of a string.
+///
+/// This is yaml_parser_load() with a parser reading the `size` bytes at
+/// `input`. If the string holds no document, the produced document has no root
+/// node. If the function fails and `error` is not NULL, the error is described
+/// in it.
+///
+/// An application is responsible for freeing any data associated with the
+/// produced document object using the yaml_document_delete() function.
+pub unsafe fn yaml_document_load_string(
+    document: *mut yaml_document_t,
+    input: *const libc::c_uchar,
+    size: size_t,
+    error: *mut yaml_error_t,
+) -> Success {
+    let mut parser = MaybeUninit::<yaml_parser_t>::uninit();
+    let parser = parser.as_mut_ptr();
+    __assert!(!document.is_null());
+    __assert!(!input.is_null());
+    let _ = yaml_parser_initialize(parser);
+    yaml_parser_set_input_string(parser, input, size);
+    let success = yaml_parser_load(parser, document);
+    if success.fail && !error.is_null() {
+        (*error).error = (*parser).error;
+        (*error).problem = (*parser).problem;
+        (*error).problem_mark = (*parser).problem_mark;
+        (*error).context = (*parser).context;
+        (*error).context_mark = (*parser).context_mark;
+    }
+    yaml_parser_delete(parser);
+    success
+}
+
 unsafe fn yaml_parser_set_composer_error(
     parser: *mut yaml_parser_t,
     problem: *const libc::c_char,
@@ -126,9 +236,9 @@ unsafe fn yaml_parser_load_document(
     event: *mut yaml_event_t,
 ) -> Success {
     let mut ctx = loader_ctx {
-        start: ptr::null_mut::<libc::c_int>(),
-        end: ptr::null_mut::<libc::c_int>(),
-        top: ptr::null_mut::<libc::c_int>(),
+        start: ptr::null_mut::<loader_frame>(),
+        end: ptr::null_mut::<loader_frame>(),
+        top: ptr::null_mut::<loader_frame>(),
     };
     __assert!((*event).type_ == YAML_DOCUMENT_START_EVENT);
     let fresh16 = addr_of_mut!((*(*parser).document).version_directive);
@@ -139,7 +249,7 @@ unsafe fn yaml_parser_load_document(
     *fresh18 = (*event).data.document_start.tag_directives.end;
     (*(*parser).document).start_implicit = (*event).data.document_start.implicit;
     (*(*parser).document).start_mark = (*event).start_mark;
-    STACK_INIT!(ctx, libc::c_int);
+    STACK_INIT!(ctx, loader_frame);
     if yaml_parser_load_nodes(parser, addr_of_mut!(ctx)).fail {
         STACK_DEL!(ctx);
         return FAIL;
@@ -197,9 +307,106 @@ unsafe fn yaml_parser_load_nodes(parser: *mut yaml_parser_t, ctx: *mut loader_ct
     }
     (*(*parser).document).end_implicit = (*event).data.document_end.implicit;
     (*(*parser).document).end_mark = (*event).end_mark;
+    yaml_parser_attach_comments(parser);
     OK
 }
 
+/// Attach the comments captured for the document to its nodes.
+///
+/// The comments and the nodes are both walked in the order of the input: the
+/// nodes once sorted by their end marks, to find the node a comment follows on
+/// the same line, and once sorted by their start marks, to find the node that
+/// follows a comment.
+unsafe fn yaml_parser_attach_comments(parser: *mut yaml_parser_t) {
+    if !(*parser).capture_comments || STACK_EMPTY!((*parser).comments) {
+        return;
+    }
+    let document: *mut yaml_document_t = (*parser).document;
+    let nodes: *mut yaml_node_t = (*document).nodes.start;
+    let count = (*document).nodes.top.c_offset_from(nodes) as usize;
+    let mut is_key: Vec<bool> = vec![false; count];
+    for index in 0..count {
+        let node = nodes.add(index);
+        if (*node).type_ == YAML_MAPPING_NODE {
+            let mut pair: *mut yaml_node_pair_t = (*node).data.mapping.pairs.start;
+            while pair < (*node).data.mapping.pairs.top {
+                is_key[((*pair).key - 1) as usize] = true;
+                pair = pair.add(1);
+            }
+        }
+    }
+    // The sorts are stable, so of the nodes ending at the same mark the last
+    // is the innermost, and of the nodes starting at the same mark the first
+    // is the outermost.
+    let mut by_end: Vec<usize> = (0..count).collect();
+    by_end.sort_by_key(|&index| (*nodes.add(index)).end_mark.index);
+    let mut by_start: Vec<usize> = (0..count).collect();
+    by_start.sort_by_key(|&index| (*nodes.add(index)).start_mark.index);
+    let mut ended = 0;
+    let mut started = 0;
+    let mut comment: *mut yaml_comment_t = (*parser).comments.start;
+    while comment < (*parser).comments.top
+        && (*comment).start_mark.index < (*document).end_mark.index
+    {
+        while ended < count
+            && (*nodes.add(by_end[ended])).end_mark.index < (*comment).start_mark.index
+        {
+            ended += 1;
+        }
+        while started < count
+            && (*nodes.add(by_start[started])).start_mark.index < (*comment).end_mark.index
+        {
+            started += 1;
+        }
+        let before = ended
+            .checked_sub(1)
+            .map(|ended| by_end[ended])
+            .filter(|&before| (*nodes.add(before)).end_mark.line == (*comment).start_mark.line);
+        let after = by_start.get(started).copied();
+        let target = match (before, after) {
+            (Some(before), _)
+                if (*nodes.add(before)).type_ == YAML_SCALAR_NODE
+                    && (*nodes.add(before)).data.scalar.style != YAML_LITERAL_SCALAR_STYLE
+                    && (*nodes.add(before)).data.scalar.style != YAML_FOLDED_SCALAR_STYLE
+                    && !is_key[before] =>
+            {
+                addr_of_mut!((*nodes.add(before)).trailing_comment)
+            }
+            (_, Some(after)) => addr_of_mut!((*nodes.add(after)).leading_comment),
+            _ => addr_of_mut!((*document).end_comment),
+        };
+        yaml_parser_append_comment(target, (*comment).value);
+        comment = comment.add(1);
+    }
+    let remaining = (*parser).comments.top.c_offset_from(comment) as usize;
+    ptr::copy(comment, (*parser).comments.start, remaining);
+    (*parser).comments.top = (*parser).comments.start.add(remaining);
+}
+
+unsafe fn yaml_parser_append_comment(target: *mut *mut yaml_char_t, value: *mut yaml_char_t) {
+    if (*target).is_null() {
+        *target = value;
+        return;
+    }
+    let first = strlen(*target as *mut libc::c_char);
+    let second = strlen(value as *mut libc::c_char);
+    let joined = yaml_malloc(first.force_add(second).force_add(2)) as *mut yaml_char_t;
+    memcpy(
+        joined as *mut libc::c_void,
+        *target as *const libc::c_void,
+        first,
+    );
+    *joined.add(first as usize) = b'\n';
+    memcpy(
+        joined.add(first as usize + 1) as *mut libc::c_void,
+        value as *const libc::c_void,
+        second.force_add(1),
+    );
+    yaml_free(*target as *mut libc::c_void);
+    yaml_free(value as *mut libc::c_void);
+    *target = joined;
+}
+
 unsafe fn yaml_parser_register_anchor(
     parser: *mut yaml_parser_t,
     index: libc::c_int,
@@ -236,6 +443,13 @@ unsafe fn yaml_parser_register_anchor(
         }
         alias_data = alias_data.wrapping_offset(1);
     }
+    let node = (*(*parser).document).nodes.start.add((index - 1) as usize);
+    (*node).anchor = yaml_strdup(anchor);
+    if (*node).anchor.is_null() {
+        yaml_free(anchor as *mut libc::c_void);
+        (*parser).error = YAML_MEMORY_ERROR;
+        return FAIL;
+    }
     PUSH!((*parser).aliases, *data);
     OK
 }
@@ -248,11 +462,11 @@ unsafe fn yaml_parser_load_node_add(
     if STACK_EMPTY!(*ctx) {
         return OK;
     }
-    let parent_index: libc::c_int = *(*ctx).top.wrapping_offset(-1_isize);
+    let frame: *mut loader_frame = (*ctx).top.wrapping_offset(-1_isize);
+    let parent_index: libc::c_int = (*frame).node;
     let parent: *mut yaml_node_t = addr_of_mut!(
         *((*(*parser).document).nodes.start).wrapping_offset((parent_index - 1) as isize)
     );
-    let current_block_17: u64;
     match (*parent).type_ {
         YAML_SEQUENCE_NODE => {
             if STACK_LIMIT!(parser, (*parent).data.sequence.items).fail {
@@ -261,30 +475,18 @@ unsafe fn yaml_parser_load_node_add(
             PUSH!((*parent).data.sequence.items, index);
         }
         YAML_MAPPING_NODE => {
-            let mut pair = MaybeUninit::<yaml_node_pair_t>::uninit();
-            let pair = pair.as_mut_ptr();
-            if !STACK_EMPTY!((*parent).data.mapping.pairs) {
-                let p: *mut yaml_node_pair_t =
-                    (*parent).data.mapping.pairs.top.wrapping_offset(-1_isize);
-                if (*p).key != 0 && (*p).value == 0 {
-                    (*p).value = index;
-                    current_block_17 = 11307063007268554308;
-                } else {
-                    current_block_17 = 17407779659766490442;
-                }
+            if (*frame).key == 0 {
+                (*frame).key = index;
             } else {
-                current_block_17 = 17407779659766490442;
-            }
-            match current_block_17 {
-                11307063007268554308 => {}
-                _ => {
-                    (*pair).key = index;
-                    (*pair).value = 0;
-                    if STACK_LIMIT!(parser, (*parent).data.mapping.pairs).fail {
-                        return FAIL;
-                    }
-                    PUSH!((*parent).data.mapping.pairs, *pair);
+                let pair = yaml_node_pair_t {
+                    key: (*frame).key,
+                    value: index,
+                };
+                if STACK_LIMIT!(parser, (*parent).data.mapping.pairs).fail {
+                    return FAIL;
                 }
+                PUSH!((*parent).data.mapping.pairs, pair);
+                (*frame).key = 0;
             }
         }
         _ => {
@@ -309,7 +511,10 @@ unsafe fn yaml_parser_load_alias(
         ) == 0
         {
             yaml_free(anchor as *mut libc::c_void);
-            return yaml_parser_load_node_add(parser, ctx, (*alias_data).index);
+            if !(*parser).alias_nodes {
+                return yaml_parser_load_node_add(parser, ctx, (*alias_data).index);
+            }
+            return yaml_parser_load_alias_node(parser, event, ctx, (*alias_data).index);
         }
         alias_data = alias_data.wrapping_offset(1);
     }
@@ -321,6 +526,35 @@ unsafe fn yaml_parser_load_alias(
     )
 }
 
+unsafe fn yaml_parser_load_alias_node(
+    parser: *mut yaml_parser_t,
+    event: *mut yaml_event_t,
+    ctx: *mut loader_ctx,
+    target: libc::c_int,
+) -> Success {
+    let mut node = MaybeUninit::<yaml_node_t>::uninit();
+    let node = node.as_mut_ptr();
+    if STACK_LIMIT!(parser, (*(*parser).document).nodes).fail {
+        return FAIL;
+    }
+    memset(
+        node as *mut libc::c_void,
+        0,
+        size_of::<yaml_node_t>() as libc::c_ulong,
+    );
+    (*node).type_ = YAML_ALIAS_NODE;
+    (*node).start_mark = (*event).start_mark;
+    (*node).end_mark = (*event).end_mark;
+    (*node).data.alias.target = target;
+    PUSH!((*(*parser).document).nodes, *node);
+    let index: libc::c_int = (*(*parser).document)
+        .nodes
+        .top
+        .c_offset_from((*(*parser).document).nodes.start)
+        as libc::c_int;
+    yaml_parser_load_node_add(parser, ctx, index)
+}
+
 unsafe fn yaml_parser_load_scalar(
     parser: *mut yaml_parser_t,
     event: *mut yaml_event_t,
@@ -363,6 +597,9 @@ unsafe fn yaml_parser_load_scalar(
             (*node).data.scalar.value = (*event).data.scalar.value;
             (*node).data.scalar.length = (*event).data.scalar.length;
             (*node).data.scalar.style = (*event).data.scalar.style;
+            (*node).data.scalar.chomping = (*event).data.scalar.chomping;
+            (*node).data.scalar.indentation_indicator = (*event).data.scalar.indentation_indicator;
+            (*node).data.scalar.source = yaml_parser_load_scalar_source(parser, event);
             PUSH!((*(*parser).document).nodes, *node);
             index = (*(*parser).document)
                 .nodes
@@ -381,6 +618,89 @@ unsafe fn yaml_parser_load_scalar(
     FAIL
 }
 
+unsafe fn yaml_parser_load_scalar_source(
+    parser: *mut yaml_parser_t,
+    event: *mut yaml_event_t,
+) -> *mut yaml_scalar_source_t {
+    let mut text: *const yaml_char_t = ptr::null::<yaml_char_t>();
+    let mut length: size_t = 0;
+    if !(*parser).keep_scalar_source
+        || yaml_parser_source_slice(
+            parser,
+            (*event).start_mark,
+            (*event).end_mark,
+            addr_of_mut!(text),
+            addr_of_mut!(length),
+        )
+        .fail
+    {
+        return ptr::null_mut::<yaml_scalar_source_t>();
+    }
+    let mut source = slice::from_raw_parts(text, length as usize);
+    // The event starts at the anchor or the tag of the scalar, if any.
+    while let [b'&' | b'!', ..] = source {
+        while let [first, rest @ ..] = source {
+            if matches!(first, b' ' | b'\t' | b'\r' | b'\n') {
+                break;
+            }
+            source = rest;
+        }
+        loop {
+            match source {
+                [b' ' | b'\t' | b'\r' | b'\n', rest @ ..] => source = rest,
+                [b'#', ..] => {
+                    while let [first, rest @ ..] = source {
+                        if matches!(first, b'\r' | b'\n') {
+                            break;
+                        }
+                        source = rest;
+                    }
+                }
+                _ => break,
+            }
+        }
+    }
+    let style = (*event).data.scalar.style;
+    if style != YAML_LITERAL_SCALAR_STYLE && style != YAML_FOLDED_SCALAR_STYLE {
+        return yaml_scalar_source_new(
+            source.as_ptr(),
+            source.len() as size_t,
+            (*event).data.scalar.value,
+            (*event).data.scalar.length,
+            style,
+        );
+    }
+    // Drop the comment after the block scalar header, and the indentation of
+    // the line after the scalar.
+    let header = source
+        .iter()
+        .position(|ch| !matches!(ch, b'|' | b'>' | b'+' | b'-' | b'0'..=b'9'))
+        .unwrap_or(source.len());
+    let body = source
+        .iter()
+        .position(|ch| matches!(ch, b'\r' | b'\n'))
+        .unwrap_or(source.len());
+    let mut end = source.len();
+    if let Some(last_break) = source.iter().rposition(|ch| matches!(ch, b'\r' | b'\n')) {
+        if source[last_break + 1..]
+            .iter()
+            .all(|ch| matches!(ch, b' ' | b'\t'))
+        {
+            end = last_break + 1;
+        }
+    }
+    let mut block: Vec<yaml_char_t> = Vec::with_capacity(header + end - body);
+    block.extend_from_slice(&source[..header]);
+    block.extend_from_slice(&source[body..end]);
+    yaml_scalar_source_new(
+        block.as_ptr(),
+        block.len() as size_t,
+        (*event).data.scalar.value,
+        (*event).data.scalar.length,
+        style,
+    )
+}
+
 unsafe fn yaml_parser_load_sequence(
     parser: *mut yaml_parser_t,
     event: *mut yaml_event_t,
@@ -451,7 +771,13 @@ unsafe fn yaml_parser_load_sequence(
             if STACK_LIMIT!(parser, *ctx).fail {
                 return FAIL;
             }
-            PUSH!(*ctx, index);
+            PUSH!(
+                *ctx,
+                loader_frame {
+                    node: index,
+                    key: 0
+                }
+            );
             return OK;
         }
     }
@@ -466,7 +792,7 @@ unsafe fn yaml_parser_load_sequence_end(
     ctx: *mut loader_ctx,
 ) -> Success {
     __assert!(((*ctx).top).c_offset_from((*ctx).start) as libc::c_long > 0_i64);
-    let index: libc::c_int = *(*ctx).top.wrapping_offset(-1_isize);
+    let index: libc::c_int = (*(*ctx).top.wrapping_offset(-1_isize)).node;
     __assert!(
         (*((*(*parser).document).nodes.start).wrapping_offset((index - 1) as isize)).type_
             == YAML_SEQUENCE_NODE
@@ -549,7 +875,13 @@ unsafe fn yaml_parser_load_mapping(
             if STACK_LIMIT!(parser, *ctx).fail {
                 return FAIL;
             }
-            PUSH!(*ctx, index);
+            PUSH!(
+                *ctx,
+                loader_frame {
+                    node: index,
+                    key: 0
+                }
+            );
             return OK;
         }
     }
@@ -564,7 +896,7 @@ unsafe fn yaml_parser_load_mapping_end(
     ctx: *mut loader_ctx,
 ) -> Success {
     __assert!(((*ctx).top).c_offset_from((*ctx).start) as libc::c_long > 0_i64);
-    let index: libc::c_int = *(*ctx).top.wrapping_offset(-1_isize);
+    let index: libc::c_int = (*(*ctx).top.wrapping_offset(-1_isize)).node;
     __assert!(
         (*((*(*parser).document).nodes.start).wrapping_offset((index - 1) as isize)).type_
             == YAML_MAPPING_NODE
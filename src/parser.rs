@@ -5,8 +5,8 @@ use crate::scanner::yaml_parser_fetch_more_tokens;
 use crate::success::{Success, FAIL, OK};
 use crate::yaml::{size_t, yaml_char_t};
 use crate::{
-    libc, yaml_event_t, yaml_mark_t, yaml_parser_t, yaml_tag_directive_t, yaml_token_t,
-    yaml_version_directive_t, YAML_ALIAS_EVENT, YAML_ALIAS_TOKEN, YAML_ANCHOR_TOKEN,
+    libc, yaml_event_t, yaml_mark_t, yaml_parser_t, yaml_stream_info_t, yaml_tag_directive_t,
+    yaml_token_t, yaml_version_directive_t, YAML_ALIAS_EVENT, YAML_ALIAS_TOKEN, YAML_ANCHOR_TOKEN,
     YAML_BLOCK_END_TOKEN, YAML_BLOCK_ENTRY_TOKEN, YAML_BLOCK_MAPPING_START_TOKEN,
     YAML_BLOCK_MAPPING_STYLE, YAML_BLOCK_SEQUENCE_START_TOKEN, YAML_BLOCK_SEQUENCE_STYLE,
     YAML_DOCUMENT_END_EVENT, YAML_DOCUMENT_END_TOKEN, YAML_DOCUMENT_START_EVENT,
@@ -47,6 +47,7 @@ unsafe fn SKIP_TOKEN(parser: *mut yaml_parser_t) {
     let fresh3 = addr_of_mut!((*parser).tokens_parsed);
     *fresh3 = (*fresh3).wrapping_add(1);
     (*parser).stream_end_produced = (*(*parser).tokens.head).type_ == YAML_STREAM_END_TOKEN;
+    (*parser).consumed_end = (*(*parser).tokens.head).end_mark;
     let fresh4 = addr_of_mut!((*parser).tokens.head);
     *fresh4 = (*fresh4).wrapping_offset(1);
 }
@@ -77,7 +78,62 @@ pub unsafe fn yaml_parser_parse(parser: *mut yaml_parser_t, event: *mut yaml_eve
     {
         return OK;
     }
-    yaml_parser_state_machine(parser, event)
+    (*parser).consumed_start = (*parser).consumed_end;
+    if yaml_parser_state_machine(parser, event).fail {
+        return FAIL;
+    }
+    (*parser).events_produced = (*parser).events_produced.force_add(1);
+    if (*event).type_ == YAML_DOCUMENT_START_EVENT {
+        (*parser).documents_produced = (*parser).documents_produced.force_add(1);
+    }
+    OK
+}
+
+/// Get the range of input consumed for the last event.
+///
+/// The range starts where the input consumed for the previous event ended and
+/// ends after the last token used for the event just produced, so consecutive
+/// ranges cover the input without gaps, including any whitespace and comments.
+/// Events that are not backed by a token, such as empty scalars or implicit
+/// DOCUMENT-END events, get an empty range.
+pub unsafe fn yaml_parser_get_consumed_span(
+    parser: *mut yaml_parser_t,
+    start_mark: *mut yaml_mark_t,
+    end_mark: *mut yaml_mark_t,
+) {
+    __assert!(!parser.is_null());
+    __assert!(!start_mark.is_null());
+    __assert!(!end_mark.is_null());
+    *start_mark = (*parser).consumed_start;
+    *end_mark = (*parser).consumed_end;
+}
+
+/// Get the summary of the parsed stream.
+///
+/// The function succeeds only after the parser has produced the
+/// YAML_STREAM_END_EVENT, either through yaml_parser_parse() or
+/// yaml_parser_load(). The summary stays available until the parser object is
+/// destroyed.
+pub unsafe fn yaml_parser_get_stream_info(
+    parser: *mut yaml_parser_t,
+    info: *mut yaml_stream_info_t,
+) -> Success {
+    __assert!(!parser.is_null());
+    __assert!(!info.is_null());
+    if (*parser).state != YAML_PARSE_END_STATE {
+        return FAIL;
+    }
+    memset(
+        info as *mut libc::c_void,
+        0,
+        size_of::<yaml_stream_info_t>() as libc::c_ulong,
+    );
+    (*info).encoding = (*parser).encoding;
+    (*info).bom = (*parser).bom;
+    (*info).line_break = (*parser).line_break;
+    (*info).documents = (*parser).documents_produced;
+    (*info).events = (*parser).events_produced;
+    OK
 }
 
 unsafe fn yaml_parser_set_parser_error(
@@ -91,6 +147,36 @@ unsafe fn yaml_parser_set_parser_error(
     (*parser).problem_mark = problem_mark;
 }
 
+unsafe fn yaml_parser_resolve_implicit_tag(
+    parser: *mut yaml_parser_t,
+    token: *mut yaml_token_t,
+    tag: *mut *mut yaml_char_t,
+) -> Success {
+    let resolver = match (*parser).implicit_resolver {
+        Some(resolver) => resolver,
+        None => return OK,
+    };
+    let mut resolved: *const yaml_char_t = ptr::null();
+    if resolver(
+        (*parser).implicit_resolver_data,
+        (*token).data.scalar.value,
+        (*token).data.scalar.length,
+        addr_of_mut!(resolved),
+    ) == 0
+    {
+        yaml_parser_set_parser_error(
+            parser,
+            b"implicit tag resolver failed\0" as *const u8 as *const libc::c_char,
+            (*token).start_mark,
+        );
+        return FAIL;
+    }
+    if !resolved.is_null() {
+        *tag = yaml_strdup(resolved as *mut yaml_char_t);
+    }
+    OK
+}
+
 unsafe fn yaml_parser_set_parser_error_context(
     parser: *mut yaml_parser_t,
     context: *const libc::c_char,
@@ -581,6 +667,13 @@ unsafe fn yaml_parser_parse_node(
                     } else if tag.is_null() {
                         quoted_implicit = true;
                     }
+                    if (*token).data.scalar.style == YAML_PLAIN_SCALAR_STYLE
+                        && tag.is_null()
+                        && yaml_parser_resolve_implicit_tag(parser, token, addr_of_mut!(tag)).fail
+                    {
+                        yaml_free(anchor as *mut libc::c_void);
+                        return FAIL;
+                    }
                     (*parser).state = POP!((*parser).states);
                     memset(
                         event as *mut libc::c_void,
@@ -600,10 +693,16 @@ unsafe fn yaml_parser_parse_node(
                     (*event).data.scalar.plain_implicit = plain_implicit;
                     (*event).data.scalar.quoted_implicit = quoted_implicit;
                     (*event).data.scalar.style = (*token).data.scalar.style;
+                    (*event).data.scalar.chomping = (*token).data.scalar.chomping;
+                    (*event).data.scalar.indentation_indicator =
+                        (*token).data.scalar.indentation_indicator;
                     SKIP_TOKEN(parser);
                     return OK;
                 } else if (*token).type_ == YAML_FLOW_SEQUENCE_START_TOKEN {
                     end_mark = (*token).end_mark;
+                    // The indicator is skipped along with the first entry,
+                    // but it is consumed for this event.
+                    (*parser).consumed_end = end_mark;
                     (*parser).state = YAML_PARSE_FLOW_SEQUENCE_FIRST_ENTRY_STATE;
                     memset(
                         event as *mut libc::c_void,
@@ -622,6 +721,9 @@ unsafe fn yaml_parser_parse_node(
                     return OK;
                 } else if (*token).type_ == YAML_FLOW_MAPPING_START_TOKEN {
                     end_mark = (*token).end_mark;
+                    // The indicator is skipped along with the first entry,
+                    // but it is consumed for this event.
+                    (*parser).consumed_end = end_mark;
                     (*parser).state = YAML_PARSE_FLOW_MAPPING_FIRST_KEY_STATE;
                     memset(
                         event as *mut libc::c_void,
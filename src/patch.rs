@@ -0,0 +1,441 @@
+use crate::api::{yaml_document_add_scalar, yaml_document_delete, yaml_stack_extend};
+use crate::document::{
+    yaml_document_compare_subtrees, yaml_document_copy_subtree, yaml_document_duplicate,
+    yaml_document_mark_reachable, yaml_document_move_to_root, yaml_document_node_count,
+    yaml_document_remove_unreachable,
+};
+use crate::success::{Success, FAIL, OK};
+use crate::yaml::{size_t, yaml_char_t};
+use crate::{
+    libc, yaml_compare_options_t, yaml_document_t, yaml_node_item_t, yaml_node_pair_t, yaml_node_t,
+    yaml_patch_op_t, PointerExt, YAML_ALIAS_NODE, YAML_ANY_SCALAR_STYLE, YAML_MAPPING_NODE,
+    YAML_PATCH_ADD, YAML_PATCH_COPY, YAML_PATCH_MOVE, YAML_PATCH_REMOVE, YAML_PATCH_REPLACE,
+    YAML_PATCH_TEST, YAML_SCALAR_NODE, YAML_SEQUENCE_NODE,
+};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::mem::MaybeUninit;
+use core::ptr::{self, addr_of_mut};
+use core::slice;
+
+/// Apply a patch to a document.
+///
+/// The `count` operations of `ops` are applied in turn with the semantics of
+/// JSON Patch (RFC 6902). Their paths are JSON Pointers (RFC 6901) made of the
+/// keys of mappings and the indices of sequences, where the empty path stands
+/// for the root node and `-` for the end of a sequence an item is added to.
+/// Mapping keys are matched with the values of scalar keys, and alias nodes are
+/// followed on the way to the location. The root node can be replaced but not
+/// removed.
+///
+/// The values added, replaced with or tested against are nodes of the `values`
+/// document, which are copied without their anchors. The copies, as well as
+/// those made by YAML_PATCH_COPY, record the transformation "patch" and the id
+/// of the node they were made from. YAML_PATCH_TEST compares
+/// nodes regardless of their styles, marks, anchors and key order. The nodes no
+/// longer reachable from the root node are removed, as by
+/// yaml_document_remove_node().
+///
+/// A change made through a node that is shared by several paths is seen at all
+/// of them. The changes found by yaml_document_diff() therefore turn a document
+/// into the other one only if the document has no shared nodes.
+///
+/// If any operation fails, the function fails and the document is left
+/// unchanged.
+pub unsafe fn yaml_document_apply_patch(
+    document: *mut yaml_document_t,
+    ops: *const yaml_patch_op_t,
+    count: size_t,
+    values: *mut yaml_document_t,
+) -> Success {
+    __assert!(!document.is_null());
+    __assert!(!ops.is_null() || count == 0);
+    let mut copy = MaybeUninit::<yaml_document_t>::uninit();
+    let copy = copy.as_mut_ptr();
+    if yaml_document_duplicate(copy, document).fail {
+        return FAIL;
+    }
+    let mut reachable = vec![false; yaml_document_node_count(copy) as usize];
+    yaml_document_mark_reachable(copy, reachable.as_mut_ptr());
+    let mut success = OK;
+    for op in slice::from_raw_parts(ops, count as usize) {
+        if yaml_patch_apply_op(copy, op, values, &mut reachable).fail {
+            success = FAIL;
+            break;
+        }
+    }
+    if success.ok {
+        let nodes = yaml_document_node_count(copy);
+        reachable.resize(nodes as usize, true);
+        success = yaml_document_remove_unreachable(copy, reachable.as_mut_ptr(), nodes);
+    }
+    if success.ok {
+        yaml_document_delete(document);
+        *document = *copy;
+    } else {
+        yaml_document_delete(copy);
+    }
+    success
+}
+
+unsafe fn yaml_patch_apply_op(
+    document: *mut yaml_document_t,
+    op: *const yaml_patch_op_t,
+    values: *mut yaml_document_t,
+    reachable: &mut Vec<bool>,
+) -> Success {
+    let path = match yaml_patch_parse_pointer((*op).path) {
+        Some(path) => path,
+        None => return FAIL,
+    };
+    match (*op).type_ {
+        YAML_PATCH_ADD | YAML_PATCH_REPLACE => {
+            let replace = (*op).type_ == YAML_PATCH_REPLACE;
+            if replace && yaml_patch_get(document, &path).is_none() {
+                return FAIL;
+            }
+            let value = yaml_patch_copy_value(document, values, (*op).value);
+            if value == 0 {
+                return FAIL;
+            }
+            yaml_patch_insert(document, &path, value, replace, reachable)
+        }
+        YAML_PATCH_REMOVE => {
+            if yaml_patch_remove(document, &path).is_none() {
+                return FAIL;
+            }
+            OK
+        }
+        YAML_PATCH_MOVE | YAML_PATCH_COPY => {
+            let from = match yaml_patch_parse_pointer((*op).from) {
+                Some(from) => from,
+                None => return FAIL,
+            };
+            if (*op).type_ == YAML_PATCH_MOVE {
+                if from == path {
+                    return if yaml_patch_get(document, &path).is_some() {
+                        OK
+                    } else {
+                        FAIL
+                    };
+                }
+                if path.starts_with(&from) {
+                    return FAIL;
+                }
+                match yaml_patch_remove(document, &from) {
+                    Some(value) => yaml_patch_insert(document, &path, value, false, reachable),
+                    None => FAIL,
+                }
+            } else {
+                let value = match yaml_patch_get(document, &from) {
+                    Some(value) => yaml_document_copy_subtree(
+                        document,
+                        document,
+                        value,
+                        b"patch\0" as *const u8 as *const libc::c_char,
+                    ),
+                    None => return FAIL,
+                };
+                if value == 0 {
+                    return FAIL;
+                }
+                yaml_patch_insert(document, &path, value, false, reachable)
+            }
+        }
+        YAML_PATCH_TEST => {
+            if values.is_null() || !yaml_patch_valid_node(values, (*op).value) {
+                return FAIL;
+            }
+            let id = match yaml_patch_get(document, &path) {
+                Some(id) => yaml_patch_resolve(document, id),
+                None => return FAIL,
+            };
+            let options = yaml_compare_options_t {
+                ignore_styles: true,
+                ignore_marks: true,
+                ignore_anchors: true,
+                ignore_key_order: true,
+            };
+            let value = yaml_patch_resolve(values, (*op).value);
+            let mut equal = false;
+            if yaml_document_compare_subtrees(document, id, values, value, &options, &mut equal)
+                .fail
+                || !equal
+            {
+                return FAIL;
+            }
+            OK
+        }
+    }
+}
+
+/// Split a JSON Pointer into its unescaped reference tokens.
+unsafe fn yaml_patch_parse_pointer(pointer: *const yaml_char_t) -> Option<Vec<Vec<u8>>> {
+    if pointer.is_null() {
+        return None;
+    }
+    let mut end = pointer;
+    while *end != b'\0' {
+        end = end.add(1);
+    }
+    let pointer = slice::from_raw_parts(pointer, end.c_offset_from(pointer) as usize);
+    let mut tokens = Vec::new();
+    if pointer.is_empty() {
+        return Some(tokens);
+    }
+    if pointer[0] != b'/' {
+        return None;
+    }
+    for token in pointer[1..].split(|&byte| byte == b'/') {
+        let mut unescaped = Vec::with_capacity(token.len());
+        let mut bytes = token.iter();
+        while let Some(&byte) = bytes.next() {
+            if byte != b'~' {
+                unescaped.push(byte);
+                continue;
+            }
+            match bytes.next() {
+                Some(b'0') => unescaped.push(b'~'),
+                Some(b'1') => unescaped.push(b'/'),
+                _ => return None,
+            }
+        }
+        tokens.push(unescaped);
+    }
+    Some(tokens)
+}
+
+/// Parse an array index, which has no leading zeros.
+fn yaml_patch_parse_index(token: &[u8]) -> Option<usize> {
+    if token.is_empty()
+        || !token.iter().all(u8::is_ascii_digit)
+        || token.len() > 1 && token[0] == b'0'
+    {
+        return None;
+    }
+    let mut index: usize = 0;
+    for digit in token {
+        index = index
+            .checked_mul(10)?
+            .checked_add((digit - b'0') as usize)?;
+    }
+    Some(index)
+}
+
+unsafe fn yaml_patch_valid_node(document: *mut yaml_document_t, id: libc::c_int) -> bool {
+    id > 0 && id <= yaml_document_node_count(document)
+}
+
+unsafe fn yaml_patch_node(document: *mut yaml_document_t, id: libc::c_int) -> *mut yaml_node_t {
+    (*document).nodes.start.add(id as usize - 1)
+}
+
+unsafe fn yaml_patch_resolve(document: *mut yaml_document_t, mut id: libc::c_int) -> libc::c_int {
+    while (*yaml_patch_node(document, id)).type_ == YAML_ALIAS_NODE {
+        id = (*yaml_patch_node(document, id)).data.alias.target;
+    }
+    id
+}
+
+unsafe fn yaml_patch_copy_value(
+    document: *mut yaml_document_t,
+    values: *mut yaml_document_t,
+    value: libc::c_int,
+) -> libc::c_int {
+    if values.is_null() || !yaml_patch_valid_node(values, value) {
+        return 0;
+    }
+    yaml_document_copy_subtree(
+        document,
+        values,
+        value,
+        b"patch\0" as *const u8 as *const libc::c_char,
+    )
+}
+
+/// Find the position of the pair with the scalar key `key` in a mapping.
+unsafe fn yaml_patch_find_key(
+    document: *mut yaml_document_t,
+    node: *mut yaml_node_t,
+    key: &[u8],
+) -> Option<usize> {
+    let pairs = (*node).data.mapping.pairs;
+    let mut index: usize = 0;
+    while pairs.start.add(index) < pairs.top {
+        let key_node = yaml_patch_node(document, (*pairs.start.add(index)).key);
+        if (*key_node).type_ == YAML_SCALAR_NODE
+            && slice::from_raw_parts(
+                (*key_node).data.scalar.value,
+                (*key_node).data.scalar.length as usize,
+            ) == key
+        {
+            return Some(index);
+        }
+        index += 1;
+    }
+    None
+}
+
+/// Find the collection holding the location of a non-empty path.
+unsafe fn yaml_patch_get_parent(
+    document: *mut yaml_document_t,
+    path: &[Vec<u8>],
+) -> Option<*mut yaml_node_t> {
+    let id = yaml_patch_get(document, &path[..path.len() - 1])?;
+    Some(yaml_patch_node(document, yaml_patch_resolve(document, id)))
+}
+
+/// Find the node at a path.
+unsafe fn yaml_patch_get(document: *mut yaml_document_t, path: &[Vec<u8>]) -> Option<libc::c_int> {
+    if yaml_document_node_count(document) == 0 {
+        return None;
+    }
+    let mut id: libc::c_int = 1;
+    for token in path {
+        let node = yaml_patch_node(document, yaml_patch_resolve(document, id));
+        id = match (*node).type_ {
+            YAML_MAPPING_NODE => {
+                let index = yaml_patch_find_key(document, node, token)?;
+                (*(*node).data.mapping.pairs.start.add(index)).value
+            }
+            YAML_SEQUENCE_NODE => {
+                let items = (*node).data.sequence.items;
+                let index = yaml_patch_parse_index(token)?;
+                if index >= items.top.c_offset_from(items.start) as usize {
+                    return None;
+                }
+                *items.start.add(index)
+            }
+            _ => return None,
+        };
+    }
+    Some(id)
+}
+
+/// Remove the node at a non-empty path from its parent and return it.
+unsafe fn yaml_patch_remove(
+    document: *mut yaml_document_t,
+    path: &[Vec<u8>],
+) -> Option<libc::c_int> {
+    if path.is_empty() {
+        return None;
+    }
+    let node = yaml_patch_get_parent(document, path)?;
+    let token = &path[path.len() - 1];
+    match (*node).type_ {
+        YAML_MAPPING_NODE => {
+            let index = yaml_patch_find_key(document, node, token)?;
+            let pairs = addr_of_mut!((*node).data.mapping.pairs);
+            let pair: *mut yaml_node_pair_t = (*pairs).start.add(index);
+            let value = (*pair).value;
+            ptr::copy(
+                pair.add(1),
+                pair,
+                (*pairs).top.c_offset_from(pair.add(1)) as usize,
+            );
+            (*pairs).top = (*pairs).top.sub(1);
+            Some(value)
+        }
+        YAML_SEQUENCE_NODE => {
+            let items = addr_of_mut!((*node).data.sequence.items);
+            let index = yaml_patch_parse_index(token)?;
+            if index >= (*items).top.c_offset_from((*items).start) as usize {
+                return None;
+            }
+            let item: *mut yaml_node_item_t = (*items).start.add(index);
+            let value = *item;
+            ptr::copy(
+                item.add(1),
+                item,
+                (*items).top.c_offset_from(item.add(1)) as usize,
+            );
+            (*items).top = (*items).top.sub(1);
+            Some(value)
+        }
+        _ => None,
+    }
+}
+
+/// Put the node `value` at a path, replacing the node there if `replace` is
+/// set and adding it otherwise.
+unsafe fn yaml_patch_insert(
+    document: *mut yaml_document_t,
+    path: &[Vec<u8>],
+    value: libc::c_int,
+    replace: bool,
+    reachable: &mut Vec<bool>,
+) -> Success {
+    if path.is_empty() {
+        if value != 1 {
+            if yaml_document_move_to_root(document, value).fail {
+                return FAIL;
+            }
+            if (value as usize) <= reachable.len() {
+                reachable.remove(value as usize - 1);
+            }
+            reachable[0] = false;
+        }
+        return OK;
+    }
+    let node = match yaml_patch_get_parent(document, path) {
+        Some(node) => node,
+        None => return FAIL,
+    };
+    let token = &path[path.len() - 1];
+    match (*node).type_ {
+        YAML_MAPPING_NODE => {
+            if let Some(index) = yaml_patch_find_key(document, node, token) {
+                (*(*node).data.mapping.pairs.start.add(index)).value = value;
+                return OK;
+            }
+            if replace {
+                return FAIL;
+            }
+            let key = yaml_document_add_scalar(
+                document,
+                ptr::null::<yaml_char_t>(),
+                token.as_ptr(),
+                token.len() as libc::c_int,
+                YAML_ANY_SCALAR_STYLE,
+            );
+            if key == 0 {
+                return FAIL;
+            }
+            // The node may have moved as the scalar was added.
+            let node = match yaml_patch_get_parent(document, path) {
+                Some(node) => node,
+                None => return FAIL,
+            };
+            PUSH!((*node).data.mapping.pairs, yaml_node_pair_t { key, value });
+            OK
+        }
+        YAML_SEQUENCE_NODE => {
+            let items = addr_of_mut!((*node).data.sequence.items);
+            let count = (*items).top.c_offset_from((*items).start) as usize;
+            let index = if !replace && token == b"-" {
+                count
+            } else {
+                match yaml_patch_parse_index(token) {
+                    Some(index) => index,
+                    None => return FAIL,
+                }
+            };
+            if replace {
+                if index >= count {
+                    return FAIL;
+                }
+                *(*items).start.add(index) = value;
+                return OK;
+            }
+            if index > count {
+                return FAIL;
+            }
+            PUSH!(*items, value);
+            let item: *mut yaml_node_item_t = (*items).start.add(index);
+            ptr::copy(item, item.add(1), count - index);
+            *item = value;
+            OK
+        }
+        _ => FAIL,
+    }
+}
@@ -52,6 +52,7 @@ unsafe fn yaml_parser_determine_encoding(parser: *mut yaml_parser_t) -> Success
         (*parser).encoding = YAML_UTF16LE_ENCODING;
         let fresh1 = addr_of_mut!((*parser).raw_buffer.pointer);
         *fresh1 = (*fresh1).wrapping_offset(2_isize);
+        (*parser).bom = true;
         let fresh2 = addr_of_mut!((*parser).offset);
         *fresh2 = (*fresh2 as libc::c_ulong).force_add(2_u64) as size_t;
     } else if (*parser)
@@ -68,6 +69,7 @@ unsafe fn yaml_parser_determine_encoding(parser: *mut yaml_parser_t) -> Success
         (*parser).encoding = YAML_UTF16BE_ENCODING;
         let fresh3 = addr_of_mut!((*parser).raw_buffer.pointer);
         *fresh3 = (*fresh3).wrapping_offset(2_isize);
+        (*parser).bom = true;
         let fresh4 = addr_of_mut!((*parser).offset);
         *fresh4 = (*fresh4 as libc::c_ulong).force_add(2_u64) as size_t;
     } else if (*parser)
@@ -84,6 +86,7 @@ unsafe fn yaml_parser_determine_encoding(parser: *mut yaml_parser_t) -> Success
         (*parser).encoding = YAML_UTF8_ENCODING;
         let fresh5 = addr_of_mut!((*parser).raw_buffer.pointer);
         *fresh5 = (*fresh5).wrapping_offset(3_isize);
+        (*parser).bom = true;
         let fresh6 = addr_of_mut!((*parser).offset);
         *fresh6 = (*fresh6 as libc::c_ulong).force_add(3_u64) as size_t;
     } else {
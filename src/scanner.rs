@@ -1,27 +1,35 @@
 use crate::api::{
     yaml_free, yaml_malloc, yaml_queue_extend, yaml_stack_extend, yaml_string_extend,
-    yaml_string_join,
+    yaml_string_join, yaml_token_delete,
 };
 use crate::externs::{memcpy, memmove, memset, strcmp, strlen};
 use crate::ops::{ForceAdd as _, ForceMul as _};
 use crate::reader::yaml_parser_update_buffer;
 use crate::success::{Success, FAIL, OK};
-use crate::yaml::{ptrdiff_t, size_t, yaml_char_t, yaml_string_t, NULL_STRING};
+use crate::yaml::{ptrdiff_t, size_t, yaml_char_t, yaml_comment_t, yaml_string_t, NULL_STRING};
 use crate::{
     libc, yaml_mark_t, yaml_parser_t, yaml_simple_key_t, yaml_token_t, yaml_token_type_t,
-    PointerExt, YAML_ALIAS_TOKEN, YAML_ANCHOR_TOKEN, YAML_BLOCK_END_TOKEN, YAML_BLOCK_ENTRY_TOKEN,
-    YAML_BLOCK_MAPPING_START_TOKEN, YAML_BLOCK_SEQUENCE_START_TOKEN, YAML_DOCUMENT_END_TOKEN,
+    PointerExt, YAML_ALIAS_TOKEN, YAML_ANCHOR_TOKEN, YAML_ANY_BREAK, YAML_BLOCK_END_TOKEN,
+    YAML_BLOCK_ENTRY_TOKEN, YAML_BLOCK_MAPPING_START_TOKEN, YAML_BLOCK_SEQUENCE_START_TOKEN,
+    YAML_CLIP_CHOMPING, YAML_CRLN_BREAK, YAML_CR_BREAK, YAML_DOCUMENT_END_TOKEN,
     YAML_DOCUMENT_START_TOKEN, YAML_DOUBLE_QUOTED_SCALAR_STYLE, YAML_FLOW_ENTRY_TOKEN,
     YAML_FLOW_MAPPING_END_TOKEN, YAML_FLOW_MAPPING_START_TOKEN, YAML_FLOW_SEQUENCE_END_TOKEN,
-    YAML_FLOW_SEQUENCE_START_TOKEN, YAML_FOLDED_SCALAR_STYLE, YAML_KEY_TOKEN,
-    YAML_LITERAL_SCALAR_STYLE, YAML_MEMORY_ERROR, YAML_NO_ERROR, YAML_PLAIN_SCALAR_STYLE,
-    YAML_SCALAR_TOKEN, YAML_SCANNER_ERROR, YAML_SINGLE_QUOTED_SCALAR_STYLE, YAML_STREAM_END_TOKEN,
-    YAML_STREAM_START_TOKEN, YAML_TAG_DIRECTIVE_TOKEN, YAML_TAG_TOKEN, YAML_VALUE_TOKEN,
+    YAML_FLOW_SEQUENCE_START_TOKEN, YAML_FOLDED_SCALAR_STYLE, YAML_KEEP_CHOMPING, YAML_KEY_TOKEN,
+    YAML_LITERAL_SCALAR_STYLE, YAML_LN_BREAK, YAML_MEMORY_ERROR, YAML_NO_ERROR,
+    YAML_PARSE_DOCUMENT_START_STATE, YAML_PARSE_END_STATE,
+    YAML_PARSE_IMPLICIT_DOCUMENT_START_STATE, YAML_PARSE_STREAM_START_STATE,
+    YAML_PLAIN_SCALAR_STYLE, YAML_SCALAR_TOKEN, YAML_SCANNER_ERROR,
+    YAML_SINGLE_QUOTED_SCALAR_STYLE, YAML_STREAM_END_TOKEN, YAML_STREAM_START_TOKEN,
+    YAML_STRIP_CHOMPING, YAML_TAG_DIRECTIVE_TOKEN, YAML_TAG_TOKEN, YAML_VALUE_TOKEN,
     YAML_VERSION_DIRECTIVE_TOKEN,
 };
 use core::mem::{size_of, MaybeUninit};
 use core::ptr::{self, addr_of_mut};
 
+/// The number of bytes after which a simple key can no longer be
+/// completed.
+pub(crate) const MAX_SIMPLE_KEY_LENGTH: u64 = 1024_u64;
+
 unsafe fn CACHE(parser: *mut yaml_parser_t, length: size_t) -> Success {
     if (*parser).unread >= length {
         OK
@@ -38,7 +46,23 @@ unsafe fn SKIP(parser: *mut yaml_parser_t) {
     (*parser).buffer.pointer = (*parser).buffer.pointer.wrapping_offset(width as isize);
 }
 
+unsafe fn yaml_parser_note_line_break(parser: *mut yaml_parser_t) {
+    if (*parser).line_break != YAML_ANY_BREAK {
+        return;
+    }
+    if CHECK_AT!((*parser).buffer, b'\r', 0) {
+        (*parser).line_break = if CHECK_AT!((*parser).buffer, b'\n', 1) {
+            YAML_CRLN_BREAK
+        } else {
+            YAML_CR_BREAK
+        };
+    } else if CHECK_AT!((*parser).buffer, b'\n', 0) {
+        (*parser).line_break = YAML_LN_BREAK;
+    }
+}
+
 unsafe fn SKIP_LINE(parser: *mut yaml_parser_t) {
+    yaml_parser_note_line_break(parser);
     if IS_CRLF!((*parser).buffer) {
         (*parser).mark.index = (*parser).mark.index.force_add(2);
         (*parser).mark.column = 0;
@@ -65,6 +89,7 @@ unsafe fn READ(parser: *mut yaml_parser_t, string: *mut yaml_string_t) {
 }
 
 unsafe fn READ_LINE(parser: *mut yaml_parser_t, string: *mut yaml_string_t) {
+    yaml_parser_note_line_break(parser);
     STRING_EXTEND!(*string);
     if CHECK_AT!((*parser).buffer, b'\r', 0) && CHECK_AT!((*parser).buffer, b'\n', 1) {
         *(*string).pointer = b'\n';
@@ -160,6 +185,105 @@ pub unsafe fn yaml_parser_scan(parser: *mut yaml_parser_t, token: *mut yaml_toke
     OK
 }
 
+/// Discard input up to the start of the next document.
+///
+/// The rest of the current document is skipped without being tokenized: the
+/// input is read line by line until a `---` or `...` indicator at column 0, or
+/// the end of the stream. Scanning and parsing continue from that indicator,
+/// so the next call of yaml_parser_scan() returns its token, and the next call
+/// of yaml_parser_parse() returns the DOCUMENT-START event of the next document
+/// (or STREAM-END). No further events are produced for the skipped document,
+/// including its DOCUMENT-END.
+///
+/// Like the indicators themselves, the check does not look inside scalars, so
+/// a `---` or `...` line within a quoted scalar also stops the skip.
+pub unsafe fn yaml_parser_skip_to_next_document(parser: *mut yaml_parser_t) -> Success {
+    __assert!(!parser.is_null());
+    if (*parser).stream_end_produced || (*parser).error != YAML_NO_ERROR {
+        return OK;
+    }
+    let mut keep: *mut yaml_token_t = (*parser).tokens.head;
+    if keep != (*parser).tokens.tail && (*keep).type_ == YAML_STREAM_START_TOKEN {
+        keep = keep.add(1);
+    }
+    let mut boundary: *mut yaml_token_t = keep;
+    while boundary != (*parser).tokens.tail
+        && (*boundary).type_ != YAML_VERSION_DIRECTIVE_TOKEN
+        && (*boundary).type_ != YAML_TAG_DIRECTIVE_TOKEN
+        && (*boundary).type_ != YAML_DOCUMENT_START_TOKEN
+        && (*boundary).type_ != YAML_DOCUMENT_END_TOKEN
+        && (*boundary).type_ != YAML_STREAM_END_TOKEN
+    {
+        boundary = boundary.add(1);
+    }
+    if boundary != (*parser).tokens.tail {
+        while (*parser).tokens.head != boundary {
+            yaml_token_delete(addr_of_mut!(DEQUEUE!((*parser).tokens)));
+            (*parser).tokens_parsed = (*parser).tokens_parsed.force_add(1);
+        }
+    } else {
+        while (*parser).tokens.tail != keep {
+            (*parser).tokens.tail = (*parser).tokens.tail.sub(1);
+            yaml_token_delete((*parser).tokens.tail);
+        }
+        if (*parser).stream_start_produced {
+            (*parser).indents.top = (*parser).indents.start;
+            (*parser).indent = -1;
+            (*parser).flow_level = 0;
+            (*(*parser).simple_keys.start).possible = false;
+            (*parser).simple_keys.top = (*parser).simple_keys.start.add(1);
+            (*parser).not_simple_keys = 1;
+            (*parser).simple_key_allowed = true;
+        }
+        loop {
+            if CACHE(parser, 4_u64).fail {
+                return FAIL;
+            }
+            if IS_Z!((*parser).buffer) {
+                break;
+            }
+            if (*parser).mark.column == 0_u64
+                && (CHECK_AT!((*parser).buffer, b'-', 0)
+                    && CHECK_AT!((*parser).buffer, b'-', 1)
+                    && CHECK_AT!((*parser).buffer, b'-', 2)
+                    || CHECK_AT!((*parser).buffer, b'.', 0)
+                        && CHECK_AT!((*parser).buffer, b'.', 1)
+                        && CHECK_AT!((*parser).buffer, b'.', 2))
+                && IS_BLANKZ_AT!((*parser).buffer, 3)
+            {
+                break;
+            }
+            while !IS_BREAKZ!((*parser).buffer) {
+                SKIP(parser);
+                if CACHE(parser, 1_u64).fail {
+                    return FAIL;
+                }
+            }
+            if IS_BREAK!((*parser).buffer) {
+                if CACHE(parser, 2_u64).fail {
+                    return FAIL;
+                }
+                SKIP_LINE(parser);
+            }
+        }
+    }
+    (*parser).token_available = false;
+    if (*parser).state != YAML_PARSE_STREAM_START_STATE
+        && (*parser).state != YAML_PARSE_IMPLICIT_DOCUMENT_START_STATE
+        && (*parser).state != YAML_PARSE_END_STATE
+    {
+        (*parser).states.top = (*parser).states.start;
+        (*parser).marks.top = (*parser).marks.start;
+        while !STACK_EMPTY!((*parser).tag_directives) {
+            let tag_directive = POP!((*parser).tag_directives);
+            yaml_free(tag_directive.handle as *mut libc::c_void);
+            yaml_free(tag_directive.prefix as *mut libc::c_void);
+        }
+        (*parser).state = YAML_PARSE_DOCUMENT_START_STATE;
+    }
+    OK
+}
+
 unsafe fn yaml_parser_set_scanner_error(
     parser: *mut yaml_parser_t,
     context: *const libc::c_char,
@@ -169,10 +293,25 @@ unsafe fn yaml_parser_set_scanner_error(
     (*parser).error = YAML_SCANNER_ERROR;
     let fresh3 = addr_of_mut!((*parser).context);
     *fresh3 = context;
-    (*parser).context_mark = context_mark;
+    (*parser).context_mark = yaml_parser_origin_mark(parser, context_mark);
     let fresh4 = addr_of_mut!((*parser).problem);
     *fresh4 = problem;
-    (*parser).problem_mark = (*parser).mark;
+    (*parser).problem_mark = yaml_parser_origin_mark(parser, (*parser).mark);
+}
+
+/// Shift a mark on the first line of the input by the column the input starts
+/// at, see yaml_parser_set_input_string_with_origin().
+///
+/// The scanner keeps the columns of the first line from 0 to find the
+/// indentation, so marks are only shifted as they leave it.
+unsafe fn yaml_parser_origin_mark(
+    parser: *mut yaml_parser_t,
+    mut mark: yaml_mark_t,
+) -> yaml_mark_t {
+    if mark.line == (*parser).origin.line {
+        mark.column = mark.column.force_add((*parser).origin.column);
+    }
+    mark
 }
 
 pub(crate) unsafe fn yaml_parser_fetch_more_tokens(parser: *mut yaml_parser_t) -> Success {
@@ -206,6 +345,12 @@ pub(crate) unsafe fn yaml_parser_fetch_more_tokens(parser: *mut yaml_parser_t) -
             return FAIL;
         }
     }
+    if (*parser).tokens_shifted <= (*parser).tokens_parsed {
+        let token = (*parser).tokens.head;
+        (*token).start_mark = yaml_parser_origin_mark(parser, (*token).start_mark);
+        (*token).end_mark = yaml_parser_origin_mark(parser, (*token).end_mark);
+        (*parser).tokens_shifted = (*parser).tokens_parsed.force_add(1);
+    }
     (*parser).token_available = true;
     OK
 }
@@ -344,7 +489,7 @@ unsafe fn yaml_parser_stale_simple_keys(parser: *mut yaml_parser_t) -> Success {
     while simple_key != (*parser).simple_keys.top {
         if (*simple_key).possible
             && ((*simple_key).mark.line < (*parser).mark.line
-                || (*simple_key).mark.index.force_add(1024_u64) < (*parser).mark.index)
+                || (*simple_key).mark.index.force_add(MAX_SIMPLE_KEY_LENGTH) < (*parser).mark.index)
         {
             if (*simple_key).required {
                 yaml_parser_set_scanner_error(
@@ -941,13 +1086,8 @@ unsafe fn yaml_parser_scan_to_next_token(parser: *mut yaml_parser_t) -> Success
                 return FAIL;
             }
         }
-        if CHECK!((*parser).buffer, b'#') {
-            while !IS_BREAKZ!((*parser).buffer) {
-                SKIP(parser);
-                if CACHE(parser, 1_u64).fail {
-                    return FAIL;
-                }
-            }
+        if CHECK!((*parser).buffer, b'#') && yaml_parser_scan_comment(parser).fail {
+            return FAIL;
         }
         if !IS_BREAK!((*parser).buffer) {
             break;
@@ -963,6 +1103,48 @@ unsafe fn yaml_parser_scan_to_next_token(parser: *mut yaml_parser_t) -> Success
     OK
 }
 
+unsafe fn yaml_parser_scan_comment(parser: *mut yaml_parser_t) -> Success {
+    let start_mark: yaml_mark_t = (*parser).mark;
+    let mut string = NULL_STRING;
+    SKIP(parser);
+    if CACHE(parser, 1_u64).fail {
+        return FAIL;
+    }
+    if !(*parser).capture_comments {
+        while !IS_BREAKZ!((*parser).buffer) {
+            SKIP(parser);
+            if CACHE(parser, 1_u64).fail {
+                return FAIL;
+            }
+        }
+        return OK;
+    }
+    STRING_INIT!(string);
+    if CHECK!((*parser).buffer, b' ') {
+        SKIP(parser);
+        if CACHE(parser, 1_u64).fail {
+            yaml_free(string.start as *mut libc::c_void);
+            return FAIL;
+        }
+    }
+    while !IS_BREAKZ!((*parser).buffer) {
+        READ!(parser, string);
+        if CACHE(parser, 1_u64).fail {
+            yaml_free(string.start as *mut libc::c_void);
+            return FAIL;
+        }
+    }
+    PUSH!(
+        (*parser).comments,
+        yaml_comment_t {
+            value: string.start,
+            start_mark: yaml_parser_origin_mark(parser, start_mark),
+            end_mark: yaml_parser_origin_mark(parser, (*parser).mark),
+        }
+    );
+    OK
+}
+
 unsafe fn yaml_parser_scan_directive(
     parser: *mut yaml_parser_t,
     token: *mut yaml_token_t,
@@ -1058,18 +1240,8 @@ unsafe fn yaml_parser_scan_directive(
                     }
                 }
                 if current_block != 11397968426844348457 {
-                    if CHECK!((*parser).buffer, b'#') {
-                        loop {
-                            if IS_BREAKZ!((*parser).buffer) {
-                                current_block = 6669252993407410313;
-                                break;
-                            }
-                            SKIP(parser);
-                            if CACHE(parser, 1_u64).fail {
-                                current_block = 11397968426844348457;
-                                break;
-                            }
-                        }
+                    if CHECK!((*parser).buffer, b'#') && yaml_parser_scan_comment(parser).fail {
+                        current_block = 11397968426844348457;
                     } else {
                         current_block = 6669252993407410313;
                     }
@@ -1889,18 +2061,8 @@ unsafe fn yaml_parser_scan_block_scalar(
                     }
                 }
                 if current_block != 14984465786483313892 {
-                    if CHECK!((*parser).buffer, b'#') {
-                        loop {
-                            if IS_BREAKZ!((*parser).buffer) {
-                                current_block = 12997042908615822766;
-                                break;
-                            }
-                            SKIP(parser);
-                            if CACHE(parser, 1_u64).fail {
-                                current_block = 14984465786483313892;
-                                break;
-                            }
-                        }
+                    if CHECK!((*parser).buffer, b'#') && yaml_parser_scan_comment(parser).fail {
+                        current_block = 14984465786483313892;
                     } else {
                         current_block = 12997042908615822766;
                     }
@@ -2029,6 +2191,13 @@ unsafe fn yaml_parser_scan_block_scalar(
                                                 } else {
                                                     YAML_FOLDED_SCALAR_STYLE
                                                 };
+                                                (*token).data.scalar.chomping = match chomping {
+                                                    1 => YAML_KEEP_CHOMPING,
+                                                    -1 => YAML_STRIP_CHOMPING,
+                                                    _ => YAML_CLIP_CHOMPING,
+                                                };
+                                                (*token).data.scalar.indentation_indicator =
+                                                    increment != 0;
                                                 STRING_DEL!(leading_break);
                                                 STRING_DEL!(trailing_breaks);
                                                 return OK;
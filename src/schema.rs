@@ -0,0 +1,605 @@
+use crate::externs::strcmp;
+use crate::success::{Success, FAIL, OK};
+use crate::yaml::{size_t, yaml_char_t};
+use crate::{
+    libc, yaml_node_t, yaml_overflow_t, yaml_resolved_scalar_t, yaml_schema_options_t,
+    yaml_schema_t, yaml_value_type_t, YAML_1_1_SCHEMA, YAML_ANY_SCALAR_STYLE, YAML_BOOL_VALUE,
+    YAML_CORE_SCHEMA, YAML_FAILSAFE_SCHEMA, YAML_FLOAT_VALUE, YAML_INT_VALUE, YAML_JSON_SCHEMA,
+    YAML_NULL_VALUE, YAML_OVERFLOW_ERROR, YAML_OVERFLOW_FLOAT, YAML_OVERFLOW_SATURATE,
+    YAML_OVERFLOW_STRING, YAML_PLAIN_SCALAR_STYLE, YAML_STR_VALUE,
+};
+use alloc::string::String;
+use core::mem::MaybeUninit;
+use core::slice;
+
+const NULL_TAG: &[u8] = b"tag:yaml.org,2002:null\0";
+const BOOL_TAG: &[u8] = b"tag:yaml.org,2002:bool\0";
+const INT_TAG: &[u8] = b"tag:yaml.org,2002:int\0";
+const FLOAT_TAG: &[u8] = b"tag:yaml.org,2002:float\0";
+const STR_TAG: &[u8] = b"tag:yaml.org,2002:str\0";
+
+/// Resolve a scalar with a schema.
+///
+/// If `tag` is NULL, the scalar is taken to be plain and its type is resolved
+/// from the `value` of `length` bytes. The non-specific tag `!` of quoted
+/// scalars makes it a string. A tag the schema defines is checked against the
+/// value, and any other tag gives a string that keeps the tag.
+///
+/// The function fails if the value does not match the tag, or if a plain
+/// scalar matches none of the rules of the JSON schema.
+pub unsafe fn yaml_scalar_resolve(
+    schema: yaml_schema_t,
+    tag: *const yaml_char_t,
+    value: *const yaml_char_t,
+    length: size_t,
+    resolved: *mut yaml_resolved_scalar_t,
+) -> Success {
+    let mut options = MaybeUninit::<yaml_schema_options_t>::uninit();
+    yaml_schema_options_initialize(options.as_mut_ptr(), schema);
+    yaml_scalar_resolve_with_options(options.as_ptr(), tag, value, length, resolved)
+}
+
+/// Initialize the settings of scalar resolution with the defaults for
+/// `schema`.
+///
+/// By default, integers too large for 64 bits resolve to floats, floats too
+/// large for a double resolve to infinities, and YAML_1_1_SCHEMA resolves base
+/// 60 numbers.
+pub unsafe fn yaml_schema_options_initialize(
+    options: *mut yaml_schema_options_t,
+    schema: yaml_schema_t,
+) {
+    __assert!(!options.is_null());
+    *options = yaml_schema_options_t {
+        schema,
+        overflow: YAML_OVERFLOW_FLOAT,
+        sexagesimal: true,
+    };
+}
+
+/// Resolve a scalar with the given settings.
+///
+/// This is yaml_scalar_resolve() with a choice of what becomes of a plain
+/// number too large for its type: an integer below `i64::MIN` or above
+/// `u64::MAX`, or a finite float whose value overflows a double. With
+/// YAML_OVERFLOW_ERROR the function fails, with YAML_OVERFLOW_SATURATE the
+/// number is clamped to the range of its type, and with YAML_OVERFLOW_STRING
+/// the scalar is a string.
+pub unsafe fn yaml_scalar_resolve_with_options(
+    options: *const yaml_schema_options_t,
+    tag: *const yaml_char_t,
+    value: *const yaml_char_t,
+    length: size_t,
+    resolved: *mut yaml_resolved_scalar_t,
+) -> Success {
+    __assert!(!options.is_null());
+    __assert!(!value.is_null() || length == 0);
+    __assert!(!resolved.is_null());
+    let value: &[u8] = if length == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(value, length as usize)
+    };
+    let schema = (*options).schema;
+    let implicit = match yaml_schema_resolve_plain(*options, value) {
+        Some(implicit) => implicit,
+        None if !tag.is_null() => Resolved::Str,
+        None => return FAIL,
+    };
+    let resolved_value = if tag.is_null() {
+        implicit
+    } else if yaml_schema_has_tag(tag, b"!\0") || yaml_schema_has_tag(tag, STR_TAG) {
+        Resolved::Str
+    } else if schema == YAML_FAILSAFE_SCHEMA {
+        yaml_schema_set_resolved(resolved, Resolved::Str, tag);
+        return OK;
+    } else if yaml_schema_has_tag(tag, NULL_TAG) {
+        if implicit != Resolved::Null {
+            return FAIL;
+        }
+        implicit
+    } else if yaml_schema_has_tag(tag, BOOL_TAG) {
+        if !matches!(implicit, Resolved::Bool(_)) {
+            return FAIL;
+        }
+        implicit
+    } else if yaml_schema_has_tag(tag, INT_TAG) {
+        if !matches!(implicit, Resolved::Int(_) | Resolved::UInt(_)) {
+            return FAIL;
+        }
+        implicit
+    } else if yaml_schema_has_tag(tag, FLOAT_TAG) {
+        match yaml_core_float(implicit) {
+            Some(float) => Resolved::Float(float),
+            None => return FAIL,
+        }
+    } else {
+        yaml_schema_set_resolved(resolved, Resolved::Str, tag);
+        return OK;
+    };
+    let canonical = match resolved_value {
+        Resolved::Null => NULL_TAG,
+        Resolved::Bool(_) => BOOL_TAG,
+        Resolved::Int(_) | Resolved::UInt(_) => INT_TAG,
+        Resolved::Float(_) => FLOAT_TAG,
+        Resolved::Str => STR_TAG,
+    };
+    yaml_schema_set_resolved(resolved, resolved_value, canonical.as_ptr());
+    OK
+}
+
+/// Resolve a scalar node with a schema.
+///
+/// The plain scalars the loader tagged as strings are resolved as untagged.
+pub(crate) unsafe fn yaml_scalar_node_resolve(
+    schema: yaml_schema_t,
+    node: *mut yaml_node_t,
+    resolved: *mut yaml_resolved_scalar_t,
+) -> Success {
+    let style = (*node).data.scalar.style;
+    let plain = (style == YAML_PLAIN_SCALAR_STYLE || style == YAML_ANY_SCALAR_STYLE)
+        && yaml_schema_has_tag((*node).tag, STR_TAG);
+    let tag = if plain {
+        core::ptr::null::<yaml_char_t>()
+    } else {
+        (*node).tag as *const yaml_char_t
+    };
+    yaml_scalar_resolve(
+        schema,
+        tag,
+        (*node).data.scalar.value,
+        (*node).data.scalar.length,
+        resolved,
+    )
+}
+
+/// An implicit tag resolver that resolves plain scalars with the settings
+/// `data` points to.
+pub(crate) unsafe fn yaml_schema_implicit_resolver(
+    data: *mut libc::c_void,
+    value: *const yaml_char_t,
+    length: size_t,
+    tag: *mut *const yaml_char_t,
+) -> libc::c_int {
+    let mut resolved = MaybeUninit::<yaml_resolved_scalar_t>::uninit();
+    if yaml_scalar_resolve_with_options(
+        data as *const yaml_schema_options_t,
+        core::ptr::null(),
+        value,
+        length,
+        resolved.as_mut_ptr(),
+    )
+    .fail
+    {
+        return 0;
+    }
+    *tag = (*resolved.as_ptr()).tag;
+    1
+}
+
+unsafe fn yaml_schema_has_tag(tag: *const yaml_char_t, expected: &[u8]) -> bool {
+    strcmp(
+        tag as *const libc::c_char,
+        expected.as_ptr() as *const libc::c_char,
+    ) == 0
+}
+
+unsafe fn yaml_schema_set_resolved(
+    resolved: *mut yaml_resolved_scalar_t,
+    value: Resolved,
+    tag: *const yaml_char_t,
+) {
+    let (type_, boolean, integer, overflow, float): (yaml_value_type_t, bool, i64, bool, f64) =
+        match value {
+            Resolved::Null => (YAML_NULL_VALUE, false, 0, false, 0.0),
+            Resolved::Bool(boolean) => (YAML_BOOL_VALUE, boolean, 0, false, 0.0),
+            Resolved::Int(int) => (YAML_INT_VALUE, false, int, false, 0.0),
+            Resolved::UInt(int) => (
+                YAML_INT_VALUE,
+                false,
+                int as i64,
+                int > i64::MAX as u64,
+                0.0,
+            ),
+            Resolved::Float(float) => (YAML_FLOAT_VALUE, false, 0, false, float),
+            Resolved::Str => (YAML_STR_VALUE, false, 0, false, 0.0),
+        };
+    *resolved = yaml_resolved_scalar_t {
+        type_,
+        tag,
+        boolean,
+        integer,
+        overflow,
+        float,
+    };
+}
+
+fn yaml_schema_resolve_plain(options: yaml_schema_options_t, value: &[u8]) -> Option<Resolved> {
+    let resolved = match options.schema {
+        YAML_FAILSAFE_SCHEMA => return Some(Resolved::Str),
+        YAML_JSON_SCHEMA => yaml_json_schema_resolve(value)?,
+        YAML_CORE_SCHEMA => yaml_core_resolve(value),
+        YAML_1_1_SCHEMA => yaml_1_1_resolve(value, options.sexagesimal),
+    };
+    yaml_schema_overflow(options.overflow, value, resolved)
+}
+
+/// Apply the overflow setting to a number that resolved to a float although it
+/// is written as an integer, or to an infinity although it is written as a
+/// finite number.
+fn yaml_schema_overflow(
+    overflow: yaml_overflow_t,
+    value: &[u8],
+    resolved: Resolved,
+) -> Option<Resolved> {
+    let float = match resolved {
+        Resolved::Float(float) => float,
+        _ => return Some(resolved),
+    };
+    let unsigned = value
+        .strip_prefix(b"-")
+        .or_else(|| value.strip_prefix(b"+"))
+        .unwrap_or(value);
+    let integer =
+        unsigned.starts_with(b"0x") || !value.iter().any(|ch| matches!(ch, b'.' | b'e' | b'E'));
+    let infinite = float.is_infinite() && !value.iter().any(|ch| matches!(ch, b'i' | b'I'));
+    if !integer && !infinite {
+        return Some(resolved);
+    }
+    match overflow {
+        YAML_OVERFLOW_FLOAT => Some(resolved),
+        YAML_OVERFLOW_ERROR => None,
+        YAML_OVERFLOW_SATURATE if integer => Some(if float < 0.0 {
+            Resolved::Int(i64::MIN)
+        } else {
+            Resolved::UInt(u64::MAX)
+        }),
+        YAML_OVERFLOW_SATURATE => Some(Resolved::Float(if float < 0.0 {
+            f64::MIN
+        } else {
+            f64::MAX
+        })),
+        YAML_OVERFLOW_STRING => Some(Resolved::Str),
+    }
+}
+
+/// Resolve the value of a plain scalar with the YAML 1.2 JSON schema, which
+/// has no rule for other strings.
+fn yaml_json_schema_resolve(value: &[u8]) -> Option<Resolved> {
+    match value {
+        b"null" => return Some(Resolved::Null),
+        b"true" => return Some(Resolved::Bool(true)),
+        b"false" => return Some(Resolved::Bool(false)),
+        _ => {}
+    }
+    let text = core::str::from_utf8(value).ok()?;
+    let unsigned = text.strip_prefix('-').unwrap_or(text);
+    let integer = unsigned.bytes().take_while(u8::is_ascii_digit).count();
+    if integer == 0 || (integer > 1 && unsigned.starts_with('0')) {
+        return None;
+    }
+    if integer == unsigned.len() {
+        return Some(match yaml_core_resolve(value) {
+            Resolved::Str => return None,
+            resolved => resolved,
+        });
+    }
+    let rest = &unsigned[integer..];
+    if !rest.starts_with(['.', 'e', 'E']) || !yaml_core_is_float(unsigned) {
+        return None;
+    }
+    text.parse().ok().map(Resolved::Float)
+}
+
+/// A plain scalar resolved with the YAML 1.2 core schema.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub(crate) enum Resolved {
+    Null,
+    Bool(bool),
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    Str,
+}
+
+/// Resolve the value of a plain scalar with the YAML 1.2 core schema.
+///
+/// Octal and hexadecimal integers too large for a u64 resolve to floats.
+pub(crate) fn yaml_core_resolve(value: &[u8]) -> Resolved {
+    match value {
+        b"" | b"~" | b"null" | b"Null" | b"NULL" => return Resolved::Null,
+        b"true" | b"True" | b"TRUE" => return Resolved::Bool(true),
+        b"false" | b"False" | b"FALSE" => return Resolved::Bool(false),
+        b".inf" | b".Inf" | b".INF" | b"+.inf" | b"+.Inf" | b"+.INF" => {
+            return Resolved::Float(f64::INFINITY)
+        }
+        b"-.inf" | b"-.Inf" | b"-.INF" => return Resolved::Float(f64::NEG_INFINITY),
+        b".nan" | b".NaN" | b".NAN" => return Resolved::Float(f64::NAN),
+        _ => {}
+    }
+    let text = match core::str::from_utf8(value) {
+        Ok(text) => text,
+        Err(_) => return Resolved::Str,
+    };
+    let (radix, digits) = if let Some(digits) = text.strip_prefix("0o") {
+        (8, digits)
+    } else if let Some(digits) = text.strip_prefix("0x") {
+        (16, digits)
+    } else {
+        (10, text)
+    };
+    if radix != 10 {
+        if digits.is_empty() || !digits.bytes().all(|ch| (ch as char).is_digit(radix)) {
+            return Resolved::Str;
+        }
+        return match u64::from_str_radix(digits, radix) {
+            Ok(int) => yaml_core_unsigned(int),
+            Err(_) => Resolved::Float(digits.chars().fold(0.0, |float, ch| {
+                float * radix as f64 + ch.to_digit(radix).unwrap() as f64
+            })),
+        };
+    }
+    let unsigned = text.strip_prefix(['-', '+']).unwrap_or(text);
+    if !unsigned.is_empty() && unsigned.bytes().all(|ch| ch.is_ascii_digit()) {
+        if let Ok(int) = text.parse::<i64>() {
+            return Resolved::Int(int);
+        }
+        if let Ok(int) = unsigned.parse::<u64>() {
+            if !text.starts_with('-') {
+                return Resolved::UInt(int);
+            }
+        }
+    } else if !yaml_core_is_float(unsigned) {
+        return Resolved::Str;
+    }
+    match text.parse::<f64>() {
+        Ok(float) => Resolved::Float(float),
+        Err(_) => Resolved::Str,
+    }
+}
+
+/// Check if a plain scalar means something else in YAML 1.1 than in YAML 1.2.
+///
+/// The `value` of `length` bytes is resolved with YAML_1_1_SCHEMA, resolving
+/// base 60 numbers if `sexagesimal` is set, and with YAML_CORE_SCHEMA. The
+/// function returns true if the two give a different type or value, as they do
+/// for `yes`, `010` or `0o10`, so that an application can warn about the scalar
+/// before switching between the versions.
+pub unsafe fn yaml_scalar_version_dependent(
+    value: *const yaml_char_t,
+    length: size_t,
+    sexagesimal: bool,
+) -> bool {
+    __assert!(!value.is_null() || length == 0);
+    let value: &[u8] = if length == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(value, length as usize)
+    };
+    match (
+        yaml_1_1_resolve(value, sexagesimal),
+        yaml_core_resolve(value),
+    ) {
+        (Resolved::Float(old), Resolved::Float(new)) => old.to_bits() != new.to_bits(),
+        (old, new) => old != new,
+    }
+}
+
+/// Resolve the value of a plain scalar with the YAML 1.1 types.
+///
+/// Integers too large for a u64 resolve to floats. Base 60 numbers are strings
+/// unless `sexagesimal` is set.
+pub(crate) fn yaml_1_1_resolve(value: &[u8], sexagesimal: bool) -> Resolved {
+    match value {
+        b"" | b"~" | b"null" | b"Null" | b"NULL" => return Resolved::Null,
+        b"y" | b"Y" | b"yes" | b"Yes" | b"YES" | b"true" | b"True" | b"TRUE" | b"on" | b"On"
+        | b"ON" => return Resolved::Bool(true),
+        b"n" | b"N" | b"no" | b"No" | b"NO" | b"false" | b"False" | b"FALSE" | b"off" | b"Off"
+        | b"OFF" => return Resolved::Bool(false),
+        b".inf" | b".Inf" | b".INF" | b"+.inf" | b"+.Inf" | b"+.INF" => {
+            return Resolved::Float(f64::INFINITY)
+        }
+        b"-.inf" | b"-.Inf" | b"-.INF" => return Resolved::Float(f64::NEG_INFINITY),
+        b".nan" | b".NaN" | b".NAN" => return Resolved::Float(f64::NAN),
+        _ => {}
+    }
+    let text = match core::str::from_utf8(value) {
+        Ok(text) => text,
+        Err(_) => return Resolved::Str,
+    };
+    let negative = text.starts_with('-');
+    let unsigned = text.strip_prefix(['-', '+']).unwrap_or(text);
+    let (int, float) = match yaml_1_1_integer(unsigned, sexagesimal) {
+        Some(integer) => integer,
+        None => {
+            return match yaml_1_1_float(unsigned, sexagesimal) {
+                Some(float) if negative => Resolved::Float(-float),
+                Some(float) => Resolved::Float(float),
+                None => Resolved::Str,
+            }
+        }
+    };
+    match int {
+        Some(int) if !negative => yaml_core_unsigned(int),
+        Some(int) if int <= i64::MAX as u64 + 1 => Resolved::Int((int as i64).wrapping_neg()),
+        _ if negative => Resolved::Float(-float),
+        _ => Resolved::Float(float),
+    }
+}
+
+/// Match an unsigned YAML 1.1 integer, giving its value if it fits a u64 and
+/// its value as a float.
+#[allow(clippy::cast_precision_loss)]
+fn yaml_1_1_integer(text: &str, sexagesimal: bool) -> Option<(Option<u64>, f64)> {
+    if text.contains(':') {
+        if !sexagesimal || !text.starts_with(|ch| matches!(ch, '1'..='9')) {
+            return None;
+        }
+        return yaml_1_1_base_60(text);
+    }
+    let (radix, digits) = if let Some(digits) = text.strip_prefix("0b") {
+        (2, digits)
+    } else if let Some(digits) = text.strip_prefix("0x") {
+        (16, digits)
+    } else if text.len() > 1 && text.starts_with('0') {
+        (8, &text[1..])
+    } else if text.starts_with(|ch: char| ch.is_ascii_digit()) {
+        (10, text)
+    } else {
+        return None;
+    };
+    let mut int = Some(0_u64);
+    let mut float = 0.0;
+    let mut count = 0;
+    for ch in digits.chars().filter(|&ch| ch != '_') {
+        let digit = ch.to_digit(radix)?;
+        int = int
+            .and_then(|int| int.checked_mul(u64::from(radix)))
+            .and_then(|int| int.checked_add(u64::from(digit)));
+        float = float * f64::from(radix) + f64::from(digit);
+        count += 1;
+    }
+    if count == 0 {
+        return None;
+    }
+    if int.is_none() && radix == 10 {
+        float = yaml_1_1_digits(digits).parse().ok()?;
+    }
+    Some((int, float))
+}
+
+/// Match a YAML 1.1 float without its sign.
+fn yaml_1_1_float(text: &str, sexagesimal: bool) -> Option<f64> {
+    let (mantissa, exponent) = match text.find(['e', 'E']) {
+        Some(index) => (&text[..index], Some(&text[index + 1..])),
+        None => (text, None),
+    };
+    let (integer, fraction) = mantissa.split_once('.')?;
+    let is_digits = |text: &str| text.bytes().all(|ch| ch.is_ascii_digit() || ch == b'_');
+    if !is_digits(fraction) {
+        return None;
+    }
+    let fraction = yaml_1_1_digits(fraction);
+    if integer.contains(':') {
+        if !sexagesimal
+            || exponent.is_some()
+            || !integer.starts_with(|ch: char| ch.is_ascii_digit())
+        {
+            return None;
+        }
+        let (_, float) = yaml_1_1_base_60(integer)?;
+        let fraction: f64 = ["0.", &fraction].concat().parse().ok()?;
+        return Some(float + fraction);
+    }
+    if !is_digits(integer) || integer.starts_with('_') || integer.is_empty() && fraction.is_empty()
+    {
+        return None;
+    }
+    let exponent = match exponent {
+        None => "0",
+        Some(exponent) => {
+            let digits = exponent.strip_prefix(['-', '+'])?;
+            if digits.is_empty() || !digits.bytes().all(|ch| ch.is_ascii_digit()) {
+                return None;
+            }
+            exponent
+        }
+    };
+    let integer = yaml_1_1_digits(integer);
+    [
+        if integer.is_empty() { "0" } else { &integer },
+        ".",
+        if fraction.is_empty() { "0" } else { &fraction },
+        "e",
+        exponent,
+    ]
+    .concat()
+    .parse()
+    .ok()
+}
+
+/// Match `[0-9][0-9_]*(:[0-5]?[0-9])+`, the part of a base 60 number before
+/// any fraction.
+#[allow(clippy::cast_precision_loss)]
+fn yaml_1_1_base_60(text: &str) -> Option<(Option<u64>, f64)> {
+    let mut parts = text.split(':');
+    let first = parts.next()?;
+    if !first.starts_with(|ch: char| ch.is_ascii_digit())
+        || !first.bytes().all(|ch| ch.is_ascii_digit() || ch == b'_')
+    {
+        return None;
+    }
+    let first = yaml_1_1_digits(first);
+    let mut int = first.parse::<u64>().ok();
+    let mut float: f64 = first.parse().ok()?;
+    for part in parts {
+        let digit = match part.as_bytes() {
+            [ones @ b'0'..=b'9'] => ones - b'0',
+            [tens @ b'0'..=b'5', ones @ b'0'..=b'9'] => (tens - b'0') * 10 + ones - b'0',
+            _ => return None,
+        };
+        int = int
+            .and_then(|int| int.checked_mul(60))
+            .and_then(|int| int.checked_add(u64::from(digit)));
+        float = float * 60.0 + f64::from(digit);
+    }
+    Some((int, float))
+}
+
+/// Drop the `_` separators of the digits of a number.
+fn yaml_1_1_digits(digits: &str) -> String {
+    digits.chars().filter(|&ch| ch != '_').collect()
+}
+
+/// The value of a resolved number as a float, for scalars tagged `!!float`.
+#[allow(clippy::cast_precision_loss)]
+pub(crate) fn yaml_core_float(resolved: Resolved) -> Option<f64> {
+    match resolved {
+        Resolved::Int(int) => Some(int as f64),
+        Resolved::UInt(int) => Some(int as f64),
+        Resolved::Float(float) => Some(float),
+        _ => None,
+    }
+}
+
+fn yaml_core_unsigned(int: u64) -> Resolved {
+    match i64::try_from(int) {
+        Ok(int) => Resolved::Int(int),
+        Err(_) => Resolved::UInt(int),
+    }
+}
+
+/// Match `(\.[0-9]+|[0-9]+(\.[0-9]*)?)([eE][-+]?[0-9]+)?`.
+fn yaml_core_is_float(text: &str) -> bool {
+    let text = text.as_bytes();
+    let mut index = 0;
+    let integer = text.iter().take_while(|ch| ch.is_ascii_digit()).count();
+    index += integer;
+    let mut fraction = 0;
+    if text.get(index) == Some(&b'.') {
+        index += 1;
+        fraction = text[index..]
+            .iter()
+            .take_while(|ch| ch.is_ascii_digit())
+            .count();
+        index += fraction;
+    }
+    if integer == 0 && fraction == 0 {
+        return false;
+    }
+    if matches!(text.get(index), Some(b'e' | b'E')) {
+        index += 1;
+        if matches!(text.get(index), Some(b'-' | b'+')) {
+            index += 1;
+        }
+        let exponent = text[index..]
+            .iter()
+            .take_while(|ch| ch.is_ascii_digit())
+            .count();
+        if exponent == 0 {
+            return false;
+        }
+        index += exponent;
+    }
+    index == text.len()
+}
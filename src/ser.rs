@@ -0,0 +1,678 @@
+use crate::error::{Error, ErrorKind, OutputError};
+use crate::schema::{yaml_core_resolve, Resolved};
+use crate::success::Success;
+use crate::yaml::{size_t, yaml_char_t};
+use crate::{
+    libc, yaml_document_end_event_initialize, yaml_document_start_event_initialize,
+    yaml_emitter_delete, yaml_emitter_emit, yaml_emitter_initialize, yaml_emitter_options_t,
+    yaml_emitter_set_options, yaml_emitter_set_output_owned, yaml_emitter_t,
+    yaml_emitter_take_output, yaml_event_t, yaml_mapping_end_event_initialize,
+    yaml_mapping_start_event_initialize, yaml_mapping_style_t, yaml_output_delete,
+    yaml_scalar_event_initialize, yaml_scalar_style_t, yaml_sequence_end_event_initialize,
+    yaml_sequence_start_event_initialize, yaml_sequence_style_t, yaml_stream_end_event_initialize,
+    yaml_stream_start_event_initialize, YAML_ANY_ENCODING, YAML_ANY_MAPPING_STYLE,
+    YAML_ANY_SCALAR_STYLE, YAML_ANY_SEQUENCE_STYLE, YAML_PLAIN_SCALAR_STYLE, YAML_UTF16BE_ENCODING,
+    YAML_UTF16LE_ENCODING,
+};
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::cmp;
+use core::fmt::{self, Display};
+use core::mem::{self, MaybeUninit};
+use core::ptr;
+use core::slice;
+use serde::ser::{self, Serialize};
+
+/// Serialize a value as a YAML document.
+///
+/// # Errors
+///
+/// Fails if the value cannot be represented in YAML or its `Serialize`
+/// implementation reports an error.
+pub fn to_string<T>(value: &T) -> Result<String, Error>
+where
+    T: ?Sized + Serialize,
+{
+    let mut serializer = Serializer::new();
+    value.serialize(&mut serializer)?;
+    serializer.into_string()
+}
+
+/// Serialize a value as a YAML document into a writer.
+///
+/// # Errors
+///
+/// Fails if the value cannot be serialized or the writer fails.
+pub fn to_writer<W, T>(writer: &mut W, value: &T) -> Result<(), Error>
+where
+    W: ?Sized + fmt::Write,
+    T: ?Sized + Serialize,
+{
+    writer
+        .write_str(&to_string(value)?)
+        .map_err(|_| Error::new(ErrorKind::Writer, "failed to write the output", None))
+}
+
+/// A destination for the YAML written by [`to_output`].
+pub trait Output {
+    /// Write all of `buf`.
+    ///
+    /// # Errors
+    ///
+    /// Fails with the cause of the failure. An output that cannot accept the
+    /// bytes right now should write none of them and fail with
+    /// [`OutputError::would_block`].
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), OutputError>;
+}
+
+impl Output for Vec<u8> {
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), OutputError> {
+        self.extend_from_slice(buf);
+        Ok(())
+    }
+}
+
+/// Writes into the slice and moves it past the written bytes. Bytes that do not
+/// fit are dropped, and the error tells how many were written.
+impl Output for &mut [u8] {
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), OutputError> {
+        let written = cmp::min(buf.len(), self.len());
+        let (head, tail) = mem::take(self).split_at_mut(written);
+        head.copy_from_slice(&buf[..written]);
+        *self = tail;
+        if written < buf.len() {
+            Err(OutputError::buffer_full(written))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<O> Output for &mut O
+where
+    O: ?Sized + Output,
+{
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), OutputError> {
+        (**self).write_all(buf)
+    }
+}
+
+/// Serialize a value as a YAML document into an output.
+///
+/// # Errors
+///
+/// Fails if the value cannot be serialized or the output fails. The error of
+/// the output is kept as the [`source`](serde::de::StdError::source) of the
+/// error, and an output that would block gives [`ErrorKind::WouldBlock`], in
+/// which case nothing was written and the value can be serialized again.
+pub fn to_output<O, T>(mut output: O, value: &T) -> Result<(), Error>
+where
+    O: Output,
+    T: ?Sized + Serialize,
+{
+    output
+        .write_all(to_string(value)?.as_bytes())
+        .map_err(Error::from_output)
+}
+
+/// A serde serializer that emits YAML events through an emitter.
+///
+/// Every value serialized becomes a document of the output stream. Strings
+/// that the YAML 1.2 core schema would read as another type are quoted, so
+/// the output deserializes back to the same values.
+pub struct Serializer {
+    emitter: Box<MaybeUninit<yaml_emitter_t>>,
+    stream_started: bool,
+    depth: usize,
+    sequence_style: yaml_sequence_style_t,
+    mapping_style: yaml_mapping_style_t,
+    string_style: yaml_scalar_style_t,
+    explicit_tags: bool,
+}
+
+impl Serializer {
+    /// Create a serializer with the default emitter settings.
+    pub fn new() -> Self {
+        let mut emitter = Box::new(MaybeUninit::<yaml_emitter_t>::uninit());
+        unsafe {
+            let _ = yaml_emitter_initialize(emitter.as_mut_ptr());
+            yaml_emitter_set_output_owned(emitter.as_mut_ptr());
+        }
+        Serializer {
+            emitter,
+            stream_started: false,
+            depth: 0,
+            sequence_style: YAML_ANY_SEQUENCE_STYLE,
+            mapping_style: YAML_ANY_MAPPING_STYLE,
+            string_style: YAML_ANY_SCALAR_STYLE,
+            explicit_tags: false,
+        }
+    }
+
+    /// Configure the emitter, see yaml_emitter_set_options().
+    ///
+    /// This must be done before anything is serialized.
+    pub fn set_options(&mut self, options: &yaml_emitter_options_t) {
+        assert!(!self.stream_started);
+        unsafe { yaml_emitter_set_options(self.emitter.as_mut_ptr(), options) };
+    }
+
+    /// Set the style of sequences.
+    pub fn set_sequence_style(&mut self, style: yaml_sequence_style_t) {
+        self.sequence_style = style;
+    }
+
+    /// Set the style of mappings.
+    pub fn set_mapping_style(&mut self, style: yaml_mapping_style_t) {
+        self.mapping_style = style;
+    }
+
+    /// Set the preferred style of strings.
+    ///
+    /// Strings that cannot be written in the style, or that would not read
+    /// back as strings, still get another one.
+    pub fn set_string_style(&mut self, style: yaml_scalar_style_t) {
+        self.string_style = style;
+    }
+
+    /// Tag every scalar with its core schema tag, such as `!!int` or `!!str`.
+    pub fn set_explicit_tags(&mut self, explicit_tags: bool) {
+        self.explicit_tags = explicit_tags;
+    }
+
+    /// Finish the stream and return the output.
+    ///
+    /// # Errors
+    ///
+    /// Fails if a value is incomplete, if the emitter fails or if the output
+    /// is not UTF-8 encoded.
+    pub fn into_string(mut self) -> Result<String, Error> {
+        if self.depth != 0 {
+            return Err(Error::new(ErrorKind::Emitter, "incomplete value", None));
+        }
+        unsafe {
+            let emitter = self.emitter.as_mut_ptr();
+            if self.stream_started {
+                self.emit(|event| yaml_stream_end_event_initialize(event))?;
+            }
+            if (*emitter).encoding == YAML_UTF16LE_ENCODING
+                || (*emitter).encoding == YAML_UTF16BE_ENCODING
+            {
+                return Err(Error::new(
+                    ErrorKind::Writer,
+                    "the output is not UTF-8 encoded",
+                    None,
+                ));
+            }
+            let mut output = ptr::null_mut::<yaml_char_t>();
+            let mut size: size_t = 0;
+            yaml_emitter_take_output(emitter, &mut output, &mut size);
+            let string = String::from_utf8(slice::from_raw_parts(output, size as usize).to_vec());
+            yaml_output_delete(output);
+            string
+                .map_err(|_| Error::new(ErrorKind::Writer, "the output is not UTF-8 encoded", None))
+        }
+    }
+
+    unsafe fn emit(
+        &mut self,
+        initialize: impl FnOnce(*mut yaml_event_t) -> Success,
+    ) -> Result<(), Error> {
+        let mut event = MaybeUninit::<yaml_event_t>::uninit();
+        let event = event.as_mut_ptr();
+        if initialize(event).fail {
+            return Err(Error::new(ErrorKind::Emitter, "invalid event", None));
+        }
+        if yaml_emitter_emit(self.emitter.as_mut_ptr(), event).fail {
+            return Err(Error::from_emitter(self.emitter.as_mut_ptr()));
+        }
+        Ok(())
+    }
+
+    fn begin_value(&mut self) -> Result<(), Error> {
+        if self.depth != 0 {
+            return Ok(());
+        }
+        unsafe {
+            if !self.stream_started {
+                self.emit(|event| yaml_stream_start_event_initialize(event, YAML_ANY_ENCODING))?;
+                self.stream_started = true;
+            }
+            self.emit(|event| {
+                yaml_document_start_event_initialize(
+                    event,
+                    ptr::null_mut(),
+                    ptr::null_mut(),
+                    ptr::null_mut(),
+                    true,
+                )
+            })
+        }
+    }
+
+    fn end_value(&mut self) -> Result<(), Error> {
+        if self.depth != 0 {
+            return Ok(());
+        }
+        unsafe { self.emit(|event| yaml_document_end_event_initialize(event, true)) }
+    }
+
+    fn emit_scalar(&mut self, value: &str, tag: &[u8]) -> Result<(), Error> {
+        self.begin_value()?;
+        let tag = if self.explicit_tags {
+            tag.as_ptr()
+        } else {
+            ptr::null()
+        };
+        unsafe {
+            self.emit(|event| {
+                yaml_scalar_event_initialize(
+                    event,
+                    ptr::null(),
+                    tag,
+                    value.as_ptr(),
+                    value.len() as libc::c_int,
+                    tag.is_null(),
+                    tag.is_null(),
+                    YAML_PLAIN_SCALAR_STYLE,
+                )
+            })?;
+        }
+        self.end_value()
+    }
+
+    fn emit_str(&mut self, value: &str) -> Result<(), Error> {
+        self.begin_value()?;
+        let tag: *const yaml_char_t = if self.explicit_tags {
+            b"tag:yaml.org,2002:str\0".as_ptr()
+        } else {
+            ptr::null()
+        };
+        let plain_implicit = tag.is_null() && yaml_core_resolve(value.as_bytes()) == Resolved::Str;
+        let style = self.string_style;
+        unsafe {
+            self.emit(|event| {
+                yaml_scalar_event_initialize(
+                    event,
+                    ptr::null(),
+                    tag,
+                    value.as_ptr(),
+                    value.len() as libc::c_int,
+                    plain_implicit,
+                    tag.is_null(),
+                    style,
+                )
+            })?;
+        }
+        self.end_value()
+    }
+
+    fn emit_sequence_start(&mut self) -> Result<(), Error> {
+        self.begin_value()?;
+        let style = self.sequence_style;
+        unsafe {
+            self.emit(|event| {
+                yaml_sequence_start_event_initialize(event, ptr::null(), ptr::null(), true, style)
+            })?;
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn emit_sequence_end(&mut self) -> Result<(), Error> {
+        unsafe { self.emit(|event| yaml_sequence_end_event_initialize(event))? };
+        self.depth -= 1;
+        self.end_value()
+    }
+
+    fn emit_mapping_start(&mut self) -> Result<(), Error> {
+        self.begin_value()?;
+        let style = self.mapping_style;
+        unsafe {
+            self.emit(|event| {
+                yaml_mapping_start_event_initialize(event, ptr::null(), ptr::null(), true, style)
+            })?;
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn emit_mapping_end(&mut self) -> Result<(), Error> {
+        unsafe { self.emit(|event| yaml_mapping_end_event_initialize(event))? };
+        self.depth -= 1;
+        self.end_value()
+    }
+
+    fn emit_display(&mut self, value: impl Display, tag: &[u8]) -> Result<(), Error> {
+        self.emit_scalar(&value.to_string(), tag)
+    }
+
+    fn emit_float(&mut self, value: f64, text: impl FnOnce() -> String) -> Result<(), Error> {
+        let text = if value.is_nan() {
+            String::from(".nan")
+        } else if value.is_infinite() {
+            String::from(if value < 0.0 { "-.inf" } else { ".inf" })
+        } else {
+            text()
+        };
+        self.emit_scalar(&text, b"tag:yaml.org,2002:float\0")
+    }
+}
+
+impl Default for Serializer {
+    fn default() -> Self {
+        Serializer::new()
+    }
+}
+
+impl Drop for Serializer {
+    fn drop(&mut self) {
+        unsafe { yaml_emitter_delete(self.emitter.as_mut_ptr()) };
+    }
+}
+
+const INT_TAG: &[u8] = b"tag:yaml.org,2002:int\0";
+
+impl ser::Serializer for &mut Serializer {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn serialize_bool(self, value: bool) -> Result<(), Error> {
+        let value = if value { "true" } else { "false" };
+        self.emit_scalar(value, b"tag:yaml.org,2002:bool\0")
+    }
+
+    fn serialize_i8(self, value: i8) -> Result<(), Error> {
+        self.emit_display(value, INT_TAG)
+    }
+
+    fn serialize_i16(self, value: i16) -> Result<(), Error> {
+        self.emit_display(value, INT_TAG)
+    }
+
+    fn serialize_i32(self, value: i32) -> Result<(), Error> {
+        self.emit_display(value, INT_TAG)
+    }
+
+    fn serialize_i64(self, value: i64) -> Result<(), Error> {
+        self.emit_display(value, INT_TAG)
+    }
+
+    fn serialize_i128(self, value: i128) -> Result<(), Error> {
+        self.emit_display(value, INT_TAG)
+    }
+
+    fn serialize_u8(self, value: u8) -> Result<(), Error> {
+        self.emit_display(value, INT_TAG)
+    }
+
+    fn serialize_u16(self, value: u16) -> Result<(), Error> {
+        self.emit_display(value, INT_TAG)
+    }
+
+    fn serialize_u32(self, value: u32) -> Result<(), Error> {
+        self.emit_display(value, INT_TAG)
+    }
+
+    fn serialize_u64(self, value: u64) -> Result<(), Error> {
+        self.emit_display(value, INT_TAG)
+    }
+
+    fn serialize_u128(self, value: u128) -> Result<(), Error> {
+        self.emit_display(value, INT_TAG)
+    }
+
+    fn serialize_f32(self, value: f32) -> Result<(), Error> {
+        self.emit_float(value as f64, || alloc::format!("{:?}", value))
+    }
+
+    fn serialize_f64(self, value: f64) -> Result<(), Error> {
+        self.emit_float(value, || alloc::format!("{:?}", value))
+    }
+
+    fn serialize_char(self, value: char) -> Result<(), Error> {
+        self.emit_str(value.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, value: &str) -> Result<(), Error> {
+        self.emit_str(value)
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<(), Error> {
+        let mut seq = ser::Serializer::serialize_seq(&mut *self, Some(value.len()))?;
+        for byte in value {
+            ser::SerializeSeq::serialize_element(&mut seq, byte)?;
+        }
+        ser::SerializeSeq::end(seq)
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        self.emit_scalar("null", b"tag:yaml.org,2002:null\0")
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        self.serialize_none()
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), Error> {
+        self.serialize_none()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), Error> {
+        self.emit_str(variant)
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.emit_mapping_start()?;
+        self.emit_str(variant)?;
+        value.serialize(&mut *self)?;
+        self.emit_mapping_end()
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self, Error> {
+        self.emit_sequence_start()?;
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<Self, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, Error> {
+        self.emit_mapping_start()?;
+        self.emit_str(variant)?;
+        self.emit_sequence_start()?;
+        Ok(self)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self, Error> {
+        self.emit_mapping_start()?;
+        Ok(self)
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Self, Error> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, Error> {
+        self.emit_mapping_start()?;
+        self.emit_str(variant)?;
+        self.emit_mapping_start()?;
+        Ok(self)
+    }
+}
+
+impl ser::SerializeSeq for &mut Serializer {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.emit_sequence_end()
+    }
+}
+
+impl ser::SerializeTuple for &mut Serializer {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.emit_sequence_end()
+    }
+}
+
+impl ser::SerializeTupleStruct for &mut Serializer {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.emit_sequence_end()
+    }
+}
+
+impl ser::SerializeTupleVariant for &mut Serializer {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.emit_sequence_end()?;
+        self.emit_mapping_end()
+    }
+}
+
+impl ser::SerializeMap for &mut Serializer {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        key.serialize(&mut **self)
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.emit_mapping_end()
+    }
+}
+
+impl ser::SerializeStruct for &mut Serializer {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.emit_str(key)?;
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.emit_mapping_end()
+    }
+}
+
+impl ser::SerializeStructVariant for &mut Serializer {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.emit_str(key)?;
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.emit_mapping_end()?;
+        self.emit_mapping_end()
+    }
+}
@@ -0,0 +1,249 @@
+use crate::api::{yaml_free, yaml_malloc};
+use crate::document::yaml_document_node_count;
+use crate::externs::{memcpy, strlen};
+use crate::success::{Success, FAIL, OK};
+use crate::yaml::{size_t, yaml_char_t};
+use crate::{libc, yaml_document_t, yaml_node_t, PointerExt, YAML_ALIAS_NODE};
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use core::slice;
+use core::str;
+
+/// Set the tag of a node.
+///
+/// The NUL-terminated `tag` may be written in any of the forms of the source:
+/// a verbatim tag such as `!<tag:yaml.org,2002:str>`, a shorthand tag such as
+/// `!!str`, `!local` or `!e!suffix` whose handle is either a default handle or
+/// a tag directive of the document, the non-specific tag `!`, or a global tag
+/// such as `tag:example.com,2000:app`. It is stored in the full form the loader
+/// gives to tags, with its URI escapes decoded, so the emitter can write it
+/// back.
+///
+/// The function fails and leaves the node unchanged if the tag is malformed or
+/// uses an undefined handle. Alias nodes stand for the nodes they refer to.
+pub unsafe fn yaml_document_set_node_tag(
+    document: *mut yaml_document_t,
+    index: libc::c_int,
+    tag: *const yaml_char_t,
+) -> Success {
+    __assert!(!document.is_null());
+    __assert!(index > 0 && index <= yaml_document_node_count(document));
+    __assert!(!tag.is_null());
+    let tag = slice::from_raw_parts(tag, strlen(tag as *const libc::c_char) as usize);
+    let normalized = match yaml_tag_normalize(document, tag) {
+        Some(normalized) => normalized,
+        None => return FAIL,
+    };
+    let copy = yaml_malloc(normalized.len() as size_t + 1) as *mut yaml_char_t;
+    memcpy(
+        copy as *mut libc::c_void,
+        normalized.as_ptr() as *const libc::c_void,
+        normalized.len() as libc::c_ulong,
+    );
+    *copy.add(normalized.len()) = b'\0';
+    let node = yaml_tag_node(document, index);
+    yaml_free((*node).tag as *mut libc::c_void);
+    (*node).tag = copy;
+    OK
+}
+
+/// Check the tag of a node.
+///
+/// The NUL-terminated `tag` is read as by yaml_document_set_node_tag(), so
+/// `!!str`, `!<tag:yaml.org,2002:str>` and `tag:yaml.org,2002:str` all match a
+/// string node. A malformed tag matches no node.
+pub unsafe fn yaml_document_node_has_tag(
+    document: *mut yaml_document_t,
+    index: libc::c_int,
+    tag: *const yaml_char_t,
+) -> bool {
+    __assert!(!document.is_null());
+    __assert!(index > 0 && index <= yaml_document_node_count(document));
+    __assert!(!tag.is_null());
+    let tag = slice::from_raw_parts(tag, strlen(tag as *const libc::c_char) as usize);
+    let node = yaml_tag_node(document, index);
+    if (*node).tag.is_null() {
+        return false;
+    }
+    let node_tag = slice::from_raw_parts(
+        (*node).tag,
+        strlen((*node).tag as *const libc::c_char) as usize,
+    );
+    yaml_tag_normalize(document, tag).as_deref() == Some(node_tag)
+}
+
+/// An iterator over the nodes of a document with a tag, made by
+/// yaml_document_tag_iter().
+///
+/// It yields the id of each node with the tag, in the order of the nodes,
+/// which is the order of the source for a loaded document. Alias nodes are
+/// skipped, as they have the tag of the nodes they refer to. The iterator
+/// borrows the document for the lifetime `'a`.
+pub struct yaml_tag_iter_t<'a> {
+    start: *mut yaml_node_t,
+    node: *mut yaml_node_t,
+    end: *mut yaml_node_t,
+    tag: Option<Vec<u8>>,
+    marker: PhantomData<&'a yaml_document_t>,
+}
+
+impl Iterator for yaml_tag_iter_t<'_> {
+    type Item = libc::c_int;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let tag = self.tag.as_deref()?;
+        unsafe {
+            while self.node < self.end {
+                let node = self.node;
+                self.node = self.node.add(1);
+                if !(*node).tag.is_null()
+                    && slice::from_raw_parts(
+                        (*node).tag,
+                        strlen((*node).tag as *const libc::c_char) as usize,
+                    ) == tag
+                {
+                    return Some(node.c_offset_from(self.start) as libc::c_int + 1);
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Iterate over the nodes of a document with a tag.
+///
+/// The NUL-terminated `tag` is read as by yaml_document_set_node_tag(), so
+/// `!secret` finds the nodes written with that tag and `!!str` the strings. A
+/// malformed tag finds no node.
+///
+/// # Safety
+///
+/// The document must have been loaded or built with the document functions.
+pub unsafe fn yaml_document_tag_iter(
+    document: &yaml_document_t,
+    tag: *const yaml_char_t,
+) -> yaml_tag_iter_t<'_> {
+    __assert!(!tag.is_null());
+    let tag = slice::from_raw_parts(tag, strlen(tag as *const libc::c_char) as usize);
+    yaml_tag_iter_t {
+        start: document.nodes.start,
+        node: document.nodes.start,
+        end: document.nodes.top,
+        tag: yaml_tag_normalize(document, tag),
+        marker: PhantomData,
+    }
+}
+
+unsafe fn yaml_tag_node(
+    document: *mut yaml_document_t,
+    mut index: libc::c_int,
+) -> *mut yaml_node_t {
+    let mut node = (*document).nodes.start.add(index as usize - 1);
+    while (*node).type_ == YAML_ALIAS_NODE {
+        index = (*node).data.alias.target;
+        node = (*document).nodes.start.add(index as usize - 1);
+    }
+    node
+}
+
+/// Turn a tag into the full form the loader gives to tags.
+unsafe fn yaml_tag_normalize(document: *const yaml_document_t, tag: &[u8]) -> Option<Vec<u8>> {
+    let mut normalized = Vec::new();
+    if let Some(verbatim) = tag.strip_prefix(b"!<") {
+        let uri = verbatim.strip_suffix(b">")?;
+        if uri.is_empty() || !yaml_tag_decode(uri, false, &mut normalized) {
+            return None;
+        }
+    } else if tag == b"!" {
+        normalized.push(b'!');
+    } else if tag.starts_with(b"!") {
+        let (handle, suffix) = match tag[1..].iter().position(|&byte| byte == b'!') {
+            Some(position) => tag.split_at(position + 2),
+            None => tag.split_at(1),
+        };
+        if suffix.is_empty()
+            || handle.len() > 2
+                && !handle[1..handle.len() - 1]
+                    .iter()
+                    .all(|&byte| byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_')
+        {
+            return None;
+        }
+        normalized.extend_from_slice(yaml_tag_prefix(document, handle)?);
+        if !yaml_tag_decode(suffix, true, &mut normalized) {
+            return None;
+        }
+    } else {
+        // A global tag is a URI, which starts with its scheme.
+        let colon = tag.iter().position(|&byte| byte == b':')?;
+        let scheme = &tag[..colon];
+        if !scheme.first()?.is_ascii_alphabetic()
+            || !scheme.iter().all(|&byte| {
+                byte.is_ascii_alphanumeric() || byte == b'+' || byte == b'-' || byte == b'.'
+            })
+            || !yaml_tag_decode(tag, false, &mut normalized)
+        {
+            return None;
+        }
+    }
+    if normalized.contains(&b'\0') || str::from_utf8(&normalized).is_err() {
+        return None;
+    }
+    Some(normalized)
+}
+
+/// Find the prefix of a tag handle in the tag directives of a document.
+unsafe fn yaml_tag_prefix<'a>(document: *const yaml_document_t, handle: &[u8]) -> Option<&'a [u8]> {
+    let mut tag_directive = (*document).tag_directives.start;
+    while tag_directive < (*document).tag_directives.end {
+        let directive_handle = slice::from_raw_parts(
+            (*tag_directive).handle,
+            strlen((*tag_directive).handle as *const libc::c_char) as usize,
+        );
+        if directive_handle == handle {
+            return Some(slice::from_raw_parts(
+                (*tag_directive).prefix,
+                strlen((*tag_directive).prefix as *const libc::c_char) as usize,
+            ));
+        }
+        tag_directive = tag_directive.add(1);
+    }
+    match handle {
+        b"!" => Some(b"!"),
+        b"!!" => Some(b"tag:yaml.org,2002:"),
+        _ => None,
+    }
+}
+
+/// Decode the URI escapes of a part of a tag, checking its characters.
+///
+/// The suffix of a shorthand tag cannot hold `!` or flow indicators.
+fn yaml_tag_decode(part: &[u8], suffix: bool, decoded: &mut Vec<u8>) -> bool {
+    let mut index = 0;
+    while index < part.len() {
+        let byte = part[index];
+        if byte == b'%' {
+            let escape = match part.get(index + 1..index + 3) {
+                Some(escape) => escape,
+                None => return false,
+            };
+            match str::from_utf8(escape)
+                .ok()
+                .filter(|escape| escape.bytes().all(|byte| byte.is_ascii_hexdigit()))
+                .and_then(|escape| u8::from_str_radix(escape, 16).ok())
+            {
+                Some(value) => decoded.push(value),
+                None => return false,
+            }
+            index += 3;
+        } else if (byte.is_ascii_alphanumeric() || b"-#;/?:@&=+$,_.!~*'()[]".contains(&byte))
+            && !(suffix && b"!,[]".contains(&byte))
+        {
+            decoded.push(byte);
+            index += 1;
+        } else {
+            return false;
+        }
+    }
+    true
+}
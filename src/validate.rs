@@ -0,0 +1,280 @@
+use crate::externs::strlen;
+use crate::schema::yaml_scalar_node_resolve;
+use crate::success::{Success, FAIL, OK};
+use crate::yaml::yaml_char_t;
+use crate::{
+    libc, yaml_document_t, yaml_mark_t, yaml_node_t, yaml_resolved_scalar_t, yaml_schema_t,
+    yaml_shape_field_t, yaml_shape_t, yaml_violation_handler_t, yaml_violation_t,
+    yaml_violation_type_t, PointerExt, YAML_ALIAS_NODE, YAML_MAPPING_NODE, YAML_NO_NODE,
+    YAML_SCALAR_NODE, YAML_SEQUENCE_NODE, YAML_VIOLATION_MISSING_KEY, YAML_VIOLATION_NODE_TYPE,
+    YAML_VIOLATION_UNKNOWN_KEY, YAML_VIOLATION_VALUE_TYPE,
+};
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::mem::MaybeUninit;
+use core::ptr;
+use core::slice;
+
+struct yaml_validate_ctx_t {
+    document: *mut yaml_document_t,
+    schema: yaml_schema_t,
+    handler: yaml_violation_handler_t,
+    data: *mut libc::c_void,
+    /// The JSON Pointer to the node being validated, without the NUL.
+    path: Vec<u8>,
+    /// The nodes being validated with their shapes, to stop at cycles.
+    stack: Vec<(libc::c_int, *const yaml_shape_t)>,
+    valid: bool,
+}
+
+/// Check that a document has the expected structure.
+///
+/// The root node of the document is matched against `shape`, which gives the
+/// node type, the value type of scalars resolved with `schema`, the shape of
+/// the items of sequences and the known keys of mappings, recursively. The
+/// `handler` is called with `data` for every violation, with the path and the
+/// marks of the offending node. An empty document only matches a shape that
+/// accepts any node type, and alias nodes are matched as the nodes they refer
+/// to.
+///
+/// `valid` is set to whether no violation was found. Returns FAIL if the
+/// handler failed, which stops the validation.
+pub unsafe fn yaml_document_validate(
+    document: *mut yaml_document_t,
+    shape: *const yaml_shape_t,
+    schema: yaml_schema_t,
+    handler: yaml_violation_handler_t,
+    data: *mut libc::c_void,
+    valid: *mut bool,
+) -> Success {
+    __assert!(!document.is_null());
+    __assert!(!shape.is_null());
+    __assert!(!valid.is_null());
+    let mut ctx = yaml_validate_ctx_t {
+        document,
+        schema,
+        handler,
+        data,
+        path: Vec::new(),
+        stack: Vec::new(),
+        valid: true,
+    };
+    let success = if (*document).nodes.start == (*document).nodes.top {
+        if (*shape).node_type == YAML_NO_NODE {
+            OK
+        } else {
+            yaml_validate_report(
+                &mut ctx,
+                YAML_VIOLATION_NODE_TYPE,
+                0,
+                ptr::null::<yaml_char_t>(),
+            )
+        }
+    } else {
+        yaml_validate_node(&mut ctx, 1, shape)
+    };
+    *valid = ctx.valid;
+    success
+}
+
+unsafe fn yaml_validate_report(
+    ctx: &mut yaml_validate_ctx_t,
+    type_: yaml_violation_type_t,
+    node: libc::c_int,
+    key: *const yaml_char_t,
+) -> Success {
+    ctx.valid = false;
+    let (start_mark, end_mark) = if node == 0 {
+        (
+            MaybeUninit::<yaml_mark_t>::zeroed().assume_init(),
+            MaybeUninit::<yaml_mark_t>::zeroed().assume_init(),
+        )
+    } else {
+        let node = (*ctx.document).nodes.start.add(node as usize - 1);
+        ((*node).start_mark, (*node).end_mark)
+    };
+    ctx.path.push(b'\0');
+    let violation = yaml_violation_t {
+        type_,
+        path: ctx.path.as_ptr(),
+        node,
+        key,
+        start_mark,
+        end_mark,
+    };
+    let result = (ctx.handler)(ctx.data, &violation);
+    ctx.path.pop();
+    if result == 0 {
+        FAIL
+    } else {
+        OK
+    }
+}
+
+unsafe fn yaml_validate_node(
+    ctx: &mut yaml_validate_ctx_t,
+    mut id: libc::c_int,
+    shape: *const yaml_shape_t,
+) -> Success {
+    let mut node: *mut yaml_node_t = (*ctx.document).nodes.start.add(id as usize - 1);
+    while (*node).type_ == YAML_ALIAS_NODE {
+        id = (*node).data.alias.target;
+        node = (*ctx.document).nodes.start.add(id as usize - 1);
+    }
+    if ctx.stack.contains(&(id, shape)) {
+        return OK;
+    }
+    if (*shape).node_type != YAML_NO_NODE && (*shape).node_type != (*node).type_ {
+        return yaml_validate_report(
+            ctx,
+            YAML_VIOLATION_NODE_TYPE,
+            id,
+            ptr::null::<yaml_char_t>(),
+        );
+    }
+    ctx.stack.push((id, shape));
+    let success = match (*node).type_ {
+        YAML_SCALAR_NODE => {
+            let mut resolved = MaybeUninit::<yaml_resolved_scalar_t>::uninit();
+            if (*shape).check_value_type
+                && (yaml_scalar_node_resolve(ctx.schema, node, resolved.as_mut_ptr()).fail
+                    || (*resolved.as_ptr()).type_ != (*shape).value_type)
+            {
+                yaml_validate_report(
+                    ctx,
+                    YAML_VIOLATION_VALUE_TYPE,
+                    id,
+                    ptr::null::<yaml_char_t>(),
+                )
+            } else {
+                OK
+            }
+        }
+        YAML_SEQUENCE_NODE => yaml_validate_sequence(ctx, node, shape),
+        YAML_MAPPING_NODE => yaml_validate_mapping(ctx, id, node, shape),
+        _ => OK,
+    };
+    ctx.stack.pop();
+    success
+}
+
+unsafe fn yaml_validate_sequence(
+    ctx: &mut yaml_validate_ctx_t,
+    node: *mut yaml_node_t,
+    shape: *const yaml_shape_t,
+) -> Success {
+    if (*shape).items.is_null() {
+        return OK;
+    }
+    let items = (*node).data.sequence.items;
+    let length = ctx.path.len();
+    let mut item = items.start;
+    while item < items.top {
+        ctx.path.push(b'/');
+        ctx.path.extend_from_slice(
+            (item.c_offset_from(items.start) as usize)
+                .to_string()
+                .as_bytes(),
+        );
+        let success = yaml_validate_node(ctx, *item, (*shape).items);
+        ctx.path.truncate(length);
+        if success.fail {
+            return FAIL;
+        }
+        item = item.add(1);
+    }
+    OK
+}
+
+unsafe fn yaml_validate_mapping(
+    ctx: &mut yaml_validate_ctx_t,
+    id: libc::c_int,
+    node: *mut yaml_node_t,
+    shape: *const yaml_shape_t,
+) -> Success {
+    let fields: &[yaml_shape_field_t] = if (*shape).fields_length == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts((*shape).fields, (*shape).fields_length as usize)
+    };
+    let pairs = (*node).data.mapping.pairs;
+    let length = ctx.path.len();
+    let mut pair = pairs.start;
+    while pair < pairs.top {
+        let key = yaml_validate_key(ctx.document, (*pair).key);
+        let field = match key {
+            Some(key) => fields
+                .iter()
+                .find(|field| yaml_validate_field_key(field) == key),
+            None => None,
+        };
+        ctx.path.push(b'/');
+        if let Some(key) = key {
+            for &byte in key {
+                match byte {
+                    b'~' => ctx.path.extend_from_slice(b"~0"),
+                    b'/' => ctx.path.extend_from_slice(b"~1"),
+                    _ => ctx.path.push(byte),
+                }
+            }
+        }
+        let success = match field {
+            Some(field) if !field.shape.is_null() => {
+                yaml_validate_node(ctx, (*pair).value, field.shape)
+            }
+            None if (*shape).closed => yaml_validate_report(
+                ctx,
+                YAML_VIOLATION_UNKNOWN_KEY,
+                (*pair).key,
+                ptr::null::<yaml_char_t>(),
+            ),
+            _ => OK,
+        };
+        ctx.path.truncate(length);
+        if success.fail {
+            return FAIL;
+        }
+        pair = pair.add(1);
+    }
+    for field in fields {
+        if !field.required {
+            continue;
+        }
+        let mut found = false;
+        pair = pairs.start;
+        while pair < pairs.top {
+            if yaml_validate_key(ctx.document, (*pair).key) == Some(yaml_validate_field_key(field))
+            {
+                found = true;
+                break;
+            }
+            pair = pair.add(1);
+        }
+        if !found && yaml_validate_report(ctx, YAML_VIOLATION_MISSING_KEY, id, field.key).fail {
+            return FAIL;
+        }
+    }
+    OK
+}
+
+unsafe fn yaml_validate_key<'a>(
+    document: *mut yaml_document_t,
+    mut id: libc::c_int,
+) -> Option<&'a [u8]> {
+    let mut node: *mut yaml_node_t = (*document).nodes.start.add(id as usize - 1);
+    while (*node).type_ == YAML_ALIAS_NODE {
+        id = (*node).data.alias.target;
+        node = (*document).nodes.start.add(id as usize - 1);
+    }
+    if (*node).type_ != YAML_SCALAR_NODE {
+        return None;
+    }
+    Some(slice::from_raw_parts(
+        (*node).data.scalar.value,
+        (*node).data.scalar.length as usize,
+    ))
+}
+
+unsafe fn yaml_validate_field_key<'a>(field: &yaml_shape_field_t) -> &'a [u8] {
+    slice::from_raw_parts(field.key, strlen(field.key as *const libc::c_char) as usize)
+}
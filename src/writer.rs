@@ -1,16 +1,30 @@
+use crate::api::yaml_string_extend;
+use crate::externs::memset;
 use crate::ops::ForceAdd as _;
 use crate::success::{Success, FAIL, OK};
-use crate::yaml::size_t;
+use crate::yaml::{size_t, yaml_emitter_metrics_t};
 use crate::{
     libc, yaml_emitter_t, PointerExt, YAML_ANY_ENCODING, YAML_UTF16LE_ENCODING, YAML_UTF8_ENCODING,
     YAML_WRITER_ERROR,
 };
+#[cfg(feature = "async")]
+use crate::{yaml_emitter_emit, yaml_event_t};
+use core::mem::size_of;
+#[cfg(feature = "async")]
+use core::ptr;
 use core::ptr::addr_of_mut;
+#[cfg(feature = "async")]
+use core::slice;
+#[cfg(feature = "async")]
+use tokio::io::{AsyncWrite, AsyncWriteExt as _};
 
 unsafe fn yaml_emitter_set_writer_error(
     emitter: *mut yaml_emitter_t,
     problem: *const libc::c_char,
 ) -> Success {
+    if (*emitter).error == YAML_WRITER_ERROR {
+        return FAIL;
+    }
     (*emitter).error = YAML_WRITER_ERROR;
     let fresh0 = addr_of_mut!((*emitter).problem);
     *fresh0 = problem;
@@ -18,6 +32,10 @@ unsafe fn yaml_emitter_set_writer_error(
 }
 
 /// Flush the accumulated characters to the output.
+///
+/// If the write handler reports that the output would block, the characters
+/// stay buffered in the emitter and are passed to the handler again by the
+/// next flush.
 pub unsafe fn yaml_emitter_flush(emitter: *mut yaml_emitter_t) -> Success {
     __assert!(!emitter.is_null());
     __assert!(((*emitter).write_handler).is_some());
@@ -26,19 +44,40 @@ pub unsafe fn yaml_emitter_flush(emitter: *mut yaml_emitter_t) -> Success {
     *fresh1 = (*emitter).buffer.pointer;
     let fresh2 = addr_of_mut!((*emitter).buffer.pointer);
     *fresh2 = (*emitter).buffer.start;
-    if (*emitter).buffer.start == (*emitter).buffer.last {
+    if (*emitter).buffer.start == (*emitter).buffer.last
+        && (*emitter).raw_buffer.start == (*emitter).raw_buffer.last
+    {
         return OK;
     }
     if (*emitter).encoding == YAML_UTF8_ENCODING {
-        if (*emitter).write_handler.expect("non-null function pointer")(
+        let result = (*emitter).write_handler.expect("non-null function pointer")(
             (*emitter).write_handler_data,
             (*emitter).buffer.start,
             (*emitter)
                 .buffer
                 .last
                 .c_offset_from((*emitter).buffer.start) as size_t,
-        ) != 0
-        {
+        );
+        if result == -1 {
+            let fresh12 = addr_of_mut!((*emitter).buffer.pointer);
+            *fresh12 = (*emitter).buffer.last;
+            if (*emitter).buffer.pointer.add(5) >= (*emitter).buffer.end {
+                yaml_string_extend(
+                    addr_of_mut!((*emitter).buffer.start),
+                    addr_of_mut!((*emitter).buffer.pointer),
+                    addr_of_mut!((*emitter).buffer.end),
+                );
+            }
+            let fresh13 = addr_of_mut!((*emitter).buffer.last);
+            *fresh13 = (*emitter).buffer.pointer;
+            return OK;
+        } else if result != 0 {
+            (*emitter).bytes_written = (*emitter).bytes_written.force_add(
+                (*emitter)
+                    .buffer
+                    .last
+                    .c_offset_from((*emitter).buffer.start) as size_t,
+            );
             let fresh3 = addr_of_mut!((*emitter).buffer.last);
             *fresh3 = (*emitter).buffer.start;
             let fresh4 = addr_of_mut!((*emitter).buffer.pointer);
@@ -61,6 +100,25 @@ pub unsafe fn yaml_emitter_flush(emitter: *mut yaml_emitter_t) -> Success {
     } else {
         0
     };
+    while (*emitter)
+        .raw_buffer
+        .end
+        .c_offset_from((*emitter).raw_buffer.last)
+        < ((*emitter)
+            .buffer
+            .last
+            .c_offset_from((*emitter).buffer.start)
+            * 2
+            + 2) as isize
+    {
+        yaml_string_extend(
+            addr_of_mut!((*emitter).raw_buffer.start),
+            addr_of_mut!((*emitter).raw_buffer.last),
+            addr_of_mut!((*emitter).raw_buffer.end),
+        );
+        let fresh14 = addr_of_mut!((*emitter).raw_buffer.pointer);
+        *fresh14 = (*emitter).raw_buffer.start;
+    }
     while (*emitter).buffer.pointer != (*emitter).buffer.last {
         let mut octet: libc::c_uchar;
         let mut value: libc::c_uint;
@@ -122,15 +180,27 @@ pub unsafe fn yaml_emitter_flush(emitter: *mut yaml_emitter_t) -> Success {
             *fresh7 = (*fresh7).wrapping_offset(4_isize);
         }
     }
-    if (*emitter).write_handler.expect("non-null function pointer")(
+    let result = (*emitter).write_handler.expect("non-null function pointer")(
         (*emitter).write_handler_data,
         (*emitter).raw_buffer.start,
         (*emitter)
             .raw_buffer
             .last
             .c_offset_from((*emitter).raw_buffer.start) as size_t,
-    ) != 0
-    {
+    );
+    if result == -1 {
+        let fresh15 = addr_of_mut!((*emitter).buffer.last);
+        *fresh15 = (*emitter).buffer.start;
+        let fresh16 = addr_of_mut!((*emitter).buffer.pointer);
+        *fresh16 = (*emitter).buffer.start;
+        OK
+    } else if result != 0 {
+        (*emitter).bytes_written = (*emitter).bytes_written.force_add(
+            (*emitter)
+                .raw_buffer
+                .last
+                .c_offset_from((*emitter).raw_buffer.start) as size_t,
+        );
         let fresh8 = addr_of_mut!((*emitter).buffer.last);
         *fresh8 = (*emitter).buffer.start;
         let fresh9 = addr_of_mut!((*emitter).buffer.pointer);
@@ -147,3 +217,153 @@ pub unsafe fn yaml_emitter_flush(emitter: *mut yaml_emitter_t) -> Success {
         )
     }
 }
+
+/// Emit an event and write the output to an asynchronous writer.
+///
+/// The output must have been set with yaml_emitter_set_output_async(). The
+/// event is emitted as by yaml_emitter_emit(). Each time the emitter flushes
+/// its buffer, which it does when the buffer is full, at the end of a document
+/// and at the end of the stream, the buffered characters are written to
+/// `writer`, which is then flushed. A failure of the writer is reported as a
+/// writer error of the emitter.
+///
+/// The returned future borrows the emitter and the event through raw pointers,
+/// so it is not Send. Neither must be used or moved until the future completes.
+#[cfg(feature = "async")]
+pub async unsafe fn yaml_emitter_emit_async<W>(
+    emitter: *mut yaml_emitter_t,
+    event: *mut yaml_event_t,
+    writer: &mut W,
+) -> Success
+where
+    W: AsyncWrite + Unpin + ?Sized,
+{
+    __assert!(!emitter.is_null());
+    __assert!((*emitter).write_handler_data.is_null());
+    // The write handler of yaml_emitter_set_output_async() records through its
+    // data that the emitter flushed. The flag is only used during the
+    // synchronous yaml_emitter_emit() call.
+    let mut flushed = false;
+    let fresh17 = addr_of_mut!((*emitter).write_handler_data);
+    *fresh17 = addr_of_mut!(flushed) as *mut libc::c_void;
+    let success = yaml_emitter_emit(emitter, event);
+    let fresh18 = addr_of_mut!((*emitter).write_handler_data);
+    *fresh18 = ptr::null_mut();
+    if success.fail {
+        return FAIL;
+    }
+    if flushed {
+        yaml_emitter_write_async(emitter, writer).await
+    } else {
+        OK
+    }
+}
+
+/// Flush the accumulated characters to an asynchronous writer.
+///
+/// The output must have been set with yaml_emitter_set_output_async(). The
+/// characters buffered in the emitter are written to `writer`, which is then
+/// flushed. A failure of the writer is reported as a writer error of the
+/// emitter.
+///
+/// The returned future borrows the emitter through a raw pointer, so it is not
+/// Send. The emitter must not be used or moved until the future completes.
+#[cfg(feature = "async")]
+pub async unsafe fn yaml_emitter_flush_async<W>(
+    emitter: *mut yaml_emitter_t,
+    writer: &mut W,
+) -> Success
+where
+    W: AsyncWrite + Unpin + ?Sized,
+{
+    __assert!(!emitter.is_null());
+    if yaml_emitter_flush(emitter).fail {
+        return FAIL;
+    }
+    yaml_emitter_write_async(emitter, writer).await
+}
+
+#[cfg(feature = "async")]
+async unsafe fn yaml_emitter_write_async<W>(emitter: *mut yaml_emitter_t, writer: &mut W) -> Success
+where
+    W: AsyncWrite + Unpin + ?Sized,
+{
+    // Characters appended to the UTF-8 buffer since the flush are converted to
+    // UTF-16 by the next one.
+    let (start, end) = if (*emitter).encoding == YAML_UTF8_ENCODING {
+        ((*emitter).buffer.start, (*emitter).buffer.pointer)
+    } else {
+        ((*emitter).raw_buffer.start, (*emitter).raw_buffer.last)
+    };
+    let size = end.c_offset_from(start) as size_t;
+    let pending = slice::from_raw_parts(start, size as usize);
+    if writer.write_all(pending).await.is_err() || writer.flush().await.is_err() {
+        return yaml_emitter_set_writer_error(
+            emitter,
+            b"write error\0" as *const u8 as *const libc::c_char,
+        );
+    }
+    (*emitter).bytes_written = (*emitter).bytes_written.force_add(size);
+    if (*emitter).encoding == YAML_UTF8_ENCODING {
+        let fresh19 = addr_of_mut!((*emitter).buffer.last);
+        *fresh19 = (*emitter).buffer.start;
+        let fresh20 = addr_of_mut!((*emitter).buffer.pointer);
+        *fresh20 = (*emitter).buffer.start;
+    } else {
+        let fresh21 = addr_of_mut!((*emitter).raw_buffer.last);
+        *fresh21 = (*emitter).raw_buffer.start;
+        let fresh22 = addr_of_mut!((*emitter).raw_buffer.pointer);
+        *fresh22 = (*emitter).raw_buffer.start;
+    }
+    OK
+}
+
+/// Get the number of bytes that are buffered in the emitter and not yet
+/// accepted by the write handler.
+///
+/// Before the stream is started, the encoding is not chosen yet and nothing is
+/// pending. With a UTF-16 encoding, the characters not converted yet are
+/// counted by their UTF-8 bytes.
+pub unsafe fn yaml_emitter_pending(emitter: *const yaml_emitter_t) -> size_t {
+    __assert!(!emitter.is_null());
+    let buffered = (*emitter)
+        .buffer
+        .pointer
+        .c_offset_from((*emitter).buffer.start) as size_t;
+    if (*emitter).encoding == YAML_ANY_ENCODING {
+        0
+    } else if (*emitter).encoding == YAML_UTF8_ENCODING {
+        buffered
+    } else {
+        ((*emitter)
+            .raw_buffer
+            .last
+            .c_offset_from((*emitter).raw_buffer.start) as size_t)
+            .force_add(buffered)
+    }
+}
+
+/// Get the characteristics of the output emitted so far.
+///
+/// Bytes that are still buffered in the emitter are not counted until the
+/// write handler accepts them.
+pub unsafe fn yaml_emitter_get_metrics(
+    emitter: *const yaml_emitter_t,
+    metrics: *mut yaml_emitter_metrics_t,
+) {
+    __assert!(!emitter.is_null());
+    __assert!(!metrics.is_null());
+    memset(
+        metrics as *mut libc::c_void,
+        0,
+        size_of::<yaml_emitter_metrics_t>() as libc::c_ulong,
+    );
+    (*metrics).bytes = (*emitter).bytes_written;
+    (*metrics).lines = (*emitter).lines_written;
+    (*metrics).max_column = if (*emitter).column > (*emitter).max_column {
+        (*emitter).column
+    } else {
+        (*emitter).max_column
+    };
+    (*metrics).quoted_scalars = (*emitter).quoted_scalars;
+}
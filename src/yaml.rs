@@ -86,6 +86,10 @@ pub enum yaml_error_type_t {
 #[non_exhaustive]
 pub struct yaml_mark_t {
     /// The position index.
+    ///
+    /// This is a byte offset into the UTF-8 text of the stream. A byte order
+    /// mark is not counted, and UTF-16 input is measured after it has been
+    /// decoded to UTF-8.
     pub index: size_t,
     /// The position line.
     pub line: size_t,
@@ -93,6 +97,26 @@ pub struct yaml_mark_t {
     pub column: size_t,
 }
 
+/// The description of an error.
+///
+/// Filled by the functions that set up a parser or an emitter of their own,
+/// such as yaml_document_load_string().
+#[derive(Copy, Clone)]
+#[repr(C)]
+#[non_exhaustive]
+pub struct yaml_error_t {
+    /// Error type.
+    pub error: yaml_error_type_t,
+    /// Error description.
+    pub problem: *const libc::c_char,
+    /// The problem position.
+    pub problem_mark: yaml_mark_t,
+    /// The error context.
+    pub context: *const libc::c_char,
+    /// The context position.
+    pub context_mark: yaml_mark_t,
+}
+
 /// Scalar styles.
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 #[repr(u32)]
@@ -112,6 +136,21 @@ pub enum yaml_scalar_style_t {
     YAML_FOLDED_SCALAR_STYLE = 5,
 }
 
+/// Chomping of the final line breaks of a block scalar.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[repr(u32)]
+#[non_exhaustive]
+pub enum yaml_chomping_t {
+    /// Let the emitter choose the chomping.
+    YAML_ANY_CHOMPING = 0,
+    /// Keep a single final line break (no indicator).
+    YAML_CLIP_CHOMPING = 1,
+    /// Strip all final line breaks (the `-` indicator).
+    YAML_STRIP_CHOMPING = 2,
+    /// Keep all final line breaks (the `+` indicator).
+    YAML_KEEP_CHOMPING = 3,
+}
+
 /// Sequence styles.
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 #[repr(u32)]
@@ -138,6 +177,122 @@ pub enum yaml_mapping_style_t {
     YAML_FLOW_MAPPING_STYLE = 2,
 }
 
+/// Document end marker policies.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[repr(u32)]
+#[non_exhaustive]
+pub enum yaml_document_end_marker_t {
+    /// Write the marker when the event is explicit or the output needs it.
+    YAML_ANY_DOCUMENT_END_MARKER = 0,
+    /// Write the marker after every document.
+    YAML_ALWAYS_DOCUMENT_END_MARKER = 1,
+    /// Write the marker only when the output needs it.
+    YAML_NEVER_DOCUMENT_END_MARKER = 2,
+}
+
+/// Document start marker policies.
+///
+/// A document with directives always gets the marker.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[repr(u32)]
+#[non_exhaustive]
+pub enum yaml_document_start_marker_t {
+    /// Leave out the marker of the first document if the event is implicit,
+    /// except in the canonical format.
+    YAML_ANY_DOCUMENT_START_MARKER = 0,
+    /// Leave out the marker of the first document if the event is implicit.
+    YAML_OMIT_FIRST_DOCUMENT_START_MARKER = 1,
+    /// Write the marker only between documents.
+    YAML_BETWEEN_DOCUMENT_START_MARKER = 2,
+}
+
+/// Handling of duplicate mapping keys in dumped documents.
+///
+/// Keys are duplicates if they are scalars with the same tag and value.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[repr(u32)]
+#[non_exhaustive]
+pub enum yaml_duplicate_keys_t {
+    /// Dump every pair as it is.
+    YAML_ALLOW_DUPLICATE_KEYS = 0,
+    /// Fail with the YAML_EMITTER_ERROR error.
+    YAML_REJECT_DUPLICATE_KEYS = 1,
+    /// Dump only the last pair with a given key.
+    YAML_KEEP_LAST_DUPLICATE_KEY = 2,
+}
+
+/// Handling of sequences in documents merged by yaml_document_merge().
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[repr(u32)]
+#[non_exhaustive]
+pub enum yaml_sequence_merge_t {
+    /// Replace the sequence with the one of the overlay.
+    YAML_REPLACE_SEQUENCES = 0,
+    /// Append the items of the overlay sequence to the sequence.
+    YAML_APPEND_SEQUENCES = 1,
+}
+
+/// The schemas of the YAML 1.2 specification, and the types of YAML 1.1.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[repr(u32)]
+#[non_exhaustive]
+pub enum yaml_schema_t {
+    /// Every scalar is a string.
+    YAML_FAILSAFE_SCHEMA = 0,
+    /// Plain scalars are JSON literals.
+    YAML_JSON_SCHEMA = 1,
+    /// Plain scalars follow the human-friendly rules of the core schema.
+    YAML_CORE_SCHEMA = 2,
+    /// Plain scalars follow the rules of the YAML 1.1 types, with `yes` and
+    /// `off` booleans, binary, octal and base 60 integers and `_` separators.
+    YAML_1_1_SCHEMA = 3,
+}
+
+/// Resolutions of plain numbers too large for their type.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[repr(u32)]
+#[non_exhaustive]
+pub enum yaml_overflow_t {
+    /// Resolve integers as floats and floats as infinities.
+    YAML_OVERFLOW_FLOAT = 0,
+    /// Fail to resolve the scalar.
+    YAML_OVERFLOW_ERROR = 1,
+    /// Resolve the number to the closest value its type can hold.
+    YAML_OVERFLOW_SATURATE = 2,
+    /// Resolve the scalar as a string.
+    YAML_OVERFLOW_STRING = 3,
+}
+
+/// The types of resolved scalar values.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[repr(u32)]
+#[non_exhaustive]
+pub enum yaml_value_type_t {
+    /// A null value.
+    YAML_NULL_VALUE = 0,
+    /// A boolean value.
+    YAML_BOOL_VALUE = 1,
+    /// An integer value.
+    YAML_INT_VALUE = 2,
+    /// A floating-point value.
+    YAML_FLOAT_VALUE = 3,
+    /// A string value.
+    YAML_STR_VALUE = 4,
+}
+
+/// Line wrapping policies for plain and single-quoted scalars.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[repr(u32)]
+#[non_exhaustive]
+pub enum yaml_wrap_policy_t {
+    /// Break a line at the first space after it reaches the preferred width.
+    YAML_WRAP_AT_WHITESPACE = 0,
+    /// Break a line at the last space that keeps it within the preferred width.
+    YAML_WRAP_AT_WIDTH = 1,
+    /// Never break a line.
+    YAML_NEVER_WRAP = 2,
+}
+
 /// Token types.
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 #[repr(u32)]
@@ -231,6 +386,10 @@ pub struct yaml_token_t {
     ///         length: u64,
     ///         /// The scalar style.
     ///         style: yaml_scalar_style_t,
+    ///         /// The chomping of a block scalar.
+    ///         chomping: yaml_chomping_t,
+    ///         /// Does a block scalar have an indentation indicator?
+    ///         indentation_indicator: bool,
     ///     },
     ///     /// The version directive (for YAML_VERSION_DIRECTIVE_TOKEN).
     ///     version_directive: struct {
@@ -319,6 +478,10 @@ pub struct unnamed_yaml_token_t_data_scalar {
     pub length: size_t,
     /// The scalar style.
     pub style: yaml_scalar_style_t,
+    /// The chomping of a block scalar.
+    pub chomping: yaml_chomping_t,
+    /// Does a block scalar have an indentation indicator?
+    pub indentation_indicator: bool,
 }
 
 #[derive(Copy, Clone)]
@@ -427,6 +590,12 @@ pub struct yaml_event_t {
     ///         quoted_implicit: i32,
     ///         /// The scalar style.
     ///         style: yaml_scalar_style_t,
+    ///         /// Are long lines kept unbroken?
+    ///         unfolded: bool,
+    ///         /// The chomping of a block scalar.
+    ///         chomping: yaml_chomping_t,
+    ///         /// Does a block scalar have an indentation indicator?
+    ///         indentation_indicator: bool,
     ///     },
     ///     /// The sequence parameters (for YAML_SEQUENCE_START_EVENT).
     ///     sequence_start: struct {
@@ -450,6 +619,11 @@ pub struct yaml_event_t {
     ///         /// The mapping style.
     ///         style: yaml_mapping_style_t,
     ///     },
+    ///     /// The alias parameters (for YAML_ALIAS_NODE).
+    ///     alias: struct {
+    ///         /// The anchored node the alias refers to.
+    ///         target: i32,
+    ///     },
     /// }
     /// # };
     /// ```
@@ -458,6 +632,13 @@ pub struct yaml_event_t {
     pub start_mark: yaml_mark_t,
     /// The end of the event.
     pub end_mark: yaml_mark_t,
+    /// The comment to write before the event, or NULL.
+    ///
+    /// For a DOCUMENT-END event, the comment is written after the document
+    /// content.
+    pub(crate) leading_comment: *mut yaml_char_t,
+    /// The comment to write after the event on the same line, or NULL.
+    pub(crate) trailing_comment: *mut yaml_char_t,
 }
 
 #[derive(Copy, Clone)]
@@ -543,6 +724,24 @@ pub struct unnamed_yaml_event_t_data_scalar {
     pub quoted_implicit: bool,
     /// The scalar style.
     pub style: yaml_scalar_style_t,
+    /// Are long lines kept unbroken?
+    ///
+    /// The emitter does not fold a scalar with this flag set at the preferred
+    /// width, whatever its style.
+    pub unfolded: bool,
+    /// The chomping of a block scalar.
+    ///
+    /// The emitter honors a requested chomping when it preserves the final
+    /// line breaks of the value, and chooses the chomping otherwise.
+    pub chomping: yaml_chomping_t,
+    /// Does a block scalar have an indentation indicator?
+    ///
+    /// The emitter writes the indicator anyway when the value starts with a
+    /// space or a line break.
+    pub indentation_indicator: bool,
+    /// The source text to write instead of the value if it still matches, or
+    /// NULL.
+    pub(crate) source: *mut yaml_scalar_source_t,
 }
 
 #[derive(Copy, Clone)]
@@ -586,6 +785,8 @@ pub enum yaml_node_type_t {
     YAML_SEQUENCE_NODE = 2,
     /// A mapping node.
     YAML_MAPPING_NODE = 3,
+    /// An alias node.
+    YAML_ALIAS_NODE = 4,
 }
 
 /// The node structure.
@@ -610,6 +811,12 @@ pub struct yaml_node_t {
     ///         length: u64,
     ///         /// The scalar style.
     ///         style: yaml_scalar_style_t,
+    ///         /// The chomping of a block scalar.
+    ///         chomping: yaml_chomping_t,
+    ///         /// Does a block scalar have an indentation indicator?
+    ///         indentation_indicator: bool,
+    ///         /// The source text of the scalar, or NULL.
+    ///         source: *mut yaml_scalar_source_t,
     ///     },
     ///     /// The sequence parameters (for YAML_SEQUENCE_NODE).
     ///     sequence: struct {
@@ -633,6 +840,20 @@ pub struct yaml_node_t {
     pub start_mark: yaml_mark_t,
     /// The end of the node.
     pub end_mark: yaml_mark_t,
+    /// The anchor the node was loaded with, or NULL.
+    pub anchor: *mut yaml_char_t,
+    /// The comment lines before the node, or NULL.
+    pub leading_comment: *mut yaml_char_t,
+    /// The comment after the node on the same line, or NULL.
+    pub trailing_comment: *mut yaml_char_t,
+    /// The data attached to the node by the application, or NULL.
+    pub user_data: *mut libc::c_void,
+    /// The transformation that made the node from another one, such as
+    /// "merge", or NULL for a node that was loaded or added.
+    pub transform: *const libc::c_char,
+    /// The id of the node it was made from, in the document given to the
+    /// transformation, or 0.
+    pub derived_from: libc::c_int,
 }
 
 #[derive(Copy, Clone)]
@@ -644,6 +865,8 @@ pub union unnamed_yaml_node_t_data {
     pub sequence: unnamed_yaml_node_t_data_sequence,
     /// The mapping parameters (for YAML_MAPPING_NODE).
     pub mapping: unnamed_yaml_node_t_data_mapping,
+    /// The alias parameters (for YAML_ALIAS_NODE).
+    pub alias: unnamed_yaml_node_t_data_alias,
 }
 
 #[derive(Copy, Clone)]
@@ -656,6 +879,36 @@ pub struct unnamed_yaml_node_t_data_scalar {
     pub length: size_t,
     /// The scalar style.
     pub style: yaml_scalar_style_t,
+    /// The chomping of a block scalar.
+    pub chomping: yaml_chomping_t,
+    /// Does a block scalar have an indentation indicator?
+    pub indentation_indicator: bool,
+    /// The source text of the scalar, or NULL.
+    ///
+    /// It is kept by yaml_parser_load() if yaml_parser_set_scalar_source() is
+    /// enabled, and freed with the node.
+    pub source: *mut yaml_scalar_source_t,
+}
+
+/// The source text of a scalar node.
+///
+/// The emitter writes the scalar as its source text while the node keeps the
+/// value and the style it was loaded with, and the text fits where the scalar
+/// is written.
+#[derive(Copy, Clone)]
+#[repr(C)]
+#[non_exhaustive]
+pub struct yaml_scalar_source_t {
+    /// The source text, with the quotes or the block scalar header.
+    pub text: *mut yaml_char_t,
+    /// The length of the source text.
+    pub length: size_t,
+    /// The value the source text stands for.
+    pub value: *mut yaml_char_t,
+    /// The length of the value.
+    pub value_length: size_t,
+    /// The scalar style of the source text.
+    pub style: yaml_scalar_style_t,
 }
 
 /// An element of a sequence node.
@@ -681,17 +934,300 @@ pub struct unnamed_yaml_node_t_data_mapping {
     pub style: yaml_mapping_style_t,
 }
 
+#[derive(Copy, Clone)]
+#[repr(C)]
+#[non_exhaustive]
+pub struct unnamed_yaml_node_t_data_alias {
+    /// The anchored node the alias refers to.
+    pub target: libc::c_int,
+}
+
 /// An element of a mapping node.
+///
+/// A pair always holds both of its nodes: the loader only adds a pair to its
+/// mapping once the value is loaded, and new() only makes pairs of two node
+/// ids. The fields keep the C layout.
 #[derive(Copy, Clone)]
 #[repr(C)]
 #[non_exhaustive]
 pub struct yaml_node_pair_t {
-    /// The key of the element.
+    /// The id of the key of the element.
+    pub(crate) key: libc::c_int,
+    /// The id of the value of the element.
+    pub(crate) value: libc::c_int,
+}
+
+impl yaml_node_pair_t {
+    /// A pair of the ids of a key and a value, or None if either is not a node
+    /// id.
+    pub fn new(key: libc::c_int, value: libc::c_int) -> Option<Self> {
+        if key > 0 && value > 0 {
+            Some(yaml_node_pair_t { key, value })
+        } else {
+            None
+        }
+    }
+
+    /// The id of the key.
+    pub fn key(&self) -> libc::c_int {
+        self.key
+    }
+
+    /// The id of the value.
+    pub fn value(&self) -> libc::c_int {
+        self.value
+    }
+}
+
+/// A reference to a node that is shared within a document.
+#[derive(Copy, Clone)]
+#[repr(C)]
+#[non_exhaustive]
+pub struct yaml_alias_edge_t {
+    /// The node holding the reference (0 for the document itself).
+    pub parent: libc::c_int,
+    /// The shared node.
+    pub target: libc::c_int,
+}
+
+/// The kinds of node visits made by yaml_document_walk().
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[repr(u32)]
+#[non_exhaustive]
+pub enum yaml_visit_type_t {
+    /// A scalar node.
+    YAML_VISIT_SCALAR = 0,
+    /// A sequence node, before its items.
+    YAML_VISIT_SEQUENCE_START = 1,
+    /// A sequence node, after its items.
+    YAML_VISIT_SEQUENCE_END = 2,
+    /// A mapping node, before its pairs.
+    YAML_VISIT_MAPPING_START = 3,
+    /// A mapping node, after its pairs.
+    YAML_VISIT_MAPPING_END = 4,
+    /// A node that was visited before and is not entered again, or an alias
+    /// node.
+    YAML_VISIT_ALIAS = 5,
+}
+
+/// A step of the path from the root node to a visited node.
+#[derive(Copy, Clone)]
+#[repr(C)]
+#[non_exhaustive]
+pub struct yaml_path_step_t {
+    /// The sequence or mapping node the step goes into.
+    pub parent: libc::c_int,
+    /// The position of the item in the sequence or of the pair in the mapping.
+    pub index: size_t,
+    /// The key node of the pair, or 0 in a sequence.
     pub key: libc::c_int,
-    /// The value of the element.
+    /// Does the step go to the key of the pair rather than to its value?
+    pub is_key: bool,
+}
+
+/// The kinds of changes reported by yaml_document_diff().
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[repr(u32)]
+#[non_exhaustive]
+pub enum yaml_diff_type_t {
+    /// A node is added at the path.
+    YAML_DIFF_ADD = 0,
+    /// The node at the path is removed.
+    YAML_DIFF_REMOVE = 1,
+    /// The node at the path is replaced.
+    YAML_DIFF_REPLACE = 2,
+}
+
+/// A change between two documents.
+#[derive(Copy, Clone)]
+#[repr(C)]
+#[non_exhaustive]
+pub struct yaml_diff_op_t {
+    /// The kind of change.
+    pub type_: yaml_diff_type_t,
+    /// The NUL-terminated JSON Pointer to the changed location.
+    pub path: *const yaml_char_t,
+    /// The node of the old document at the path, or 0 for YAML_DIFF_ADD.
+    pub old_node: libc::c_int,
+    /// The node of the new document at the path, or 0 for YAML_DIFF_REMOVE.
+    pub new_node: libc::c_int,
+}
+
+/// The kinds of operations applied by yaml_document_apply_patch().
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[repr(u32)]
+#[non_exhaustive]
+pub enum yaml_patch_type_t {
+    /// Add the value at the path, inserting it into a sequence.
+    YAML_PATCH_ADD = 0,
+    /// Remove the node at the path.
+    YAML_PATCH_REMOVE = 1,
+    /// Replace the node at the path with the value.
+    YAML_PATCH_REPLACE = 2,
+    /// Remove the node at `from` and add it at the path.
+    YAML_PATCH_MOVE = 3,
+    /// Add a copy of the node at `from` at the path.
+    YAML_PATCH_COPY = 4,
+    /// Check that the node at the path equals the value.
+    YAML_PATCH_TEST = 5,
+}
+
+/// An operation of a patch.
+#[derive(Copy, Clone)]
+#[repr(C)]
+#[non_exhaustive]
+pub struct yaml_patch_op_t {
+    /// The kind of operation.
+    pub type_: yaml_patch_type_t,
+    /// The NUL-terminated JSON Pointer to the location operated on.
+    pub path: *const yaml_char_t,
+    /// The NUL-terminated JSON Pointer to the source of YAML_PATCH_MOVE and
+    /// YAML_PATCH_COPY, or NULL.
+    pub from: *const yaml_char_t,
+    /// The node of the values document for YAML_PATCH_ADD, YAML_PATCH_REPLACE
+    /// and YAML_PATCH_TEST, or 0.
     pub value: libc::c_int,
 }
 
+/// The parts of two documents left out by yaml_document_compare().
+///
+/// Initialize it with yaml_compare_options_initialize(), which compares
+/// everything.
+#[derive(Copy, Clone)]
+#[repr(C)]
+#[non_exhaustive]
+pub struct yaml_compare_options_t {
+    /// Ignore the scalar, sequence and mapping styles?
+    pub ignore_styles: bool,
+    /// Ignore the start and end marks of the nodes?
+    pub ignore_marks: bool,
+    /// Ignore the anchors of the nodes and which nodes are shared?
+    pub ignore_anchors: bool,
+    /// Ignore the order of the pairs of mappings?
+    pub ignore_key_order: bool,
+}
+
+/// The options of yaml_document_canonicalize().
+///
+/// Initialize it with yaml_canonicalize_options_initialize(), which expands
+/// every alias.
+#[derive(Copy, Clone)]
+#[repr(C)]
+#[non_exhaustive]
+pub struct yaml_canonicalize_options_t {
+    /// Keep the shared nodes shared rather than copying them for every
+    /// reference?
+    pub keep_aliases: bool,
+}
+
+/// The expected structure of a node, checked by yaml_document_validate().
+///
+/// A shape zeroed with memset() accepts any node.
+#[derive(Copy, Clone)]
+#[repr(C)]
+#[non_exhaustive]
+pub struct yaml_shape_t {
+    /// The expected node type, or YAML_NO_NODE for any type.
+    pub node_type: yaml_node_type_t,
+    /// Check the value type of scalars?
+    pub check_value_type: bool,
+    /// The type a scalar should resolve to with the schema.
+    pub value_type: yaml_value_type_t,
+    /// The shape of the items of sequences, or NULL.
+    pub items: *const yaml_shape_t,
+    /// The known keys of mappings.
+    pub fields: *const yaml_shape_field_t,
+    /// The number of known keys.
+    pub fields_length: size_t,
+    /// Are the keys of mappings limited to the known keys?
+    pub closed: bool,
+}
+
+/// A known key of a mapping shape.
+#[derive(Copy, Clone)]
+#[repr(C)]
+#[non_exhaustive]
+pub struct yaml_shape_field_t {
+    /// The NUL-terminated value of the scalar key.
+    pub key: *const yaml_char_t,
+    /// Must the mapping have the key?
+    pub required: bool,
+    /// The shape of the value, or NULL.
+    pub shape: *const yaml_shape_t,
+}
+
+/// The kinds of violations reported by yaml_document_validate().
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[repr(u32)]
+#[non_exhaustive]
+pub enum yaml_violation_type_t {
+    /// The node is not of the expected node type.
+    YAML_VIOLATION_NODE_TYPE = 0,
+    /// The scalar does not resolve to the expected value type.
+    YAML_VIOLATION_VALUE_TYPE = 1,
+    /// The mapping lacks a required key.
+    YAML_VIOLATION_MISSING_KEY = 2,
+    /// The key is not a known key of a closed mapping shape.
+    YAML_VIOLATION_UNKNOWN_KEY = 3,
+}
+
+/// A node that does not match its shape.
+#[derive(Copy, Clone)]
+#[repr(C)]
+#[non_exhaustive]
+pub struct yaml_violation_t {
+    /// The kind of violation.
+    pub type_: yaml_violation_type_t,
+    /// The NUL-terminated JSON Pointer to the node.
+    pub path: *const yaml_char_t,
+    /// The node, the unknown key for YAML_VIOLATION_UNKNOWN_KEY or the mapping
+    /// for YAML_VIOLATION_MISSING_KEY, or 0 if the document is empty.
+    pub node: libc::c_int,
+    /// The missing key for YAML_VIOLATION_MISSING_KEY, or NULL.
+    pub key: *const yaml_char_t,
+    /// The beginning of the node.
+    pub start_mark: yaml_mark_t,
+    /// The end of the node.
+    pub end_mark: yaml_mark_t,
+}
+
+/// A scalar resolved by yaml_scalar_resolve().
+#[derive(Copy, Clone)]
+#[repr(C)]
+#[non_exhaustive]
+pub struct yaml_resolved_scalar_t {
+    /// The value type.
+    pub type_: yaml_value_type_t,
+    /// The canonical tag of the value, or the tag the scalar was given if the
+    /// schema does not define it.
+    pub tag: *const yaml_char_t,
+    /// The value (for YAML_BOOL_VALUE).
+    pub boolean: bool,
+    /// The value (for YAML_INT_VALUE), or its bits as a u64 if `overflow` is
+    /// set.
+    pub integer: i64,
+    /// Whether the integer is above `i64::MAX`, so that `integer as u64` is its
+    /// value (for YAML_INT_VALUE).
+    pub overflow: bool,
+    /// The value (for YAML_FLOAT_VALUE).
+    pub float: f64,
+}
+
+/// The settings of scalar resolution.
+#[derive(Copy, Clone)]
+#[repr(C)]
+#[non_exhaustive]
+pub struct yaml_schema_options_t {
+    /// The schema.
+    pub schema: yaml_schema_t,
+    /// The resolution of integers beyond the range of a 64-bit integer and of
+    /// floats beyond the range of a double.
+    pub overflow: yaml_overflow_t,
+    /// Are base 60 numbers like `1:30:00` resolved as numbers by
+    /// YAML_1_1_SCHEMA?
+    pub sexagesimal: bool,
+}
+
 /// The document structure.
 #[derive(Copy, Clone)]
 #[repr(C)]
@@ -722,6 +1258,8 @@ pub struct yaml_document_t {
     pub start_mark: yaml_mark_t,
     /// The end of the document.
     pub end_mark: yaml_mark_t,
+    /// The comment lines after the document content, or NULL.
+    pub end_comment: *mut yaml_char_t,
 }
 
 #[derive(Copy, Clone)]
@@ -734,6 +1272,22 @@ pub struct unnamed_yaml_document_t_tag_directives {
     pub end: *mut yaml_tag_directive_t,
 }
 
+/// A stream of documents.
+///
+/// The stream is filled by yaml_stream_load() or yaml_stream_initialize(),
+/// written by yaml_stream_dump() and destroyed by yaml_stream_delete().
+#[derive(Copy, Clone)]
+#[repr(C)]
+#[non_exhaustive]
+pub struct yaml_stream_t {
+    /// The documents of the stream, with their directives and markers.
+    pub documents: yaml_stack_t<yaml_document_t>,
+    /// The encoding of the stream.
+    pub encoding: yaml_encoding_t,
+    /// The line break of the stream.
+    pub line_break: yaml_break_t,
+}
+
 /// The prototype of a read handler.
 ///
 /// The read handler is called when the parser needs to read more bytes from the
@@ -833,6 +1387,18 @@ pub struct yaml_alias_data_t {
     pub mark: yaml_mark_t,
 }
 
+/// A comment captured by the scanner.
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub(crate) struct yaml_comment_t {
+    /// The comment text, without the '#' indicator and the space after it.
+    pub value: *mut yaml_char_t,
+    /// The beginning of the comment.
+    pub start_mark: yaml_mark_t,
+    /// The end of the comment.
+    pub end_mark: yaml_mark_t,
+}
+
 /// The parser structure.
 ///
 /// All members are internal. Manage the structure using the `yaml_parser_`
@@ -880,6 +1446,13 @@ pub struct yaml_parser_t {
     pub(crate) read_handler: Option<yaml_read_handler_t>,
     /// A pointer for passing to the read handler.
     pub(crate) read_handler_data: *mut libc::c_void,
+    /// Implicit tag resolver.
+    pub(crate) implicit_resolver: Option<yaml_implicit_resolver_t>,
+    /// A pointer for passing to the implicit tag resolver.
+    pub(crate) implicit_resolver_data: *mut libc::c_void,
+    /// The settings of the schema resolver set by
+    /// yaml_parser_set_schema_options().
+    pub(crate) schema_options: yaml_schema_options_t,
     /// Standard (string or file) input data.
     pub(crate) input: unnamed_yaml_parser_t_input,
     /// EOF flag
@@ -892,10 +1465,16 @@ pub struct yaml_parser_t {
     pub(crate) raw_buffer: yaml_buffer_t<libc::c_uchar>,
     /// The input encoding.
     pub(crate) encoding: yaml_encoding_t,
+    /// Was the input encoding determined from a byte order mark?
+    pub(crate) bom: bool,
+    /// The first line break style found in the input.
+    pub(crate) line_break: yaml_break_t,
     /// The offset of the current position (in bytes).
     pub(crate) offset: size_t,
     /// The mark of the current position.
     pub(crate) mark: yaml_mark_t,
+    /// The mark of the start of the input.
+    pub(crate) origin: yaml_mark_t,
     /// Have we started to scan the input stream?
     pub(crate) stream_start_produced: bool,
     /// Have we reached the end of the input stream?
@@ -908,6 +1487,8 @@ pub struct yaml_parser_t {
     pub(crate) tokens_parsed: size_t,
     /// Does the tokens queue contain a token ready for dequeueing.
     pub(crate) token_available: bool,
+    /// The number of tokens whose marks are shifted to the origin.
+    pub(crate) tokens_shifted: size_t,
     /// The indentation levels stack.
     pub(crate) indents: yaml_stack_t<libc::c_int>,
     /// The current indentation level.
@@ -930,6 +1511,22 @@ pub struct yaml_parser_t {
     pub(crate) aliases: yaml_stack_t<yaml_alias_data_t>,
     /// The currently parsed document.
     pub(crate) document: *mut yaml_document_t,
+    /// The number of events produced.
+    pub(crate) events_produced: size_t,
+    /// The number of documents started.
+    pub(crate) documents_produced: size_t,
+    /// The end of the input consumed before the last event.
+    pub(crate) consumed_start: yaml_mark_t,
+    /// The end of the last token consumed by the parser.
+    pub(crate) consumed_end: yaml_mark_t,
+    /// Are comments captured for the loader?
+    pub(crate) capture_comments: bool,
+    /// Is the source text of scalars kept by the loader?
+    pub(crate) keep_scalar_source: bool,
+    /// Are aliases loaded as alias nodes?
+    pub(crate) alias_nodes: bool,
+    /// The captured comments not yet attached to a document.
+    pub(crate) comments: yaml_stack_t<yaml_comment_t>,
 }
 
 #[repr(C)]
@@ -959,6 +1556,28 @@ impl Deref for yaml_parser_t {
     }
 }
 
+/// The summary of a parsed stream.
+///
+/// Filled by yaml_parser_get_stream_info() once the parser has produced the
+/// STREAM-END event.
+#[derive(Copy, Clone)]
+#[repr(C)]
+#[non_exhaustive]
+pub struct yaml_stream_info_t {
+    /// The detected input encoding.
+    pub encoding: yaml_encoding_t,
+    /// Did the input start with a byte order mark?
+    pub bom: bool,
+    /// The first line break style found in the input (YAML_ANY_BREAK if the
+    /// input contains no line breaks).
+    pub line_break: yaml_break_t,
+    /// The number of documents in the stream.
+    pub documents: size_t,
+    /// The total number of events produced, including STREAM-START and
+    /// STREAM-END.
+    pub events: size_t,
+}
+
 #[derive(Copy, Clone)]
 #[repr(C)]
 pub(crate) union unnamed_yaml_parser_t_input {
@@ -977,6 +1596,23 @@ pub(crate) struct unnamed_yaml_parser_t_input_string {
     pub current: *const libc::c_uchar,
 }
 
+/// The prototype of an implicit tag resolver.
+///
+/// The resolver is called by the parser for each plain scalar without an
+/// explicit tag, with the scalar `value` of `length` bytes. To resolve the
+/// scalar to a specific tag, the resolver should point `tag` at a
+/// NUL-terminated tag, which the parser copies. Leaving `tag` NULL keeps the
+/// scalar untagged.
+///
+/// On success, the resolver should return 1. If the resolver failed, the
+/// returned value should be 0.
+pub type yaml_implicit_resolver_t = unsafe fn(
+    data: *mut libc::c_void,
+    value: *const yaml_char_t,
+    length: size_t,
+    tag: *mut *const yaml_char_t,
+) -> libc::c_int;
+
 /// The prototype of a write handler.
 ///
 /// The write handler is called when the emitter needs to flush the accumulated
@@ -984,10 +1620,125 @@ pub(crate) struct unnamed_yaml_parser_t_input_string {
 /// `buffer` to the output.
 ///
 /// On success, the handler should return 1. If the handler failed, the returned
-/// value should be 0.
+/// value should be 0. If the output cannot accept the bytes right now, the
+/// handler should write nothing and return -1; the bytes stay buffered in the
+/// emitter and are passed again by the next yaml_emitter_flush() call.
 pub type yaml_write_handler_t =
     unsafe fn(data: *mut libc::c_void, buffer: *mut libc::c_uchar, size: size_t) -> libc::c_int;
 
+/// The prototype of an event handler.
+///
+/// The event handler is called by yaml_document_dump_events() with each event
+/// of the dumped stream. The handler takes the responsibility for the event
+/// and should destroy it with yaml_event_delete(), even if it fails.
+///
+/// On success, the handler should return 1. If the handler failed, the returned
+/// value should be 0.
+pub type yaml_event_handler_t =
+    unsafe fn(data: *mut libc::c_void, event: *mut yaml_event_t) -> libc::c_int;
+
+/// The prototype of a document banner handler.
+///
+/// The banner handler is called before each document is emitted, with the
+/// zero-based `index` of the document in the stream. To put a banner before
+/// the document, the handler should point `banner` at `length` bytes of UTF-8
+/// text, which are written as comment lines. The text must stay valid until
+/// the handler is called again or the emitter is destroyed.
+///
+/// On success, the handler should return 1. If the handler failed, the returned
+/// value should be 0.
+pub type yaml_banner_handler_t = unsafe fn(
+    data: *mut libc::c_void,
+    index: size_t,
+    banner: *mut *const yaml_char_t,
+    length: *mut size_t,
+) -> libc::c_int;
+
+/// The prototype of a mapping key comparator.
+///
+/// The comparator is called by yaml_emitter_dump() with the key nodes of two
+/// pairs of the same mapping. It should return a negative value if the pair
+/// with key `a` goes first, a positive value if the pair with key `b` goes
+/// first, and 0 to keep their original order. If the results are not a
+/// consistent order, every pair is still dumped once, in an unspecified order.
+pub type yaml_key_comparator_t =
+    unsafe fn(data: *mut libc::c_void, a: *const yaml_node_t, b: *const yaml_node_t) -> libc::c_int;
+
+/// The prototype of a node visitor.
+///
+/// The visitor is called by yaml_document_walk() with the kind of `visit`, the
+/// id and the `node` visited, and the `path` of `depth` steps from the root
+/// node to it. A node referenced a second time is visited as YAML_VISIT_ALIAS
+/// and not entered again, so documents with cycles are walked to the end. An
+/// alias node is visited as YAML_VISIT_ALIAS as well.
+///
+/// On success, the visitor should return 1. Returning -1 from a
+/// YAML_VISIT_SEQUENCE_START or YAML_VISIT_MAPPING_START visit skips the
+/// contents of the collection, but not its end visit. If the visitor failed,
+/// the returned value should be 0.
+pub type yaml_node_visitor_t = unsafe fn(
+    data: *mut libc::c_void,
+    visit: yaml_visit_type_t,
+    id: libc::c_int,
+    node: *mut yaml_node_t,
+    path: *const yaml_path_step_t,
+    depth: size_t,
+) -> libc::c_int;
+
+/// The prototype of a unicode handler.
+///
+/// The unicode handler is called by the emitter with the `value` and `tag` of
+/// each scalar, where `tag` is null if the scalar has none. It should return 1
+/// if the non-ASCII characters of the scalar are written as they are, and 0 if
+/// they are escaped in a double-quoted scalar.
+pub type yaml_unicode_handler_t = unsafe fn(
+    data: *mut libc::c_void,
+    value: *const yaml_char_t,
+    length: size_t,
+    tag: *const yaml_char_t,
+) -> libc::c_int;
+
+/// The prototype of a diff handler.
+///
+/// The handler is called by yaml_document_diff() with each change in turn. The
+/// `path` of the change is only valid during the call.
+///
+/// On success, the handler should return 1. If the handler failed, the returned
+/// value should be 0.
+pub type yaml_diff_handler_t =
+    unsafe fn(data: *mut libc::c_void, op: *const yaml_diff_op_t) -> libc::c_int;
+
+/// The prototype of a violation handler.
+///
+/// The handler is called by yaml_document_validate() with each violation in
+/// turn. The `path` of the violation is only valid during the call.
+///
+/// On success, the handler should return 1. If the handler failed, the returned
+/// value should be 0.
+pub type yaml_violation_handler_t =
+    unsafe fn(data: *mut libc::c_void, violation: *const yaml_violation_t) -> libc::c_int;
+
+/// The prototype of a scalar handler.
+///
+/// The scalar handler is called by the emitter with each scalar before it is
+/// written. `value` and `length` point at the value of the scalar, and `tag` is
+/// the tag of the scalar or null. If the scalar is a mapping value and the key
+/// of the pair is a scalar, `key` points at `key_length` bytes of the key,
+/// otherwise it is null. To replace the value, the handler should point
+/// `value` at `length` bytes of the new value, which are copied before the
+/// handler is called again.
+///
+/// On success, the handler should return 1. If the handler failed, the returned
+/// value should be 0.
+pub type yaml_scalar_handler_t = unsafe fn(
+    data: *mut libc::c_void,
+    value: *mut *const yaml_char_t,
+    length: *mut size_t,
+    tag: *const yaml_char_t,
+    key: *const yaml_char_t,
+    key_length: size_t,
+) -> libc::c_int;
+
 /// The emitter states.
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 #[repr(u32)]
@@ -1040,6 +1791,134 @@ pub(crate) struct yaml_anchors_t {
     pub anchor: libc::c_int,
     /// If the node has been emitted?
     pub serialized: bool,
+    /// The pairs of a mapping left out as duplicate keys, or NULL.
+    pub duplicates: *mut bool,
+}
+
+/// The characteristics of the emitted output.
+///
+/// Filled by yaml_emitter_get_metrics(). The counters cover everything emitted
+/// since the emitter was initialized or reset.
+#[derive(Copy, Clone)]
+#[repr(C)]
+#[non_exhaustive]
+pub struct yaml_emitter_metrics_t {
+    /// The number of bytes accepted by the write handler.
+    pub bytes: size_t,
+    /// The number of line breaks written.
+    pub lines: size_t,
+    /// The longest line written, in characters.
+    pub max_column: libc::c_int,
+    /// The number of scalars that were requested in the plain style but had to
+    /// be quoted.
+    pub quoted_scalars: size_t,
+}
+
+/// The features compiled into the library.
+///
+/// Filled by yaml_get_capabilities(), so that applications can tell at run
+/// time which optional parts of the crate are available.
+#[derive(Copy, Clone)]
+#[repr(C)]
+#[non_exhaustive]
+pub struct yaml_capabilities_t {
+    /// Can streams be read and written in UTF-16LE and UTF-16BE, besides UTF-8?
+    pub utf16: bool,
+    /// Can plain scalars be resolved with the failsafe, JSON, core and YAML 1.1
+    /// schemas?
+    pub schemas: bool,
+    /// Can comments be kept by the parser and written by the emitter?
+    pub comments: bool,
+    /// Is the serde data model available through from_str() and to_string()?
+    pub serde: bool,
+    /// Can documents be converted to and from serde_json values?
+    pub serde_json: bool,
+    /// Can documents be converted to and from yaml-rust2 values?
+    pub yaml_rust2: bool,
+    /// Do serde errors implement miette::Diagnostic?
+    pub miette: bool,
+    /// Can output be written to a tokio::io::AsyncWrite with
+    /// yaml_emitter_emit_async()?
+    pub async_output: bool,
+    /// The maximum nesting of sequences, mappings and aliases accepted by the
+    /// serde deserializer, or 0 without serde.
+    pub recursion_limit: size_t,
+    /// The number of bytes after which a simple key can no longer be
+    /// completed.
+    pub simple_key_length: size_t,
+}
+
+/// The configuration of an emitter.
+///
+/// Initialize it with yaml_emitter_options_initialize() or copy it from an
+/// emitter with yaml_emitter_get_options(), then apply it to any number of
+/// emitters with yaml_emitter_set_options().
+#[derive(Copy, Clone)]
+#[repr(C)]
+#[non_exhaustive]
+pub struct yaml_emitter_options_t {
+    /// The output encoding.
+    pub encoding: yaml_encoding_t,
+    /// Is the output in the canonical style?
+    pub canonical: bool,
+    /// The indentation increment of flow collections in the canonical style.
+    pub canonical_indent: libc::c_int,
+    /// Does every canonical document start with the `%YAML` and `%TAG`
+    /// directives?
+    pub canonical_directives: bool,
+    /// Are implicit tags written?
+    pub always_emit_tags: bool,
+    /// Are tags written in the verbatim form?
+    pub verbatim_tags: bool,
+    /// The version directive written at the start of every document.
+    pub version_directive: yaml_version_directive_t,
+    /// Are aliases checked to refer to an emitted anchor?
+    pub check_aliases: bool,
+    /// The indentation increment.
+    pub indent: libc::c_int,
+    /// The preferred line width. -1 means unlimited.
+    pub width: libc::c_int,
+    /// Are unescaped non-ASCII characters allowed?
+    pub unicode: bool,
+    /// Are the requested scalar styles kept whenever they are legal?
+    pub strict_styles: bool,
+    /// The style of scalars that cannot be written plain.
+    pub quote_style: yaml_scalar_style_t,
+    /// How long plain and single-quoted scalars are broken into lines.
+    pub wrap_policy: yaml_wrap_policy_t,
+    /// Are double-quoted scalars hard-wrapped at the preferred width?
+    pub strict_width: bool,
+    /// Do nested block collections start on the line of their indicator?
+    pub compact: bool,
+    /// The number of spaces after the block indicators.
+    pub indicator_spaces: libc::c_int,
+    /// The length under which block collections are written in the flow style.
+    pub compact_flow_threshold: libc::c_int,
+    /// Are block sequences that are mapping values indented?
+    pub indent_sequences: bool,
+    /// Are mapping pairs sorted by key in yaml_emitter_dump()?
+    pub sort_keys: bool,
+    /// Does yaml_emitter_dump() anchor every node?
+    pub anchor_all: bool,
+    /// Does yaml_emitter_dump() emit a document without nodes instead of
+    /// closing the stream?
+    pub empty_documents: bool,
+    /// How yaml_emitter_dump() handles duplicate mapping keys.
+    pub duplicate_keys: yaml_duplicate_keys_t,
+    /// When the document start marker is written.
+    pub document_start_marker: yaml_document_start_marker_t,
+    /// When the document end marker is written.
+    pub document_end_marker: yaml_document_end_marker_t,
+    /// Is the line break after the last document left out?
+    pub omit_final_break: bool,
+    /// Are long comment lines wrapped?
+    pub wrap_comments: bool,
+    /// The preferred line break.
+    pub line_break: yaml_break_t,
+    /// The line break for line breaks inside scalars.
+    pub scalar_break: yaml_break_t,
+    /// Are line breaks inside scalars written exactly as they are?
+    pub preserve_scalar_breaks: bool,
 }
 
 /// The emitter structure.
@@ -1066,6 +1945,36 @@ pub struct yaml_emitter_t {
     pub(crate) write_handler_data: *mut libc::c_void,
     /// Standard (string or file) output data.
     pub(crate) output: unnamed_yaml_emitter_t_output,
+    /// Document banner handler.
+    pub(crate) banner_handler: Option<yaml_banner_handler_t>,
+    /// A pointer for passing to the document banner handler.
+    pub(crate) banner_handler_data: *mut libc::c_void,
+    /// The number of documents started.
+    pub(crate) document_index: size_t,
+    /// Sort the mapping keys of dumped documents?
+    pub(crate) sort_keys: bool,
+    /// Give an anchor to every node of dumped documents?
+    pub(crate) anchor_all: bool,
+    /// Dump documents without nodes instead of closing the stream?
+    pub(crate) empty_documents: bool,
+    /// How duplicate mapping keys of dumped documents are handled.
+    pub(crate) duplicate_keys: yaml_duplicate_keys_t,
+    /// Mapping key comparator.
+    pub(crate) key_comparator: Option<yaml_key_comparator_t>,
+    /// A pointer for passing to the mapping key comparator.
+    pub(crate) key_comparator_data: *mut libc::c_void,
+    /// Unicode handler.
+    pub(crate) unicode_handler: Option<yaml_unicode_handler_t>,
+    /// A pointer for passing to the unicode handler.
+    pub(crate) unicode_handler_data: *mut libc::c_void,
+    /// Scalar handler.
+    pub(crate) scalar_handler: Option<yaml_scalar_handler_t>,
+    /// A pointer for passing to the scalar handler.
+    pub(crate) scalar_handler_data: *mut libc::c_void,
+    /// The last scalar mapping key passed over, for the scalar handler.
+    pub(crate) scalar_key: *mut yaml_char_t,
+    /// The length of the last scalar mapping key.
+    pub(crate) scalar_key_length: size_t,
     /// The working buffer.
     pub(crate) buffer: yaml_buffer_t<yaml_char_t>,
     /// The raw buffer.
@@ -1074,12 +1983,52 @@ pub struct yaml_emitter_t {
     pub(crate) encoding: yaml_encoding_t,
     /// If the output is in the canonical style?
     pub(crate) canonical: bool,
+    /// Write the tags of all nodes, even implicit ones?
+    pub(crate) always_emit_tags: bool,
+    /// Write tags in the verbatim `!<...>` form instead of shortening them with
+    /// the tag directives?
+    pub(crate) verbatim_tags: bool,
+    /// The indentation of flow collections in the canonical style, or 0 for
+    /// the regular indentation.
+    pub(crate) canonical_indent: libc::c_int,
+    /// Write the version and tag directives of every canonical document?
+    pub(crate) canonical_directives: bool,
     /// The number of indentation spaces.
     pub(crate) best_indent: libc::c_int,
     /// The preferred width of the output lines.
     pub(crate) best_width: libc::c_int,
     /// Allow unescaped non-ASCII characters?
     pub(crate) unicode: bool,
+    /// Keep the requested scalar styles whenever they are legal?
+    pub(crate) strict_styles: bool,
+    /// The style of scalars that cannot be written plain.
+    pub(crate) quote_style: yaml_scalar_style_t,
+    /// How long plain and single-quoted scalars are broken into lines.
+    pub(crate) wrap_policy: yaml_wrap_policy_t,
+    /// Hard-wrap double-quoted scalars so that no line exceeds the preferred
+    /// width?
+    pub(crate) strict_width: bool,
+    /// Indent block sequences nested in block mappings?
+    pub(crate) indent_sequences: bool,
+    /// Start nested block collections on a new line after `-`, `?` or `:`?
+    pub(crate) expanded: bool,
+    /// The number of spaces after the `-`, `?` and `:` block indicators.
+    pub(crate) indicator_spaces: libc::c_int,
+    /// Block collections shorter than this many characters are written in the
+    /// flow style, or 0 to keep them in the block style.
+    pub(crate) compact_flow_threshold: libc::c_int,
+    /// When to write the document start marker.
+    pub(crate) document_start_marker: yaml_document_start_marker_t,
+    /// When to write the document end marker.
+    pub(crate) document_end_marker: yaml_document_end_marker_t,
+    /// Leave out the line break after the last document?
+    pub(crate) omit_final_break: bool,
+    /// Wrap long comment lines at the preferred width?
+    pub(crate) wrap_comments: bool,
+    /// The line break for line breaks inside scalars.
+    pub(crate) scalar_break: yaml_break_t,
+    /// Write line breaks inside scalars as they are?
+    pub(crate) preserve_scalar_breaks: bool,
     /// The preferred line break.
     pub(crate) line_break: yaml_break_t,
     /// The stack of states.
@@ -1092,6 +2041,14 @@ pub struct yaml_emitter_t {
     pub(crate) indents: yaml_stack_t<libc::c_int>,
     /// The list of tag directives.
     pub(crate) tag_directives: yaml_stack_t<yaml_tag_directive_t>,
+    /// The version directive of every document, or 0.0 for none.
+    pub(crate) version_directive: yaml_version_directive_t,
+    /// The tag directives of every document.
+    pub(crate) document_tag_directives: yaml_stack_t<yaml_tag_directive_t>,
+    /// Check that aliases refer to anchors emitted before them?
+    pub(crate) check_aliases: bool,
+    /// The anchors emitted in the current document.
+    pub(crate) emitted_anchors: yaml_stack_t<*mut yaml_char_t>,
     /// The current indentation level.
     pub(crate) indent: libc::c_int,
     /// The current flow level.
@@ -1108,12 +2065,22 @@ pub struct yaml_emitter_t {
     pub(crate) line: libc::c_int,
     /// The current column.
     pub(crate) column: libc::c_int,
+    /// The number of bytes accepted by the write handler.
+    pub(crate) bytes_written: size_t,
+    /// The number of line breaks written.
+    pub(crate) lines_written: size_t,
+    /// The longest line written, in characters.
+    pub(crate) max_column: libc::c_int,
+    /// The number of scalars quoted because the plain style is not allowed.
+    pub(crate) quoted_scalars: size_t,
     /// If the last character was a whitespace?
     pub(crate) whitespace: bool,
     /// If the last character was an indentation character (' ', '-', '?', ':')?
     pub(crate) indention: bool,
     /// If an explicit document end is required?
     pub(crate) open_ended: libc::c_int,
+    /// The number of extra spaces owed after a block indicator.
+    pub(crate) pending_spaces: libc::c_int,
     /// Anchor analysis.
     pub(crate) anchor_data: unnamed_yaml_emitter_t_anchor_data,
     /// Tag analysis.
@@ -1130,6 +2097,17 @@ pub struct yaml_emitter_t {
     pub(crate) last_anchor_id: libc::c_int,
     /// The currently emitted document.
     pub(crate) document: *mut yaml_document_t,
+    /// Is the string output buffer owned by the emitter?
+    pub(crate) owned_output: bool,
+    /// The number of bytes in the output buffer owned by the emitter.
+    pub(crate) owned_output_size: size_t,
+    /// The handler given the events of yaml_document_dump_events() instead of
+    /// emitting them.
+    pub(crate) event_handler: Option<yaml_event_handler_t>,
+    /// A pointer for passing to the event handler.
+    pub(crate) event_handler_data: *mut libc::c_void,
+    /// The comment of a block collection, written before its first entry.
+    pub(crate) pending_comment: *mut yaml_char_t,
 }
 
 #[repr(C)]
@@ -1208,6 +2186,16 @@ pub(crate) struct unnamed_yaml_emitter_t_scalar_data {
     pub single_quoted_allowed: bool,
     /// Can the scalar be expressed in the literal or folded styles?
     pub block_allowed: bool,
+    /// Can the scalar be expressed in the literal style at all?
+    pub literal_allowed: bool,
+    /// Are long lines kept unbroken?
+    pub unfolded: bool,
+    /// Are non-ASCII characters written unescaped?
+    pub unicode: bool,
+    /// The requested chomping of a block scalar.
+    pub chomping: yaml_chomping_t,
+    /// Is the indentation indicator of a block scalar always written?
+    pub indentation_indicator: bool,
     /// The output style.
     pub style: yaml_scalar_style_t,
 }
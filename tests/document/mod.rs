@@ -0,0 +1,156 @@
+#![allow(clippy::uninlined_format_args)]
+
+use std::ffi::CStr;
+use std::mem::MaybeUninit;
+use std::ptr;
+use std::slice;
+use unsafe_libyaml::{
+    yaml_diff_op_t, yaml_diff_type_t, yaml_document_delete, yaml_document_diff,
+    yaml_document_dump_string, yaml_document_get_node, yaml_document_load_string, yaml_document_t,
+    yaml_node_t, yaml_output_delete, yaml_parser_delete, yaml_parser_initialize, yaml_parser_load,
+    yaml_parser_set_alias_nodes, yaml_parser_set_input_string, yaml_parser_t, YAML_SCALAR_NODE,
+};
+
+pub struct Document {
+    document: Box<MaybeUninit<yaml_document_t>>,
+}
+
+impl Document {
+    pub fn load(text: &str) -> Self {
+        let mut document = Box::new(MaybeUninit::uninit());
+        let success = unsafe {
+            yaml_document_load_string(
+                document.as_mut_ptr(),
+                text.as_ptr(),
+                text.len() as u64,
+                ptr::null_mut(),
+            )
+        };
+        assert!(success.ok, "failed to load {:?}", text);
+        Document { document }
+    }
+
+    pub fn create(create: impl FnOnce(*mut yaml_document_t) -> bool) -> Self {
+        let mut document = Box::new(MaybeUninit::uninit());
+        assert!(
+            create(document.as_mut_ptr()),
+            "failed to create the document"
+        );
+        Document { document }
+    }
+
+    pub fn load_alias_nodes(text: &str) -> Self {
+        Self::load_with(text, |parser| unsafe {
+            yaml_parser_set_alias_nodes(parser, true);
+        })
+    }
+
+    pub fn load_with(text: &str, configure: impl FnOnce(*mut yaml_parser_t)) -> Self {
+        let mut document = Box::new(MaybeUninit::uninit());
+        let mut parser = MaybeUninit::<yaml_parser_t>::uninit();
+        let parser = parser.as_mut_ptr();
+        let success = unsafe {
+            assert!(yaml_parser_initialize(parser).ok);
+            yaml_parser_set_input_string(parser, text.as_ptr(), text.len() as u64);
+            configure(parser);
+            let success = yaml_parser_load(parser, document.as_mut_ptr());
+            yaml_parser_delete(parser);
+            success
+        };
+        assert!(success.ok, "failed to load {:?}", text);
+        Document { document }
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut yaml_document_t {
+        self.document.as_mut_ptr()
+    }
+
+    pub fn get(&self) -> &yaml_document_t {
+        unsafe { self.document.assume_init_ref() }
+    }
+
+    pub fn get_mut(&mut self) -> &mut yaml_document_t {
+        unsafe { self.document.assume_init_mut() }
+    }
+
+    pub fn dump(&mut self) -> String {
+        let mut output = ptr::null_mut();
+        let mut size = 0;
+        unsafe {
+            let success = yaml_document_dump_string(
+                self.as_mut_ptr(),
+                &mut output,
+                &mut size,
+                ptr::null_mut(),
+            );
+            assert!(success.ok);
+            let size = usize::try_from(size).unwrap();
+            let string = String::from_utf8(slice::from_raw_parts(output, size).to_vec());
+            yaml_output_delete(output);
+            string.unwrap()
+        }
+    }
+
+    pub fn node_count(&mut self) -> usize {
+        let document = self.as_mut_ptr();
+        let count = unsafe { (*document).nodes.top.offset_from((*document).nodes.start) };
+        usize::try_from(count).unwrap()
+    }
+
+    pub fn node(&mut self, id: i32) -> *mut yaml_node_t {
+        let node = unsafe { yaml_document_get_node(self.as_mut_ptr(), id) };
+        assert!(!node.is_null());
+        node
+    }
+
+    pub fn scalar(&mut self, id: i32) -> &str {
+        let node = self.node(id);
+        unsafe {
+            assert_eq!((*node).type_, YAML_SCALAR_NODE);
+            let length = usize::try_from((*node).data.scalar.length).unwrap();
+            let value = std::slice::from_raw_parts((*node).data.scalar.value, length);
+            std::str::from_utf8(value).unwrap()
+        }
+    }
+}
+
+impl Drop for Document {
+    fn drop(&mut self) {
+        unsafe { yaml_document_delete(self.as_mut_ptr()) }
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub struct Change {
+    pub type_: yaml_diff_type_t,
+    pub path: String,
+    pub old_node: i32,
+    pub new_node: i32,
+}
+
+pub fn diff(a: &mut Document, b: &mut Document) -> Vec<Change> {
+    unsafe fn collect(data: *mut std::ffi::c_void, op: *const yaml_diff_op_t) -> i32 {
+        let changes: *mut Vec<Change> = data.cast();
+        (*changes).push(Change {
+            type_: (*op).type_,
+            path: CStr::from_ptr((*op).path.cast())
+                .to_str()
+                .unwrap()
+                .to_owned(),
+            old_node: (*op).old_node,
+            new_node: (*op).new_node,
+        });
+        1
+    }
+    let mut changes = Vec::new();
+    let success = unsafe {
+        yaml_document_diff(
+            a.as_mut_ptr(),
+            b.as_mut_ptr(),
+            collect,
+            ptr::addr_of_mut!(changes).cast(),
+        )
+    };
+    assert!(success.ok);
+    changes
+}
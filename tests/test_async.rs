@@ -0,0 +1,251 @@
+#![cfg(feature = "async")]
+#![allow(clippy::uninlined_format_args)]
+
+use std::future::Future;
+use std::io;
+use std::mem::MaybeUninit;
+use std::pin::Pin;
+use std::ptr;
+use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+use tokio::io::AsyncWrite;
+use unsafe_libyaml::{
+    yaml_document_end_event_initialize, yaml_document_start_event_initialize, yaml_emitter_delete,
+    yaml_emitter_emit_async, yaml_emitter_flush_async, yaml_emitter_initialize,
+    yaml_emitter_pending, yaml_emitter_set_output_async, yaml_emitter_set_unicode, yaml_emitter_t,
+    yaml_encoding_t, yaml_event_t, yaml_scalar_event_initialize,
+    yaml_sequence_end_event_initialize, yaml_sequence_start_event_initialize,
+    yaml_stream_end_event_initialize, yaml_stream_start_event_initialize, YAML_ANY_SEQUENCE_STYLE,
+    YAML_PLAIN_SCALAR_STYLE, YAML_UTF16LE_ENCODING, YAML_UTF8_ENCODING, YAML_WRITER_ERROR,
+};
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    unsafe fn clone(data: *const ()) -> RawWaker {
+        RawWaker::new(data, &VTABLE)
+    }
+    unsafe fn noop(_data: *const ()) {}
+    static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+
+    let waker = unsafe { Waker::from_raw(RawWaker::new(ptr::null(), &VTABLE)) };
+    let mut context = Context::from_waker(&waker);
+    let mut future = Box::pin(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+    }
+}
+
+/// A writer that records the bytes written between flushes, and accepts a
+/// write only every other time it is polled.
+#[derive(Default)]
+struct Chunks {
+    chunks: Vec<Vec<u8>>,
+    current: Vec<u8>,
+    ready: bool,
+    fail: bool,
+}
+
+impl AsyncWrite for Chunks {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        context: &mut Context,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        if this.fail {
+            return Poll::Ready(Err(io::ErrorKind::BrokenPipe.into()));
+        }
+        this.ready = !this.ready;
+        if !this.ready {
+            context.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+        let size = buf.len().min(1000);
+        this.current.extend_from_slice(&buf[..size]);
+        Poll::Ready(Ok(size))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _context: &mut Context) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if !this.current.is_empty() {
+            this.chunks.push(std::mem::take(&mut this.current));
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _context: &mut Context) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl Chunks {
+    fn output(&self) -> Vec<u8> {
+        let mut output = self.chunks.concat();
+        output.extend_from_slice(&self.current);
+        output
+    }
+}
+
+struct Emitter {
+    emitter: Box<MaybeUninit<yaml_emitter_t>>,
+}
+
+impl Emitter {
+    fn new() -> Self {
+        let mut emitter = Box::new(MaybeUninit::uninit());
+        unsafe {
+            assert!(yaml_emitter_initialize(emitter.as_mut_ptr()).ok);
+            yaml_emitter_set_output_async(emitter.as_mut_ptr());
+        }
+        Emitter { emitter }
+    }
+
+    fn as_mut_ptr(&mut self) -> *mut yaml_emitter_t {
+        self.emitter.as_mut_ptr()
+    }
+
+    fn emit(&mut self, writer: &mut Chunks, event: impl FnOnce(*mut yaml_event_t) -> bool) -> bool {
+        let mut raw = MaybeUninit::<yaml_event_t>::uninit();
+        assert!(event(raw.as_mut_ptr()));
+        let emitter = self.as_mut_ptr();
+        block_on(unsafe { yaml_emitter_emit_async(emitter, raw.as_mut_ptr(), writer) }).ok
+    }
+
+    fn emit_items(&mut self, writer: &mut Chunks, encoding: yaml_encoding_t, items: &[&str]) {
+        unsafe {
+            assert!(self.emit(writer, |event| {
+                yaml_stream_start_event_initialize(event, encoding).ok
+            }));
+            assert!(self.emit(writer, |event| {
+                yaml_document_start_event_initialize(
+                    event,
+                    ptr::null_mut(),
+                    ptr::null_mut(),
+                    ptr::null_mut(),
+                    true,
+                )
+                .ok
+            }));
+            assert!(self.emit(writer, |event| {
+                yaml_sequence_start_event_initialize(
+                    event,
+                    ptr::null(),
+                    ptr::null(),
+                    true,
+                    YAML_ANY_SEQUENCE_STYLE,
+                )
+                .ok
+            }));
+            for item in items {
+                assert!(self.emit(writer, |event| {
+                    yaml_scalar_event_initialize(
+                        event,
+                        ptr::null(),
+                        ptr::null(),
+                        item.as_ptr(),
+                        item.len() as i32,
+                        true,
+                        true,
+                        YAML_PLAIN_SCALAR_STYLE,
+                    )
+                    .ok
+                }));
+            }
+        }
+    }
+
+    fn emit_end(&mut self, writer: &mut Chunks) {
+        unsafe {
+            assert!(self.emit(writer, |event| {
+                yaml_sequence_end_event_initialize(event).ok
+            }));
+            assert!(self.emit(writer, |event| {
+                yaml_document_end_event_initialize(event, true).ok
+            }));
+            assert!(self.emit(writer, |event| yaml_stream_end_event_initialize(event).ok));
+        }
+    }
+}
+
+impl Drop for Emitter {
+    fn drop(&mut self) {
+        unsafe { yaml_emitter_delete(self.as_mut_ptr()) }
+    }
+}
+
+#[test]
+fn test_emit_async_streams_chunks() {
+    let items = vec!["item"; 20000];
+    let mut emitter = Emitter::new();
+    let mut writer = Chunks::default();
+    emitter.emit_items(&mut writer, YAML_UTF8_ENCODING, &items);
+
+    // The full buffers were written while the events were emitted, so only
+    // the output since the last of them is held by the emitter.
+    let written = writer.output().len();
+    assert!(writer.chunks.len() >= 3, "{} chunks", writer.chunks.len());
+    let pending = unsafe { yaml_emitter_pending(emitter.as_mut_ptr()) } as usize;
+    assert!(pending < written, "{} bytes pending", pending);
+    assert_eq!(written + pending, "- item\n".len() * items.len() - 1);
+
+    emitter.emit_end(&mut writer);
+    assert_eq!(unsafe { yaml_emitter_pending(emitter.as_mut_ptr()) }, 0);
+    assert_eq!(writer.output(), "- item\n".repeat(items.len()).as_bytes());
+}
+
+#[test]
+fn test_emit_async_document_end() {
+    let mut emitter = Emitter::new();
+    let mut writer = Chunks::default();
+    emitter.emit_items(&mut writer, YAML_UTF8_ENCODING, &["a", "b"]);
+    assert!(writer.chunks.is_empty());
+    emitter.emit_end(&mut writer);
+    assert_eq!(writer.chunks, [b"- a\n- b\n".to_vec()]);
+}
+
+#[test]
+fn test_emit_async_utf16() {
+    let mut emitter = Emitter::new();
+    unsafe { yaml_emitter_set_unicode(emitter.as_mut_ptr(), true) };
+    let mut writer = Chunks::default();
+    emitter.emit_items(&mut writer, YAML_UTF16LE_ENCODING, &["é", "ü"]);
+    emitter.emit_end(&mut writer);
+    let expected: Vec<u8> = "\u{feff}- é\n- ü\n"
+        .encode_utf16()
+        .flat_map(u16::to_le_bytes)
+        .collect();
+    assert_eq!(writer.output(), expected);
+}
+
+#[test]
+fn test_flush_async() {
+    let mut emitter = Emitter::new();
+    let mut writer = Chunks::default();
+    emitter.emit_items(&mut writer, YAML_UTF8_ENCODING, &["a", "b"]);
+    assert!(writer.chunks.is_empty());
+    let emitter_ptr = emitter.as_mut_ptr();
+    assert!(block_on(unsafe { yaml_emitter_flush_async(emitter_ptr, &mut writer) }).ok);
+    assert_eq!(writer.chunks, [b"- a\n- b".to_vec()]);
+    emitter.emit_end(&mut writer);
+    assert_eq!(writer.chunks, [b"- a\n- b".to_vec(), b"\n".to_vec()]);
+}
+
+#[test]
+fn test_emit_async_write_error() {
+    let mut emitter = Emitter::new();
+    let mut writer = Chunks {
+        fail: true,
+        ..Chunks::default()
+    };
+    emitter.emit_items(&mut writer, YAML_UTF8_ENCODING, &["a"]);
+    unsafe {
+        assert!(
+            !emitter.emit(&mut writer, |event| {
+                yaml_sequence_end_event_initialize(event).ok
+            }) || !emitter.emit(&mut writer, |event| {
+                yaml_document_end_event_initialize(event, true).ok
+            })
+        );
+        assert_eq!((*emitter.as_mut_ptr()).error, YAML_WRITER_ERROR);
+    }
+}
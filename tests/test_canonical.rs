@@ -0,0 +1,104 @@
+#![allow(clippy::uninlined_format_args)]
+
+#[allow(dead_code)]
+mod document;
+
+use self::document::Document;
+use std::ffi::CStr;
+use std::mem::MaybeUninit;
+use unsafe_libyaml::{
+    yaml_canonicalize_options_initialize, yaml_canonicalize_options_t, yaml_document_canonicalize,
+};
+
+fn canonicalize(document: &mut Document, keep_aliases: bool) {
+    let mut options = MaybeUninit::<yaml_canonicalize_options_t>::uninit();
+    unsafe {
+        yaml_canonicalize_options_initialize(options.as_mut_ptr());
+        (*options.as_mut_ptr()).keep_aliases = keep_aliases;
+        assert!(yaml_document_canonicalize(document.as_mut_ptr(), options.as_ptr()).ok);
+    }
+}
+
+fn canonical(text: &str) -> String {
+    let mut document = Document::load(text);
+    canonicalize(&mut document, false);
+    document.dump()
+}
+
+const INPUTS: &[&str] = &[
+    "{b: 0x1F, a: True, c: 'yes', d: \"1.0\", e: ~, f: 1.50, h: .NaN, i: 'true'}",
+    "{a: &x [1, {b: 2}], c: *x, 2: y, [k]: v, 1: z}",
+    "{a: !t 1, c: !!int 3, d: !!float 1}",
+    "[foo, '123', \"\", '', -0o7, 0o17]",
+    "{z: 1, y: {x: 2, w: 3}}",
+    "~",
+    "",
+];
+
+#[test]
+fn test_canonical_scalars() {
+    assert_eq!(
+        canonical("{b: 0x1F, a: True, d: \"1.0\", e: ~, f: 1.50, h: .NaN, i: 'true'}"),
+        "a: !!bool true\nb: !!int 31\nd: \"1.0\"\ne: !!null null\nf: !!float 1.5\nh: !!float .nan\ni: \"true\"\n",
+    );
+    assert_eq!(canonical("{a: !t 1, c: yes}"), "a: !t \"1\"\nc: yes\n");
+}
+
+#[test]
+fn test_canonical_equivalent() {
+    let a = canonical("{a: 0x1F, b: [TRUE, ~], c: {d: 1.0, e: 'x'}}");
+    let b = canonical("c: {e: \"x\", d: 1.00}\nb:\n- true\n- null\na: 31\n");
+    assert_eq!(a, b);
+    assert_ne!(
+        a,
+        canonical("{a: 31, b: [true, null], c: {d: 1.0, e: 'y'}}")
+    );
+}
+
+#[test]
+fn test_canonical_aliases() {
+    let text = "{b: &x [1], a: *x}";
+    assert_eq!(canonical(text), "a:\n- !!int 1\nb:\n- !!int 1\n");
+    let mut document = Document::load(text);
+    canonicalize(&mut document, true);
+    assert_eq!(document.dump(), "a: &id001\n- !!int 1\nb: *id001\n");
+}
+
+#[test]
+fn test_canonical_idempotent() {
+    for text in INPUTS {
+        for keep_aliases in [false, true] {
+            let mut document = Document::load(text);
+            canonicalize(&mut document, keep_aliases);
+            let once = document.dump();
+            canonicalize(&mut document, keep_aliases);
+            assert_eq!(document.dump(), once, "{:?}", text);
+            if text.is_empty() {
+                // An empty document is written as an empty node, which is
+                // read back as a null scalar.
+                continue;
+            }
+            // The normal form also survives being written and read again.
+            let mut reloaded = Document::load(&once);
+            canonicalize(&mut reloaded, keep_aliases);
+            assert_eq!(reloaded.dump(), once, "{:?}", text);
+        }
+    }
+}
+
+#[test]
+fn test_canonical_provenance() {
+    let mut document = Document::load("{b: &x [1], a: *x}");
+    canonicalize(&mut document, false);
+    assert_eq!(document.dump(), "a:\n- !!int 1\nb:\n- !!int 1\n");
+    // Each node points back to the node it was made from, and the alias is
+    // expanded into copies of the same sequence.
+    let derived_from: Vec<i32> = (1..=i32::try_from(document.node_count()).unwrap())
+        .map(|id| unsafe {
+            let node = *document.node(id);
+            assert_eq!(CStr::from_ptr(node.transform).to_str(), Ok("canonicalize"));
+            node.derived_from
+        })
+        .collect();
+    assert_eq!(derived_from, [1, 2, 3, 4, 5, 3, 4]);
+}
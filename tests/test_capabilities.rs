@@ -0,0 +1,51 @@
+use std::mem::MaybeUninit;
+use std::ptr;
+use unsafe_libyaml::{
+    yaml_capabilities_t, yaml_document_delete, yaml_document_load_string, yaml_document_t,
+    yaml_get_capabilities,
+};
+
+fn capabilities() -> yaml_capabilities_t {
+    let mut capabilities = MaybeUninit::<yaml_capabilities_t>::uninit();
+    unsafe {
+        yaml_get_capabilities(capabilities.as_mut_ptr());
+        capabilities.assume_init()
+    }
+}
+
+fn loads(input: &str) -> bool {
+    let mut document = MaybeUninit::<yaml_document_t>::uninit();
+    unsafe {
+        let success = yaml_document_load_string(
+            document.as_mut_ptr(),
+            input.as_ptr(),
+            input.len() as u64,
+            ptr::null_mut(),
+        );
+        if success.ok {
+            yaml_document_delete(document.as_mut_ptr());
+        }
+        success.ok
+    }
+}
+
+#[test]
+fn test_features() {
+    let capabilities = capabilities();
+    assert!(capabilities.utf16);
+    assert!(capabilities.schemas);
+    assert!(capabilities.comments);
+    assert_eq!(capabilities.serde, cfg!(feature = "serde"));
+    assert_eq!(capabilities.serde_json, cfg!(feature = "serde_json"));
+    assert_eq!(capabilities.yaml_rust2, cfg!(feature = "yaml-rust2"));
+    assert_eq!(capabilities.miette, cfg!(feature = "miette"));
+    assert_eq!(capabilities.async_output, cfg!(feature = "async"));
+    assert_eq!(capabilities.recursion_limit > 0, cfg!(feature = "serde"));
+}
+
+#[test]
+fn test_simple_key_length() {
+    let length = usize::try_from(capabilities().simple_key_length).unwrap();
+    assert!(loads(&format!("{}: v", "k".repeat(length))));
+    assert!(!loads(&format!("{}: v", "k".repeat(length + 1))));
+}
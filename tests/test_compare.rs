@@ -0,0 +1,106 @@
+#![allow(clippy::uninlined_format_args)]
+
+#[allow(dead_code)]
+mod document;
+
+use self::document::Document;
+use std::fmt::Write as _;
+use std::mem::MaybeUninit;
+use unsafe_libyaml::{
+    yaml_compare_options_initialize, yaml_compare_options_t, yaml_document_compare,
+};
+
+fn compare(a: &str, b: &str, configure: impl FnOnce(&mut yaml_compare_options_t)) -> bool {
+    let mut a = Document::load(a);
+    let mut b = Document::load(b);
+    let mut options = MaybeUninit::<yaml_compare_options_t>::uninit();
+    let mut equal = false;
+    unsafe {
+        yaml_compare_options_initialize(options.as_mut_ptr());
+        configure(&mut *options.as_mut_ptr());
+        assert!(
+            yaml_document_compare(a.as_mut_ptr(), b.as_mut_ptr(), options.as_ptr(), &mut equal).ok
+        );
+    }
+    equal
+}
+
+fn ignore_marks(options: &mut yaml_compare_options_t) {
+    options.ignore_marks = true;
+}
+
+#[test]
+fn test_compare_same() {
+    let text = "a: [1, {b: 2}]\nc: &x d\ne: *x\n";
+    assert!(compare(text, text, |_| {}));
+}
+
+#[test]
+fn test_compare_marks() {
+    let a = "{a: 1, b: 2}";
+    let b = "{ a: 1,  b: 2 }";
+    assert!(!compare(a, b, |_| {}));
+    assert!(compare(a, b, ignore_marks));
+}
+
+#[test]
+fn test_compare_values() {
+    assert!(!compare("{a: 1}", "{a: 2}", ignore_marks));
+    assert!(!compare("{a: 1}", "{b: 1}", ignore_marks));
+    assert!(!compare("[1, 2]", "[1, 2, 3]", ignore_marks));
+    assert!(!compare("{a: 1}", "{a: !!int 1}", ignore_marks));
+}
+
+#[test]
+fn test_compare_styles() {
+    let a = "{a: x, b: [1]}";
+    let b = "{a: 'x', b: [1]}";
+    assert!(!compare(a, b, ignore_marks));
+    assert!(compare(a, b, |options| {
+        options.ignore_marks = true;
+        options.ignore_styles = true;
+    }));
+}
+
+#[test]
+fn test_compare_key_order() {
+    let a = "{a: 1, b: {c: 2, d: 3}}";
+    let b = "{b: {d: 3, c: 2}, a: 1}";
+    assert!(!compare(a, b, ignore_marks));
+    assert!(compare(a, b, |options| {
+        options.ignore_marks = true;
+        options.ignore_key_order = true;
+    }));
+}
+
+#[test]
+fn test_compare_sharing() {
+    let a = "[&x {k: v}, *x]";
+    let b = "[{k: v}, {k: v}]";
+    assert!(!compare(a, b, ignore_marks));
+    assert!(compare(a, b, |options| {
+        options.ignore_marks = true;
+        options.ignore_anchors = true;
+    }));
+}
+
+#[test]
+fn test_compare_shared_chain() {
+    let chain = |bottom: &str| {
+        let mut text = format!("a0: &a0 {}\n", bottom);
+        for level in 1..40 {
+            let _ = writeln!(text, "a{0}: &a{0} [*a{1}, *a{1}]", level, level - 1);
+        }
+        text
+    };
+    let ignore_anchors = |options: &mut yaml_compare_options_t| {
+        options.ignore_marks = true;
+        options.ignore_anchors = true;
+    };
+    assert!(compare(&chain("x"), &chain("x"), ignore_anchors));
+    assert!(!compare(&chain("x"), &chain("y"), ignore_anchors));
+    assert!(compare(&chain("x"), &chain("x"), |options| {
+        ignore_anchors(options);
+        options.ignore_key_order = true;
+    }));
+}
@@ -0,0 +1,107 @@
+#![allow(clippy::uninlined_format_args)]
+
+#[allow(dead_code)]
+mod document;
+
+use self::document::{diff, Change, Document};
+use unsafe_libyaml::{yaml_diff_type_t, YAML_DIFF_ADD, YAML_DIFF_REMOVE, YAML_DIFF_REPLACE};
+
+fn changes(a: &str, b: &str) -> Vec<Change> {
+    let mut a = Document::load(a);
+    let mut b = Document::load(b);
+    diff(&mut a, &mut b)
+}
+
+fn change(type_: yaml_diff_type_t, path: &str, old_node: i32, new_node: i32) -> Change {
+    Change {
+        type_,
+        path: path.to_owned(),
+        old_node,
+        new_node,
+    }
+}
+
+#[test]
+fn test_diff_equal() {
+    assert_eq!(changes("{a: [1, {b: 2}]}", "{a: [1, {b: 2}]}"), []);
+    assert_eq!(changes("{a: 1, b: 2}", "{b: 2, a: 1}"), []);
+    assert_eq!(changes("{a: 'x'}", "{a: \"x\"}"), []);
+    assert_eq!(changes("", ""), []);
+}
+
+#[test]
+fn test_diff_mapping() {
+    assert_eq!(
+        changes("{a: 1, b: 2}", "{a: 1, b: 3}"),
+        [change(YAML_DIFF_REPLACE, "/b", 5, 5)],
+    );
+    assert_eq!(
+        changes("{a: 1}", "{b: 1}"),
+        [
+            change(YAML_DIFF_REMOVE, "/a", 3, 0),
+            change(YAML_DIFF_ADD, "/b", 0, 3),
+        ],
+    );
+}
+
+#[test]
+fn test_diff_sequence() {
+    assert_eq!(
+        changes("[1, 2]", "[1, 2, 3, 4]"),
+        [
+            change(YAML_DIFF_ADD, "/2", 0, 4),
+            change(YAML_DIFF_ADD, "/3", 0, 5),
+        ],
+    );
+    // Removed items are reported from the last one.
+    assert_eq!(
+        changes("[1, 2, 3]", "[4]"),
+        [
+            change(YAML_DIFF_REPLACE, "/0", 2, 2),
+            change(YAML_DIFF_REMOVE, "/2", 4, 0),
+            change(YAML_DIFF_REMOVE, "/1", 3, 0),
+        ],
+    );
+}
+
+#[test]
+fn test_diff_escaped_path() {
+    assert_eq!(
+        changes("{a/b: 1, c~d: [x]}", "{a/b: 2, c~d: [y]}"),
+        [
+            change(YAML_DIFF_REPLACE, "/a~1b", 3, 3),
+            change(YAML_DIFF_REPLACE, "/c~0d/0", 6, 6),
+        ],
+    );
+}
+
+#[test]
+fn test_diff_tags() {
+    assert_eq!(
+        changes("{a: [1]}", "{a: !t [1]}"),
+        [change(YAML_DIFF_REPLACE, "/a", 3, 3)],
+    );
+    assert_eq!(
+        changes("{a: 1}", "{a: !!int 1}"),
+        [change(YAML_DIFF_REPLACE, "/a", 3, 3)],
+    );
+}
+
+#[test]
+fn test_diff_root() {
+    assert_eq!(changes("", "{a: 1}"), [change(YAML_DIFF_ADD, "", 0, 1)],);
+    assert_eq!(changes("{a: 1}", ""), [change(YAML_DIFF_REMOVE, "", 1, 0)],);
+    assert_eq!(
+        changes("[1]", "{a: 1}"),
+        [change(YAML_DIFF_REPLACE, "", 1, 1)],
+    );
+}
+
+#[test]
+fn test_diff_shared_nodes() {
+    // A shared node is compared at each of its paths.
+    assert_eq!(
+        changes("{a: &x {b: 1}, c: *x}", "{a: {b: 1}, c: {b: 2}}"),
+        [change(YAML_DIFF_REPLACE, "/c/b", 5, 9)],
+    );
+}
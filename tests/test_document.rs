@@ -0,0 +1,698 @@
+#![allow(clippy::uninlined_format_args)]
+
+#[allow(dead_code)]
+mod document;
+
+use self::document::Document;
+use std::ffi::{c_void, CStr};
+use std::fmt::Write as _;
+use std::mem::{self, MaybeUninit};
+use std::ptr;
+use std::slice;
+use unsafe_libyaml::{
+    yaml_alias_edge_t, yaml_document_add_child_mapping, yaml_document_add_child_scalar,
+    yaml_document_add_child_sequence, yaml_document_add_path, yaml_document_add_scalar,
+    yaml_document_add_sequence, yaml_document_anchor_iter, yaml_document_append_sequence_item,
+    yaml_document_append_unique_mapping_pair, yaml_document_detach_subtree, yaml_document_display,
+    yaml_document_extract_subtree, yaml_document_get_alias_graph, yaml_document_get_anchored_node,
+    yaml_document_get_mapping_value, yaml_document_get_mapping_value_by_node,
+    yaml_document_get_node_data, yaml_document_initialize, yaml_document_mapping_iter,
+    yaml_document_memory_usage, yaml_document_node_iter, yaml_document_node_ref,
+    yaml_document_nodes, yaml_document_nodes_mut, yaml_document_remove_node,
+    yaml_document_replace_node, yaml_document_root_mapping_iter, yaml_document_root_ref,
+    yaml_document_sequence_iter, yaml_document_set_node_data, yaml_document_set_node_tag,
+    yaml_document_t, yaml_document_tag_iter, yaml_document_walk, yaml_node_pair_t, yaml_node_t,
+    yaml_parser_set_comments, yaml_parser_set_scalar_source, yaml_path_step_t, yaml_visit_type_t,
+    YAML_ALIAS_NODE, YAML_ANY_MAPPING_STYLE, YAML_ANY_SCALAR_STYLE, YAML_ANY_SEQUENCE_STYLE,
+    YAML_FLOW_SEQUENCE_STYLE, YAML_PLAIN_SCALAR_STYLE, YAML_VISIT_MAPPING_START,
+};
+
+unsafe fn value<'a>(node: *mut yaml_node_t) -> &'a str {
+    let length = usize::try_from((*node).data.scalar.length).unwrap();
+    let value = slice::from_raw_parts((*node).data.scalar.value, length);
+    std::str::from_utf8(value).unwrap()
+}
+
+#[test]
+fn test_remove_node_renumbers() {
+    let mut document = Document::load("{a: 1, b: [2, 3], c: 4}");
+    assert_eq!(document.node_count(), 9);
+    assert_eq!(document.scalar(6), "2");
+    unsafe {
+        assert!(yaml_document_remove_node(document.as_mut_ptr(), 6).ok);
+    }
+    assert_eq!(document.node_count(), 8);
+    assert_eq!(document.scalar(6), "3");
+    assert_eq!(document.scalar(7), "c");
+    assert_eq!(document.scalar(8), "4");
+    assert_eq!(document.dump(), "{a: 1, b: [3], c: 4}\n");
+}
+
+#[test]
+fn test_remove_key_node() {
+    let mut document = Document::load("{a: 1, b: 2}");
+    unsafe {
+        assert!(yaml_document_remove_node(document.as_mut_ptr(), 2).ok);
+    }
+    assert_eq!(document.node_count(), 4);
+    assert_eq!(document.dump(), "{b: 2}\n");
+}
+
+#[test]
+fn test_remove_node_alias_chain() {
+    let mut document = Document::load_alias_nodes("[&a x, *a, *a, y]");
+    unsafe {
+        assert_eq!((*document.node(3)).type_, YAML_ALIAS_NODE);
+        assert_eq!((*document.node(4)).type_, YAML_ALIAS_NODE);
+        // Make the first alias refer to the second one, which comes after it.
+        (*document.node(3)).data.alias.target = 4;
+        assert!(yaml_document_remove_node(document.as_mut_ptr(), 2).ok);
+    }
+    assert_eq!(document.node_count(), 2);
+    assert_eq!(document.scalar(2), "y");
+    assert_eq!(document.dump(), "[y]\n");
+}
+
+#[test]
+fn test_replace_node() {
+    let mut document = Document::load("{a: 1, b: [2, 3], c: 4}");
+    unsafe {
+        assert!(yaml_document_replace_node(document.as_mut_ptr(), 5, 9).ok);
+    }
+    assert_eq!(document.node_count(), 8);
+    assert_eq!(document.scalar(5), "4");
+    assert_eq!(document.dump(), "{a: 1, b: &id001 4, c: *id001}\n");
+}
+
+#[test]
+fn test_replace_node_with_ancestor() {
+    let mut document = Document::load("{a: 1, b: [2, [3]], c: 4}");
+    let before = document.dump();
+    unsafe {
+        assert!(yaml_document_replace_node(document.as_mut_ptr(), 8, 5).fail);
+        assert!(yaml_document_replace_node(document.as_mut_ptr(), 7, 5).fail);
+    }
+    assert_eq!(document.node_count(), 10);
+    assert_eq!(document.dump(), before);
+}
+
+#[test]
+fn test_detach_subtree() {
+    let mut document = Document::load("{a: &x 1, b: [*x, [2]], c: 3}");
+    unsafe {
+        assert!(yaml_document_detach_subtree(document.as_mut_ptr(), 5).ok);
+    }
+    assert_eq!(document.node_count(), 6);
+    assert_eq!(document.scalar(3), "1");
+    assert_eq!(document.dump(), "{a: &x 1, c: 3}\n");
+}
+
+#[test]
+fn test_iterators() {
+    let document = Document::load("{a: 1, b: [2, 3]}");
+    unsafe {
+        let pairs = yaml_document_root_mapping_iter(document.get());
+        assert_eq!(pairs.size_hint(), (2, Some(2)));
+        let keys: Vec<_> = pairs.map(|(key, _)| value(key)).collect();
+        assert_eq!(keys, ["a", "b"]);
+        let items: Vec<_> = yaml_document_sequence_iter(document.get(), 5)
+            .map(|item| value(item))
+            .collect();
+        assert_eq!(items, ["2", "3"]);
+        let ids: Vec<_> = yaml_document_node_iter(document.get())
+            .map(|(id, _)| id)
+            .collect();
+        assert_eq!(ids, [1, 2, 3, 4, 5, 6, 7]);
+        // Iterating over a node of another type yields nothing.
+        assert_eq!(yaml_document_mapping_iter(document.get(), 5).count(), 0);
+        assert_eq!(yaml_document_sequence_iter(document.get(), 1).count(), 0);
+    }
+}
+
+#[test]
+fn test_anchor_iter() {
+    let document = Document::load("{a: &x 1, b: [&y 2, *y], c: *x}");
+    let anchors: Vec<_> = unsafe { yaml_document_anchor_iter(document.get()) }.collect();
+    assert_eq!(anchors, [(&b"x"[..], 3), (&b"y"[..], 6)]);
+}
+
+#[test]
+fn test_node_ref() {
+    let mut document = Document::load("{servers: [{host: a, port: 1}, {host: b}], list: [x]}");
+    let root = unsafe { yaml_document_root_ref(document.as_mut_ptr()) }.unwrap();
+    assert_eq!(root.id(), 1);
+    let servers = root.get("servers").unwrap();
+    assert_eq!(
+        servers.get_item(1).unwrap().get("host").unwrap().as_str(),
+        Some("b"),
+    );
+    assert_eq!(
+        servers.get_item(0).unwrap().get("port").unwrap().as_str(),
+        Some("1"),
+    );
+    let list = root.get("list").unwrap();
+    assert_eq!(list.get_item(0).unwrap().id(), 14);
+    assert!(root.get("nope").is_none());
+    assert!(list.get_item(1).is_none());
+    assert!(list.get("x").is_none());
+    assert!(root.get_item(0).is_none());
+    assert!(list.get_item(0).unwrap().as_bytes() == Some(b"x"));
+}
+
+#[test]
+fn test_node_ref_copy() {
+    let mut document = Document::load("[a, [b], c]");
+    let root = unsafe { yaml_document_root_ref(document.as_mut_ptr()) }.unwrap();
+    // The references are values, which outlive the reference they came from.
+    let items: Vec<_> = (0..3).map(|index| root.get_item(index).unwrap()).collect();
+    let copy = items[1];
+    assert_eq!(copy.id(), items[1].id());
+    assert_eq!(copy.get_item(0).unwrap().as_str(), Some("b"));
+    assert_eq!(
+        items.iter().map(|item| item.id()).collect::<Vec<_>>(),
+        [2, 3, 5],
+    );
+}
+
+#[test]
+fn test_node_ref_aliases() {
+    let mut document = Document::load_alias_nodes("{a: &x [1], b: *x, c: *x}");
+    unsafe {
+        let root = yaml_document_root_ref(document.as_mut_ptr()).unwrap();
+        let b = root.get("b").unwrap();
+        assert_eq!(b.id(), 3);
+        assert_eq!(b.get_item(0).unwrap().as_str(), Some("1"));
+        // A cycle of alias nodes ends at one of them.
+        (*document.node(6)).data.alias.target = 8;
+        (*document.node(8)).data.alias.target = 6;
+        let node = yaml_document_node_ref(document.as_mut_ptr(), 6);
+        assert_eq!(node.node().type_, YAML_ALIAS_NODE);
+        assert!(node.get_item(0).is_none());
+    }
+}
+
+#[test]
+fn test_display() {
+    let mut document = Document::load("{a: [1, &x 2], b: *x}");
+    unsafe {
+        let display = yaml_document_display(document.as_mut_ptr());
+        assert_eq!(display.to_string(), "{a: [1, &x 2], b: *x}\n");
+        let root = yaml_document_root_ref(document.as_mut_ptr()).unwrap();
+        assert_eq!(root.get("a").unwrap().display().to_string(), "[1, 2]\n");
+        assert_eq!(root.get("b").unwrap().display().to_string(), "2\n");
+        // An anchor with a space cannot be dumped.
+        *(*document.node(5)).anchor = b' ';
+        let display = yaml_document_display(document.as_mut_ptr());
+        assert_eq!(
+            display.to_string(),
+            "<anchor value must contain alphanumerical characters only>",
+        );
+    }
+}
+
+#[test]
+fn test_pair_accessors() {
+    let mut document = Document::load("{a: 1, b: 2}");
+    unsafe {
+        let pairs = (*document.node(1)).data.mapping.pairs;
+        let pair = *pairs.start.add(1);
+        assert_eq!((pair.key(), pair.value()), (4, 5));
+    }
+    let pair = yaml_node_pair_t::new(2, 3).unwrap();
+    assert_eq!((pair.key(), pair.value()), (2, 3));
+    assert!(yaml_node_pair_t::new(2, 0).is_none());
+    assert!(yaml_node_pair_t::new(-1, 3).is_none());
+}
+
+#[test]
+fn test_display_subtree_aliases() {
+    // The subtree refers to nodes before and after its own.
+    let mut document = Document::load_alias_nodes("{a: &x 1, b: [*x, &y [2], *y], c: 3}");
+    unsafe {
+        let root = yaml_document_root_ref(document.as_mut_ptr()).unwrap();
+        assert_eq!(
+            root.get("b").unwrap().display().to_string(),
+            "[1, &id001 [2], *id001]\n",
+        );
+    }
+}
+
+#[test]
+fn test_get_mapping_value_by_node() {
+    let mut document = Document::load("{[1, 2]: a, {b: c}: d, x: e, 'y': f}");
+    let mut keys = Document::load("[[1, 2], {b: c}, x, \"y\", [2, 1]]");
+    let (document, keys) = (document.as_mut_ptr(), keys.as_mut_ptr());
+    let find = |key| unsafe { yaml_document_get_mapping_value_by_node(document, 1, keys, key) };
+    // Sequence and mapping keys are found by their content, whatever their
+    // style.
+    assert_eq!(find(2), 5);
+    assert_eq!(find(5), 9);
+    assert_eq!(find(8), 11);
+    assert_eq!(find(9), 13);
+    assert_eq!(find(10), 0);
+    // A node that is not a mapping has no values.
+    assert_eq!(
+        unsafe { yaml_document_get_mapping_value_by_node(document, 2, document, 3) },
+        0,
+    );
+}
+
+#[test]
+fn test_get_mapping_value_by_shared_key() {
+    let mut chain = String::from("{a0: &a0 x");
+    for level in 1..40 {
+        let _ = write!(chain, ", a{0}: &a{0} [*a{1}, *a{1}]", level, level - 1);
+    }
+    chain.push('}');
+    let mut document = Document::load(&format!("{{{}: found, other: value}}", chain));
+    let mut key = Document::load(&chain);
+    unsafe {
+        let value =
+            yaml_document_get_mapping_value_by_node(document.as_mut_ptr(), 1, key.as_mut_ptr(), 1);
+        assert_eq!(document.scalar(value), "found");
+    }
+}
+
+#[test]
+fn test_append_unique_mapping_pair() {
+    let mut document = Document::load("{a: 1, [b]: 2}");
+    unsafe {
+        let document = document.as_mut_ptr();
+        let add_scalar = |value: &str| {
+            yaml_document_add_scalar(
+                document,
+                ptr::null(),
+                value.as_ptr(),
+                i32::try_from(value.len()).unwrap(),
+                YAML_PLAIN_SCALAR_STYLE,
+            )
+        };
+        let (key, value) = (add_scalar("a"), add_scalar("3"));
+        assert!(yaml_document_append_unique_mapping_pair(document, 1, key, value).fail);
+        let sequence = yaml_document_add_sequence(document, ptr::null(), YAML_FLOW_SEQUENCE_STYLE);
+        assert!(yaml_document_append_sequence_item(document, sequence, add_scalar("b")).ok);
+        assert!(yaml_document_append_unique_mapping_pair(document, 1, sequence, value).fail);
+        let key = add_scalar("c");
+        assert!(yaml_document_append_unique_mapping_pair(document, 1, key, value).ok);
+        assert!(yaml_document_append_unique_mapping_pair(document, 1, key, value).fail);
+    }
+    assert_eq!(document.dump(), "{a: 1, ? [b] : 2, c: 3}\n");
+}
+
+#[test]
+fn test_nodes() {
+    let mut document = Document::load("[a, b]");
+    unsafe {
+        let nodes = yaml_document_nodes(document.get());
+        assert_eq!(nodes.len(), 3);
+        assert_eq!(*nodes[2].data.scalar.value, b'b');
+        let nodes = yaml_document_nodes_mut(document.get_mut());
+        *nodes[1].data.scalar.value = b'c';
+    }
+    assert_eq!(document.dump(), "[c, b]\n");
+    let empty = Document::load("");
+    assert!(unsafe { yaml_document_nodes(empty.get()) }.is_empty());
+}
+
+#[test]
+fn test_scalar_source() {
+    let input = "a: 'single'\nb: \"\\x41\"\nc: |\n  literal\nd: >-\n  folded\n  text\ne: plain\n";
+    let mut document = Document::load_with(input, |parser| unsafe {
+        yaml_parser_set_scalar_source(parser, true);
+    });
+    assert_eq!(document.dump(), input);
+
+    // A scalar whose style changed is formatted anew.
+    unsafe {
+        assert_eq!(document.scalar(5), "A");
+        (*document.node(5)).data.scalar.style = YAML_PLAIN_SCALAR_STYLE;
+    }
+    let expected = input.replace("\"\\x41\"", "A");
+    assert_eq!(document.dump(), expected);
+}
+
+#[test]
+fn test_comments() {
+    let input = "# head\na: 1 # trailing\n# before b\nb: [2, 3]\n# end\n";
+    let mut document = Document::load_with(input, |parser| unsafe {
+        yaml_parser_set_comments(parser, true);
+    });
+    assert_eq!(document.dump(), input);
+}
+
+#[test]
+fn test_comments_attached() {
+    let input = "# top\nm:\n  # inner\n  k: v # after v\n  s:\n  - 1 # one\n  \
+                 # before two\n  - 2\n  l: |\n    text\n  # after literal\n\
+                 f: {x: 1} # flow\n# end\n";
+    let mut document = Document::load_with(input, |parser| unsafe {
+        yaml_parser_set_comments(parser, true);
+    });
+    let text = |comment: *mut u8| {
+        (!comment.is_null()).then(|| {
+            unsafe { CStr::from_ptr(comment.cast()) }
+                .to_str()
+                .unwrap()
+                .to_owned()
+        })
+    };
+    let mut comments = Vec::new();
+    for id in 1..=i32::try_from(document.node_count()).unwrap() {
+        let node = document.node(id);
+        let (leading, trailing) = unsafe {
+            (
+                text((*node).leading_comment),
+                text((*node).trailing_comment),
+            )
+        };
+        if leading.is_some() || trailing.is_some() {
+            comments.push((id, leading, trailing));
+        }
+    }
+    let comment = |id, leading: Option<&str>, trailing: Option<&str>| {
+        (id, leading.map(str::to_owned), trailing.map(str::to_owned))
+    };
+    assert_eq!(
+        comments,
+        [
+            comment(1, Some("top"), None),
+            comment(3, Some("inner"), None),
+            comment(5, None, Some("after v")),
+            comment(8, None, Some("one")),
+            comment(9, Some("before two"), None),
+            comment(12, Some("after literal"), None),
+        ],
+    );
+    // Comments after a collection or at the end go to the document.
+    let end_comment = text(document.get().end_comment);
+    assert_eq!(end_comment.as_deref(), Some("flow\nend"));
+}
+
+#[test]
+fn test_alias_graph() {
+    let mut document = Document::load("&r [&a x, *a, {k: *a}, *r]");
+    let mut count = 0;
+    let mut edges = [MaybeUninit::<yaml_alias_edge_t>::uninit(); 4];
+    unsafe {
+        // The size is found out first.
+        assert!(
+            yaml_document_get_alias_graph(document.as_mut_ptr(), ptr::null_mut(), 0, &mut count,)
+                .ok
+        );
+        assert_eq!(count, 5);
+        // Only as many edges as fit are stored.
+        assert!(
+            yaml_document_get_alias_graph(
+                document.as_mut_ptr(),
+                edges.as_mut_ptr().cast(),
+                4,
+                &mut count,
+            )
+            .ok
+        );
+    }
+    assert_eq!(count, 5);
+    let edges: Vec<(i32, i32)> = edges
+        .iter()
+        .map(|edge| unsafe { (edge.assume_init().parent, edge.assume_init().target) })
+        .collect();
+    // The reference from the mapping to `x` is counted but not stored.
+    assert_eq!(edges, [(0, 1), (1, 2), (1, 2), (1, 1)]);
+}
+
+#[test]
+fn test_set_node_tag() {
+    let mut document = Document::load("%TAG !e! tag:e,2000:\n--- [a, b, c, d]");
+    unsafe {
+        assert!(yaml_document_set_node_tag(document.as_mut_ptr(), 2, b"!e!x\0".as_ptr()).ok);
+        assert!(yaml_document_set_node_tag(document.as_mut_ptr(), 3, b"!!int\0".as_ptr()).ok);
+        assert!(yaml_document_set_node_tag(document.as_mut_ptr(), 4, b"!<!a%21>\0".as_ptr()).ok);
+        // The handle is not defined, and the node keeps its tag.
+        assert!(yaml_document_set_node_tag(document.as_mut_ptr(), 5, b"!u!x\0".as_ptr()).fail);
+        // The tags are stored in full, with the escapes decoded.
+        let tag = CStr::from_ptr((*document.node(2)).tag.cast());
+        assert_eq!(tag.to_str(), Ok("tag:e,2000:x"));
+        let tag = CStr::from_ptr((*document.node(4)).tag.cast());
+        assert_eq!(tag.to_str(), Ok("!a!"));
+    }
+    assert_eq!(
+        document.dump(),
+        "%TAG !e! tag:e,2000:\n--- [!e!x a, !!int b, !a%21 c, d]\n",
+    );
+}
+
+#[test]
+fn test_tag_iter() {
+    let document = Document::load_alias_nodes("[&s !secret a, b, !!str c, !secret [d], *s]");
+    let ids = |tag: &[u8]| -> Vec<i32> {
+        unsafe { yaml_document_tag_iter(document.get(), tag.as_ptr()) }.collect()
+    };
+    // The alias node is skipped.
+    assert_eq!(ids(b"!secret\0"), [2, 5]);
+    assert_eq!(ids(b"!!str\0"), [3, 4, 6]);
+    assert!(ids(b"!<\0").is_empty());
+}
+
+#[test]
+fn test_walk() {
+    unsafe fn visit(
+        data: *mut c_void,
+        visit: yaml_visit_type_t,
+        id: i32,
+        _node: *mut yaml_node_t,
+        path: *const yaml_path_step_t,
+        depth: u64,
+    ) -> i32 {
+        let visits = &mut *data.cast::<Vec<String>>();
+        let depth = usize::try_from(depth).unwrap();
+        let mut line = format!("{:?} {}", visit, id);
+        for step in slice::from_raw_parts(path, depth) {
+            let side = if step.is_key { "key" } else { "value" };
+            write!(line, " {}[{}].{}", step.parent, step.index, side).unwrap();
+        }
+        visits.push(line);
+        // The contents of the inner mapping are skipped.
+        if visit == YAML_VISIT_MAPPING_START && id == 6 {
+            -1
+        } else {
+            1
+        }
+    }
+    let mut document = Document::load("{k: [&x x, *x], m: {n: o}}");
+    let mut visits = Vec::<String>::new();
+    unsafe {
+        assert!(
+            yaml_document_walk(
+                document.as_mut_ptr(),
+                visit,
+                ptr::addr_of_mut!(visits).cast(),
+            )
+            .ok
+        );
+    }
+    let expected = [
+        "YAML_VISIT_MAPPING_START 1",
+        "YAML_VISIT_SCALAR 2 1[0].key",
+        "YAML_VISIT_SEQUENCE_START 3 1[0].value",
+        "YAML_VISIT_SCALAR 4 1[0].value 3[0].value",
+        "YAML_VISIT_ALIAS 4 1[0].value 3[1].value",
+        "YAML_VISIT_SEQUENCE_END 3 1[0].value",
+        "YAML_VISIT_SCALAR 5 1[1].key",
+        "YAML_VISIT_MAPPING_START 6 1[1].value",
+        "YAML_VISIT_MAPPING_END 6 1[1].value",
+        "YAML_VISIT_MAPPING_END 1",
+    ];
+    assert_eq!(visits, expected);
+}
+
+#[test]
+fn test_get_anchored_node() {
+    let mut document = Document::load("[&a x, &b [y], *b]");
+    let mut id = |anchor: &[u8]| unsafe {
+        yaml_document_get_anchored_node(document.as_mut_ptr(), anchor.as_ptr())
+    };
+    assert_eq!(id(b"a\0"), 2);
+    assert_eq!(id(b"b\0"), 3);
+    assert_eq!(id(b"c\0"), 0);
+}
+
+#[test]
+fn test_node_data() {
+    let mut document = Document::load("[a, b]");
+    let mut annotation = String::from("checked");
+    unsafe {
+        assert!(yaml_document_get_node_data(document.as_mut_ptr(), 2).is_null());
+        let data = ptr::addr_of_mut!(annotation).cast::<c_void>();
+        yaml_document_set_node_data(document.as_mut_ptr(), 2, data);
+        assert_eq!(yaml_document_get_node_data(document.as_mut_ptr(), 2), data);
+        assert_eq!((*document.node(2)).user_data, data);
+        assert!(yaml_document_get_node_data(document.as_mut_ptr(), 3).is_null());
+        yaml_document_set_node_data(document.as_mut_ptr(), 2, ptr::null_mut());
+        assert!(yaml_document_get_node_data(document.as_mut_ptr(), 2).is_null());
+        yaml_document_set_node_data(document.as_mut_ptr(), 3, data);
+    }
+    // The data is not freed with the document.
+    drop(document);
+    assert_eq!(annotation, "checked");
+}
+
+#[test]
+fn test_memory_usage() {
+    let mut small = Document::load("a");
+    let mut large = Document::load(&"- [abc, {d: e}]\n".repeat(100));
+    let value = "x".repeat(1000);
+    unsafe {
+        let small_usage = yaml_document_memory_usage(small.as_mut_ptr());
+        let large_usage = yaml_document_memory_usage(large.as_mut_ptr());
+        assert!(small_usage > mem::size_of::<yaml_document_t>() as u64);
+        assert!(large_usage > small_usage);
+
+        // The value of a new node is counted.
+        let length = i32::try_from(value.len()).unwrap();
+        let id = yaml_document_add_scalar(
+            small.as_mut_ptr(),
+            ptr::null(),
+            value.as_ptr(),
+            length,
+            YAML_PLAIN_SCALAR_STYLE,
+        );
+        assert_eq!(id, 2);
+        let usage = yaml_document_memory_usage(small.as_mut_ptr());
+        assert!(usage >= small_usage + 1000);
+    }
+}
+
+#[test]
+fn test_extract_subtree() {
+    let input = "%TAG !e! tag:e,2000:\n--- {a: [&x !e!t {c: d}, *x], f: g}\n";
+    let mut document = Document::load(input);
+    let mut extracted = Document::create(|extracted| unsafe {
+        yaml_document_extract_subtree(extracted, document.as_mut_ptr(), 3).ok
+    });
+    // The shared node is shared in the copy, under an anchor of its own.
+    assert_eq!(
+        extracted.dump(),
+        "%TAG !e! tag:e,2000:\n--- [&id001 !e!t {c: d}, *id001]\n",
+    );
+    unsafe {
+        let root = extracted.node(1);
+        assert_eq!(CStr::from_ptr((*root).transform).to_str(), Ok("extract"));
+        assert_eq!((*root).derived_from, 3);
+        assert_eq!(
+            (*root).start_mark.index,
+            (*document.node(3)).start_mark.index
+        );
+    }
+    // The source is left unchanged.
+    assert_eq!(document.dump(), input);
+}
+
+#[test]
+fn test_add_child() {
+    let mut document = Document::load("{}");
+    unsafe {
+        let document = document.as_mut_ptr();
+        let value = yaml_document_add_child_scalar(
+            document,
+            1,
+            b"a\0".as_ptr(),
+            ptr::null(),
+            b"x".as_ptr(),
+            1,
+            YAML_ANY_SCALAR_STYLE,
+        );
+        assert_eq!(value, 2);
+        let items = yaml_document_add_child_sequence(
+            document,
+            1,
+            b"b\0".as_ptr(),
+            ptr::null(),
+            YAML_ANY_SEQUENCE_STYLE,
+        );
+        assert_eq!(items, 4);
+        let item = yaml_document_add_child_scalar(
+            document,
+            items,
+            ptr::null(),
+            ptr::null(),
+            b"y".as_ptr(),
+            1,
+            YAML_ANY_SCALAR_STYLE,
+        );
+        assert_eq!(item, 6);
+        let mapping = yaml_document_add_child_mapping(
+            document,
+            1,
+            b"c\0".as_ptr(),
+            ptr::null(),
+            YAML_ANY_MAPPING_STYLE,
+        );
+        assert_eq!(mapping, 7);
+        // A key that is not valid UTF-8 leaves the document unchanged.
+        let invalid = yaml_document_add_child_mapping(
+            document,
+            1,
+            b"\xFF\0".as_ptr(),
+            ptr::null(),
+            YAML_ANY_MAPPING_STYLE,
+        );
+        assert_eq!(invalid, 0);
+    }
+    assert_eq!(document.node_count(), 8);
+    assert_eq!(document.dump(), "{a: x, b: [y], c: {}}\n");
+}
+
+#[test]
+fn test_get_mapping_value() {
+    let mut document = Document::load("{a: 1, [b]: 2, b: 3, \"c\\0d\": 4}");
+    let mut value = |mapping: i32, key: &[u8], length: i32| unsafe {
+        yaml_document_get_mapping_value(document.as_mut_ptr(), mapping, key.as_ptr(), length)
+    };
+    assert_eq!(value(1, b"a\0", -1), 3);
+    // The sequence key is skipped.
+    assert_eq!(value(1, b"b\0", -1), 8);
+    assert_eq!(value(1, b"c\0d", 3), 10);
+    assert_eq!(value(1, b"c\0", -1), 0);
+    // The node is not a mapping.
+    assert_eq!(value(2, b"a\0", -1), 0);
+}
+
+#[test]
+fn test_add_path() {
+    let mut document = Document::create(|document| unsafe {
+        yaml_document_initialize(
+            document,
+            ptr::null_mut(),
+            ptr::null_mut(),
+            ptr::null_mut(),
+            true,
+            true,
+        )
+        .ok
+    });
+    let mut add = |path: &[&[u8]], value: &str| unsafe {
+        let path: Vec<*const u8> = path.iter().map(|segment| segment.as_ptr()).collect();
+        let length = i32::try_from(value.len()).unwrap();
+        yaml_document_add_path(
+            document.as_mut_ptr(),
+            path.as_ptr(),
+            path.len() as u64,
+            value.as_ptr(),
+            length,
+        )
+        .ok
+    };
+    assert!(add(&[b"a\0", b"b\0"], "1"));
+    assert!(add(&[b"a\0", b"c\0", b"0\0"], "x"));
+    assert!(add(&[b"a\0", b"c\0", b"1\0"], "y"));
+    assert!(add(&[b"d\0"], "2"));
+    // The node exists.
+    assert!(!add(&[b"a\0", b"b\0"], "3"));
+    // The path goes through a scalar.
+    assert!(!add(&[b"a\0", b"b\0", b"e\0"], "3"));
+    // A key is used as a sequence index.
+    assert!(!add(&[b"a\0", b"c\0", b"e\0"], "3"));
+    // A sequence item is skipped.
+    assert!(!add(&[b"a\0", b"c\0", b"3\0"], "3"));
+    assert_eq!(document.dump(), "a:\n  b: 1\n  c:\n  - x\n  - y\nd: 2\n");
+}
@@ -0,0 +1,176 @@
+#![allow(clippy::uninlined_format_args)]
+
+use std::ffi::c_void;
+use std::mem::MaybeUninit;
+use std::ptr::{self, addr_of_mut};
+use std::slice;
+use unsafe_libyaml::{
+    yaml_document_delete, yaml_document_dump_events, yaml_document_dump_string,
+    yaml_document_load_string, yaml_document_t, yaml_emitter_delete, yaml_emitter_emit,
+    yaml_emitter_initialize, yaml_emitter_set_output, yaml_emitter_set_unicode, yaml_emitter_t,
+    yaml_error_t, yaml_event_delete, yaml_event_format, yaml_event_t, yaml_output_delete,
+    YAML_WRITER_ERROR,
+};
+
+unsafe fn write_to_vec(data: *mut c_void, buffer: *mut u8, size: u64) -> i32 {
+    let output = &mut *data.cast::<Vec<u8>>();
+    let size = usize::try_from(size).unwrap();
+    output.extend_from_slice(slice::from_raw_parts(buffer, size));
+    1
+}
+
+unsafe fn format_event(data: *mut c_void, event: *mut yaml_event_t) -> i32 {
+    let lines = &mut *data.cast::<Vec<String>>();
+    let mut line = Vec::new();
+    assert!(yaml_event_format(event, write_to_vec, addr_of_mut!(line).cast()).ok);
+    lines.push(String::from_utf8(line).unwrap());
+    yaml_event_delete(event);
+    1
+}
+
+unsafe fn emit_event(data: *mut c_void, event: *mut yaml_event_t) -> i32 {
+    i32::from(yaml_emitter_emit(data.cast(), event).ok)
+}
+
+unsafe fn refuse_event(data: *mut c_void, event: *mut yaml_event_t) -> i32 {
+    *data.cast::<usize>() += 1;
+    yaml_event_delete(event);
+    0
+}
+
+struct Document {
+    document: Box<MaybeUninit<yaml_document_t>>,
+}
+
+impl Document {
+    fn load(text: &str) -> Self {
+        let mut document = Box::new(MaybeUninit::uninit());
+        let success = unsafe {
+            yaml_document_load_string(
+                document.as_mut_ptr(),
+                text.as_ptr(),
+                text.len() as u64,
+                ptr::null_mut(),
+            )
+        };
+        assert!(success.ok);
+        Document { document }
+    }
+
+    fn as_mut_ptr(&mut self) -> *mut yaml_document_t {
+        self.document.as_mut_ptr()
+    }
+
+    fn events(&mut self) -> Vec<String> {
+        let mut lines = Vec::<String>::new();
+        let success = unsafe {
+            yaml_document_dump_events(
+                self.as_mut_ptr(),
+                format_event,
+                addr_of_mut!(lines).cast(),
+                ptr::null_mut(),
+            )
+        };
+        assert!(success.ok);
+        lines
+    }
+}
+
+impl Drop for Document {
+    fn drop(&mut self) {
+        unsafe { yaml_document_delete(self.as_mut_ptr()) }
+    }
+}
+
+#[test]
+fn test_dump_events() {
+    let mut document = Document::load("- &x [a, 'b']\n- *x\n- {c: d}\n");
+    assert_eq!(
+        document.events(),
+        [
+            "+STR",
+            "+DOC",
+            "+SEQ <tag:yaml.org,2002:seq>",
+            "+SEQ &x <tag:yaml.org,2002:seq>",
+            "=VAL <tag:yaml.org,2002:str> :a",
+            "=VAL <tag:yaml.org,2002:str> 'b",
+            "-SEQ",
+            "=ALI *x",
+            "+MAP <tag:yaml.org,2002:map>",
+            "=VAL <tag:yaml.org,2002:str> :c",
+            "=VAL <tag:yaml.org,2002:str> :d",
+            "-MAP",
+            "-SEQ",
+            "-DOC",
+            "-STR",
+        ],
+    );
+    // The document is left intact.
+    assert_eq!(document.events().len(), 15);
+}
+
+#[test]
+fn test_dump_events_empty() {
+    let mut document = Document::load("");
+    assert_eq!(
+        document.events(),
+        ["+STR", "+DOC ---", "=VAL ", "-DOC ...", "-STR"],
+    );
+}
+
+#[test]
+fn test_dump_events_to_emitter() {
+    let mut document = Document::load("a: &x [1, 2]\nb: *x\nc: \"é\"\n");
+    let mut expected = ptr::null_mut();
+    let mut size = 0;
+    let mut output = Vec::<u8>::new();
+    unsafe {
+        assert!(
+            yaml_document_dump_string(
+                document.as_mut_ptr(),
+                &mut expected,
+                &mut size,
+                ptr::null_mut(),
+            )
+            .ok
+        );
+        let mut emitter = MaybeUninit::<yaml_emitter_t>::uninit();
+        let emitter = emitter.as_mut_ptr();
+        assert!(yaml_emitter_initialize(emitter).ok);
+        yaml_emitter_set_unicode(emitter, true);
+        yaml_emitter_set_output(emitter, write_to_vec, addr_of_mut!(output).cast());
+        assert!(
+            yaml_document_dump_events(
+                document.as_mut_ptr(),
+                emit_event,
+                emitter.cast(),
+                ptr::null_mut()
+            )
+            .ok
+        );
+        yaml_emitter_delete(emitter);
+        let size = usize::try_from(size).unwrap();
+        assert_eq!(output, slice::from_raw_parts(expected, size));
+        yaml_output_delete(expected);
+    }
+}
+
+#[test]
+fn test_dump_events_handler_error() {
+    let mut document = Document::load("[a, b]");
+    let mut calls = 0_usize;
+    let mut error = MaybeUninit::<yaml_error_t>::uninit();
+    unsafe {
+        assert!(
+            yaml_document_dump_events(
+                document.as_mut_ptr(),
+                refuse_event,
+                addr_of_mut!(calls).cast(),
+                error.as_mut_ptr(),
+            )
+            .fail
+        );
+        assert_eq!((*error.as_mut_ptr()).error, YAML_WRITER_ERROR);
+    }
+    assert_eq!(calls, 1);
+}
@@ -34,4 +34,49 @@ fn test(id: &str) {
     assert!(output.success);
 }
 
+fn emit_error(events: &str) -> String {
+    let mut stdout = Vec::new();
+    let result =
+        unsafe { run_emitter_test_suite::unsafe_main(&mut events.as_bytes(), &mut stdout) };
+    result.expect_err(events).to_string()
+}
+
+#[test]
+fn test_event_order_errors() {
+    let cases = [
+        ("+DOC\n=VAL :a\n", "expected STREAM-START at the start of the stream"),
+        (
+            "+STR\n-SEQ\n",
+            "expected DOCUMENT-START or STREAM-END after STREAM-START",
+        ),
+        (
+            "+STR\n+DOC\n=VAL :a\n-DOC\n=VAL :b\n",
+            "expected DOCUMENT-START or STREAM-END after DOCUMENT-END",
+        ),
+        (
+            "+STR\n+DOC\n=VAL :a\n=VAL :b\n",
+            "expected DOCUMENT-END after the document content",
+        ),
+        (
+            "+STR\n+DOC\n-DOC\n",
+            "expected SCALAR, SEQUENCE-START, MAPPING-START, or ALIAS as the document content",
+        ),
+        (
+            "+STR\n+DOC\n+SEQ\n-MAP\n",
+            "expected SCALAR, SEQUENCE-START, MAPPING-START, ALIAS, or SEQUENCE-END in a sequence",
+        ),
+        (
+            "+STR\n+DOC\n+MAP\n-SEQ\n",
+            "expected SCALAR, SEQUENCE-START, MAPPING-START, ALIAS, or MAPPING-END for a mapping key",
+        ),
+        (
+            "+STR\n+DOC\n+MAP\n=VAL :a\n-MAP\n",
+            "expected SCALAR, SEQUENCE-START, MAPPING-START, or ALIAS after a mapping key",
+        ),
+    ];
+    for (events, problem) in cases {
+        assert_eq!(emit_error(events), format!("Emitter error: {}", problem));
+    }
+}
+
 unsafe_libyaml_test_suite::test_emitter!();
@@ -0,0 +1,1261 @@
+#![allow(clippy::uninlined_format_args)]
+
+use std::ffi::{c_void, CStr};
+use std::fmt::Write as _;
+use std::mem::MaybeUninit;
+use std::ptr::{self, addr_of_mut};
+use std::slice;
+use unsafe_libyaml::{
+    yaml_document_add_sequence, yaml_document_append_sequence_item, yaml_document_delete,
+    yaml_document_initialize, yaml_document_load_string, yaml_document_t,
+    yaml_emitter_add_tag_directive, yaml_emitter_close, yaml_emitter_delete, yaml_emitter_dump,
+    yaml_emitter_emit, yaml_emitter_flush, yaml_emitter_get_metrics, yaml_emitter_initialize,
+    yaml_emitter_metrics_t, yaml_emitter_open, yaml_emitter_options_initialize,
+    yaml_emitter_options_t, yaml_emitter_pending, yaml_emitter_reset,
+    yaml_emitter_set_always_emit_tags, yaml_emitter_set_anchor_all, yaml_emitter_set_break,
+    yaml_emitter_set_canonical, yaml_emitter_set_canonical_directives,
+    yaml_emitter_set_canonical_indent, yaml_emitter_set_check_aliases, yaml_emitter_set_compact,
+    yaml_emitter_set_compact_flow_threshold, yaml_emitter_set_document_banner,
+    yaml_emitter_set_document_end_marker, yaml_emitter_set_document_start_marker,
+    yaml_emitter_set_duplicate_keys, yaml_emitter_set_empty_documents, yaml_emitter_set_encoding,
+    yaml_emitter_set_indent, yaml_emitter_set_indent_sequences, yaml_emitter_set_indicator_spaces,
+    yaml_emitter_set_key_comparator, yaml_emitter_set_omit_final_break, yaml_emitter_set_options,
+    yaml_emitter_set_output, yaml_emitter_set_output_owned,
+    yaml_emitter_set_preserve_scalar_breaks, yaml_emitter_set_quote_style,
+    yaml_emitter_set_scalar_break, yaml_emitter_set_scalar_handler, yaml_emitter_set_sort_keys,
+    yaml_emitter_set_strict_styles, yaml_emitter_set_strict_width, yaml_emitter_set_tag_directives,
+    yaml_emitter_set_unicode, yaml_emitter_set_unicode_handler, yaml_emitter_set_verbatim_tags,
+    yaml_emitter_set_version_directive, yaml_emitter_set_width, yaml_emitter_set_wrap_comments,
+    yaml_emitter_set_wrap_policy, yaml_emitter_t, yaml_emitter_take_output, yaml_emitter_write_raw,
+    yaml_error_type_t, yaml_event_t, yaml_node_t, yaml_output_delete, yaml_owned_emitter_t,
+    yaml_parser_delete, yaml_parser_initialize, yaml_parser_load, yaml_parser_parse,
+    yaml_parser_set_alias_nodes, yaml_parser_set_input_string, yaml_parser_t,
+    YAML_ALWAYS_DOCUMENT_END_MARKER, YAML_ANY_MAPPING_STYLE, YAML_ANY_SCALAR_STYLE,
+    YAML_ANY_SEQUENCE_STYLE, YAML_BETWEEN_DOCUMENT_START_MARKER, YAML_CRLN_BREAK,
+    YAML_DOUBLE_QUOTED_SCALAR_STYLE, YAML_EMITTER_ERROR, YAML_KEEP_CHOMPING,
+    YAML_KEEP_LAST_DUPLICATE_KEY, YAML_LITERAL_SCALAR_STYLE, YAML_MAPPING_START_EVENT,
+    YAML_NEVER_WRAP, YAML_OMIT_FIRST_DOCUMENT_START_MARKER, YAML_PLAIN_SCALAR_STYLE,
+    YAML_REJECT_DUPLICATE_KEYS, YAML_SCALAR_EVENT, YAML_SEQUENCE_START_EVENT,
+    YAML_STREAM_END_EVENT, YAML_STREAM_START_EVENT, YAML_UTF16LE_ENCODING, YAML_WRAP_AT_WIDTH,
+};
+
+unsafe fn write_to_vec(data: *mut c_void, buffer: *mut u8, size: u64) -> i32 {
+    let output = &mut *data.cast::<Vec<u8>>();
+    let size = usize::try_from(size).unwrap();
+    output.extend_from_slice(slice::from_raw_parts(buffer, size));
+    1
+}
+
+struct Emitter {
+    emitter: Box<MaybeUninit<yaml_emitter_t>>,
+    // The write handler holds the address of the vector.
+    #[allow(clippy::box_collection)]
+    output: Box<Vec<u8>>,
+}
+
+impl Emitter {
+    fn new(configure: impl FnOnce(*mut yaml_emitter_t)) -> Self {
+        let mut emitter = Box::new(MaybeUninit::uninit());
+        let mut output = Box::new(Vec::new());
+        unsafe {
+            assert!(yaml_emitter_initialize(emitter.as_mut_ptr()).ok);
+            yaml_emitter_set_output(
+                emitter.as_mut_ptr(),
+                write_to_vec,
+                addr_of_mut!(*output).cast(),
+            );
+        }
+        configure(emitter.as_mut_ptr());
+        Emitter { emitter, output }
+    }
+
+    fn as_mut_ptr(&mut self) -> *mut yaml_emitter_t {
+        self.emitter.as_mut_ptr()
+    }
+
+    fn output(&self) -> String {
+        String::from_utf8(self.output.to_vec()).unwrap()
+    }
+}
+
+impl Drop for Emitter {
+    fn drop(&mut self) {
+        unsafe { yaml_emitter_delete(self.as_mut_ptr()) }
+    }
+}
+
+/// Parse `input` and emit its events, after letting `edit` change each one.
+fn try_emit_with(
+    input: &str,
+    configure: impl FnOnce(*mut yaml_emitter_t),
+    mut edit: impl FnMut(*mut yaml_event_t),
+) -> Result<String, yaml_error_type_t> {
+    let mut emitter = Emitter::new(configure);
+    unsafe {
+        let mut parser = MaybeUninit::<yaml_parser_t>::uninit();
+        let parser = parser.as_mut_ptr();
+        assert!(yaml_parser_initialize(parser).ok);
+        yaml_parser_set_input_string(parser, input.as_ptr(), input.len() as u64);
+        let mut event = MaybeUninit::<yaml_event_t>::uninit();
+        let event = event.as_mut_ptr();
+        loop {
+            assert!(yaml_parser_parse(parser, event).ok);
+            edit(event);
+            let done = (*event).type_ == YAML_STREAM_END_EVENT;
+            if yaml_emitter_emit(emitter.as_mut_ptr(), event).fail {
+                yaml_parser_delete(parser);
+                return Err((*emitter.as_mut_ptr()).error);
+            }
+            if done {
+                break;
+            }
+        }
+        yaml_parser_delete(parser);
+    }
+    Ok(emitter.output())
+}
+
+fn emit_with(
+    input: &str,
+    configure: impl FnOnce(*mut yaml_emitter_t),
+    edit: impl FnMut(*mut yaml_event_t),
+) -> String {
+    try_emit_with(input, configure, edit).unwrap()
+}
+
+fn emit(input: &str, configure: impl FnOnce(*mut yaml_emitter_t)) -> String {
+    emit_with(input, configure, |_| {})
+}
+
+/// Load each of `inputs` and dump them as one stream.
+fn try_dump(
+    inputs: &[&str],
+    configure: impl FnOnce(*mut yaml_emitter_t),
+) -> Result<String, yaml_error_type_t> {
+    let mut emitter = Emitter::new(configure);
+    unsafe {
+        assert!(yaml_emitter_open(emitter.as_mut_ptr()).ok);
+        for input in inputs {
+            let mut document = MaybeUninit::<yaml_document_t>::uninit();
+            assert!(
+                yaml_document_load_string(
+                    document.as_mut_ptr(),
+                    input.as_ptr(),
+                    input.len() as u64,
+                    ptr::null_mut(),
+                )
+                .ok
+            );
+            if yaml_emitter_dump(emitter.as_mut_ptr(), document.as_mut_ptr()).fail {
+                return Err((*emitter.as_mut_ptr()).error);
+            }
+        }
+        assert!(yaml_emitter_close(emitter.as_mut_ptr()).ok);
+    }
+    Ok(emitter.output())
+}
+
+fn dump(inputs: &[&str], configure: impl FnOnce(*mut yaml_emitter_t)) -> String {
+    try_dump(inputs, configure).unwrap()
+}
+
+#[test]
+fn test_defaults() {
+    assert_eq!(
+        emit("a: [1, {b: c}]\nd:\n- e\n", |_| {}),
+        "a: [1, {b: c}]\nd:\n- e\n",
+    );
+}
+
+#[test]
+fn test_indent() {
+    let input = "a:\n  b:\n  - c\n";
+    assert_eq!(
+        emit(input, |emitter| unsafe {
+            yaml_emitter_set_indent(emitter, 4);
+        }),
+        "a:\n    b:\n    - c\n",
+    );
+    // Values outside of 2..=9 fall back to 2.
+    assert_eq!(
+        emit(input, |emitter| unsafe {
+            yaml_emitter_set_indent(emitter, 12);
+        }),
+        "a:\n  b:\n  - c\n",
+    );
+}
+
+#[test]
+fn test_indent_sequences() {
+    assert_eq!(
+        emit("a:\n- b\n- c\n", |emitter| unsafe {
+            yaml_emitter_set_indent_sequences(emitter, true);
+        }),
+        "a:\n  - b\n  - c\n",
+    );
+}
+
+#[test]
+fn test_compact() {
+    let input = "- a: 1\n  b: 2\n- - c\n";
+    assert_eq!(emit(input, |_| {}), input);
+    assert_eq!(
+        emit(input, |emitter| unsafe {
+            yaml_emitter_set_compact(emitter, false);
+        }),
+        "-\n  a: 1\n  b: 2\n-\n  - c\n",
+    );
+}
+
+#[test]
+fn test_indicator_spaces() {
+    assert_eq!(
+        emit("a: [b]\nc:\n- d\n", |emitter| unsafe {
+            yaml_emitter_set_indicator_spaces(emitter, 3);
+        }),
+        "a:   [b]\nc:\n-   d\n",
+    );
+}
+
+#[test]
+fn test_width() {
+    let input = "a: one two three four five six\n";
+    assert_eq!(
+        emit(input, |emitter| unsafe {
+            yaml_emitter_set_width(emitter, 10);
+        }),
+        "a: one two three\n  four five\n  six\n",
+    );
+    assert_eq!(
+        emit("a: 'one two three four five six'\n", |emitter| unsafe {
+            yaml_emitter_set_width(emitter, 10);
+        }),
+        "a: 'one two\n  three four\n  five six'\n",
+    );
+    assert_eq!(
+        emit(input, |emitter| unsafe {
+            yaml_emitter_set_width(emitter, -1);
+        }),
+        input,
+    );
+}
+
+#[test]
+fn test_wrap_policy() {
+    let input = "a: one two three four five six\n";
+    assert_eq!(
+        emit(input, |emitter| unsafe {
+            yaml_emitter_set_width(emitter, 10);
+            yaml_emitter_set_wrap_policy(emitter, YAML_WRAP_AT_WIDTH);
+        }),
+        "a: one two\n  three\n  four\n  five six\n",
+    );
+    assert_eq!(
+        emit(input, |emitter| unsafe {
+            yaml_emitter_set_width(emitter, 10);
+            yaml_emitter_set_wrap_policy(emitter, YAML_NEVER_WRAP);
+        }),
+        input,
+    );
+}
+
+#[test]
+fn test_strict_width() {
+    assert_eq!(
+        emit("a: \"abcdefghijklmnopqrstuvwxyz\"\n", |emitter| unsafe {
+            yaml_emitter_set_width(emitter, 16);
+            yaml_emitter_set_strict_width(emitter, true);
+        }),
+        "a: \"abcdefghijk\\\n  lmnopqrstuvwx\\\n  yz\"\n",
+    );
+}
+
+#[test]
+fn test_unfolded() {
+    let input = "a: one two three four five six\n";
+    assert_eq!(
+        emit_with(
+            input,
+            |emitter| unsafe { yaml_emitter_set_width(emitter, 10) },
+            |event| unsafe {
+                if (*event).type_ == YAML_SCALAR_EVENT {
+                    (*event).data.scalar.unfolded = true;
+                }
+            },
+        ),
+        input,
+    );
+}
+
+#[test]
+fn test_quote_style() {
+    // Scalars without a requested style that cannot be written plain.
+    let input = "- 'a: b'\n- ' c'\n- d\n";
+    let any_style = |event: *mut yaml_event_t| unsafe {
+        if (*event).type_ == YAML_SCALAR_EVENT {
+            (*event).data.scalar.style = YAML_ANY_SCALAR_STYLE;
+        }
+    };
+    assert_eq!(emit_with(input, |_| {}, any_style), input);
+    assert_eq!(
+        emit_with(
+            input,
+            |emitter| unsafe {
+                yaml_emitter_set_quote_style(emitter, YAML_DOUBLE_QUOTED_SCALAR_STYLE);
+            },
+            any_style,
+        ),
+        "- \"a: b\"\n- \" c\"\n- d\n",
+    );
+}
+
+#[test]
+fn test_strict_styles() {
+    let input = "a: |\n  b  \n";
+    assert_eq!(emit(input, |_| {}), "a: \"b  \\n\"\n");
+    assert_eq!(
+        emit(input, |emitter| unsafe {
+            yaml_emitter_set_strict_styles(emitter, true);
+        }),
+        input,
+    );
+}
+
+#[test]
+fn test_chomping() {
+    assert_eq!(
+        emit_with(
+            "a: |\n  b\n",
+            |_| {},
+            |event| unsafe {
+                if (*event).type_ == YAML_SCALAR_EVENT
+                    && (*event).data.scalar.style == YAML_LITERAL_SCALAR_STYLE
+                {
+                    (*event).data.scalar.chomping = YAML_KEEP_CHOMPING;
+                }
+            },
+        ),
+        "a: |+\n  b\n...\n",
+    );
+}
+
+#[test]
+fn test_unicode() {
+    let input = "- é\n";
+    assert_eq!(dump(&[input], |_| {}), "- \"\\xE9\"\n");
+    assert_eq!(
+        dump(&[input], |emitter| unsafe {
+            yaml_emitter_set_unicode(emitter, true);
+        }),
+        input,
+    );
+}
+
+#[test]
+fn test_canonical() {
+    assert_eq!(
+        dump(&["a: [b]\n"], |emitter| unsafe {
+            yaml_emitter_set_canonical(emitter, true);
+        }),
+        "---\n!!map {\n  ? !!str \"a\"\n  : !!seq [\n    !!str \"b\",\n  ],\n}\n",
+    );
+}
+
+#[test]
+fn test_verbatim_tags() {
+    assert_eq!(
+        emit("!!str a\n", |emitter| unsafe {
+            yaml_emitter_set_verbatim_tags(emitter, true);
+        }),
+        "!<tag:yaml.org,2002:str> a\n",
+    );
+}
+
+#[test]
+fn test_version_directive() {
+    assert_eq!(
+        emit("a\n", |emitter| unsafe {
+            yaml_emitter_set_version_directive(emitter, 1, 2);
+        }),
+        "%YAML 1.2\n--- a\n",
+    );
+}
+
+#[test]
+fn test_document_markers() {
+    let input = "a\n--- b\n";
+    assert_eq!(
+        emit(input, |emitter| unsafe {
+            yaml_emitter_set_document_end_marker(emitter, YAML_ALWAYS_DOCUMENT_END_MARKER);
+        }),
+        "a\n...\n--- b\n...\n",
+    );
+    assert_eq!(
+        emit("--- a\n--- b\n", |emitter| unsafe {
+            yaml_emitter_set_document_start_marker(emitter, YAML_OMIT_FIRST_DOCUMENT_START_MARKER);
+        }),
+        "--- a\n--- b\n",
+    );
+    assert_eq!(
+        emit("--- a\n--- b\n", |emitter| unsafe {
+            yaml_emitter_set_document_start_marker(emitter, YAML_BETWEEN_DOCUMENT_START_MARKER);
+        }),
+        "a\n--- b\n",
+    );
+}
+
+#[test]
+fn test_omit_final_break() {
+    assert_eq!(
+        emit("a: b\n", |emitter| unsafe {
+            yaml_emitter_set_omit_final_break(emitter, true);
+        }),
+        "a: b",
+    );
+}
+
+#[test]
+fn test_line_breaks() {
+    assert_eq!(
+        emit("a: b\nc: \"d\\ne\"\n", |emitter| unsafe {
+            yaml_emitter_set_break(emitter, YAML_CRLN_BREAK);
+        }),
+        "a: b\r\nc: \"d\\ne\"\r\n",
+    );
+    assert_eq!(
+        emit("a: |\n  b\n  c\n", |emitter| unsafe {
+            yaml_emitter_set_scalar_break(emitter, YAML_CRLN_BREAK);
+        }),
+        "a: |\r\n  b\r\n  c\r\n",
+    );
+}
+
+#[test]
+fn test_compact_flow_threshold() {
+    let input = "a:\n- 1\n- 2\nb:\n- 1\n- 2\n- 3\n- 4\n";
+    assert_eq!(
+        emit(input, |emitter| unsafe {
+            yaml_emitter_set_compact_flow_threshold(emitter, 10);
+        }),
+        "a: [1, 2]\nb:\n- 1\n- 2\n- 3\n- 4\n",
+    );
+    // The threshold counts characters.
+    assert_eq!(
+        emit("- é\n- ü\n", |emitter| unsafe {
+            yaml_emitter_set_unicode(emitter, true);
+            yaml_emitter_set_compact_flow_threshold(emitter, 7);
+        }),
+        "[é, ü]\n",
+    );
+}
+
+#[test]
+fn test_compact_flow_threshold_empty() {
+    let compact = |input: &str, threshold: i32| {
+        emit_with(
+            input,
+            |emitter| unsafe { yaml_emitter_set_compact_flow_threshold(emitter, threshold) },
+            |event| unsafe {
+                if (*event).type_ == YAML_SEQUENCE_START_EVENT {
+                    (*event).data.sequence_start.style = YAML_ANY_SEQUENCE_STYLE;
+                } else if (*event).type_ == YAML_MAPPING_START_EVENT {
+                    (*event).data.mapping_start.style = YAML_ANY_MAPPING_STYLE;
+                }
+            },
+        )
+    };
+    // Empty collections stay empty even when they do not fit.
+    assert_eq!(compact("key: []\nm: {}\n", 1), "key: []\nm: {}\n");
+    assert_eq!(compact("key: []\nm: {}\n", 5), "key: []\nm: {}\n");
+    assert_eq!(compact("key: []\nm: {}\n", 20), "{key: [], m: {}}\n");
+    assert_eq!(compact("key: &anchor []\n", 5), "key: &anchor []\n");
+    assert_eq!(compact("key: &anchor {}\n", 5), "key: &anchor {}\n");
+}
+
+#[test]
+fn test_sort_keys() {
+    let input = "{b: 1, a: [2], c: {e: 3, d: 4}}";
+    assert_eq!(dump(&[input], |_| {}), "{b: 1, a: [2], c: {e: 3, d: 4}}\n");
+    assert_eq!(
+        dump(&[input], |emitter| unsafe {
+            yaml_emitter_set_sort_keys(emitter, true);
+        }),
+        "{a: [2], b: 1, c: {d: 4, e: 3}}\n",
+    );
+}
+
+#[test]
+fn test_key_comparator() {
+    unsafe fn reverse(_data: *mut c_void, a: *const yaml_node_t, b: *const yaml_node_t) -> i32 {
+        let value = |node: *const yaml_node_t| {
+            let length = usize::try_from((*node).data.scalar.length).unwrap();
+            slice::from_raw_parts((*node).data.scalar.value, length)
+        };
+        let (a, b) = (value(a), value(b));
+        b.cmp(a) as i32
+    }
+    assert_eq!(
+        dump(&["{b: 1, a: 2, c: 3}"], |emitter| unsafe {
+            yaml_emitter_set_key_comparator(emitter, reverse, ptr::null_mut());
+        }),
+        "{c: 3, b: 1, a: 2}\n",
+    );
+}
+
+#[test]
+fn test_key_comparator_inconsistent() {
+    // Answers at random, which is no order at all.
+    unsafe fn random(data: *mut c_void, _a: *const yaml_node_t, _b: *const yaml_node_t) -> i32 {
+        let state = &mut *data.cast::<u32>();
+        *state ^= *state << 13;
+        *state ^= *state >> 17;
+        *state ^= *state << 5;
+        i32::try_from(*state % 3).unwrap() - 1
+    }
+    let keys: Vec<String> = (0..1000).map(|i| format!("k{:03}", i)).collect();
+    let mut input = String::new();
+    for key in &keys {
+        let _ = writeln!(input, "{}: 0", key);
+    }
+    let mut state = 1_u32;
+    let output = dump(&[&input], |emitter| unsafe {
+        yaml_emitter_set_key_comparator(emitter, random, addr_of_mut!(state).cast());
+    });
+    let mut dumped: Vec<&str> = output.lines().map(|line| &line[..4]).collect();
+    dumped.sort_unstable();
+    assert_eq!(dumped, keys);
+}
+
+#[test]
+fn test_duplicate_keys() {
+    let input = "{a: 1, b: 2, a: 3}";
+    assert_eq!(dump(&[input], |_| {}), "{a: 1, b: 2, a: 3}\n");
+    assert_eq!(
+        dump(&[input], |emitter| unsafe {
+            yaml_emitter_set_duplicate_keys(emitter, YAML_KEEP_LAST_DUPLICATE_KEY);
+        }),
+        "{b: 2, a: 3}\n",
+    );
+    assert_eq!(
+        try_dump(&[input], |emitter| unsafe {
+            yaml_emitter_set_duplicate_keys(emitter, YAML_REJECT_DUPLICATE_KEYS);
+        }),
+        Err(YAML_EMITTER_ERROR),
+    );
+}
+
+#[test]
+fn test_duplicate_keys_not_scalars() {
+    let input = "{[a]: 1, {b: c}: 2, [a]: 3, {b: c}: 4, [b]: 5, !t a: 6, a: 7}";
+    assert_eq!(
+        dump(&[input], |emitter| unsafe {
+            yaml_emitter_set_duplicate_keys(emitter, YAML_KEEP_LAST_DUPLICATE_KEY);
+        }),
+        "{? [a] : 3, ? {b: c} : 4, ? [b] : 5, !t a: 6, a: 7}\n",
+    );
+    assert_eq!(
+        try_dump(&["{[a]: 1, [\"a\"]: 2}"], |emitter| unsafe {
+            yaml_emitter_set_duplicate_keys(emitter, YAML_REJECT_DUPLICATE_KEYS);
+        }),
+        Err(YAML_EMITTER_ERROR),
+    );
+}
+
+#[test]
+fn test_duplicate_keys_anchors() {
+    // The node shared with the dropped pair is dumped in full where it is kept.
+    assert_eq!(
+        dump(&["{a: &x [1], b: *x, a: 2}"], |emitter| unsafe {
+            yaml_emitter_set_duplicate_keys(emitter, YAML_KEEP_LAST_DUPLICATE_KEY);
+        }),
+        "{b: &x [1], a: 2}\n",
+    );
+}
+
+#[test]
+fn test_duplicate_keys_sorted() {
+    assert_eq!(
+        dump(&["{b: 1, a: 2, b: 3, a: 4}"], |emitter| unsafe {
+            yaml_emitter_set_sort_keys(emitter, true);
+            yaml_emitter_set_duplicate_keys(emitter, YAML_KEEP_LAST_DUPLICATE_KEY);
+        }),
+        "{a: 4, b: 3}\n",
+    );
+}
+
+#[test]
+fn test_anchor_all() {
+    assert_eq!(
+        dump(&["[a, {b: c}]"], |emitter| unsafe {
+            yaml_emitter_set_anchor_all(emitter, true);
+        }),
+        "&id001 [&id002 a, &id003 {&id004 b: &id005 c}]\n",
+    );
+}
+
+#[test]
+fn test_empty_documents() {
+    assert_eq!(dump(&["a", ""], |_| {}), "a\n");
+    assert_eq!(
+        dump(&["a", "", "b"], |emitter| unsafe {
+            yaml_emitter_set_empty_documents(emitter, true);
+        }),
+        "a\n---\n...\n--- b\n",
+    );
+}
+
+#[test]
+fn test_options() {
+    let mut options = MaybeUninit::<yaml_emitter_options_t>::uninit();
+    let options = unsafe {
+        yaml_emitter_options_initialize(options.as_mut_ptr());
+        let mut options = options.assume_init();
+        options.indent = 4;
+        options.indent_sequences = true;
+        options.quote_style = YAML_DOUBLE_QUOTED_SCALAR_STYLE;
+        options.sort_keys = true;
+        options.omit_final_break = true;
+        options
+    };
+    assert_eq!(
+        dump(&["b: x\na:\n- 1\n"], |emitter| unsafe {
+            yaml_emitter_set_options(emitter, &options);
+        }),
+        "a:\n    - 1\nb: x",
+    );
+}
+
+#[test]
+fn test_canonical_indent() {
+    assert_eq!(
+        dump(&["[a]"], |emitter| unsafe {
+            yaml_emitter_set_canonical(emitter, true);
+            yaml_emitter_set_canonical_indent(emitter, 4);
+        }),
+        "---\n!!seq [\n    !!str \"a\",\n]\n",
+    );
+}
+
+#[test]
+fn test_canonical_directives() {
+    assert_eq!(
+        dump(&["a"], |emitter| unsafe {
+            yaml_emitter_set_canonical(emitter, true);
+            yaml_emitter_set_canonical_directives(emitter, true);
+        }),
+        "%YAML 1.1\n%TAG !! tag:yaml.org,2002:\n---\n!!str \"a\"\n",
+    );
+}
+
+#[test]
+fn test_always_emit_tags() {
+    assert_eq!(
+        dump(&["{a: [1]}"], |emitter| unsafe {
+            yaml_emitter_set_always_emit_tags(emitter, true);
+        }),
+        "!!map {!!str a: !!seq [!!str 1]}\n",
+    );
+}
+
+#[test]
+fn test_tag_directives() {
+    assert_eq!(
+        dump(&["!<tag:example.com,2000:x> a"], |emitter| unsafe {
+            yaml_emitter_add_tag_directive(
+                emitter,
+                b"!e!\0".as_ptr(),
+                b"tag:example.com,2000:\0".as_ptr(),
+            );
+        }),
+        "%TAG !e! tag:example.com,2000:\n--- !e!x a\n",
+    );
+}
+
+#[test]
+fn test_check_aliases() {
+    let input = "[*a]";
+    assert_eq!(emit(input, |_| {}), "[*a]\n");
+    assert_eq!(
+        try_emit_with(
+            input,
+            |emitter| unsafe {
+                yaml_emitter_set_check_aliases(emitter, true);
+            },
+            |_| {},
+        ),
+        Err(YAML_EMITTER_ERROR),
+    );
+}
+
+#[test]
+fn test_document_banner() {
+    unsafe fn banner(
+        _data: *mut c_void,
+        index: u64,
+        banner: *mut *const u8,
+        length: *mut u64,
+    ) -> i32 {
+        let text: &[u8] = if index == 0 {
+            b"first document of the stream"
+        } else {
+            b"second"
+        };
+        *banner = text.as_ptr();
+        *length = text.len() as u64;
+        1
+    }
+    assert_eq!(
+        emit("a\n--- b\n", |emitter| unsafe {
+            yaml_emitter_set_document_banner(emitter, banner, ptr::null_mut());
+        }),
+        "# first document of the stream\na\n# second\n--- b\n",
+    );
+    assert_eq!(
+        emit("a\n", |emitter| unsafe {
+            yaml_emitter_set_document_banner(emitter, banner, ptr::null_mut());
+            yaml_emitter_set_width(emitter, 10);
+            yaml_emitter_set_wrap_comments(emitter, true);
+        }),
+        "# first document\n# of the stream\na\n",
+    );
+}
+
+#[test]
+fn test_preserve_scalar_breaks() {
+    let input = "a: |\n  b\n  c\n";
+    assert_eq!(
+        emit(input, |emitter| unsafe {
+            yaml_emitter_set_break(emitter, YAML_CRLN_BREAK);
+        }),
+        "a: |\r\n  b\r\n  c\r\n",
+    );
+    assert_eq!(
+        emit(input, |emitter| unsafe {
+            yaml_emitter_set_break(emitter, YAML_CRLN_BREAK);
+            yaml_emitter_set_preserve_scalar_breaks(emitter, true);
+        }),
+        "a: |\r\n  b\n  c\n",
+    );
+}
+
+struct Blocking {
+    blocked: bool,
+    output: Vec<u8>,
+}
+
+unsafe fn write_unless_blocked(data: *mut c_void, buffer: *mut u8, size: u64) -> i32 {
+    let blocking = &mut *data.cast::<Blocking>();
+    if blocking.blocked {
+        return -1;
+    }
+    let size = usize::try_from(size).unwrap();
+    blocking
+        .output
+        .extend_from_slice(slice::from_raw_parts(buffer, size));
+    1
+}
+
+#[test]
+fn test_pending_would_block() {
+    let input = "[a, b]";
+    let expected: Vec<u8> = "\u{feff}[a, b]\n"
+        .encode_utf16()
+        .flat_map(u16::to_le_bytes)
+        .collect();
+    let mut blocking = Blocking {
+        blocked: true,
+        output: Vec::new(),
+    };
+    let mut pending = Vec::new();
+    unsafe {
+        let mut emitter = MaybeUninit::<yaml_emitter_t>::uninit();
+        let emitter = emitter.as_mut_ptr();
+        assert!(yaml_emitter_initialize(emitter).ok);
+        yaml_emitter_set_output(emitter, write_unless_blocked, addr_of_mut!(blocking).cast());
+        // Nothing is pending before the stream starts.
+        assert_eq!(yaml_emitter_pending(emitter), 0);
+        let mut parser = MaybeUninit::<yaml_parser_t>::uninit();
+        let parser = parser.as_mut_ptr();
+        assert!(yaml_parser_initialize(parser).ok);
+        yaml_parser_set_input_string(parser, input.as_ptr(), input.len() as u64);
+        let mut event = MaybeUninit::<yaml_event_t>::uninit();
+        let event = event.as_mut_ptr();
+        loop {
+            assert!(yaml_parser_parse(parser, event).ok);
+            let done = (*event).type_ == YAML_STREAM_END_EVENT;
+            if (*event).type_ == YAML_STREAM_START_EVENT {
+                (*event).data.stream_start.encoding = YAML_UTF16LE_ENCODING;
+            }
+            assert!(yaml_emitter_emit(emitter, event).ok);
+            pending.push(yaml_emitter_pending(emitter));
+            if done {
+                break;
+            }
+        }
+        yaml_parser_delete(parser);
+        assert!(blocking.output.is_empty());
+
+        blocking.blocked = false;
+        assert!(yaml_emitter_flush(emitter).ok);
+        assert_eq!(yaml_emitter_pending(emitter), 0);
+        yaml_emitter_delete(emitter);
+    }
+    // The BOM is counted by its UTF-8 bytes until it is converted.
+    assert_eq!(pending[0], 3);
+    assert_eq!(pending.last().copied(), Some(expected.len() as u64));
+    assert_eq!(blocking.output, expected);
+}
+
+#[test]
+fn test_output_owned() {
+    let input = "- ".to_owned() + &"x".repeat(100) + "\n- y\n";
+    unsafe {
+        let mut emitter = MaybeUninit::<yaml_emitter_t>::uninit();
+        let emitter = emitter.as_mut_ptr();
+        assert!(yaml_emitter_initialize(emitter).ok);
+        yaml_emitter_set_output_owned(emitter);
+        let mut parser = MaybeUninit::<yaml_parser_t>::uninit();
+        let parser = parser.as_mut_ptr();
+        assert!(yaml_parser_initialize(parser).ok);
+        yaml_parser_set_input_string(parser, input.as_ptr(), input.len() as u64);
+        let mut event = MaybeUninit::<yaml_event_t>::uninit();
+        let event = event.as_mut_ptr();
+        loop {
+            assert!(yaml_parser_parse(parser, event).ok);
+            let done = (*event).type_ == YAML_STREAM_END_EVENT;
+            assert!(yaml_emitter_emit(emitter, event).ok);
+            if done {
+                break;
+            }
+        }
+        yaml_parser_delete(parser);
+
+        let mut output = ptr::null_mut();
+        let mut size = 0;
+        yaml_emitter_take_output(emitter, &mut output, &mut size);
+        let length = usize::try_from(size).unwrap();
+        assert_eq!(
+            slice::from_raw_parts(output, length + 1),
+            format!("{}\0", input).as_bytes()
+        );
+        yaml_output_delete(output);
+
+        // The emitter starts a new buffer.
+        yaml_emitter_take_output(emitter, &mut output, &mut size);
+        assert_eq!(size, 0);
+        assert_eq!(*output, b'\0');
+        yaml_output_delete(output);
+        yaml_emitter_delete(emitter);
+    }
+}
+
+unsafe fn load(document: *mut yaml_document_t, input: &str) {
+    assert!(
+        yaml_document_load_string(
+            document,
+            input.as_ptr(),
+            input.len() as u64,
+            ptr::null_mut()
+        )
+        .ok
+    );
+}
+
+#[test]
+fn test_owned_emitter() {
+    let mut emitter = yaml_owned_emitter_t::new();
+    let mut options = MaybeUninit::<yaml_emitter_options_t>::uninit();
+    unsafe {
+        yaml_emitter_options_initialize(options.as_mut_ptr());
+        (*options.as_mut_ptr()).indent = 4;
+        emitter.set_options(&*options.as_ptr());
+    }
+    let mut emitter = emitter.open().ok().unwrap();
+    for input in ["a:\n  b: c", "", "- d"] {
+        let mut document = MaybeUninit::<yaml_document_t>::uninit();
+        unsafe {
+            load(document.as_mut_ptr(), input);
+            assert!(emitter.dump(document.as_mut_ptr()).is_ok());
+        }
+    }
+    // The document without nodes does not close the stream.
+    assert_eq!(
+        emitter.close().ok().unwrap(),
+        b"a:\n    b: c\n---\n...\n---\n- d\n"
+    );
+}
+
+#[test]
+fn test_owned_emitter_error() {
+    let mut emitter = yaml_owned_emitter_t::new().open().ok().unwrap();
+    let mut document = MaybeUninit::<yaml_document_t>::uninit();
+    let error = unsafe {
+        load(document.as_mut_ptr(), "[&x a, *x]");
+        // An anchor with a space cannot be dumped.
+        let node = (*document.as_mut_ptr()).nodes.start.add(1);
+        *(*node).anchor = b' ';
+        emitter.dump(document.as_mut_ptr()).err().unwrap()
+    };
+    assert_eq!(error.error, YAML_EMITTER_ERROR);
+    assert_eq!(
+        unsafe { CStr::from_ptr(error.problem) }.to_str(),
+        Ok("anchor value must contain alphanumerical characters only"),
+    );
+}
+
+/// Dump `document` and return the output or the emitter problem.
+unsafe fn try_dump_document(
+    document: *mut yaml_document_t,
+    configure: impl FnOnce(*mut yaml_emitter_t),
+) -> Result<String, String> {
+    let mut emitter = Emitter::new(configure);
+    assert!(yaml_emitter_open(emitter.as_mut_ptr()).ok);
+    if yaml_emitter_dump(emitter.as_mut_ptr(), document).fail {
+        let problem = CStr::from_ptr((*emitter.as_mut_ptr()).problem);
+        return Err(problem.to_str().unwrap().to_owned());
+    }
+    assert!(yaml_emitter_close(emitter.as_mut_ptr()).ok);
+    Ok(emitter.output())
+}
+
+#[test]
+fn test_dump_cycle() {
+    let mut document = MaybeUninit::<yaml_document_t>::uninit();
+    let document = document.as_mut_ptr();
+    let output = unsafe {
+        assert!(
+            yaml_document_initialize(
+                document,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                true,
+                true,
+            )
+            .ok
+        );
+        // Two sequences that contain each other.
+        let outer = yaml_document_add_sequence(document, ptr::null(), YAML_ANY_SEQUENCE_STYLE);
+        let inner = yaml_document_add_sequence(document, ptr::null(), YAML_ANY_SEQUENCE_STYLE);
+        assert!(yaml_document_append_sequence_item(document, outer, inner).ok);
+        assert!(yaml_document_append_sequence_item(document, inner, outer).ok);
+        try_dump_document(document, |_| {})
+    };
+    assert_eq!(output.unwrap(), "&id001\n- - *id001\n");
+}
+
+#[test]
+fn test_dump_unanchored_reference() {
+    let input = "[&x a, *x, &y b]";
+    let mut document = MaybeUninit::<yaml_document_t>::uninit();
+    let document = document.as_mut_ptr();
+    let error = unsafe {
+        let mut parser = MaybeUninit::<yaml_parser_t>::uninit();
+        let parser = parser.as_mut_ptr();
+        assert!(yaml_parser_initialize(parser).ok);
+        yaml_parser_set_input_string(parser, input.as_ptr(), input.len() as u64);
+        yaml_parser_set_alias_nodes(parser, true);
+        assert!(yaml_parser_load(parser, document).ok);
+        yaml_parser_delete(parser);
+        // Reference the alias node twice, as in [*x, *x, &x b]. Sorting drops
+        // the redefined anchor of its target, which is only counted once.
+        let items = (*(*document).nodes.start).data.sequence.items;
+        *items.start = *items.start.add(1);
+        *(*(*document).nodes.start.add(3)).anchor = b'x';
+        try_dump_document(document, |emitter| unsafe {
+            yaml_emitter_set_sort_keys(emitter, true);
+        })
+    };
+    assert_eq!(
+        error.unwrap_err(),
+        "node is referenced again but has no anchor",
+    );
+}
+
+/// Parse `input` and emit its events, writing `text` after the event at
+/// `index`, and return the output or the emitter problem.
+fn try_emit_raw(input: &str, index: usize, text: &[u8]) -> Result<String, String> {
+    let mut emitter = Emitter::new(|_| {});
+    unsafe {
+        let mut parser = MaybeUninit::<yaml_parser_t>::uninit();
+        let parser = parser.as_mut_ptr();
+        assert!(yaml_parser_initialize(parser).ok);
+        yaml_parser_set_input_string(parser, input.as_ptr(), input.len() as u64);
+        let mut event = MaybeUninit::<yaml_event_t>::uninit();
+        let event = event.as_mut_ptr();
+        for i in 0.. {
+            assert!(yaml_parser_parse(parser, event).ok);
+            let done = (*event).type_ == YAML_STREAM_END_EVENT;
+            assert!(yaml_emitter_emit(emitter.as_mut_ptr(), event).ok);
+            if i == index
+                && yaml_emitter_write_raw(emitter.as_mut_ptr(), text.as_ptr(), text.len() as u64)
+                    .fail
+            {
+                yaml_parser_delete(parser);
+                let problem = CStr::from_ptr((*emitter.as_mut_ptr()).problem);
+                return Err(problem.to_str().unwrap().to_owned());
+            }
+            if done {
+                break;
+            }
+        }
+        yaml_parser_delete(parser);
+    }
+    Ok(emitter.output())
+}
+
+#[test]
+fn test_write_raw() {
+    // The sequence is written up to `- b` once `b` has been emitted.
+    let input = "- a\n- b\n- c\n";
+    assert_eq!(
+        try_emit_raw(input, 4, b"# spliced\n# lines\n"),
+        Ok("- a\n- b\n# spliced\n# lines\n- c\n".to_owned()),
+    );
+    // Between documents, after the DOCUMENT-END event.
+    assert_eq!(
+        try_emit_raw("a\n--- b\n", 3, b"# license\n"),
+        Ok("a\n# license\n--- b\n".to_owned()),
+    );
+}
+
+#[test]
+fn test_write_raw_flow() {
+    assert_eq!(
+        try_emit_raw("[a, b, c]", 4, b"# x\n"),
+        Err("raw text is only allowed between documents or block collection entries".to_owned()),
+    );
+}
+
+#[test]
+fn test_write_raw_pending() {
+    // The SEQUENCE-START event is held back until the emitter has seen `b`.
+    assert_eq!(
+        try_emit_raw("- a\n- b\n", 3, b"# x\n"),
+        Err("raw text is not allowed while events are pending".to_owned()),
+    );
+}
+
+#[test]
+fn test_write_raw_invalid() {
+    let input = "- a\n- b\n- c\n";
+    assert_eq!(
+        try_emit_raw(input, 4, b"# \xff\n"),
+        Err("raw text is not valid UTF-8".to_owned()),
+    );
+    assert_eq!(
+        try_emit_raw(input, 4, b"# x"),
+        Err("raw text must end with a line break".to_owned()),
+    );
+}
+
+/// Parse `input` and emit its first `count` events.
+unsafe fn emit_events(emitter: *mut yaml_emitter_t, input: &str, count: usize) -> bool {
+    let mut parser = MaybeUninit::<yaml_parser_t>::uninit();
+    let parser = parser.as_mut_ptr();
+    assert!(yaml_parser_initialize(parser).ok);
+    yaml_parser_set_input_string(parser, input.as_ptr(), input.len() as u64);
+    let mut event = MaybeUninit::<yaml_event_t>::uninit();
+    let event = event.as_mut_ptr();
+    let mut ok = true;
+    for _ in 0..count {
+        assert!(yaml_parser_parse(parser, event).ok);
+        if yaml_emitter_emit(emitter, event).fail {
+            ok = false;
+            break;
+        }
+    }
+    yaml_parser_delete(parser);
+    ok
+}
+
+#[test]
+fn test_reset() {
+    let mut emitter = Emitter::new(|_| {});
+    let mut metrics = MaybeUninit::<yaml_emitter_metrics_t>::uninit();
+    unsafe {
+        // Stop in the middle of the sequence, after the anchor and the tag
+        // directive have been written.
+        let input = "%TAG !e! tag:e,2000:\n--- [&a !e!x b, *a, c]\n";
+        assert!(emit_events(emitter.as_mut_ptr(), input, 6));
+        assert!(yaml_emitter_flush(emitter.as_mut_ptr()).ok);
+        assert_eq!(
+            emitter.output(),
+            "%TAG !e! tag:e,2000:\n--- [&a !e!x b, *a, c",
+        );
+
+        // The anchor is forgotten.
+        yaml_emitter_reset(emitter.as_mut_ptr());
+        yaml_emitter_set_check_aliases(emitter.as_mut_ptr(), true);
+        assert!(!emit_events(emitter.as_mut_ptr(), "[*a]", 5));
+        assert_eq!((*emitter.as_mut_ptr()).error, YAML_EMITTER_ERROR);
+
+        // So is the tag directive, and the new stream starts on the first line.
+        yaml_emitter_reset(emitter.as_mut_ptr());
+        emitter.output.clear();
+        assert!(emit_events(
+            emitter.as_mut_ptr(),
+            "[!<tag:e,2000:x> b, c]",
+            7
+        ));
+        yaml_emitter_get_metrics(emitter.as_mut_ptr(), metrics.as_mut_ptr());
+    }
+    let metrics = unsafe { metrics.assume_init() };
+    assert_eq!(emitter.output(), "[!<tag:e,2000:x> b, c]\n");
+    assert_eq!(metrics.bytes, 23);
+    assert_eq!(metrics.lines, 1);
+    assert_eq!(metrics.max_column, 22);
+}
+
+#[test]
+fn test_scalar_handler() {
+    unsafe fn redact(
+        _data: *mut c_void,
+        value: *mut *const u8,
+        length: *mut u64,
+        _tag: *const u8,
+        key: *const u8,
+        key_length: u64,
+    ) -> i32 {
+        let key_length = usize::try_from(key_length).unwrap();
+        if !key.is_null() && slice::from_raw_parts(key, key_length) == b"password" {
+            let replacement = b"redacted: yes";
+            *value = replacement.as_ptr();
+            *length = replacement.len() as u64;
+        }
+        1
+    }
+    // The replacement cannot be written as a plain scalar.
+    assert_eq!(
+        dump(&["user: x\npassword: secret\n"], |emitter| unsafe {
+            yaml_emitter_set_scalar_handler(emitter, redact, ptr::null_mut());
+        }),
+        "user: x\npassword: 'redacted: yes'\n",
+    );
+}
+
+#[test]
+fn test_unicode_handler() {
+    unsafe fn raw_only(_data: *mut c_void, _value: *const u8, _length: u64, tag: *const u8) -> i32 {
+        i32::from(!tag.is_null() && CStr::from_ptr(tag.cast()).to_bytes() == b"!raw")
+    }
+    let input = "- !raw \u{e9}\n- \u{e9}\n";
+    assert_eq!(
+        dump(&[input], |emitter| unsafe {
+            yaml_emitter_set_unicode_handler(emitter, raw_only, ptr::null_mut());
+        }),
+        "- !raw \u{e9}\n- \"\\xE9\"\n",
+    );
+    // The handler takes precedence over the emitter setting.
+    assert_eq!(
+        dump(&[input], |emitter| unsafe {
+            yaml_emitter_set_unicode(emitter, true);
+            yaml_emitter_set_unicode_handler(emitter, raw_only, ptr::null_mut());
+        }),
+        "- !raw \u{e9}\n- \"\\xE9\"\n",
+    );
+}
+
+#[test]
+fn test_metrics() {
+    let mut emitter = Emitter::new(|_| {});
+    let mut document = MaybeUninit::<yaml_document_t>::uninit();
+    let mut metrics = MaybeUninit::<yaml_emitter_metrics_t>::uninit();
+    unsafe {
+        load(document.as_mut_ptr(), "- 'a: b'\n- c\n- ' d'\n");
+        // Request every scalar in the plain style.
+        for id in 2..5 {
+            let node = (*document.as_mut_ptr()).nodes.start.add(id - 1);
+            (*node).data.scalar.style = YAML_PLAIN_SCALAR_STYLE;
+        }
+        assert!(yaml_emitter_open(emitter.as_mut_ptr()).ok);
+        assert!(yaml_emitter_dump(emitter.as_mut_ptr(), document.as_mut_ptr()).ok);
+        assert!(yaml_emitter_close(emitter.as_mut_ptr()).ok);
+        yaml_emitter_get_metrics(emitter.as_mut_ptr(), metrics.as_mut_ptr());
+    }
+    let metrics = unsafe { metrics.assume_init() };
+    assert_eq!(emitter.output(), "- 'a: b'\n- c\n- ' d'\n");
+    assert_eq!(metrics.bytes, 20);
+    assert_eq!(metrics.lines, 3);
+    assert_eq!(metrics.max_column, 8);
+    assert_eq!(metrics.quoted_scalars, 2);
+}
+
+/// Dump `input` and return the output with the metrics of the emitter.
+fn dump_with_metrics(
+    input: &str,
+    configure: impl FnOnce(*mut yaml_emitter_t),
+) -> (Vec<u8>, yaml_emitter_metrics_t) {
+    let mut emitter = Emitter::new(configure);
+    let mut document = MaybeUninit::<yaml_document_t>::uninit();
+    let mut metrics = MaybeUninit::<yaml_emitter_metrics_t>::uninit();
+    unsafe {
+        load(document.as_mut_ptr(), input);
+        assert!(yaml_emitter_open(emitter.as_mut_ptr()).ok);
+        assert!(yaml_emitter_dump(emitter.as_mut_ptr(), document.as_mut_ptr()).ok);
+        assert!(yaml_emitter_close(emitter.as_mut_ptr()).ok);
+        yaml_emitter_get_metrics(emitter.as_mut_ptr(), metrics.as_mut_ptr());
+        (emitter.output.to_vec(), metrics.assume_init())
+    }
+}
+
+#[test]
+fn test_plain_scalar_runs() {
+    // A run longer than the output buffer, which is split between characters.
+    // The UTF-16 output starts with a byte order mark, which puts the end of
+    // the buffer in the middle of a character.
+    let input = format!("ka: {}\n", "é".repeat(10_000));
+    let (output, metrics) = dump_with_metrics(&input, |emitter| unsafe {
+        yaml_emitter_set_unicode(emitter, true);
+        yaml_emitter_set_width(emitter, -1);
+    });
+    assert_eq!(String::from_utf8(output).unwrap(), input);
+    assert_eq!(metrics.bytes, 20_005);
+    assert_eq!(metrics.max_column, 10_004);
+    let (output, _) = dump_with_metrics(&input, |emitter| unsafe {
+        yaml_emitter_set_unicode(emitter, true);
+        yaml_emitter_set_width(emitter, -1);
+        yaml_emitter_set_encoding(emitter, YAML_UTF16LE_ENCODING);
+    });
+    let expected: Vec<u8> = input.encode_utf16().flat_map(u16::to_le_bytes).collect();
+    assert_eq!(output[2..], expected);
+
+    // The column counts characters, so the runs fit on one line.
+    let input = "[éééé, éééé, éééé]\n";
+    let (output, metrics) = dump_with_metrics(input, |emitter| unsafe {
+        yaml_emitter_set_unicode(emitter, true);
+        yaml_emitter_set_width(emitter, 20);
+    });
+    assert_eq!(String::from_utf8(output).unwrap(), input);
+    assert_eq!(metrics.max_column, 18);
+}
+
+#[test]
+fn test_set_tag_directives() {
+    let mut source = MaybeUninit::<yaml_document_t>::uninit();
+    let (start, end) = unsafe {
+        load(
+            source.as_mut_ptr(),
+            "%TAG !a! tag:a:\n%TAG !b! tag:b:\n--- x",
+        );
+        let directives = (*source.as_ptr()).tag_directives;
+        (directives.start, directives.end)
+    };
+    let input = "!<tag:a:x> [!<tag:b:y> z, !<tag:c:w> v]";
+    // The directives replace the ones added before.
+    assert_eq!(
+        dump(&[input], |emitter| unsafe {
+            yaml_emitter_add_tag_directive(emitter, b"!c!\0".as_ptr(), b"tag:c:\0".as_ptr());
+            yaml_emitter_set_tag_directives(emitter, start, end);
+        }),
+        "%TAG !a! tag:a:\n%TAG !b! tag:b:\n--- !a!x [!b!y z, !<tag:c:w> v]\n",
+    );
+    assert_eq!(
+        dump(&[input], |emitter| unsafe {
+            yaml_emitter_add_tag_directive(emitter, b"!c!\0".as_ptr(), b"tag:c:\0".as_ptr());
+            yaml_emitter_set_tag_directives(emitter, ptr::null(), ptr::null());
+        }),
+        "!<tag:a:x> [!<tag:b:y> z, !<tag:c:w> v]\n",
+    );
+    unsafe { yaml_document_delete(source.as_mut_ptr()) };
+}
@@ -0,0 +1,137 @@
+#![allow(clippy::uninlined_format_args)]
+
+#[path = "../src/bin/run-parser-test-suite.rs"]
+#[allow(dead_code)]
+mod run_parser_test_suite;
+
+use std::ffi::c_void;
+use std::mem::MaybeUninit;
+use std::ptr;
+use std::slice;
+use unsafe_libyaml::{
+    yaml_event_delete, yaml_event_format, yaml_event_t, yaml_parser_delete, yaml_parser_initialize,
+    yaml_parser_parse, yaml_parser_set_input_string, yaml_parser_t, YAML_SCALAR_EVENT,
+    YAML_STREAM_END_EVENT,
+};
+
+unsafe fn write_to_vec(data: *mut c_void, buffer: *mut u8, size: u64) -> i32 {
+    let output = &mut *data.cast::<Vec<u8>>();
+    let size = usize::try_from(size).unwrap();
+    output.extend_from_slice(slice::from_raw_parts(buffer, size));
+    1
+}
+
+unsafe fn write_error(_data: *mut c_void, _buffer: *mut u8, _size: u64) -> i32 {
+    0
+}
+
+/// Parse `input` and format each event, after letting `edit` change it.
+fn format_events(input: &str, mut edit: impl FnMut(*mut yaml_event_t)) -> Vec<Vec<u8>> {
+    let mut lines = Vec::new();
+    unsafe {
+        let mut parser = MaybeUninit::<yaml_parser_t>::uninit();
+        let parser = parser.as_mut_ptr();
+        assert!(yaml_parser_initialize(parser).ok);
+        yaml_parser_set_input_string(parser, input.as_ptr(), input.len() as u64);
+        let mut event = MaybeUninit::<yaml_event_t>::uninit();
+        let event = event.as_mut_ptr();
+        loop {
+            assert!(yaml_parser_parse(parser, event).ok);
+            edit(event);
+            let mut line = Vec::new();
+            assert!(yaml_event_format(event, write_to_vec, ptr::addr_of_mut!(line).cast()).ok);
+            lines.push(line);
+            let done = (*event).type_ == YAML_STREAM_END_EVENT;
+            yaml_event_delete(event);
+            if done {
+                break;
+            }
+        }
+        yaml_parser_delete(parser);
+    }
+    lines
+}
+
+fn lines(lines: &[&str]) -> Vec<Vec<u8>> {
+    lines.iter().map(|line| line.as_bytes().to_vec()).collect()
+}
+
+#[test]
+fn test_event_format() {
+    let input = "--- &a !!map\nb: &c [d, *a]\n'e': \"f\\tg\"\nh: |\n  i\\\n...\n";
+    assert_eq!(
+        format_events(input, |_| {}),
+        lines(&[
+            "+STR",
+            "+DOC ---",
+            "+MAP &a <tag:yaml.org,2002:map>",
+            "=VAL :b",
+            "+SEQ &c",
+            "=VAL :d",
+            "=ALI *a",
+            "-SEQ",
+            "=VAL 'e",
+            "=VAL \"f\\tg",
+            "=VAL :h",
+            "=VAL |i\\\\\\n",
+            "-MAP",
+            "-DOC ...",
+            "-STR",
+        ]),
+    );
+}
+
+#[test]
+fn test_event_format_implicit() {
+    assert_eq!(
+        format_events("{a: ''}", |_| {}),
+        lines(&["+STR", "+DOC", "+MAP", "=VAL :a", "=VAL '", "-MAP", "-DOC", "-STR"]),
+    );
+}
+
+#[test]
+fn test_event_format_bytes() {
+    // Bytes that are not valid UTF-8 are written as they are.
+    let events = format_events("[aé, b]", |event| unsafe {
+        if (*event).type_ == YAML_SCALAR_EVENT && *(*event).data.scalar.value == b'b' {
+            *(*event).data.scalar.value = 0xFF;
+        }
+    });
+    assert_eq!(events[3], "=VAL :a\u{e9}".as_bytes());
+    assert_eq!(events[4], b"=VAL :\xFF");
+}
+
+#[test]
+fn test_event_format_long() {
+    let value = "x".repeat(1000);
+    let events = format_events(&value, |_| {});
+    assert_eq!(events[2], format!("=VAL :{}", value).into_bytes());
+}
+
+#[test]
+fn test_event_format_error() {
+    let input = "a";
+    unsafe {
+        let mut parser = MaybeUninit::<yaml_parser_t>::uninit();
+        let parser = parser.as_mut_ptr();
+        assert!(yaml_parser_initialize(parser).ok);
+        yaml_parser_set_input_string(parser, input.as_ptr(), input.len() as u64);
+        let mut event = MaybeUninit::<yaml_event_t>::uninit();
+        let event = event.as_mut_ptr();
+        assert!(yaml_parser_parse(parser, event).ok);
+        assert!(yaml_event_format(event, write_error, std::ptr::null_mut()).fail);
+        yaml_event_delete(event);
+        yaml_parser_delete(parser);
+    }
+}
+
+#[test]
+fn test_parser_test_suite_output() {
+    let mut input: &[u8] = b"- a\n- \"b\\0\"\n";
+    let mut output = Vec::new();
+    unsafe { run_parser_test_suite::unsafe_main(&mut input, &mut output) }.unwrap();
+    assert_eq!(
+        output,
+        b"+STR\n+DOC\n+SEQ\n=VAL :a\n=VAL \"b\\0\n-SEQ\n-DOC\n-STR\n",
+    );
+}
@@ -0,0 +1,119 @@
+#![cfg(feature = "yaml-rust2")]
+
+#[allow(dead_code)]
+mod document;
+
+use self::document::Document;
+use unsafe_libyaml::{yaml_document_from_yaml_rust2, yaml_document_to_yaml_rust2};
+use yaml_rust2::yaml::Hash;
+use yaml_rust2::Yaml;
+
+fn to_yaml_rust2(document: &mut Document) -> Option<Yaml> {
+    let mut value = Yaml::Null;
+    let success = unsafe { yaml_document_to_yaml_rust2(document.as_mut_ptr(), &mut value) };
+    if success.ok {
+        Some(value)
+    } else {
+        None
+    }
+}
+
+fn from_yaml_rust2(value: &Yaml) -> Document {
+    Document::create(|document| unsafe { yaml_document_from_yaml_rust2(document, value) }.ok)
+}
+
+fn string(value: &str) -> Yaml {
+    Yaml::String(value.to_owned())
+}
+
+fn real(value: &str) -> Yaml {
+    Yaml::Real(value.to_owned())
+}
+
+#[test]
+fn test_to_yaml_rust2_tags() {
+    let mut document = Document::load("[!!int '12', !!int 12, !!float 1, !foo 12, !!int x]");
+    assert_eq!(
+        to_yaml_rust2(&mut document),
+        Some(Yaml::Array(vec![
+            string("12"),
+            Yaml::Integer(12),
+            real("1"),
+            string("12"),
+            Yaml::BadValue,
+        ])),
+    );
+    assert_eq!(to_yaml_rust2(&mut Document::load("")), Some(Yaml::BadValue));
+}
+
+#[test]
+fn test_to_yaml_rust2_integers() {
+    let mut document =
+        Document::load("[9223372036854775807, 18446744073709551615, -9223372036854775809]");
+    assert_eq!(
+        to_yaml_rust2(&mut document),
+        Some(Yaml::Array(vec![
+            Yaml::Integer(i64::MAX),
+            real("18446744073709551615"),
+            real("-9223372036854775809"),
+        ])),
+    );
+}
+
+#[test]
+fn test_to_yaml_rust2_keys() {
+    let mut document = Document::load("{1: a, true: b, ~: c, [1, 2]: d, {x: y}: e}");
+    let mut key = Hash::new();
+    key.insert(string("x"), string("y"));
+    let mut hash = Hash::new();
+    hash.insert(Yaml::Integer(1), string("a"));
+    hash.insert(Yaml::Boolean(true), string("b"));
+    hash.insert(Yaml::Null, string("c"));
+    hash.insert(
+        Yaml::Array(vec![Yaml::Integer(1), Yaml::Integer(2)]),
+        string("d"),
+    );
+    hash.insert(Yaml::Hash(key), string("e"));
+    assert_eq!(to_yaml_rust2(&mut document), Some(Yaml::Hash(hash)));
+}
+
+#[test]
+fn test_yaml_rust2_round_trip() {
+    let mut key = Hash::new();
+    key.insert(Yaml::Integer(1), Yaml::Null);
+    let mut hash = Hash::new();
+    hash.insert(Yaml::Integer(1), string("1"));
+    hash.insert(Yaml::Boolean(false), string("false"));
+    hash.insert(Yaml::Null, string("~"));
+    hash.insert(real("0.5"), string("0x1F"));
+    hash.insert(Yaml::Array(vec![string("a")]), Yaml::Hash(Hash::new()));
+    hash.insert(Yaml::Hash(key), Yaml::Array(Vec::new()));
+    hash.insert(
+        string("integers"),
+        Yaml::Array(vec![
+            Yaml::Integer(i64::MIN),
+            Yaml::Integer(i64::MAX),
+            real("18446744073709551616"),
+        ]),
+    );
+    hash.insert(
+        string("reals"),
+        Yaml::Array(vec![real("1e3"), real(".inf"), real("1")]),
+    );
+    let value = Yaml::Hash(hash);
+    let mut document = from_yaml_rust2(&value);
+    assert_eq!(to_yaml_rust2(&mut document), Some(value.clone()));
+    let mut reloaded = Document::load(&document.dump());
+    assert_eq!(to_yaml_rust2(&mut reloaded), Some(value));
+}
+
+#[test]
+fn test_from_yaml_rust2_unsupported() {
+    let mut document = from_yaml_rust2(&Yaml::BadValue);
+    assert_eq!(document.node_count(), 0);
+    for value in [Yaml::Alias(0), Yaml::Array(vec![Yaml::BadValue])] {
+        let mut document = std::mem::MaybeUninit::uninit();
+        let success = unsafe { yaml_document_from_yaml_rust2(document.as_mut_ptr(), &value) };
+        assert!(success.fail);
+    }
+}
@@ -0,0 +1,98 @@
+#![cfg(feature = "serde_json")]
+#![allow(clippy::uninlined_format_args)]
+
+#[allow(dead_code)]
+mod document;
+
+use self::document::Document;
+use serde_json::{json, Value};
+use std::fmt::Write as _;
+use unsafe_libyaml::{yaml_document_from_json, yaml_document_to_json};
+
+fn to_json(document: &mut Document) -> Option<Value> {
+    let mut value = Value::Null;
+    let success = unsafe { yaml_document_to_json(document.as_mut_ptr(), &mut value) };
+    if success.ok {
+        Some(value)
+    } else {
+        None
+    }
+}
+
+fn from_json(value: &Value) -> Document {
+    Document::create(|document| unsafe { yaml_document_from_json(document, value) }.ok)
+}
+
+#[test]
+fn test_to_json_tags() {
+    let mut document = Document::load("{a: !!int '12', b: !!float 1, c: !foo 12, d: '12', e: ~}");
+    assert_eq!(
+        to_json(&mut document),
+        Some(json!({"a": 12, "b": 1.0, "c": "12", "d": "12", "e": null})),
+    );
+    assert_eq!(to_json(&mut Document::load("!!bool yes")), None);
+    assert_eq!(to_json(&mut Document::load("!!float .nan")), None);
+}
+
+#[test]
+fn test_to_json_integers() {
+    let mut document =
+        Document::load("[18446744073709551615, -9223372036854775808, -9223372036854775809]");
+    assert_eq!(
+        to_json(&mut document),
+        Some(json!([u64::MAX, i64::MIN, -9_223_372_036_854_775_809.0])),
+    );
+}
+
+#[test]
+fn test_to_json_keys() {
+    let mut document = Document::load("{1: a, true: b, null: c, 'x': d}");
+    assert_eq!(
+        to_json(&mut document),
+        Some(json!({"1": "a", "true": "b", "null": "c", "x": "d"})),
+    );
+    assert_eq!(to_json(&mut Document::load("{[1]: a}")), None);
+}
+
+#[test]
+fn test_json_round_trip() {
+    let value = json!({
+        "1": 1,
+        "true": true,
+        "null": null,
+        "": "",
+        "strings": ["12", "true", "~", "0x1F", ".inf", "a: b"],
+        "integers": [u64::MAX, i64::MIN, 0],
+        "floats": [1.0, 0.5, -1e300],
+        "nested": [{"a": []}, {}],
+    });
+    let mut document = from_json(&value);
+    assert_eq!(to_json(&mut document), Some(value.clone()));
+    let mut reloaded = Document::load(&document.dump());
+    assert_eq!(to_json(&mut reloaded), Some(value));
+}
+
+#[test]
+fn test_to_json_recursion_limit() {
+    let nested = |depth: usize| "[".repeat(depth) + &"]".repeat(depth);
+    assert!(to_json(&mut Document::load(&nested(100))).is_some());
+    assert_eq!(to_json(&mut Document::load(&nested(20_000))), None);
+}
+
+#[test]
+fn test_to_json_alias_expansion_limit() {
+    let mut yaml = String::from("a: &a [x, x, x, x, x, x, x, x, x, x]\n");
+    for (name, previous) in ["b", "c", "d"].iter().zip(["a", "b", "c"]) {
+        let aliases = vec![format!("*{}", previous); 10].join(", ");
+        let _ = writeln!(yaml, "{}: &{} [{}]", name, name, aliases);
+    }
+    assert!(to_json(&mut Document::load(&yaml)).is_some());
+    for (name, previous) in ["e", "f", "g", "h", "i"]
+        .iter()
+        .zip(["d", "e", "f", "g", "h"])
+    {
+        let aliases = vec![format!("*{}", previous); 10].join(", ");
+        let _ = writeln!(yaml, "{}: &{} [{}]", name, name, aliases);
+    }
+    assert_eq!(to_json(&mut Document::load(&yaml)), None);
+}
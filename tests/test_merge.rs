@@ -0,0 +1,119 @@
+#![allow(clippy::uninlined_format_args)]
+
+#[allow(dead_code)]
+mod document;
+
+use self::document::Document;
+use std::ffi::CStr;
+use unsafe_libyaml::{
+    yaml_document_merge, yaml_sequence_merge_t, YAML_APPEND_SEQUENCES, YAML_REPLACE_SEQUENCES,
+};
+
+fn merge(document: &str, overlay: &str, sequences: yaml_sequence_merge_t) -> Document {
+    let mut document = Document::load(document);
+    let mut overlay = Document::load(overlay);
+    unsafe {
+        assert!(yaml_document_merge(document.as_mut_ptr(), overlay.as_mut_ptr(), sequences).ok);
+    }
+    document
+}
+
+#[test]
+fn test_merge_nested_mappings() {
+    let mut document = merge(
+        "{a: {b: 1, c: {d: 2}}, e: 3}",
+        "{a: {c: {f: 4}, g: 5}, h: 6}",
+        YAML_REPLACE_SEQUENCES,
+    );
+    assert_eq!(
+        document.dump(),
+        "{a: {b: 1, c: {d: 2, f: 4}, g: 5}, e: 3, h: 6}\n",
+    );
+}
+
+#[test]
+fn test_merge_sequences() {
+    let document = "{a: [1, 2], b: {c: [3]}}";
+    let overlay = "{a: [4], b: {c: [5]}}";
+    let mut replaced = merge(document, overlay, YAML_REPLACE_SEQUENCES);
+    assert_eq!(replaced.dump(), "{a: [4], b: {c: [5]}}\n");
+    // The replaced sequences and their items are removed from the document.
+    assert_eq!(replaced.node_count(), 9);
+    let mut appended = merge(document, overlay, YAML_APPEND_SEQUENCES);
+    assert_eq!(appended.dump(), "{a: [1, 2, 4], b: {c: [3, 5]}}\n");
+}
+
+#[test]
+fn test_merge_shared_mapping() {
+    let mut document = merge(
+        "{x: &s {k: 1, n: {m: 2}}, y: *s}",
+        "{x: {n: {o: 3}}, y: {j: 4}}",
+        YAML_REPLACE_SEQUENCES,
+    );
+    // The shared node is merged once and seen through both references.
+    assert_eq!(
+        document.dump(),
+        "{x: &s {k: 1, n: {m: 2, o: 3}, j: 4}, y: *s}\n",
+    );
+}
+
+#[test]
+fn test_merge_shared_overlay() {
+    let mut document = merge(
+        "{a: 1}",
+        "{b: &o {c: 2}, d: {e: *o}}",
+        YAML_REPLACE_SEQUENCES,
+    );
+    // The shared node of the overlay is copied once, without its anchor.
+    assert_eq!(
+        document.dump(),
+        "{a: 1, b: &id001 {c: 2}, d: {e: *id001}}\n",
+    );
+}
+
+#[test]
+fn test_merge_replace_value() {
+    let mut document = merge(
+        "{a: {b: 1}, c: [2]}",
+        "{a: x, c: {d: 3}}",
+        YAML_APPEND_SEQUENCES,
+    );
+    assert_eq!(document.dump(), "{a: x, c: {d: 3}}\n");
+    assert_eq!(document.node_count(), 7);
+}
+
+#[test]
+fn test_merge_replace_root() {
+    let mut document = merge("[1, 2]", "{a: 1}", YAML_APPEND_SEQUENCES);
+    assert_eq!(document.dump(), "{a: 1}\n");
+    assert_eq!(document.node_count(), 3);
+}
+
+#[test]
+fn test_merge_provenance() {
+    let mut document = merge("{a: 1}", "{a: 2,\n b: [3]}", YAML_REPLACE_SEQUENCES);
+    assert_eq!(document.dump(), "{a: 2, b: [3]}\n");
+    let provenance = |document: &mut Document, id| unsafe {
+        let node = *document.node(id);
+        let transform = if node.transform.is_null() {
+            None
+        } else {
+            CStr::from_ptr(node.transform).to_str().ok()
+        };
+        (
+            transform,
+            node.derived_from,
+            node.start_mark.line,
+            node.start_mark.column,
+        )
+    };
+    // The nodes of the document are kept, and the copies of the overlay nodes
+    // point back to them. The replaced value is removed.
+    assert_eq!(provenance(&mut document, 1), (None, 0, 0, 0));
+    assert_eq!(provenance(&mut document, 2), (None, 0, 0, 1));
+    assert_eq!(document.scalar(3), "2");
+    assert_eq!(provenance(&mut document, 3), (Some("merge"), 3, 0, 4));
+    assert_eq!(document.scalar(4), "b");
+    assert_eq!(provenance(&mut document, 4), (Some("merge"), 4, 1, 1));
+    assert_eq!(provenance(&mut document, 5), (Some("merge"), 5, 1, 4));
+}
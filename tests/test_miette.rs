@@ -0,0 +1,33 @@
+#![cfg(feature = "miette")]
+
+use miette::Diagnostic;
+use serde_json::Value;
+use unsafe_libyaml::from_str;
+
+#[test]
+fn test_diagnostic() {
+    let error = from_str::<Value>("a: [1, 2\nb: 3\n").unwrap_err();
+    assert_eq!(error.code().unwrap().to_string(), "unsafe_libyaml::parser");
+    let labels: Vec<_> = error
+        .labels()
+        .unwrap()
+        .map(|label| {
+            (
+                label.label().map(str::to_owned),
+                label.offset(),
+                label.primary(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        labels,
+        [
+            (Some("while parsing a flow sequence".to_owned()), 3, false),
+            (
+                Some("did not find expected ',' or ']'".to_owned()),
+                10,
+                true
+            ),
+        ],
+    );
+}
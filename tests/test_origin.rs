@@ -0,0 +1,104 @@
+use std::ffi::CStr;
+use std::mem::MaybeUninit;
+use unsafe_libyaml::{
+    yaml_event_delete, yaml_event_t, yaml_mark_t, yaml_parser_delete, yaml_parser_initialize,
+    yaml_parser_parse, yaml_parser_set_input_string_with_origin, yaml_parser_t, YAML_PARSER_ERROR,
+    YAML_SCANNER_ERROR, YAML_STREAM_END_EVENT,
+};
+
+/// Parse `input` as if it started at `(index, line, column)` of a larger text,
+/// returning the start mark of each event. If it fails, the parser is passed
+/// to `check_error`.
+fn parse(
+    input: &str,
+    index: u64,
+    line: u64,
+    column: u64,
+    check_error: impl FnOnce(&yaml_parser_t),
+) -> Vec<(u64, u64, u64)> {
+    let mut marks = Vec::new();
+    unsafe {
+        let mut parser = MaybeUninit::<yaml_parser_t>::uninit();
+        let parser = parser.as_mut_ptr();
+        assert!(yaml_parser_initialize(parser).ok);
+        yaml_parser_set_input_string_with_origin(
+            parser,
+            input.as_ptr(),
+            input.len() as u64,
+            index,
+            line,
+            column,
+        );
+        let mut event = MaybeUninit::<yaml_event_t>::uninit();
+        let event = event.as_mut_ptr();
+        loop {
+            if yaml_parser_parse(parser, event).fail {
+                check_error(&*parser);
+                break;
+            }
+            let mark: yaml_mark_t = (*event).start_mark;
+            marks.push((mark.index, mark.line, mark.column));
+            let done = (*event).type_ == YAML_STREAM_END_EVENT;
+            yaml_event_delete(event);
+            if done {
+                break;
+            }
+        }
+        yaml_parser_delete(parser);
+    }
+    marks
+}
+
+fn mark(mark: yaml_mark_t) -> (u64, u64, u64) {
+    (mark.index, mark.line, mark.column)
+}
+
+#[test]
+fn test_origin_marks() {
+    // Only the columns of the first line are shifted, and the block mapping
+    // is still indented from column 0.
+    let marks = parse("a:\n  b: [c]\n", 100, 4, 10, |_| panic!("parse failed"));
+    assert_eq!(
+        marks,
+        [
+            (100, 4, 10),
+            (100, 4, 10),
+            (100, 4, 10),
+            (100, 4, 10),
+            (105, 5, 2),
+            (105, 5, 2),
+            (108, 5, 5),
+            (109, 5, 6),
+            (110, 5, 7),
+            (112, 6, 0),
+            (112, 6, 0),
+            (112, 6, 0),
+            (112, 6, 0),
+        ],
+    );
+}
+
+#[test]
+fn test_origin_scanner_error() {
+    parse("a: b: c", 40, 2, 7, |parser| {
+        assert_eq!(parser.error, YAML_SCANNER_ERROR);
+        assert_eq!(
+            unsafe { CStr::from_ptr(parser.problem) }.to_str(),
+            Ok("mapping values are not allowed in this context"),
+        );
+        assert_eq!(mark(parser.problem_mark), (44, 2, 11));
+    });
+}
+
+#[test]
+fn test_origin_parser_error() {
+    parse("[a, b\n", 40, 2, 3, |parser| {
+        assert_eq!(parser.error, YAML_PARSER_ERROR);
+        assert_eq!(
+            unsafe { CStr::from_ptr(parser.context) }.to_str(),
+            Ok("while parsing a flow sequence"),
+        );
+        assert_eq!(mark(parser.context_mark), (40, 2, 3));
+        assert_eq!(mark(parser.problem_mark), (46, 3, 0));
+    });
+}
@@ -0,0 +1,258 @@
+#![allow(clippy::uninlined_format_args)]
+
+#[allow(dead_code)]
+mod document;
+
+use self::document::{diff, Document};
+use std::ffi::CString;
+use std::mem::{self, MaybeUninit};
+use std::ptr;
+use unsafe_libyaml::{
+    yaml_compare_options_initialize, yaml_compare_options_t, yaml_document_apply_patch,
+    yaml_document_compare, yaml_patch_op_t, yaml_patch_type_t, YAML_DIFF_ADD, YAML_DIFF_REMOVE,
+    YAML_PATCH_ADD, YAML_PATCH_COPY, YAML_PATCH_MOVE, YAML_PATCH_REMOVE, YAML_PATCH_REPLACE,
+    YAML_PATCH_TEST,
+};
+
+struct Op {
+    type_: yaml_patch_type_t,
+    path: String,
+    from: Option<String>,
+    value: i32,
+}
+
+fn op(type_: yaml_patch_type_t, path: &str, value: i32) -> Op {
+    Op {
+        type_,
+        path: path.to_owned(),
+        from: None,
+        value,
+    }
+}
+
+fn op_from(type_: yaml_patch_type_t, from: &str, path: &str) -> Op {
+    Op {
+        type_,
+        path: path.to_owned(),
+        from: Some(from.to_owned()),
+        value: 0,
+    }
+}
+
+fn apply(document: &mut Document, ops: &[Op], values: &mut Document) -> bool {
+    let paths: Vec<_> = ops
+        .iter()
+        .map(|op| CString::new(op.path.as_str()).unwrap())
+        .collect();
+    let froms: Vec<_> = ops
+        .iter()
+        .map(|op| {
+            op.from
+                .as_ref()
+                .map(|from| CString::new(from.as_str()).unwrap())
+        })
+        .collect();
+    let ops: Vec<yaml_patch_op_t> = ops
+        .iter()
+        .zip(&paths)
+        .zip(&froms)
+        .map(|((op, path), from)| {
+            let mut patch_op: yaml_patch_op_t = unsafe { mem::zeroed() };
+            patch_op.type_ = op.type_;
+            patch_op.path = path.as_ptr().cast();
+            patch_op.from = from
+                .as_ref()
+                .map_or(ptr::null(), |from| from.as_ptr().cast());
+            patch_op.value = op.value;
+            patch_op
+        })
+        .collect();
+    unsafe {
+        yaml_document_apply_patch(
+            document.as_mut_ptr(),
+            ops.as_ptr(),
+            ops.len() as u64,
+            values.as_mut_ptr(),
+        )
+        .ok
+    }
+}
+
+fn patched(document: &str, ops: &[Op], values: &str) -> String {
+    let mut document = Document::load(document);
+    let mut values = Document::load(values);
+    assert!(apply(&mut document, ops, &mut values));
+    document.dump()
+}
+
+fn equal(a: &mut Document, b: &mut Document) -> bool {
+    let mut options = MaybeUninit::<yaml_compare_options_t>::uninit();
+    let mut equal = false;
+    unsafe {
+        yaml_compare_options_initialize(options.as_mut_ptr());
+        let options = options.as_mut_ptr();
+        (*options).ignore_styles = true;
+        (*options).ignore_marks = true;
+        (*options).ignore_anchors = true;
+        (*options).ignore_key_order = true;
+        assert!(yaml_document_compare(a.as_mut_ptr(), b.as_mut_ptr(), options, &mut equal).ok);
+    }
+    equal
+}
+
+#[test]
+fn test_patch_add() {
+    let values = "[2, [3]]";
+    assert_eq!(
+        patched("{a: 1}", &[op(YAML_PATCH_ADD, "/b", 2)], values),
+        "{a: 1, b: 2}\n",
+    );
+    assert_eq!(
+        patched(
+            "[1, 3]",
+            &[op(YAML_PATCH_ADD, "/1", 2), op(YAML_PATCH_ADD, "/-", 3)],
+            values,
+        ),
+        "[1, 2, 3, [3]]\n",
+    );
+}
+
+#[test]
+fn test_patch_remove() {
+    assert_eq!(
+        patched(
+            "{a: 1, b: [1, 2]}",
+            &[
+                op(YAML_PATCH_REMOVE, "/b/0", 0),
+                op(YAML_PATCH_REMOVE, "/a", 0)
+            ],
+            "",
+        ),
+        "{b: [2]}\n",
+    );
+}
+
+#[test]
+fn test_patch_replace() {
+    assert_eq!(
+        patched(
+            "{a: 1}",
+            &[
+                op(YAML_PATCH_REPLACE, "", 3),
+                op(YAML_PATCH_REPLACE, "/0", 2)
+            ],
+            "[2, [3]]",
+        ),
+        "[2]\n",
+    );
+}
+
+#[test]
+fn test_patch_move_and_copy() {
+    let document = "{a: {b: 1}, c: []}";
+    assert_eq!(
+        patched(document, &[op_from(YAML_PATCH_MOVE, "/a/b", "/c/-")], ""),
+        "{a: {}, c: [1]}\n",
+    );
+    assert_eq!(
+        patched(
+            document,
+            &[
+                op_from(YAML_PATCH_COPY, "/a", "/d"),
+                op_from(YAML_PATCH_COPY, "/a/b", "/c/0"),
+            ],
+            "",
+        ),
+        "{a: {b: 1}, c: [1], d: {b: 1}}\n",
+    );
+}
+
+#[test]
+fn test_patch_test() {
+    let document = "{a: {b: 1}, c: []}";
+    assert_eq!(
+        patched(document, &[op(YAML_PATCH_TEST, "/a", 1)], "{b: 1}"),
+        "{a: {b: 1}, c: []}\n",
+    );
+    let mut document = Document::load(document);
+    let mut values = Document::load("2");
+    let before = document.dump();
+    assert!(!apply(
+        &mut document,
+        &[
+            op(YAML_PATCH_REMOVE, "/c", 0),
+            op(YAML_PATCH_TEST, "/a/b", 1)
+        ],
+        &mut values,
+    ));
+    // A failed patch leaves the document unchanged.
+    assert_eq!(document.dump(), before);
+}
+
+#[test]
+fn test_patch_invalid() {
+    let mut document = Document::load("{a: [1]}");
+    let mut values = Document::load("2");
+    let before = document.dump();
+    for ops in [
+        [op(YAML_PATCH_REMOVE, "/b", 0)],
+        [op(YAML_PATCH_REMOVE, "", 0)],
+        [op(YAML_PATCH_REMOVE, "/a/1", 0)],
+        [op(YAML_PATCH_ADD, "/a/2", 1)],
+        [op(YAML_PATCH_REPLACE, "/a/x", 1)],
+        [op(YAML_PATCH_ADD, "a", 1)],
+        [op_from(YAML_PATCH_MOVE, "/a", "/a/0")],
+    ] {
+        assert!(!apply(&mut document, &ops, &mut values), "{}", ops[0].path);
+        assert_eq!(document.dump(), before);
+    }
+}
+
+#[test]
+fn test_patch_shared_node() {
+    // The alias is followed, so the change is seen through both paths.
+    assert_eq!(
+        patched(
+            "{a: &x {b: 1}, c: *x}",
+            &[op(YAML_PATCH_REPLACE, "/c/b", 1)],
+            "2",
+        ),
+        "{a: &x {b: 2}, c: *x}\n",
+    );
+}
+
+#[test]
+fn test_diff_patch_round_trip() {
+    for (a, b) in [
+        ("{a: 1, b: 2}", "{a: 1, b: 3}"),
+        ("{a: 1}", "{b: 1}"),
+        ("[1, 2, 3]", "[4]"),
+        ("[1, 2]", "[0, 1, 2, 3, 4]"),
+        ("{a/b: 1, c~d: [x]}", "{a/b: 2, c~d: [y, z]}"),
+        ("{a: [1]}", "{a: !t [1]}"),
+        ("[1]", "{a: 1}"),
+        (
+            "{a: {b: [1, 2]}, c: {b: [1, 2]}, d: {e: f}}",
+            "{a: {b: [2]}, c: &x {b: [1, 2, 3]}, g: [h, *x]}",
+        ),
+    ] {
+        let mut document = Document::load(a);
+        let mut target = Document::load(b);
+        let ops: Vec<_> = diff(&mut document, &mut target)
+            .into_iter()
+            .map(|change| match change.type_ {
+                YAML_DIFF_ADD => op(YAML_PATCH_ADD, &change.path, change.new_node),
+                YAML_DIFF_REMOVE => op(YAML_PATCH_REMOVE, &change.path, 0),
+                _ => op(YAML_PATCH_REPLACE, &change.path, change.new_node),
+            })
+            .collect();
+        assert!(
+            apply(&mut document, &ops, &mut target),
+            "{:?} -> {:?}",
+            a,
+            b
+        );
+        assert!(equal(&mut document, &mut target), "{:?} -> {:?}", a, b);
+        assert_eq!(diff(&mut document, &mut target), []);
+    }
+}
@@ -0,0 +1,107 @@
+#![allow(clippy::uninlined_format_args)]
+
+mod bin;
+#[path = "../src/bin/run-scanner-test-suite.rs"]
+#[allow(dead_code)]
+mod run_scanner_test_suite;
+
+use std::fs;
+use std::path::Path;
+
+// The expected listings are the `.tokens` files of the PyYAML test data.
+fn test(name: &str, input: &str, tokens: &str) {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!("{}.yaml", name));
+    fs::write(&path, input).unwrap();
+
+    let output = bin::run(
+        env!("CARGO_BIN_EXE_run-scanner-test-suite"),
+        run_scanner_test_suite::unsafe_main,
+        &path,
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.success, "{}", stderr);
+    assert_eq!(stdout.lines().count(), 1, "{}", stdout);
+    assert_eq!(
+        stdout.split_whitespace().collect::<Vec<_>>(),
+        tokens.split_whitespace().collect::<Vec<_>>(),
+    );
+}
+
+#[test]
+fn test_spec_02_01() {
+    test(
+        "spec-02-01",
+        "- Mark McGwire\n- Sammy Sosa\n- Ken Griffey\n",
+        "[[ , _ , _ , _ ]}",
+    );
+}
+
+#[test]
+fn test_spec_02_02() {
+    test(
+        "spec-02-02",
+        "hr:  65    # Home runs\navg: 0.278 # Batting average\nrbi: 147   # Runs Batted In\n",
+        "{{\n? _ : _\n? _ : _\n? _ : _\n]}",
+    );
+}
+
+#[test]
+fn test_spec_02_05() {
+    test(
+        "spec-02-05",
+        "- [name        , hr, avg  ]\n- [Mark McGwire, 65, 0.278]\n- [Sammy Sosa  , 63, 0.288]\n",
+        "[[\n, [ _ , _ , _ ]\n, [ _ , _ , _ ]\n, [ _ , _ , _ ]\n]}",
+    );
+}
+
+#[test]
+fn test_spec_02_06() {
+    test(
+        "spec-02-06",
+        "Mark McGwire: {hr: 65, avg: 0.278}\nSammy Sosa: {\n    hr: 63,\n    avg: 0.288\n  }\n",
+        "{{\n? _ : { ? _ : _ , ? _ : _ }\n? _ : { ? _ : _ , ? _ : _ }\n]}",
+    );
+}
+
+#[test]
+fn test_spec_02_10() {
+    test(
+        "spec-02-10",
+        "---\nhr:\n  - Mark McGwire\n  # Following node labeled SS\n  - &SS Sammy Sosa\nrbi:\n  - *SS # Subsequent occurrence\n  - Ken Griffey\n",
+        "---\n{{\n? _ : [[ , _ , & _ ]}\n? _ : [[ , * , _ ]}\n]}",
+    );
+}
+
+#[test]
+fn test_directives_and_tags() {
+    test(
+        "directives",
+        "%YAML 1.1\n%TAG !e! tag:example.com,2000:\n--- !<tag:yaml.org,2002:seq>\n- !e!a a\n- ! b\n- !!str c\n...\n",
+        "% % --- ! [[ , ! _ , ! _ , ! _ ]} ...",
+    );
+}
+
+#[test]
+fn test_empty() {
+    test("empty", "", "");
+}
+
+#[test]
+fn test_scan_error() {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("scan-error.yaml");
+    fs::write(&path, "a: b: c\n").unwrap();
+
+    let output = bin::run(
+        env!("CARGO_BIN_EXE_run-scanner-test-suite"),
+        run_scanner_test_suite::unsafe_main,
+        &path,
+    );
+
+    assert!(!output.success);
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "Scan error: mapping values are not allowed in this context\nLine: 1 Column: 5\n",
+    );
+}
@@ -0,0 +1,225 @@
+use std::ffi::{c_void, CStr};
+use std::mem::MaybeUninit;
+use std::ptr;
+use unsafe_libyaml::{
+    yaml_event_delete, yaml_event_t, yaml_parser_delete, yaml_parser_initialize, yaml_parser_parse,
+    yaml_parser_set_implicit_resolver, yaml_parser_set_input_string, yaml_parser_t,
+    yaml_resolved_scalar_t, yaml_scalar_resolve, yaml_scalar_resolve_with_options,
+    yaml_schema_options_initialize, yaml_schema_options_t, yaml_schema_t, YAML_1_1_SCHEMA,
+    YAML_BOOL_VALUE, YAML_CORE_SCHEMA, YAML_FAILSAFE_SCHEMA, YAML_FLOAT_VALUE, YAML_INT_VALUE,
+    YAML_JSON_SCHEMA, YAML_NULL_VALUE, YAML_OVERFLOW_ERROR, YAML_OVERFLOW_SATURATE,
+    YAML_SCALAR_EVENT, YAML_STREAM_END_EVENT, YAML_STR_VALUE,
+};
+
+fn resolve(
+    schema: yaml_schema_t,
+    tag: Option<&'static [u8]>,
+    value: &str,
+) -> Option<yaml_resolved_scalar_t> {
+    let mut resolved = MaybeUninit::<yaml_resolved_scalar_t>::uninit();
+    let success = unsafe {
+        yaml_scalar_resolve(
+            schema,
+            tag.map_or(ptr::null(), <[u8]>::as_ptr),
+            value.as_ptr(),
+            value.len() as u64,
+            resolved.as_mut_ptr(),
+        )
+    };
+    if success.fail {
+        return None;
+    }
+    Some(unsafe { resolved.assume_init() })
+}
+
+fn tag(resolved: &yaml_resolved_scalar_t) -> &str {
+    unsafe { CStr::from_ptr(resolved.tag.cast()) }
+        .to_str()
+        .unwrap()
+}
+
+#[test]
+fn test_resolve_core() {
+    let resolved = resolve(YAML_CORE_SCHEMA, None, "~").unwrap();
+    assert_eq!(resolved.type_, YAML_NULL_VALUE);
+    assert_eq!(tag(&resolved), "tag:yaml.org,2002:null");
+
+    let resolved = resolve(YAML_CORE_SCHEMA, None, "True").unwrap();
+    assert_eq!(resolved.type_, YAML_BOOL_VALUE);
+    assert!(resolved.boolean);
+
+    let resolved = resolve(YAML_CORE_SCHEMA, None, "0x1F").unwrap();
+    assert_eq!(resolved.type_, YAML_INT_VALUE);
+    assert_eq!((resolved.integer, resolved.overflow), (31, false));
+    assert_eq!(tag(&resolved), "tag:yaml.org,2002:int");
+
+    let resolved = resolve(YAML_CORE_SCHEMA, None, "-.inf").unwrap();
+    assert_eq!(resolved.type_, YAML_FLOAT_VALUE);
+    assert_eq!(resolved.float.to_bits(), f64::NEG_INFINITY.to_bits());
+
+    let resolved = resolve(YAML_CORE_SCHEMA, None, "yes").unwrap();
+    assert_eq!(resolved.type_, YAML_STR_VALUE);
+    assert_eq!(tag(&resolved), "tag:yaml.org,2002:str");
+}
+
+#[test]
+fn test_resolve_integer_range() {
+    let resolved = resolve(YAML_CORE_SCHEMA, None, "-9223372036854775808").unwrap();
+    assert_eq!(resolved.type_, YAML_INT_VALUE);
+    assert_eq!((resolved.integer, resolved.overflow), (i64::MIN, false));
+
+    let resolved = resolve(YAML_CORE_SCHEMA, None, "9223372036854775807").unwrap();
+    assert_eq!((resolved.integer, resolved.overflow), (i64::MAX, false));
+
+    // Integers above i64::MAX keep their bits and set the overflow flag.
+    let resolved = resolve(YAML_CORE_SCHEMA, None, "9223372036854775808").unwrap();
+    assert_eq!(resolved.type_, YAML_INT_VALUE);
+    assert!(resolved.overflow);
+    assert_eq!(u64::from_ne_bytes(resolved.integer.to_ne_bytes()), 1 << 63);
+
+    let resolved = resolve(YAML_CORE_SCHEMA, None, "18446744073709551615").unwrap();
+    assert!(resolved.overflow);
+    assert_eq!(u64::from_ne_bytes(resolved.integer.to_ne_bytes()), u64::MAX);
+
+    // Beyond a u64, the integer resolves to a float by default.
+    let resolved = resolve(YAML_CORE_SCHEMA, None, "18446744073709551616").unwrap();
+    assert_eq!(resolved.type_, YAML_FLOAT_VALUE);
+    assert_eq!(
+        resolved.float.to_bits(),
+        18_446_744_073_709_551_616.0_f64.to_bits(),
+    );
+}
+
+#[test]
+fn test_resolve_overflow_options() {
+    let value = "-99999999999999999999";
+    let mut options = MaybeUninit::<yaml_schema_options_t>::uninit();
+    let mut resolved = MaybeUninit::<yaml_resolved_scalar_t>::uninit();
+    unsafe {
+        yaml_schema_options_initialize(options.as_mut_ptr(), YAML_CORE_SCHEMA);
+        (*options.as_mut_ptr()).overflow = YAML_OVERFLOW_ERROR;
+        assert!(
+            yaml_scalar_resolve_with_options(
+                options.as_ptr(),
+                ptr::null(),
+                value.as_ptr(),
+                value.len() as u64,
+                resolved.as_mut_ptr(),
+            )
+            .fail
+        );
+
+        (*options.as_mut_ptr()).overflow = YAML_OVERFLOW_SATURATE;
+        assert!(
+            yaml_scalar_resolve_with_options(
+                options.as_ptr(),
+                ptr::null(),
+                value.as_ptr(),
+                value.len() as u64,
+                resolved.as_mut_ptr(),
+            )
+            .ok
+        );
+        let resolved = resolved.assume_init();
+        assert_eq!(resolved.type_, YAML_INT_VALUE);
+        assert_eq!((resolved.integer, resolved.overflow), (i64::MIN, false));
+    }
+}
+
+#[test]
+fn test_resolve_tags() {
+    let int = b"tag:yaml.org,2002:int\0";
+    let resolved = resolve(YAML_CORE_SCHEMA, Some(int), "12").unwrap();
+    assert_eq!((resolved.type_, resolved.integer), (YAML_INT_VALUE, 12));
+    assert!(resolve(YAML_CORE_SCHEMA, Some(int), "twelve").is_none());
+
+    // Quoted scalars are strings.
+    let resolved = resolve(YAML_CORE_SCHEMA, Some(b"!\0"), "12").unwrap();
+    assert_eq!(resolved.type_, YAML_STR_VALUE);
+
+    // Unknown tags are kept.
+    let resolved = resolve(YAML_CORE_SCHEMA, Some(b"!color\0"), "red").unwrap();
+    assert_eq!(resolved.type_, YAML_STR_VALUE);
+    assert_eq!(tag(&resolved), "!color");
+}
+
+#[test]
+fn test_resolve_schemas() {
+    let resolved = resolve(YAML_FAILSAFE_SCHEMA, None, "12").unwrap();
+    assert_eq!(resolved.type_, YAML_STR_VALUE);
+
+    let resolved = resolve(YAML_JSON_SCHEMA, None, "-12").unwrap();
+    assert_eq!((resolved.type_, resolved.integer), (YAML_INT_VALUE, -12));
+    // A plain scalar outside of the JSON rules does not resolve.
+    assert!(resolve(YAML_JSON_SCHEMA, None, "True").is_none());
+
+    let resolved = resolve(YAML_1_1_SCHEMA, None, "yes").unwrap();
+    assert_eq!(resolved.type_, YAML_BOOL_VALUE);
+    let resolved = resolve(YAML_1_1_SCHEMA, None, "1:30").unwrap();
+    assert_eq!((resolved.type_, resolved.integer), (YAML_INT_VALUE, 90));
+}
+
+unsafe fn resolve_digits(
+    _data: *mut c_void,
+    value: *const u8,
+    length: u64,
+    tag: *mut *const u8,
+) -> i32 {
+    let length = usize::try_from(length).unwrap();
+    let value = std::slice::from_raw_parts(value, length);
+    if value == b"fail" {
+        return 0;
+    }
+    if !value.is_empty() && value.iter().all(u8::is_ascii_digit) {
+        *tag = b"!digits\0".as_ptr();
+    }
+    1
+}
+
+/// Parse `input` with the digits resolver and return the tag of each scalar,
+/// or None if parsing failed.
+fn scalar_tags(input: &str) -> Option<Vec<Option<String>>> {
+    let mut tags = Vec::new();
+    unsafe {
+        let mut parser = MaybeUninit::<yaml_parser_t>::uninit();
+        let parser = parser.as_mut_ptr();
+        assert!(yaml_parser_initialize(parser).ok);
+        yaml_parser_set_input_string(parser, input.as_ptr(), input.len() as u64);
+        yaml_parser_set_implicit_resolver(parser, resolve_digits, ptr::null_mut());
+        let mut event = MaybeUninit::<yaml_event_t>::uninit();
+        let event = event.as_mut_ptr();
+        loop {
+            if yaml_parser_parse(parser, event).fail {
+                yaml_parser_delete(parser);
+                return None;
+            }
+            if (*event).type_ == YAML_SCALAR_EVENT {
+                let tag = (*event).data.scalar.tag;
+                tags.push(if tag.is_null() {
+                    None
+                } else {
+                    let tag = CStr::from_ptr(tag.cast());
+                    Some(tag.to_str().unwrap().to_owned())
+                });
+            }
+            let done = (*event).type_ == YAML_STREAM_END_EVENT;
+            yaml_event_delete(event);
+            if done {
+                break;
+            }
+        }
+        yaml_parser_delete(parser);
+    }
+    Some(tags)
+}
+
+#[test]
+fn test_implicit_resolver() {
+    // Only plain scalars without a tag are resolved.
+    let tags = scalar_tags("[12, a, '34', !t 56]").unwrap();
+    let expected = [Some("!digits"), None, None, Some("!t")];
+    let expected: Vec<Option<String>> = expected.iter().map(|tag| tag.map(str::to_owned)).collect();
+    assert_eq!(tags, expected);
+    // A failure of the resolver stops the parser.
+    assert_eq!(scalar_tags("[12, fail]"), None);
+}
@@ -0,0 +1,258 @@
+#![cfg(feature = "serde")]
+#![allow(clippy::uninlined_format_args)]
+
+#[allow(dead_code)]
+mod document;
+
+use self::document::Document;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::error::Error as _;
+use std::fmt::{self, Write as _};
+use unsafe_libyaml::{
+    from_document, from_str, to_output, to_string, ErrorKind, Output, OutputError,
+};
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Config {
+    name: String,
+    version: String,
+    ports: Vec<u16>,
+    enabled: bool,
+    ratio: Option<f64>,
+}
+
+#[derive(Deserialize, Debug)]
+#[allow(dead_code)]
+struct Nested(Vec<Nested>);
+
+#[test]
+fn test_resolve_scalars() {
+    let value: Value = from_str("[~, null, true, False, 12, -3, 0x1F, 0o17, 1.5, abc]").unwrap();
+    assert_eq!(
+        value,
+        json!([null, null, true, false, 12, -3, 31, 15, 1.5, "abc"]),
+    );
+}
+
+#[test]
+fn test_resolve_tagged_and_quoted() {
+    assert_eq!(from_str::<Value>("!!str 123").unwrap(), json!("123"));
+    assert_eq!(from_str::<Value>("'123'").unwrap(), json!("123"));
+    assert_eq!(from_str::<Value>("\"true\"").unwrap(), json!("true"));
+    assert_eq!(from_str::<Value>("!!str null").unwrap(), json!("null"));
+    assert_eq!(from_str::<Value>("").unwrap(), Value::Null);
+}
+
+#[test]
+fn test_quote_ambiguous_strings() {
+    assert_eq!(to_string(&"null").unwrap(), "'null'\n");
+    assert_eq!(to_string(&"abc").unwrap(), "abc\n");
+    for string in [
+        "null", "~", "", "true", "False", "123", "0x1F", "1.5", ".inf", "abc",
+    ] {
+        let yaml = to_string(&string).unwrap();
+        assert_eq!(from_str::<String>(&yaml).unwrap(), string, "{:?}", yaml);
+        assert_eq!(
+            from_str::<Value>(&yaml).unwrap(),
+            json!(string),
+            "{:?}",
+            yaml
+        );
+    }
+}
+
+#[test]
+fn test_round_trip_struct() {
+    let config = Config {
+        name: "server".to_owned(),
+        version: "1.10".to_owned(),
+        ports: vec![80, 443],
+        enabled: true,
+        ratio: None,
+    };
+    let yaml = to_string(&config).unwrap();
+    assert_eq!(from_str::<Config>(&yaml).unwrap(), config);
+}
+
+#[test]
+fn test_aliases() {
+    let value: Value = from_str("{a: &x [1, {b: 2}], c: *x, d: &y 3, e: *y}").unwrap();
+    assert_eq!(
+        value,
+        json!({"a": [1, {"b": 2}], "c": [1, {"b": 2}], "d": 3, "e": 3}),
+    );
+    let config: Config =
+        from_str("name: &n server\nversion: *n\nports: &p [1, 2]\nenabled: true\nratio: 0.5\n")
+            .unwrap();
+    assert_eq!(config.version, "server");
+    assert_eq!(config.ports, [1, 2]);
+}
+
+#[test]
+fn test_recursion_limit() {
+    let nested = |depth: usize| "[".repeat(depth) + &"]".repeat(depth);
+    assert!(from_str::<Value>(&nested(100)).is_ok());
+    let error = from_str::<Value>(&nested(1000)).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Limit);
+    let error = from_str::<Nested>(&nested(1000)).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Limit);
+}
+
+#[test]
+fn test_alias_expansion_limit() {
+    let mut yaml = String::from("a: &a [x, x, x, x, x, x, x, x, x, x]\n");
+    for (name, previous) in ["b", "c", "d", "e", "f", "g", "h", "i"]
+        .iter()
+        .zip(["a", "b", "c", "d", "e", "f", "g", "h"])
+    {
+        let aliases = vec![format!("*{}", previous); 10].join(", ");
+        let _ = writeln!(yaml, "{}: &{} [{}]", name, name, aliases);
+    }
+    let error = from_str::<Value>(&yaml).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Limit);
+    assert!(error.to_string().contains("alias expansion"), "{}", error);
+}
+
+#[test]
+fn test_from_document() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Server {
+        host: String,
+        ports: Vec<u16>,
+    }
+
+    let mut document = Document::load("{host: &h example.com, ports: [80, 443], alias: *h}");
+    let server: Server = unsafe { from_document(&*document.as_mut_ptr()) }.unwrap();
+    assert_eq!(
+        server,
+        Server {
+            host: "example.com".to_owned(),
+            ports: vec![80, 443],
+        },
+    );
+    let value: Value = unsafe { from_document(&*document.as_mut_ptr()) }.unwrap();
+    assert_eq!(value["alias"], json!("example.com"));
+
+    let mut empty = Document::load("");
+    let value: Option<Server> = unsafe { from_document(&*empty.as_mut_ptr()) }.unwrap();
+    assert_eq!(value, None);
+}
+
+#[test]
+fn test_from_document_recursion_limit() {
+    let nested = |depth: usize| "[".repeat(depth) + &"]".repeat(depth);
+    let mut document = Document::load(&nested(100));
+    assert!(unsafe { from_document::<Value>(&*document.as_mut_ptr()) }.is_ok());
+    // Deep enough to overflow the stack without the limit.
+    let mut document = Document::load(&nested(20_000));
+    let error = unsafe { from_document::<Value>(&*document.as_mut_ptr()) }.unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Limit);
+}
+
+#[test]
+fn test_to_output() {
+    #[derive(Debug)]
+    struct Closed;
+
+    impl fmt::Display for Closed {
+        fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("closed")
+        }
+    }
+
+    impl std::error::Error for Closed {}
+
+    struct Failing(Option<OutputError>);
+
+    impl Output for Failing {
+        fn write_all(&mut self, _buf: &[u8]) -> Result<(), OutputError> {
+            Err(self.0.take().unwrap())
+        }
+    }
+
+    let mut output = Vec::new();
+    to_output(&mut output, &[1, 2]).unwrap();
+    assert_eq!(output, b"- 1\n- 2\n");
+
+    let error = to_output(Failing(Some(OutputError::new(Closed))), &1).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Writer);
+    let source = error.source().unwrap();
+    assert_eq!(source.to_string(), "failed to write the output: closed");
+    assert!(source.source().unwrap().is::<Closed>());
+
+    let error = to_output(Failing(Some(OutputError::would_block())), &1).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::WouldBlock);
+    let source = error.source().unwrap();
+    assert!(source
+        .downcast_ref::<OutputError>()
+        .unwrap()
+        .is_would_block());
+}
+
+#[test]
+fn test_to_output_slice() {
+    let mut buffer = [0; 12];
+    let mut output = &mut buffer[..];
+    to_output(&mut output, &[1, 2]).unwrap();
+    assert_eq!(output.len(), 4);
+    assert_eq!(&buffer[..8], b"- 1\n- 2\n");
+
+    let mut buffer = [0; 5];
+    let mut output = &mut buffer[..];
+    let error = to_output(&mut output, &[1, 2]).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Writer);
+    assert_eq!(error.to_string(), "the output buffer is full after 5 bytes");
+    let source = error.source().unwrap().downcast_ref::<OutputError>();
+    assert_eq!(source.unwrap().bytes_written(), Some(5));
+    assert!(output.is_empty());
+    assert_eq!(&buffer, b"- 1\n-");
+}
+
+#[test]
+fn test_display_with_source() {
+    let input = "a: b: c";
+    let error = from_str::<Value>(input).unwrap_err();
+    assert_eq!(
+        error.display_with_source(input).to_string(),
+        "mapping values are not allowed in this context at line 1 column 5\n  \
+         |\n\
+         1 | a: b: c\n  \
+         |     ^ mapping values are not allowed in this context",
+    );
+}
+
+#[test]
+fn test_display_with_source_context() {
+    // The problem is on the line after the end of the input.
+    let input = "[a, b\n";
+    let error = from_str::<Value>(input).unwrap_err();
+    let expected = "did not find expected ',' or ']' at line 2 column 1, \
+        while parsing a flow sequence at line 1 column 1\n  \
+        |\n\
+        1 | [a, b\n  \
+        | ^ while parsing a flow sequence\n  \
+        |\n\
+        2 |\n  \
+        | ^ did not find expected ',' or ']'";
+    assert_eq!(error.display_with_source(input).to_string(), expected);
+    // Without the line break, the line is past the end of the source.
+    assert_eq!(error.display_with_source("[a, b").to_string(), expected);
+}
+
+#[test]
+fn test_display_with_source_tabs() {
+    let input = "a:\t[b: c, d: e: f]";
+    let error = from_str::<Value>(input).unwrap_err();
+    assert_eq!(
+        error.display_with_source(input).to_string(),
+        "did not find expected ',' or ']' at line 1 column 15, \
+         while parsing a flow sequence at line 1 column 4\n  \
+         |\n\
+         1 | a:\t[b: c, d: e: f]\n  \
+         |   \t^ while parsing a flow sequence\n  \
+         |\n\
+         1 | a:\t[b: c, d: e: f]\n  \
+         |   \t           ^ did not find expected ',' or ']'",
+    );
+}
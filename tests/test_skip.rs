@@ -0,0 +1,141 @@
+use std::ffi::c_void;
+use std::mem::MaybeUninit;
+use std::ptr::addr_of_mut;
+use std::slice;
+use unsafe_libyaml::{
+    yaml_event_delete, yaml_event_format, yaml_event_t, yaml_parser_delete, yaml_parser_initialize,
+    yaml_parser_parse, yaml_parser_set_input_string, yaml_parser_skip_to_next_document,
+    yaml_parser_t, YAML_STREAM_END_EVENT,
+};
+
+unsafe fn write_to_vec(data: *mut c_void, buffer: *mut u8, size: u64) -> i32 {
+    let output = &mut *data.cast::<Vec<u8>>();
+    let size = usize::try_from(size).unwrap();
+    output.extend_from_slice(slice::from_raw_parts(buffer, size));
+    1
+}
+
+/// Parse `input`, skipping to the next document after each event formatted
+/// as `skip_after`, and return the formatted events.
+fn parse(input: &str, skip_after: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    unsafe {
+        let mut parser = MaybeUninit::<yaml_parser_t>::uninit();
+        let parser = parser.as_mut_ptr();
+        assert!(yaml_parser_initialize(parser).ok);
+        yaml_parser_set_input_string(parser, input.as_ptr(), input.len() as u64);
+        let mut event = MaybeUninit::<yaml_event_t>::uninit();
+        let event = event.as_mut_ptr();
+        loop {
+            assert!(yaml_parser_parse(parser, event).ok);
+            let mut line = Vec::new();
+            assert!(yaml_event_format(event, write_to_vec, addr_of_mut!(line).cast()).ok);
+            let line = String::from_utf8(line).unwrap();
+            let done = (*event).type_ == YAML_STREAM_END_EVENT;
+            yaml_event_delete(event);
+            let skip = line == skip_after;
+            lines.push(line);
+            if done {
+                break;
+            }
+            if skip {
+                assert!(yaml_parser_skip_to_next_document(parser).ok);
+            }
+        }
+        yaml_parser_delete(parser);
+    }
+    lines
+}
+
+#[test]
+fn test_skip_mid_mapping() {
+    let events = parse("a: 1\nb: 2\n--- c\n", "=VAL :1");
+    let expected = [
+        "+STR", "+DOC", "+MAP", "=VAL :a", "=VAL :1", "+DOC ---", "=VAL :c", "-DOC", "-STR",
+    ];
+    assert_eq!(events, expected);
+}
+
+#[test]
+fn test_skip_mid_flow() {
+    let events = parse("[a, {b: c\n--- d\n", "=VAL :a");
+    let expected = [
+        "+STR", "+DOC", "+SEQ", "=VAL :a", "+DOC ---", "=VAL :d", "-DOC", "-STR",
+    ];
+    assert_eq!(events, expected);
+}
+
+#[test]
+fn test_skip_to_document_end() {
+    let events = parse("a: [1\n...\n--- b\n", "=VAL :1");
+    let expected = [
+        "+STR", "+DOC", "+MAP", "=VAL :a", "+SEQ", "=VAL :1", "+DOC ---", "=VAL :b", "-DOC", "-STR",
+    ];
+    assert_eq!(events, expected);
+}
+
+#[test]
+fn test_skip_after_document_end() {
+    let events = parse("a\n...\n--- b\n", "-DOC ...");
+    let expected = [
+        "+STR", "+DOC", "=VAL :a", "-DOC ...", "+DOC ---", "=VAL :b", "-DOC", "-STR",
+    ];
+    assert_eq!(events, expected);
+}
+
+#[test]
+fn test_skip_last_document() {
+    let events = parse("a: [1, 2]\n", "=VAL :a");
+    let expected = ["+STR", "+DOC", "+MAP", "=VAL :a", "-STR"];
+    assert_eq!(events, expected);
+}
+
+#[test]
+fn test_skip_between_documents() {
+    // The DOCUMENT-END of an implicitly ended document is produced once the
+    // next `---` has been scanned, so that document is not skipped.
+    let events = parse("--- a\n--- b\n--- c\n", "-DOC");
+    let expected = [
+        "+STR", "+DOC ---", "=VAL :a", "-DOC", "+DOC ---", "=VAL :b", "-DOC", "+DOC ---",
+        "=VAL :c", "-DOC", "-STR",
+    ];
+    assert_eq!(events, expected);
+
+    let events = parse("--- a\n--- b\n--- c\n", "=VAL :a");
+    let expected = [
+        "+STR", "+DOC ---", "=VAL :a", "+DOC ---", "=VAL :b", "-DOC", "+DOC ---", "=VAL :c",
+        "-DOC", "-STR",
+    ];
+    assert_eq!(events, expected);
+}
+
+#[test]
+fn test_skip_quoted_scalar() {
+    // The skip does not look inside scalars, so it stops at the `---` line in
+    // the middle of the quoted scalar.
+    let events = parse("a: 'x\n--- y'\n--- b\n", "=VAL :a");
+    let expected = [
+        "+STR", "+DOC", "+MAP", "=VAL :a", "+DOC ---", "=VAL :y'", "-DOC", "+DOC ---", "=VAL :b",
+        "-DOC", "-STR",
+    ];
+    assert_eq!(events, expected);
+}
+
+#[test]
+fn test_skip_to_directives() {
+    let events = parse(
+        "--- [x,\n...\n%TAG !e! tag:e,2000:\n--- !e!b z\n",
+        "=VAL :x",
+    );
+    let expected = [
+        "+STR",
+        "+DOC ---",
+        "+SEQ",
+        "=VAL :x",
+        "+DOC ---",
+        "=VAL <tag:e,2000:b> :z",
+        "-DOC",
+        "-STR",
+    ];
+    assert_eq!(events, expected);
+}
@@ -0,0 +1,198 @@
+use std::mem::MaybeUninit;
+use std::ptr;
+use std::slice;
+use unsafe_libyaml::{
+    yaml_document_delete, yaml_document_get_node_source, yaml_document_nodes, yaml_document_t,
+    yaml_event_delete, yaml_event_t, yaml_mark_t, yaml_parser_delete,
+    yaml_parser_get_consumed_span, yaml_parser_initialize, yaml_parser_load, yaml_parser_parse,
+    yaml_parser_set_input_string_with_origin, yaml_parser_source_slice, yaml_parser_t,
+    YAML_STREAM_END_EVENT,
+};
+
+/// Parse `input`, which starts at byte `origin` of a larger text, and call
+/// `visit` with the parser and each event.
+fn parse(input: &[u8], origin: u64, mut visit: impl FnMut(*mut yaml_parser_t, *mut yaml_event_t)) {
+    unsafe {
+        let mut parser = MaybeUninit::<yaml_parser_t>::uninit();
+        let parser = parser.as_mut_ptr();
+        assert!(yaml_parser_initialize(parser).ok);
+        yaml_parser_set_input_string_with_origin(
+            parser,
+            input.as_ptr(),
+            input.len() as u64,
+            origin,
+            0,
+            0,
+        );
+        let mut event = MaybeUninit::<yaml_event_t>::uninit();
+        let event = event.as_mut_ptr();
+        loop {
+            assert!(yaml_parser_parse(parser, event).ok);
+            visit(parser, event);
+            let done = (*event).type_ == YAML_STREAM_END_EVENT;
+            yaml_event_delete(event);
+            if done {
+                break;
+            }
+        }
+        yaml_parser_delete(parser);
+    }
+}
+
+unsafe fn source_slice(
+    parser: *mut yaml_parser_t,
+    start_mark: yaml_mark_t,
+    end_mark: yaml_mark_t,
+) -> Option<String> {
+    let mut slice = ptr::null();
+    let mut length = 0;
+    if yaml_parser_source_slice(parser, start_mark, end_mark, &mut slice, &mut length).fail {
+        return None;
+    }
+    let length = usize::try_from(length).unwrap();
+    let slice = slice::from_raw_parts(slice, length);
+    Some(String::from_utf8(slice.to_vec()).unwrap())
+}
+
+/// The source text of each event of `input`.
+fn event_sources(input: &[u8], origin: u64) -> Vec<String> {
+    let mut sources = Vec::new();
+    parse(input, origin, |parser, event| unsafe {
+        let source = source_slice(parser, (*event).start_mark, (*event).end_mark);
+        sources.push(source.unwrap());
+    });
+    sources
+}
+
+#[test]
+fn test_source_slice() {
+    let expected = ["", "", "", "a", "[", "b", "'c'", "]", "", "", ""];
+    assert_eq!(event_sources(b"a: [b, 'c']\n", 0), expected);
+    // The marks do not count the byte order mark.
+    assert_eq!(event_sources(b"\xEF\xBB\xBFa: [b, 'c']\n", 0), expected);
+    // The marks count from the origin, which is subtracted again.
+    assert_eq!(event_sources(b"a: [b, 'c']\n", 100), expected);
+    assert_eq!(event_sources(b"\xEF\xBB\xBFa: [b, 'c']\n", 100), expected);
+}
+
+#[test]
+fn test_source_slice_out_of_range() {
+    let mut checked = false;
+    parse(b"a: b\n", 100, |parser, event| unsafe {
+        if checked || (*event).start_mark.index == (*event).end_mark.index {
+            return;
+        }
+        let start_mark = (*event).start_mark;
+        let end_mark = (*event).end_mark;
+        assert_eq!(source_slice(parser, start_mark, end_mark).unwrap(), "a");
+        // Out of order.
+        assert_eq!(source_slice(parser, end_mark, start_mark), None);
+        // Before the origin.
+        let mut before = start_mark;
+        before.index = 99;
+        assert_eq!(source_slice(parser, before, end_mark), None);
+        // Past the end of the input.
+        let mut past = end_mark;
+        past.index = 106;
+        assert_eq!(source_slice(parser, start_mark, past), None);
+        past.index = 105;
+        assert_eq!(source_slice(parser, start_mark, past).unwrap(), "a: b\n");
+        checked = true;
+    });
+    assert!(checked);
+}
+
+/// The input consumed for each event of `input`.
+fn consumed_sources(input: &[u8], origin: u64) -> Vec<String> {
+    let mut sources = Vec::new();
+    parse(input, origin, |parser, _event| unsafe {
+        let mut start_mark = MaybeUninit::<yaml_mark_t>::uninit();
+        let mut end_mark = MaybeUninit::<yaml_mark_t>::uninit();
+        yaml_parser_get_consumed_span(parser, start_mark.as_mut_ptr(), end_mark.as_mut_ptr());
+        let source = source_slice(parser, start_mark.assume_init(), end_mark.assume_init());
+        sources.push(source.unwrap());
+    });
+    sources
+}
+
+#[test]
+fn test_consumed_span() {
+    let input = "# c\na: [b, 'c'] # d\n--- {e: f}\n...\n";
+    let expected = [
+        "", "", "", "# c\na", ": [", "b", ", 'c'", "]", " # d\n", "", "---", " {", "e", ": f", "}",
+        "\n...", "\n",
+    ];
+    assert_eq!(consumed_sources(input.as_bytes(), 0), expected);
+    assert_eq!(consumed_sources(input.as_bytes(), 100), expected);
+    let with_bom = format!("\u{feff}{input}");
+    assert_eq!(consumed_sources(with_bom.as_bytes(), 0), expected);
+    assert_eq!(consumed_sources(with_bom.as_bytes(), 100), expected);
+}
+
+/// Load the document embedded in `text` from byte `origin`, and return the
+/// source text of each of its nodes.
+fn node_sources(text: &str, origin: usize) -> Vec<String> {
+    let input = &text.as_bytes()[origin..];
+    let mut sources = Vec::new();
+    unsafe {
+        let mut parser = MaybeUninit::<yaml_parser_t>::uninit();
+        let parser = parser.as_mut_ptr();
+        assert!(yaml_parser_initialize(parser).ok);
+        yaml_parser_set_input_string_with_origin(
+            parser,
+            input.as_ptr(),
+            input.len() as u64,
+            origin as u64,
+            0,
+            0,
+        );
+        let mut document = MaybeUninit::<yaml_document_t>::uninit();
+        let document = document.as_mut_ptr();
+        assert!(yaml_parser_load(parser, document).ok);
+        yaml_parser_delete(parser);
+        let count = i32::try_from(yaml_document_nodes(&*document).len()).unwrap();
+        for index in 1..=count {
+            let mut slice = ptr::null();
+            let mut length = 0;
+            assert!(
+                yaml_document_get_node_source(
+                    document,
+                    index,
+                    text.as_ptr(),
+                    text.len() as u64,
+                    &mut slice,
+                    &mut length,
+                )
+                .ok
+            );
+            let length = usize::try_from(length).unwrap();
+            let slice = slice::from_raw_parts(slice, length);
+            sources.push(String::from_utf8(slice.to_vec()).unwrap());
+        }
+        yaml_document_delete(document);
+    }
+    sources
+}
+
+#[test]
+fn test_node_source() {
+    let expected = [
+        "a: [b, 'c']\nd: {e: f}\n",
+        "a",
+        "[b, 'c']",
+        "b",
+        "'c'",
+        "d",
+        "{e: f}",
+        "e",
+        "f",
+    ];
+    let input = "a: [b, 'c']\nd: {e: f}\n";
+    assert_eq!(node_sources(input, 0), expected);
+    // The marks do not count the byte order mark.
+    let with_bom = format!("\u{feff}{input}");
+    assert_eq!(node_sources(&with_bom, 0), expected);
+    // The marks of an embedded document point into the enclosing text.
+    let enclosing = format!("# prefix\n{input}");
+    assert_eq!(node_sources(&enclosing, 9), expected);
+}
@@ -0,0 +1,212 @@
+use std::ffi::c_void;
+use std::mem::MaybeUninit;
+use std::ptr::{self, addr_of_mut};
+use std::slice;
+use unsafe_libyaml::{
+    yaml_document_delete, yaml_document_load_string, yaml_document_t, yaml_emitter_delete,
+    yaml_emitter_dump_all, yaml_emitter_initialize, yaml_emitter_set_output, yaml_emitter_t,
+    yaml_event_delete, yaml_event_t, yaml_parser_delete, yaml_parser_get_stream_info,
+    yaml_parser_initialize, yaml_parser_load_all, yaml_parser_parse, yaml_parser_set_input_string,
+    yaml_parser_t, yaml_stream_append_document, yaml_stream_delete, yaml_stream_dump,
+    yaml_stream_info_t, yaml_stream_initialize, yaml_stream_load, yaml_stream_t, YAML_ANY_BREAK,
+    YAML_CRLN_BREAK, YAML_LN_BREAK, YAML_STREAM_END_EVENT, YAML_UTF16LE_ENCODING,
+    YAML_UTF8_ENCODING,
+};
+
+unsafe fn write_to_vec(data: *mut c_void, buffer: *mut u8, size: u64) -> i32 {
+    let output = &mut *data.cast::<Vec<u8>>();
+    let size = usize::try_from(size).unwrap();
+    output.extend_from_slice(slice::from_raw_parts(buffer, size));
+    1
+}
+
+unsafe fn load(stream: *mut yaml_stream_t, input: &str) {
+    let mut parser = MaybeUninit::<yaml_parser_t>::uninit();
+    let parser = parser.as_mut_ptr();
+    assert!(yaml_parser_initialize(parser).ok);
+    yaml_parser_set_input_string(parser, input.as_ptr(), input.len() as u64);
+    assert!(yaml_stream_load(stream, parser).ok);
+    yaml_parser_delete(parser);
+}
+
+unsafe fn dump(stream: *mut yaml_stream_t) -> String {
+    let mut output = Vec::new();
+    let mut emitter = MaybeUninit::<yaml_emitter_t>::uninit();
+    let emitter = emitter.as_mut_ptr();
+    assert!(yaml_emitter_initialize(emitter).ok);
+    yaml_emitter_set_output(emitter, write_to_vec, addr_of_mut!(output).cast());
+    assert!(yaml_stream_dump(emitter, stream).ok);
+    yaml_emitter_delete(emitter);
+    String::from_utf8(output).unwrap()
+}
+
+fn count(stream: *mut yaml_stream_t) -> usize {
+    let count = unsafe {
+        (*stream)
+            .documents
+            .top
+            .offset_from((*stream).documents.start)
+    };
+    usize::try_from(count).unwrap()
+}
+
+#[test]
+fn test_stream_round_trip() {
+    let input = "a\r\n--- b\r\n...\r\n--- [c]\r\n";
+    unsafe {
+        let mut stream = MaybeUninit::<yaml_stream_t>::uninit();
+        let stream = stream.as_mut_ptr();
+        load(stream, input);
+        assert_eq!(count(stream), 3);
+        assert_eq!((*stream).line_break, YAML_CRLN_BREAK);
+        assert_eq!(dump(stream), input);
+        assert_eq!(count(stream), 0);
+        yaml_stream_delete(stream);
+    }
+}
+
+#[test]
+fn test_stream_append_document() {
+    unsafe {
+        let mut stream = MaybeUninit::<yaml_stream_t>::uninit();
+        let stream = stream.as_mut_ptr();
+        yaml_stream_initialize(stream, YAML_UTF8_ENCODING);
+        assert_eq!(count(stream), 0);
+        for input in ["a: 1", "- b", "c"] {
+            let mut document = MaybeUninit::<yaml_document_t>::uninit();
+            let document = document.as_mut_ptr();
+            assert!(
+                yaml_document_load_string(
+                    document,
+                    input.as_ptr(),
+                    input.len() as u64,
+                    ptr::null_mut(),
+                )
+                .ok
+            );
+            assert!(yaml_stream_append_document(stream, document).ok);
+        }
+        assert_eq!(count(stream), 3);
+        assert_eq!(dump(stream), "a: 1\n---\n- b\n--- c\n");
+        yaml_stream_delete(stream);
+    }
+}
+
+#[test]
+fn test_stream_delete() {
+    // The documents left in the stream are deleted with it.
+    unsafe {
+        let mut stream = MaybeUninit::<yaml_stream_t>::uninit();
+        let stream = stream.as_mut_ptr();
+        load(stream, &"--- [a, {b: c}]\n".repeat(40));
+        assert_eq!(count(stream), 40);
+        yaml_stream_delete(stream);
+        assert!((*stream).documents.start.is_null());
+    }
+}
+
+/// Parse `input` and get the summary of the stream, which is only available
+/// once the STREAM-END event has been produced.
+fn stream_info(input: &[u8]) -> yaml_stream_info_t {
+    let mut info = MaybeUninit::<yaml_stream_info_t>::uninit();
+    unsafe {
+        let mut parser = MaybeUninit::<yaml_parser_t>::uninit();
+        let parser = parser.as_mut_ptr();
+        assert!(yaml_parser_initialize(parser).ok);
+        yaml_parser_set_input_string(parser, input.as_ptr(), input.len() as u64);
+        let mut event = MaybeUninit::<yaml_event_t>::uninit();
+        let event = event.as_mut_ptr();
+        loop {
+            assert!(yaml_parser_get_stream_info(parser, info.as_mut_ptr()).fail);
+            assert!(yaml_parser_parse(parser, event).ok);
+            let done = (*event).type_ == YAML_STREAM_END_EVENT;
+            yaml_event_delete(event);
+            if done {
+                break;
+            }
+        }
+        assert!(yaml_parser_get_stream_info(parser, info.as_mut_ptr()).ok);
+        yaml_parser_delete(parser);
+        info.assume_init()
+    }
+}
+
+#[test]
+fn test_stream_info() {
+    let info = stream_info(b"a\r\n--- b\n");
+    assert_eq!(info.encoding, YAML_UTF8_ENCODING);
+    assert!(!info.bom);
+    assert_eq!(info.line_break, YAML_CRLN_BREAK);
+    assert_eq!(info.documents, 2);
+    assert_eq!(info.events, 8);
+
+    let info = stream_info(b"\xEF\xBB\xBFa\n");
+    assert_eq!(info.encoding, YAML_UTF8_ENCODING);
+    assert!(info.bom);
+    assert_eq!(info.line_break, YAML_LN_BREAK);
+    assert_eq!(info.documents, 1);
+    assert_eq!(info.events, 5);
+
+    let info = stream_info(b"\xFF\xFEa\x00");
+    assert_eq!(info.encoding, YAML_UTF16LE_ENCODING);
+    assert!(info.bom);
+    assert_eq!(info.line_break, YAML_ANY_BREAK);
+    assert_eq!(info.documents, 1);
+
+    let info = stream_info(b"");
+    assert_eq!(info.documents, 0);
+    assert_eq!(info.events, 2);
+}
+
+unsafe fn load_all(documents: &mut Vec<yaml_document_t>, input: &str) -> bool {
+    let mut parser = MaybeUninit::<yaml_parser_t>::uninit();
+    let parser = parser.as_mut_ptr();
+    assert!(yaml_parser_initialize(parser).ok);
+    yaml_parser_set_input_string(parser, input.as_ptr(), input.len() as u64);
+    let success = yaml_parser_load_all(parser, documents);
+    yaml_parser_delete(parser);
+    success.ok
+}
+
+#[test]
+fn test_load_all() {
+    let mut documents = Vec::new();
+    unsafe {
+        assert!(load_all(&mut documents, "a\n--- [b]\n"));
+        assert_eq!(documents.len(), 2);
+        assert!(load_all(&mut documents, "{c: d}\n--- e\n"));
+        assert_eq!(documents.len(), 4);
+        assert_eq!(*(*documents[2].nodes.start.add(1)).data.scalar.value, b'c');
+        // The documents loaded before the error are removed again.
+        assert!(!load_all(&mut documents, "f\n--- [g\n"));
+        assert_eq!(documents.len(), 4);
+        for document in &mut documents {
+            yaml_document_delete(document);
+        }
+    }
+}
+
+#[test]
+fn test_dump_all() {
+    let mut documents = Vec::new();
+    let mut output = Vec::new();
+    unsafe {
+        assert!(load_all(&mut documents, "a\n--- [b, c]\n"));
+        let mut empty = MaybeUninit::<yaml_document_t>::uninit();
+        assert!(yaml_document_load_string(empty.as_mut_ptr(), b"".as_ptr(), 0, ptr::null_mut()).ok);
+        documents.push(empty.assume_init());
+        let mut emitter = MaybeUninit::<yaml_emitter_t>::uninit();
+        let emitter = emitter.as_mut_ptr();
+        assert!(yaml_emitter_initialize(emitter).ok);
+        yaml_emitter_set_output(emitter, write_to_vec, addr_of_mut!(output).cast());
+        let count = documents.len() as u64;
+        // The emitter destroys the documents.
+        assert!(yaml_emitter_dump_all(emitter, documents.as_mut_ptr(), count).ok);
+        yaml_emitter_delete(emitter);
+    }
+    // The document without nodes holds an empty scalar.
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "a\n--- [b, c]\n---\n...\n"
+    );
+}
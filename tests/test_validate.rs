@@ -0,0 +1,303 @@
+#![allow(clippy::uninlined_format_args)]
+
+#[allow(dead_code)]
+mod document;
+
+use self::document::Document;
+use std::ffi::{c_void, CStr};
+use std::mem;
+use std::ptr;
+use unsafe_libyaml::{
+    yaml_document_validate, yaml_node_type_t, yaml_shape_field_t, yaml_shape_t, yaml_value_type_t,
+    yaml_violation_t, yaml_violation_type_t, YAML_CORE_SCHEMA, YAML_FAILSAFE_SCHEMA,
+    YAML_INT_VALUE, YAML_MAPPING_NODE, YAML_NO_NODE, YAML_SCALAR_NODE, YAML_SEQUENCE_NODE,
+    YAML_STR_VALUE, YAML_VIOLATION_MISSING_KEY, YAML_VIOLATION_NODE_TYPE,
+    YAML_VIOLATION_UNKNOWN_KEY, YAML_VIOLATION_VALUE_TYPE,
+};
+
+#[derive(PartialEq, Debug)]
+struct Violation {
+    type_: yaml_violation_type_t,
+    path: String,
+    node: i32,
+    key: Option<String>,
+    line: u64,
+}
+
+struct Violations {
+    violations: Vec<Violation>,
+    limit: usize,
+}
+
+unsafe fn handler(data: *mut c_void, violation: *const yaml_violation_t) -> i32 {
+    let violations = &mut *data.cast::<Violations>();
+    let c_str = |s: *const u8| CStr::from_ptr(s.cast()).to_str().unwrap().to_owned();
+    violations.violations.push(Violation {
+        type_: (*violation).type_,
+        path: c_str((*violation).path),
+        node: (*violation).node,
+        key: if (*violation).key.is_null() {
+            None
+        } else {
+            Some(c_str((*violation).key))
+        },
+        line: (*violation).start_mark.line,
+    });
+    i32::from(violations.violations.len() < violations.limit)
+}
+
+fn shape(node_type: yaml_node_type_t) -> yaml_shape_t {
+    let mut shape: yaml_shape_t = unsafe { mem::zeroed() };
+    shape.node_type = node_type;
+    shape
+}
+
+fn scalar(value_type: yaml_value_type_t) -> yaml_shape_t {
+    let mut shape = shape(YAML_SCALAR_NODE);
+    shape.check_value_type = true;
+    shape.value_type = value_type;
+    shape
+}
+
+fn sequence(items: &yaml_shape_t) -> yaml_shape_t {
+    let mut shape = shape(YAML_SEQUENCE_NODE);
+    shape.items = items;
+    shape
+}
+
+fn mapping(fields: &[yaml_shape_field_t], closed: bool) -> yaml_shape_t {
+    let mut shape = shape(YAML_MAPPING_NODE);
+    shape.fields = fields.as_ptr();
+    shape.fields_length = fields.len() as u64;
+    shape.closed = closed;
+    shape
+}
+
+fn field(key: &'static [u8], required: bool, shape: &yaml_shape_t) -> yaml_shape_field_t {
+    let mut field: yaml_shape_field_t = unsafe { mem::zeroed() };
+    field.key = key.as_ptr();
+    field.required = required;
+    field.shape = shape;
+    field
+}
+
+fn validate_limit(text: &str, shape: &yaml_shape_t, limit: usize) -> (bool, bool, Vec<Violation>) {
+    let mut document = Document::load(text);
+    let mut violations = Violations {
+        violations: Vec::new(),
+        limit,
+    };
+    let mut valid = false;
+    let success = unsafe {
+        yaml_document_validate(
+            document.as_mut_ptr(),
+            shape,
+            YAML_CORE_SCHEMA,
+            handler,
+            ptr::addr_of_mut!(violations).cast(),
+            &mut valid,
+        )
+    };
+    (success.ok, valid, violations.violations)
+}
+
+fn validate(text: &str, shape: &yaml_shape_t) -> (bool, Vec<Violation>) {
+    let (ok, valid, violations) = validate_limit(text, shape, usize::MAX);
+    assert!(ok, "validation of {:?} failed", text);
+    assert_eq!(valid, violations.is_empty());
+    (valid, violations)
+}
+
+fn violation(type_: yaml_violation_type_t, path: &str, node: i32, line: u64) -> Violation {
+    Violation {
+        type_,
+        path: path.to_owned(),
+        node,
+        key: None,
+        line,
+    }
+}
+
+#[test]
+fn test_validate_any() {
+    let any = shape(YAML_NO_NODE);
+    for text in ["", "a", "[1, 2]", "{a: b}"] {
+        assert_eq!(validate(text, &any), (true, vec![]), "{:?}", text);
+    }
+}
+
+#[test]
+fn test_validate_empty() {
+    assert_eq!(
+        validate("", &shape(YAML_MAPPING_NODE)),
+        (false, vec![violation(YAML_VIOLATION_NODE_TYPE, "", 0, 0)]),
+    );
+}
+
+#[test]
+fn test_validate_node_type() {
+    assert_eq!(validate("[1]", &shape(YAML_SEQUENCE_NODE)), (true, vec![]));
+    assert_eq!(
+        validate("\n[1]", &shape(YAML_MAPPING_NODE)),
+        (false, vec![violation(YAML_VIOLATION_NODE_TYPE, "", 1, 1)]),
+    );
+}
+
+#[test]
+fn test_validate_value_type() {
+    let int = scalar(YAML_INT_VALUE);
+    let ints = sequence(&int);
+    assert_eq!(validate("[1, 0x2, -3]", &ints), (true, vec![]));
+    assert_eq!(
+        validate("- 1\n- a\n- '3'\n- [4]\n", &ints),
+        (
+            false,
+            vec![
+                violation(YAML_VIOLATION_VALUE_TYPE, "/1", 3, 1),
+                violation(YAML_VIOLATION_VALUE_TYPE, "/2", 4, 2),
+                violation(YAML_VIOLATION_NODE_TYPE, "/3", 5, 3),
+            ],
+        ),
+    );
+}
+
+#[test]
+fn test_validate_schema() {
+    let int = scalar(YAML_INT_VALUE);
+    let mut document = Document::load("1");
+    let mut violations = Violations {
+        violations: Vec::new(),
+        limit: usize::MAX,
+    };
+    let mut valid = true;
+    unsafe {
+        assert!(
+            yaml_document_validate(
+                document.as_mut_ptr(),
+                &int,
+                YAML_FAILSAFE_SCHEMA,
+                handler,
+                ptr::addr_of_mut!(violations).cast(),
+                &mut valid,
+            )
+            .ok
+        );
+    }
+    assert!(!valid);
+    assert_eq!(
+        violations.violations,
+        [violation(YAML_VIOLATION_VALUE_TYPE, "", 1, 0)],
+    );
+    assert!(!validate("1", &scalar(YAML_STR_VALUE)).0);
+}
+
+#[test]
+fn test_validate_mapping() {
+    let int = scalar(YAML_INT_VALUE);
+    let str = scalar(YAML_STR_VALUE);
+    let fields = [
+        field(b"name\0", true, &str),
+        field(b"age\0", false, &int),
+        field(b"id/~\0", true, &int),
+    ];
+    let open = mapping(&fields, false);
+    let closed = mapping(&fields, true);
+
+    assert_eq!(validate("{name: a, id/~: 1}", &closed), (true, vec![]));
+    assert_eq!(validate("{name: a, id/~: 1, x: 2}", &open), (true, vec![]));
+
+    let (valid, violations) = validate("age: x\nnope: 1\n", &closed);
+    assert!(!valid);
+    assert_eq!(
+        violations,
+        [
+            violation(YAML_VIOLATION_VALUE_TYPE, "/age", 3, 0),
+            violation(YAML_VIOLATION_UNKNOWN_KEY, "/nope", 4, 1),
+            Violation {
+                key: Some("name".to_owned()),
+                ..violation(YAML_VIOLATION_MISSING_KEY, "", 1, 0)
+            },
+            Violation {
+                key: Some("id/~".to_owned()),
+                ..violation(YAML_VIOLATION_MISSING_KEY, "", 1, 0)
+            },
+        ],
+    );
+
+    let (_, violations) = validate("{name: a, id/~: x}", &closed);
+    assert_eq!(
+        violations,
+        [violation(YAML_VIOLATION_VALUE_TYPE, "/id~1~0", 5, 0)],
+    );
+}
+
+#[test]
+fn test_validate_aliases() {
+    let int = scalar(YAML_INT_VALUE);
+    let ints = sequence(&int);
+    assert_eq!(validate("[&x 1, *x]", &ints), (true, vec![]));
+
+    let mut document = Document::load_alias_nodes("[&x a, *x]");
+    let mut violations = Violations {
+        violations: Vec::new(),
+        limit: usize::MAX,
+    };
+    let mut valid = true;
+    unsafe {
+        assert!(
+            yaml_document_validate(
+                document.as_mut_ptr(),
+                &ints,
+                YAML_CORE_SCHEMA,
+                handler,
+                ptr::addr_of_mut!(violations).cast(),
+                &mut valid,
+            )
+            .ok
+        );
+    }
+    assert!(!valid);
+    assert_eq!(
+        violations.violations,
+        [
+            violation(YAML_VIOLATION_VALUE_TYPE, "/0", 2, 0),
+            violation(YAML_VIOLATION_VALUE_TYPE, "/1", 2, 0),
+        ],
+    );
+}
+
+#[test]
+fn test_validate_recursive() {
+    let mut tree = shape(YAML_MAPPING_NODE);
+    let children = sequence(&tree);
+    let fields = [field(b"children\0", false, &children)];
+    tree.fields = fields.as_ptr();
+    tree.fields_length = 1;
+    tree.closed = true;
+
+    assert_eq!(
+        validate("&a {children: [{children: []}, *a]}", &tree),
+        (true, vec![]),
+    );
+    let (valid, violations) = validate("{children: [{children: [1]}]}", &tree);
+    assert!(!valid);
+    assert_eq!(
+        violations,
+        [violation(
+            YAML_VIOLATION_NODE_TYPE,
+            "/children/0/children/0",
+            7,
+            0
+        )],
+    );
+}
+
+#[test]
+fn test_validate_handler_error() {
+    let int = scalar(YAML_INT_VALUE);
+    let ints = sequence(&int);
+    let (ok, valid, violations) = validate_limit("[a, b, c]", &ints, 2);
+    assert!(!ok);
+    assert!(!valid);
+    assert_eq!(violations.len(), 2);
+}